@@ -0,0 +1,138 @@
+//! An append-only log of what happened to the archive.
+//!
+//! Every summary made, refined, edited, exported or deleted leaves an [`Event`]
+//! in a sled tree next to the summaries, as does each incident in
+//! `summera daemon`, such as a job failing or getting stuck. Events are never changed or removed, so
+//! the log still explains an entry after the entry itself is gone.
+
+use crate::storage::StoredSummary;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What was done to a summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Summarised,
+    Refined,
+    Edited,
+    Exported,
+    Deleted,
+    /// Something went wrong in `summera daemon`
+    Incident,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Summarised => write!(f, "summarised"),
+            Self::Refined => write!(f, "refined"),
+            Self::Edited => write!(f, "edited"),
+            Self::Exported => write!(f, "exported"),
+            Self::Deleted => write!(f, "deleted"),
+            Self::Incident => write!(f, "incident"),
+        }
+    }
+}
+
+impl std::str::FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "summarised" | "summarized" => Ok(Self::Summarised),
+            "refined" => Ok(Self::Refined),
+            "edited" => Ok(Self::Edited),
+            "exported" => Ok(Self::Exported),
+            "deleted" => Ok(Self::Deleted),
+            "incident" => Ok(Self::Incident),
+            other => Err(format!(
+                "unknown action '{}'; expected summarised, refined, edited, exported, deleted or incident",
+                other
+            )),
+        }
+    }
+}
+
+/// One entry in the activity log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub at: DateTime<Utc>,
+    pub action: Action,
+    pub url: String,
+    /// Title of the summary at the time, so deleted entries can still be recognised
+    pub title: String,
+    /// Why or how, e.g. "discarded in review", "to Readwise" or a refine instruction
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl Event {
+    /// An event for `stored`, happening now
+    pub fn new(action: Action, stored: &StoredSummary) -> Self {
+        Self {
+            at: Utc::now(),
+            action,
+            url: stored.url.clone(),
+            title: stored.summary.title.clone(),
+            detail: None,
+        }
+    }
+
+    /// An incident in a `summera daemon` job, happening now
+    pub fn incident(job: impl fmt::Display, detail: impl Into<String>) -> Self {
+        Self {
+            at: Utc::now(),
+            action: Action::Incident,
+            url: format!("daemon:{}", job),
+            title: format!("summera daemon: {}", job),
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// Say why or how
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Whether `query` appears in the URL, title or detail, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [Some(&self.url), Some(&self.title), self.detail.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Storage, Summary};
+
+    #[test]
+    fn events_are_kept_in_order_after_the_summary_is_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let summary = Summary::new("Lost post".into(), "C".into(), vec![], vec![], vec![]);
+        let stored = StoredSummary::new("https://a.example/post".to_string(), summary);
+        storage.put(&stored).unwrap();
+        storage
+            .log_event(&Event::new(Action::Summarised, &stored))
+            .unwrap();
+        storage.delete(&stored.url).unwrap();
+        storage
+            .log_event(&Event::new(Action::Deleted, &stored).with_detail("discarded in review"))
+            .unwrap();
+
+        let events = storage.events().unwrap();
+        let actions: Vec<Action> = events.iter().map(|e| e.action).collect();
+        assert_eq!(actions, [Action::Summarised, Action::Deleted]);
+        assert!(events[1].matches("REVIEW"));
+        assert!(events[1].matches("lost"));
+        assert!(!events[0].matches("review"));
+        assert_eq!("summarized".parse(), Ok(Action::Summarised));
+    }
+}
@@ -1,97 +1,938 @@
 //! LLM agent module for structured summarization.
 //!
-//! Uses rstructor for structured output from LLMs.
+//! Uses rstructor for structured output from LLMs: summaries are requested with the
+//! provider's native JSON schema mode and validated, with repair retries on failure.
 
-pub use crate::summary::Summary;
+pub use crate::summary::{Critique, Summary};
 
-use crate::config::Config;
-use rstructor::{GeminiClient, GeminiModel, LLMClient};
+use crate::config::{Config, ProviderConfig, RetryConfig};
+use crate::extractive;
+use crate::storage::StoredSummary;
+use crate::tokens::{self, Budget};
+use rstructor::{
+    ApiErrorKind, GeminiClient, GeminiModel, LLMClient, OpenAIClient, OpenAIModel, RStructorError,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum AgentError {
+    /// The provider rejected the request; retrying it won't help
     #[error("LLM request failed: {0}")]
     RequestFailed(String),
+    /// Rate limited, overloaded or unreachable; worth retrying after a wait
+    #[error("LLM provider unavailable: {message}")]
+    Unavailable {
+        message: String,
+        /// How long the provider asked us to wait, if it said
+        retry_after: Option<Duration>,
+    },
     #[error("failed to parse response: {0}")]
     ParseError(String),
     #[error("configuration error: {0}")]
     ConfigError(#[from] crate::config::ConfigError),
+    #[error("unsupported provider: {0}")]
+    UnsupportedProvider(String),
 }
 
-/// Run the summarization agent on the provided text
-pub async fn summarize(text: &str, config: &Config) -> Result<Summary, AgentError> {
-    let api_key = config.api_key()?;
+/// Default endpoint of a local Ollama server
+const OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
 
-    // Parse the model from config
-    let model = parse_gemini_model(&config.agent.model);
+/// Model used for Ollama when none is configured
+const OLLAMA_DEFAULT_MODEL: &str = "llama3.1";
 
-    // Build the client
-    let client = GeminiClient::new(api_key)
-        .map_err(|e| AgentError::RequestFailed(e.to_string()))?
-        .model(model);
+/// A summary and the provider that produced it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Summarised {
+    pub summary: Summary,
+    /// The provider and model that answered, e.g. "openai/gpt-4o-mini", or
+    /// "extractive" for the offline fallback
+    pub provider: String,
+    /// The self-critique score, when `agent.critique` is enabled
+    #[serde(default)]
+    pub critique: Option<Critique>,
+}
+
+impl Summarised {
+    /// Whether the offline extractive summariser wrote this, not an LLM
+    pub fn is_extractive(&self) -> bool {
+        self.provider == extractive::PROVIDER
+    }
+
+    /// The self-critique score from 1 to 5, if the summary was scored
+    pub fn score(&self) -> Option<u8> {
+        self.critique.as_ref().map(Critique::score)
+    }
+}
+
+/// Progress reported while summarising, so a UI can show what's happening
+#[derive(Debug, Clone, PartialEq)]
+pub enum Progress {
+    /// Starting on one chunk of text too long for a single request
+    Chunk { index: usize, total: usize },
+    /// Waiting for a provider to answer
+    Prompting { provider: String },
+    /// Waiting to retry after a provider was rate limited or unavailable
+    Retrying {
+        provider: String,
+        attempt: u32,
+        delay: Duration,
+    },
+    /// Merging the partial summaries of a long text
+    Merging { partials: usize },
+    /// Scoring the summary against the text
+    Critiquing,
+    /// Writing the summary again because it scored too low
+    Regenerating { score: u8 },
+    /// No provider could be reached, so the text is summarised offline
+    Extractive { reason: String },
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Chunk { index, total } => write!(f, "Summarising part {} of {}", index, total),
+            Self::Prompting { provider } => write!(f, "Waiting for {}", provider),
+            Self::Retrying {
+                provider,
+                attempt,
+                delay,
+            } => write!(
+                f,
+                "{} unavailable, retrying in {:.1}s (attempt {})",
+                provider,
+                delay.as_secs_f64(),
+                attempt
+            ),
+            Self::Merging { partials } => write!(f, "Merging {} partial summaries", partials),
+            Self::Critiquing => write!(f, "Checking the summary against the text"),
+            Self::Regenerating { score } => {
+                write!(f, "Summary scored {}/5, writing it again", score)
+            }
+            Self::Extractive { reason } => {
+                write!(f, "No LLM reachable ({}), summarising offline", reason)
+            }
+        }
+    }
+}
+
+/// Callback receiving [`Progress`] updates
+pub type OnProgress<'a> = &'a (dyn Fn(Progress) + Sync);
+
+/// Context about text that didn't come from a web page or document,
+/// such as meeting notes or an email
+#[derive(Debug, Clone, Default)]
+pub struct TextMetadata {
+    /// Title to use instead of the one the LLM picks
+    pub title: Option<String>,
+    /// What kind of text this is, e.g. "meeting notes" or "email"
+    pub kind: Option<String>,
+    /// Where the text came from (sender, meeting name, file path, ...)
+    pub source: Option<String>,
+}
+
+impl TextMetadata {
+    /// Prefix `text` with whatever metadata is known, so the LLM can use it
+    pub fn with_context(&self, text: &str) -> String {
+        let fields = [
+            ("Kind", &self.kind),
+            ("Title", &self.title),
+            ("Source", &self.source),
+        ];
+        let header: Vec<String> = fields
+            .iter()
+            .filter_map(|(label, value)| value.as_ref().map(|v| format!("{}: {}", label, v)))
+            .collect();
+        if header.is_empty() {
+            text.to_string()
+        } else {
+            format!("{}\n\n{}", header.join("\n"), text)
+        }
+    }
+}
+
+/// Summarise arbitrary text, such as notes or emails, using the same agent as web pages
+pub async fn summarize_text(
+    text: &str,
+    metadata: &TextMetadata,
+    config: &Config,
+) -> Result<Summarised, AgentError> {
+    let mut summarised = summarize(&metadata.with_context(text), config).await?;
+    if let Some(ref title) = metadata.title {
+        summarised.summary.title = title.clone();
+    }
+    Ok(summarised)
+}
+
+/// Estimate how the summarisation prompt for `text` fits the configured model's context
+pub fn prompt_budget(text: &str, config: &Config) -> Budget {
+    Budget {
+        tokens: tokens::count_tokens(&build_prompt(text, config)),
+        limit: config.agent.context_limit(),
+    }
+}
+
+/// The prompts summarising `text` sends, one per chunk, as built for the provider.
+///
+/// rstructor adds the response schema to each. Text split into several chunks is
+/// followed by a merge prompt built from the partial summaries, which can't be
+/// known before they are written.
+pub fn prompts(text: &str, config: &Config) -> Vec<String> {
+    split_into_chunks(text, chunk_len(text, config), config.agent.chunk_overlap)
+        .into_iter()
+        .map(|chunk| build_prompt(chunk, config))
+        .collect()
+}
+
+/// Run the summarization agent on the provided text.
+///
+/// Text longer than `agent.chunk_chars`, or too many tokens for the model's context
+/// window, is split into overlapping chunks that are summarised one by one, then
+/// merged into a single summary (map-reduce). If no provider can be reached and
+/// `agent.extractive_fallback` is on, the text is summarised offline instead.
+pub async fn summarize(text: &str, config: &Config) -> Result<Summarised, AgentError> {
+    summarize_with_progress(text, config, &|_| {}).await
+}
+
+/// [`summarize`], reporting each stage to `on_progress` as it starts
+pub async fn summarize_with_progress(
+    text: &str,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<Summarised, AgentError> {
+    match summarize_checked(text, config, on_progress).await {
+        // Offline or unconfigured; a rejected request or bad response still fails
+        Err(e @ (AgentError::Unavailable { .. } | AgentError::ConfigError(_)))
+            if config.agent.extractive_fallback =>
+        {
+            on_progress(Progress::Extractive {
+                reason: e.to_string(),
+            });
+            Ok(Summarised {
+                summary: extractive::summarize(text, config.agent.length.key_points().1),
+                provider: extractive::PROVIDER.to_string(),
+                critique: None,
+            })
+        }
+        result => result,
+    }
+}
+
+/// Summarise with the provider chain and, when `agent.critique` is enabled, score the
+/// summary against the text, writing it again while it scores below `min_score`.
+///
+/// The best-scoring version is kept. A critique that fails leaves the summary unscored
+/// rather than failing it.
+async fn summarize_checked(
+    text: &str,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<Summarised, AgentError> {
+    let settings = &config.agent.critique;
+    let mut best: Option<Summarised> = None;
+    for _ in 0..=settings.regenerations {
+        if let Some(score) = best.as_ref().and_then(Summarised::score) {
+            on_progress(Progress::Regenerating { score });
+        }
+        let mut summarised = match summarize_with_llm(text, config, on_progress).await {
+            Ok(summarised) => summarised,
+            // A failed rewrite keeps the best summary so far rather than losing it
+            Err(e) => return best.ok_or(e),
+        };
+        if !settings.enabled {
+            return Ok(summarised);
+        }
+        on_progress(Progress::Critiquing);
+        match critique(text, &summarised.summary, config, on_progress).await {
+            Ok(critique) => summarised.critique = Some(critique),
+            Err(_) => return Ok(best.unwrap_or(summarised)),
+        }
+        let score = summarised.score().unwrap_or_default();
+        if best.as_ref().is_none_or(|b| b.score() < Some(score)) {
+            best = Some(summarised);
+        }
+        if score >= settings.min_score {
+            break;
+        }
+    }
+    Ok(best.expect("at least one summary is written"))
+}
+
+/// Score how faithfully and completely `summary` represents `text`
+pub async fn critique(
+    text: &str,
+    summary: &Summary,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<Critique, AgentError> {
+    let prompt = build_critique_prompt(text, summary, config)?;
+    let (critique, _provider) = complete(&prompt, config, on_progress).await?;
+    Ok(critique)
+}
+
+/// Summarise with the provider chain, in chunks if the text is too long
+async fn summarize_with_llm(
+    text: &str,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<Summarised, AgentError> {
+    let chunks = split_into_chunks(text, chunk_len(text, config), config.agent.chunk_overlap);
+    if chunks.len() == 1 {
+        return summarize_chunk(text, config, on_progress).await;
+    }
+
+    let total = chunks.len();
+    let mut partials = Vec::with_capacity(total);
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        on_progress(Progress::Chunk {
+            index: i + 1,
+            total,
+        });
+        partials.push(summarize_chunk(chunk, config, on_progress).await?.summary);
+    }
+    on_progress(Progress::Merging {
+        partials: partials.len(),
+    });
+    let prompt = build_synthesis_prompt(&partials, config)?;
+    let (summary, provider) = complete(&prompt, config, on_progress).await?;
+    Ok(Summarised {
+        summary,
+        provider,
+        critique: None,
+    })
+}
+
+/// Largest chunk, in bytes, that fits both `agent.chunk_chars` and the context window
+fn chunk_len(text: &str, config: &Config) -> usize {
+    let budget = prompt_budget(text, config);
+    if budget.fits() {
+        return config.agent.chunk_chars;
+    }
+    // Scale by this text's bytes per token, with headroom for the prompt around it
+    let bytes_per_token = text.len() as f64 / budget.tokens.max(1) as f64;
+    let fitting = (budget.available() as f64 * bytes_per_token * 0.8) as usize;
+    fitting.clamp(1, config.agent.chunk_chars.max(1))
+}
+
+/// Summarise text that fits in a single request
+async fn summarize_chunk(
+    text: &str,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<Summarised, AgentError> {
+    let prompt = build_prompt(text, config);
+    let (summary, provider) = complete(&prompt, config, on_progress).await?;
+    Ok(Summarised {
+        summary,
+        provider,
+        critique: None,
+    })
+}
+
+/// Rewrite `previous` following a user's `instruction`, such as "shorter" or
+/// "focus on the security implications", re-reading the source `text`.
+///
+/// There's no offline fallback: an extractive summary can't follow instructions.
+pub async fn refine(
+    text: &str,
+    previous: &Summary,
+    instruction: &str,
+    config: &Config,
+) -> Result<Summarised, AgentError> {
+    let prompt = build_refine_prompt(text, previous, instruction, config)?;
+    let (summary, provider) = complete(&prompt, config, &|_| {}).await?;
+    Ok(Summarised {
+        summary,
+        provider,
+        critique: None,
+    })
+}
+
+/// Answer a question about a stored summary, grounded in the summary, the source
+/// text when it was kept, and the questions already asked about it
+pub async fn answer(
+    question: &str,
+    stored: &StoredSummary,
+    config: &Config,
+) -> Result<String, AgentError> {
+    let prompt = build_question_prompt(question, stored, config)?;
+    let (response, _provider) = complete::<String>(&prompt, config, &|_| {}).await?;
+    Ok(response.trim().to_string())
+}
 
-    // Build the prompt including persona, schema, and text
+/// Answer a question from several stored summaries, citing them as `[1]`, `[2]`, …
+/// in the order given
+pub async fn answer_from_library(
+    question: &str,
+    sources: &[StoredSummary],
+    config: &Config,
+) -> Result<String, AgentError> {
+    let prompt = build_library_prompt(question, sources, config)?;
+    let (response, _provider) = complete::<String>(&prompt, config, &|_| {}).await?;
+    Ok(response.trim().to_string())
+}
+
+/// Summarise only what changed between two versions of a page, given as a `-`/`+` patch
+pub async fn summarize_changes(patch: &str, config: &Config) -> Result<String, AgentError> {
     let prompt = format!(
         r#"{}
 
+The following lines were removed (-) from or added (+) to a web page since it was last read.
+Summarise what changed and why it matters in a few sentences of plain prose. Ignore navigation,
+cookie banners and other boilerplate. Do not summarise the unchanged page.{}
+
+---
+
+{}"#,
+        config.agent.persona(),
+        language_instruction(config)
+            .map(|i| format!(" {}", i))
+            .unwrap_or_default(),
+        patch
+    );
+    let (response, _provider) = complete::<String>(&prompt, config, &|_| {}).await?;
+    Ok(response.trim().to_string())
+}
+
+/// What a prompt asks a provider for: plain text, or a structure in the provider's JSON mode
+trait Reply: Sized + Send {
+    fn request<C: LLMClient + Sync>(
+        client: &C,
+        prompt: &str,
+    ) -> impl Future<Output = Result<Self, AgentError>> + Send;
+}
+
+impl Reply for String {
+    async fn request<C: LLMClient + Sync>(client: &C, prompt: &str) -> Result<Self, AgentError> {
+        let result = client
+            .generate_with_metadata(prompt)
+            .await
+            .map_err(request_error)?;
+        Ok(result.text)
+    }
+}
+
+impl Reply for Summary {
+    /// rstructor sends the schema natively and re-prompts with the error when the
+    /// response doesn't parse or validate
+    async fn request<C: LLMClient + Sync>(client: &C, prompt: &str) -> Result<Self, AgentError> {
+        client.materialize(prompt).await.map_err(request_error)
+    }
+}
+
+impl Reply for Critique {
+    async fn request<C: LLMClient + Sync>(client: &C, prompt: &str) -> Result<Self, AgentError> {
+        client.materialize(prompt).await.map_err(request_error)
+    }
+}
+
+/// Send a prompt along the provider chain, returning the response and who answered.
+///
+/// A provider is skipped when it is rate limited, down, or has no API key; any
+/// other error is returned straight away.
+async fn complete<T: Reply>(
+    prompt: &str,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<(T, String), AgentError> {
+    let providers = config.agent.providers();
+    let mut last_error = None;
+    for provider in &providers {
+        match complete_with_retry(prompt, provider, config, on_progress).await {
+            Ok(response) => return Ok(response),
+            Err(e @ (AgentError::Unavailable { .. } | AgentError::ConfigError(_))) => {
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| AgentError::UnsupportedProvider(String::new())))
+}
+
+/// Send a prompt to one provider, retrying transient failures with backoff
+async fn complete_with_retry<T: Reply>(
+    prompt: &str,
+    provider: &ProviderConfig,
+    config: &Config,
+    on_progress: OnProgress<'_>,
+) -> Result<(T, String), AgentError> {
+    let retry = &config.agent.retry;
+    let name = match provider.model {
+        Some(ref model) => format!("{}/{}", provider.provider, model),
+        None => provider.provider.clone(),
+    };
+    let mut attempt = 1;
+    loop {
+        on_progress(Progress::Prompting {
+            provider: name.clone(),
+        });
+        match complete_with(prompt, provider, config).await {
+            Err(AgentError::Unavailable { retry_after, .. }) if attempt < retry.max_attempts => {
+                let delay = backoff_delay(retry, attempt, retry_after);
+                attempt += 1;
+                on_progress(Progress::Retrying {
+                    provider: name.clone(),
+                    attempt,
+                    delay,
+                });
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Wait before retry number `attempt`, preferring the provider's own hint
+fn backoff_delay(retry: &RetryConfig, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    let max = Duration::from_millis(retry.max_delay_ms);
+    let exponential = Duration::from_millis(retry.initial_delay_ms)
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+    let delay = retry_after.unwrap_or(exponential).min(max);
+    if retry.jitter && retry_after.is_none() {
+        // Anything random will do; RandomState is seeded differently every time
+        let random = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        delay.mul_f64(0.5 + (random % 1_000) as f64 / 2_000.0)
+    } else {
+        delay
+    }
+}
+
+/// Send a prompt to one provider
+async fn complete_with<T: Reply>(
+    prompt: &str,
+    provider: &ProviderConfig,
+    config: &Config,
+) -> Result<(T, String), AgentError> {
+    let model = provider.model.as_deref().unwrap_or_default();
+    match provider.provider.as_str() {
+        "gemini" => {
+            let model = parse_gemini_model(model);
+            let label = format!("gemini/{}", model.as_str());
+            let mut client = GeminiClient::new(config.api_key_for("gemini")?)
+                .map_err(request_error)?
+                .model(model);
+            if let Some(ref base_url) = provider.base_url {
+                client = client.base_url(base_url);
+            }
+            Ok((T::request(&client, prompt).await?, label))
+        }
+        "openai" => {
+            let model = parse_openai_model(model);
+            let label = format!("openai/{}", model.as_str());
+            let mut client = OpenAIClient::new(config.api_key_for("openai")?)
+                .map_err(request_error)?
+                .model(model);
+            if let Some(ref base_url) = provider.base_url {
+                client = client.base_url(base_url);
+            }
+            Ok((T::request(&client, prompt).await?, label))
+        }
+        "ollama" => {
+            // Ollama speaks the OpenAI API and ignores the key
+            let model = if model.is_empty() {
+                OLLAMA_DEFAULT_MODEL
+            } else {
+                model
+            };
+            let client = OpenAIClient::new("ollama")
+                .map_err(request_error)?
+                .model(OpenAIModel::Custom(model.to_string()))
+                .base_url(provider.base_url.as_deref().unwrap_or(OLLAMA_BASE_URL));
+            Ok((
+                T::request(&client, prompt).await?,
+                format!("ollama/{}", model),
+            ))
+        }
+        other => Err(AgentError::UnsupportedProvider(other.to_string())),
+    }
+}
+
+/// Whether an HTTP status means the provider may answer if asked again: a timeout,
+/// a rate limit or a server error
+fn is_transient(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500..=599)
+}
+
+/// Classify a client error, so outages and rate limits can fall back to the next provider.
+///
+/// Other client errors, such as a bad key or an unknown model, are fatal.
+fn request_error(error: RStructorError) -> AgentError {
+    let transient = match error {
+        ref e if e.is_retryable() => true,
+        RStructorError::ApiError {
+            kind: ApiErrorKind::Other { code, .. },
+            ..
+        } => is_transient(code),
+        // Without a status the request never got an answer, e.g. the connection failed
+        RStructorError::HttpError(ref e) => e.status().is_none_or(|s| is_transient(s.as_u16())),
+        _ => false,
+    };
+    match error {
+        e if transient => AgentError::Unavailable {
+            retry_after: e.retry_delay(),
+            message: e.to_string(),
+        },
+        // Still malformed after rstructor's repair retries
+        RStructorError::ValidationError(message) | RStructorError::SerializationError(message) => {
+            AgentError::ParseError(message)
+        }
+        RStructorError::JsonError(e) => AgentError::ParseError(e.to_string()),
+        e => AgentError::RequestFailed(e.to_string()),
+    }
+}
+
+/// Build the prompt including persona, instructions, and text.
+///
+/// The schema itself travels in the request, not the prompt.
+fn build_prompt(text: &str, config: &Config) -> String {
+    format!(
+        r#"{}
+
 {}
 
-You MUST respond with valid JSON matching this exact schema:
-{{
-  "title": "string - a concise title for the content",
-  "conclusion": "string - the main takeaway or conclusion of the article in 1-2 sentences",
-  "key_points": ["array of key takeaways"],
-  "entities": ["array of named entities like people, organizations, technologies"],
-  "action_items": ["array of actionable items or next steps, can be empty"]
-}}
+{}
+
+---
+
+{}"#,
+        config.agent.persona(),
+        config.agent.prompt,
+        instructions(config),
+        text
+    )
+}
+
+/// Length, language and extra field instructions that follow the prompt
+fn instructions(config: &Config) -> String {
+    let mut instructions = config.agent.length.instructions();
+    for extra in [language_instruction(config), fields_instruction(config)]
+        .into_iter()
+        .flatten()
+    {
+        instructions = format!("{} {}", instructions, extra);
+    }
+    instructions
+}
 
-Do not include any markdown formatting, code blocks, or explanations. Only output the raw JSON object.
+/// Instruction to fill the `[agent.fields]` declared in the config under `custom`
+fn fields_instruction(config: &Config) -> Option<String> {
+    if config.agent.fields.is_empty() {
+        return None;
+    }
+    let fields = config
+        .agent
+        .fields
+        .iter()
+        .map(|(name, description)| format!("- {}: {}", name, description.trim()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(format!(
+        "Also fill `custom` with these fields, using each name as the key:\n{}",
+        fields
+    ))
+}
+
+/// Instruction to write in `output_language`, if one is set
+fn language_instruction(config: &Config) -> Option<String> {
+    let language = config.agent.output_language.as_deref()?.trim();
+    (!language.is_empty()).then(|| {
+        format!(
+            "Write every part of the summary in {}, whatever language the text is in.",
+            language_name(language)
+        )
+    })
+}
+
+/// English name of a language given by name or ISO 639 code ("sv" or "swe" for Swedish)
+pub fn language_name(language: &str) -> String {
+    let code = language.to_lowercase();
+    let name = match code.as_str() {
+        "en" => Some("English"),
+        "de" => Some("German"),
+        "fr" => Some("French"),
+        "es" => Some("Spanish"),
+        "it" => Some("Italian"),
+        "pt" => Some("Portuguese"),
+        "nl" => Some("Dutch"),
+        "sv" => Some("Swedish"),
+        "da" => Some("Danish"),
+        "no" | "nb" => Some("Norwegian"),
+        "fi" => Some("Finnish"),
+        "pl" => Some("Polish"),
+        "ru" => Some("Russian"),
+        "uk" => Some("Ukrainian"),
+        "ja" => Some("Japanese"),
+        "zh" => Some("Chinese"),
+        "ko" => Some("Korean"),
+        _ => whatlang::Lang::from_code(&code).map(|lang| lang.eng_name()),
+    };
+    name.map_or_else(|| language.to_string(), str::to_string)
+}
+
+/// The language a summary is written in: `output_language` when an LLM wrote
+/// it, otherwise the language it's reliably detected to be in
+pub fn summary_language(summary: &Summary, provider: &str, config: &Config) -> Option<String> {
+    match config.agent.output_language.as_deref().map(str::trim) {
+        Some(language) if !language.is_empty() && provider != extractive::PROVIDER => {
+            Some(language_name(language))
+        }
+        _ => {
+            let text = format!("{} {}", summary.conclusion, summary.key_points.join(" "));
+            let info = whatlang::detect(&text)?;
+            info.is_reliable()
+                .then(|| info.lang().eng_name().to_string())
+        }
+    }
+}
+
+/// Build the prompt that merges the summaries of each chunk into one
+fn build_synthesis_prompt(partials: &[Summary], config: &Config) -> Result<String, AgentError> {
+    let partials = serde_json::to_string_pretty(partials)
+        .map_err(|e| AgentError::ParseError(e.to_string()))?;
+    Ok(format!(
+        r#"{}
+
+The text was too long to read at once, so it was split into consecutive parts that were
+summarised separately. Combine these partial summaries into a single summary of the whole text.
+Merge duplicate points and entities, keep the most important key points, and write a conclusion
+for the text as a whole rather than for any single part. {}
 
 ---
 
 {}"#,
-        config.agent.persona, config.agent.prompt, text
-    );
+        config.agent.persona(),
+        instructions(config),
+        partials
+    ))
+}
+
+/// Build the prompt that revises a summary, with as much of the source text as fits
+fn build_refine_prompt(
+    text: &str,
+    previous: &Summary,
+    instruction: &str,
+    config: &Config,
+) -> Result<String, AgentError> {
+    let previous = serde_json::to_string_pretty(previous)
+        .map_err(|e| AgentError::ParseError(e.to_string()))?;
+    // Refinements are a single request, so a long text is cut at the first chunk
+    let text = split_into_chunks(text, chunk_len(text, config), 0)[0];
+    Ok(format!(
+        r#"{}
+
+Below is a summary you wrote earlier, followed by the text it summarises. Rewrite the summary
+following this instruction from the reader, using the text to add or correct detail:
+
+{}
 
-    // Get structured output using the Instructor trait
-    let result = client
-        .generate_with_metadata(&prompt)
-        .await
-        .map_err(|e| AgentError::RequestFailed(e.to_string()))?;
+{}
 
-    // Debug: print raw response
-    // eprintln!("--- Raw LLM Response ---");
-    // eprintln!("{}", result.text);
-    // eprintln!("--- End Response ---");
+---
 
-    // Clean the response (strip markdown code blocks if present)
-    let cleaned = strip_markdown_json(&result.text);
+{}
 
-    // Parse the JSON response into Summary
-    let summary: Summary = serde_json::from_str(&cleaned)
-        .map_err(|e| AgentError::ParseError(format!("{}: {}", e, cleaned)))?;
+---
 
-    Ok(summary)
+{}"#,
+        config.agent.persona(),
+        instruction.trim(),
+        instructions(config),
+        previous,
+        text
+    ))
 }
 
-/// Strip markdown code block wrappers from JSON response
-fn strip_markdown_json(text: &str) -> String {
-    let trimmed = text.trim();
+/// Build the prompt that scores a summary against the text it summarises
+fn build_critique_prompt(
+    text: &str,
+    summary: &Summary,
+    config: &Config,
+) -> Result<String, AgentError> {
+    let summary =
+        serde_json::to_string_pretty(summary).map_err(|e| AgentError::ParseError(e.to_string()))?;
+    // One request, so a long text is cut at the first chunk like refinements are
+    let text = split_into_chunks(text, chunk_len(text, config), 0)[0];
+    Ok(format!(
+        r#"You are a careful editor checking a summary against the text it summarises.
+Score its faithfulness (are its claims supported by the text?) and its coverage (does it
+include what matters most?) from 1 to 5, and give the main reason in one sentence.
+Judge the summary only against the text below, not against outside knowledge.
 
-    // Remove ```json ... ``` or ``` ... ```
-    if trimmed.starts_with("```") {
-        let without_prefix = if trimmed.starts_with("```json") {
-            &trimmed[7..]
-        } else {
-            &trimmed[3..]
-        };
+---
+
+{}
 
-        if let Some(end_idx) = without_prefix.rfind("```") {
-            return without_prefix[..end_idx].trim().to_string();
+---
+
+{}"#,
+        summary, text
+    ))
+}
+
+/// Earlier questions included with a new one, most recent last
+const QA_CONTEXT: usize = 5;
+
+/// Build the prompt that answers a question about a stored summary
+fn build_question_prompt(
+    question: &str,
+    stored: &StoredSummary,
+    config: &Config,
+) -> Result<String, AgentError> {
+    let summary = serde_json::to_string_pretty(&stored.summary)
+        .map_err(|e| AgentError::ParseError(e.to_string()))?;
+    let mut context = vec![format!("Summary:\n{}", summary)];
+    let earlier = &stored.qa_history[stored.qa_history.len().saturating_sub(QA_CONTEXT)..];
+    if !earlier.is_empty() {
+        let exchanges: Vec<String> = earlier
+            .iter()
+            .map(|qa| format!("Q: {}\nA: {}", qa.question, qa.answer))
+            .collect();
+        context.push(format!("Earlier questions:\n{}", exchanges.join("\n\n")));
+    }
+    if let Some(ref text) = stored.raw_text {
+        // One request, so a long text is cut at the first chunk
+        let text = split_into_chunks(text, chunk_len(text, config), 0)[0];
+        context.push(format!("Source text:\n{}", text));
+    }
+    Ok(format!(
+        r#"{}
+
+Answer the reader's question about the content below in a few sentences of plain prose. Use only
+the summary and source text; if they don't answer it, say so instead of guessing.{}
+
+Question: {}
+
+---
+
+{}"#,
+        config.agent.persona(),
+        config
+            .agent
+            .output_language
+            .as_deref()
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+            .map(|language| format!(" Answer in {}.", language_name(language)))
+            .unwrap_or_default(),
+        question.trim(),
+        context.join("\n\n---\n\n")
+    ))
+}
+
+/// Build the prompt that answers a question from numbered library sources
+fn build_library_prompt(
+    question: &str,
+    sources: &[StoredSummary],
+    config: &Config,
+) -> Result<String, AgentError> {
+    // The sources share one request, so each gets an even part of a chunk's text
+    let excerpt_len = config.agent.chunk_chars / sources.len().max(1);
+    let mut context = Vec::new();
+    for (i, stored) in sources.iter().enumerate() {
+        let summary = serde_json::to_string_pretty(&stored.summary)
+            .map_err(|e| AgentError::ParseError(e.to_string()))?;
+        let mut source = format!(
+            "[{}] {}
+
+Summary:
+{}",
+            i + 1,
+            stored.url,
+            summary
+        );
+        if let Some(ref text) = stored.raw_text {
+            let text = split_into_chunks(text, excerpt_len, 0)[0];
+            source.push_str(&format!("\n\nSource text:\n{}", text));
         }
+        context.push(source);
     }
+    Ok(format!(
+        r#"{}
 
-    trimmed.to_string()
+Answer the reader's question from the numbered sources below in a few paragraphs of plain prose.
+Cite the sources each statement rests on by number, like [1] or [2][3]. Use only the sources; if
+they don't answer the question, say so instead of guessing.{}
+
+Question: {}
+
+---
+
+{}"#,
+        config.agent.persona(),
+        config
+            .agent
+            .output_language
+            .as_deref()
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+            .map(|language| format!(" Answer in {}.", language_name(language)))
+            .unwrap_or_default(),
+        question.trim(),
+        context.join("\n\n---\n\n")
+    ))
+}
+
+/// Split `text` into chunks of at most `max_len` bytes, each starting `overlap` bytes
+/// before the previous one ended so context isn't lost at the seams.
+///
+/// Chunks end at a paragraph or sentence break where one falls in their second half.
+fn split_into_chunks(text: &str, max_len: usize, overlap: usize) -> Vec<&str> {
+    if max_len == 0 || text.len() <= max_len {
+        return vec![text];
+    }
+    // Overlap must leave room for progress through the text
+    let overlap = overlap.min(max_len / 4);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    loop {
+        let mut end = floor_char_boundary(text, start + max_len);
+        if end == start {
+            // A character wider than `max_len` gets a chunk of its own
+            end = ceil_char_boundary(text, start + 1);
+        }
+        if end >= text.len() {
+            chunks.push(&text[start..]);
+            return chunks;
+        }
+        let window = &text[start..end];
+        let break_at = window
+            .rfind("\n\n")
+            .or_else(|| window.rfind(". "))
+            .filter(|&pos| pos > window.len() / 2);
+        if let Some(pos) = break_at {
+            // Both breaks start with a one-byte character, so this is a char boundary
+            end = start + pos + 1;
+        }
+        chunks.push(&text[start..end]);
+        start = match ceil_char_boundary(text, end.saturating_sub(overlap)) {
+            next if next > start => next,
+            _ => end,
+        };
+    }
+}
+
+/// The largest char boundary in `text` at or before `index`
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// The smallest char boundary in `text` at or after `index`
+fn ceil_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
 }
 
 /// Parse a model string into a GeminiModel
@@ -103,3 +944,304 @@ fn parse_gemini_model(model: &str) -> GeminiModel {
         _ => GeminiModel::Gemini20Flash, // Default
     }
 }
+
+/// Parse a model string into an OpenAIModel
+fn parse_openai_model(model: &str) -> OpenAIModel {
+    if model.is_empty() || model.starts_with("gemini") {
+        // The config default is a Gemini model, so fall back to a sensible OpenAI one
+        return OpenAIModel::Gpt4OMini;
+    }
+    OpenAIModel::from_string(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PersonaPreset;
+
+    #[test]
+    fn parses_openai_models() {
+        assert_eq!(parse_openai_model("gpt-4o"), OpenAIModel::Gpt4O);
+        assert_eq!(
+            parse_openai_model("gemini-2.0-flash"),
+            OpenAIModel::Gpt4OMini
+        );
+        assert_eq!(
+            parse_openai_model("my-finetune"),
+            OpenAIModel::Custom("my-finetune".to_string())
+        );
+    }
+
+    #[test]
+    fn splits_long_text_into_overlapping_chunks() {
+        let text = "First paragraph here.\n\nSecond paragraph is longer.\n\nThird one ends it.";
+        let chunks = split_into_chunks(text, 32, 6);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= 32));
+        assert_eq!(chunks[0], "First paragraph here.\n");
+        // Every chunk after the first repeats the tail of the one before it
+        for pair in chunks.windows(2) {
+            let tail = &pair[0][pair[0].len() - 6..];
+            assert!(pair[1].starts_with(tail), "{:?}", pair);
+        }
+        assert!(chunks.last().unwrap().ends_with("ends it."));
+
+        assert_eq!(split_into_chunks("short", 32, 6), ["short"]);
+    }
+
+    #[test]
+    fn chunks_respect_char_boundaries() {
+        let text = "å".repeat(50);
+        for chunk in split_into_chunks(&text, 15, 4) {
+            assert!(chunk.chars().all(|c| c == 'å'));
+        }
+
+        // Narrower than a character, every chunk still takes at least one
+        let text = "日本語 😀 text";
+        let chunks = split_into_chunks(text, 2, 1);
+        assert_eq!(chunks[..4], ["日", "本", "語", " "]);
+        assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[tokio::test]
+    async fn skips_providers_without_keys() {
+        let config: Config = toml::from_str(
+            r#"
+            [agent]
+            provider = "gemini"
+            fallback = [{ provider = "openai" }, { provider = "carrier-pigeon" }]
+            "#,
+        )
+        .unwrap();
+        // Neither key is configured, so the chain reaches the last provider
+        let err = complete::<String>("prompt", &config, &|_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AgentError::UnsupportedProvider(p) if p == "carrier-pigeon"));
+
+        // Each provider in the chain is reported as it's tried
+        let seen = std::sync::Mutex::new(Vec::new());
+        let _ = complete::<String>("prompt", &config, &|p| {
+            seen.lock().unwrap().push(p.to_string())
+        })
+        .await;
+        assert_eq!(
+            seen.into_inner().unwrap(),
+            [
+                "Waiting for gemini/gemini-2.0-flash",
+                "Waiting for openai",
+                "Waiting for carrier-pigeon"
+            ]
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        let retry = RetryConfig {
+            jitter: false,
+            max_delay_ms: 5_000,
+            ..RetryConfig::default()
+        };
+        let delays: Vec<_> = (1..=4).map(|n| backoff_delay(&retry, n, None)).collect();
+        assert_eq!(
+            delays,
+            [1_000, 2_000, 4_000, 5_000].map(Duration::from_millis)
+        );
+        // A Retry-After hint wins, within the cap
+        let hint = Some(Duration::from_secs(3));
+        assert_eq!(backoff_delay(&retry, 1, hint), Duration::from_secs(3));
+
+        let jittered = backoff_delay(&RetryConfig::default(), 2, None);
+        assert!(
+            jittered >= Duration::from_millis(1_000) && jittered <= Duration::from_millis(2_000)
+        );
+    }
+
+    #[test]
+    fn prefixes_known_metadata() {
+        let metadata = TextMetadata {
+            kind: Some("email".to_string()),
+            source: Some("alice@example.com".to_string()),
+            ..TextMetadata::default()
+        };
+        assert_eq!(
+            metadata.with_context("Hi"),
+            "Kind: email\nSource: alice@example.com\n\nHi"
+        );
+        assert_eq!(TextMetadata::default().with_context("Hi"), "Hi");
+    }
+
+    #[test]
+    fn rejects_empty_summaries() {
+        use rstructor::Instructor;
+        let empty = Summary::new("T".into(), String::new(), vec![], vec![], vec![]);
+        let error = empty.validate().unwrap_err();
+        assert!(matches!(request_error(error), AgentError::ParseError(_)));
+
+        let summary = Summary::new("T".into(), "C".into(), vec!["P".into()], vec![], vec![]);
+        assert!(summary.validate().is_ok());
+    }
+
+    #[test]
+    fn only_retries_errors_a_provider_may_recover_from() {
+        let api = |kind| request_error(RStructorError::api_error("OpenAI", kind));
+        let unavailable = |e: AgentError| matches!(e, AgentError::Unavailable { .. });
+        assert!(unavailable(api(ApiErrorKind::RateLimited {
+            retry_after: None
+        })));
+        assert!(unavailable(api(ApiErrorKind::Other {
+            code: 408,
+            message: "timeout".into()
+        })));
+        assert!(unavailable(request_error(RStructorError::Timeout)));
+        assert!(!unavailable(api(ApiErrorKind::AuthenticationFailed)));
+        assert!(!unavailable(api(ApiErrorKind::Other {
+            code: 422,
+            message: "unprocessable".into()
+        })));
+    }
+
+    #[test]
+    fn asks_for_the_configured_length() {
+        let mut config = Config::default();
+        config.agent.length = "brief".parse().unwrap();
+        let prompt = build_prompt("Text", &config);
+        assert!(prompt.contains("Give between 2 and 3 key points."));
+        assert!(prompt.ends_with("---\n\nText"));
+        assert!("long".parse::<crate::config::SummaryLength>().is_err());
+    }
+
+    #[test]
+    fn asks_for_the_configured_fields() {
+        let mut config = Config::default();
+        assert!(!build_prompt("Text", &config).contains("custom"));
+        config.agent.fields.insert(
+            "risks".to_string(),
+            "Risks the content raises, as a list".to_string(),
+        );
+        let prompt = build_prompt("Text", &config);
+        assert!(prompt.contains("fill `custom`"));
+        assert!(prompt.contains("- risks: Risks the content raises, as a list"));
+    }
+
+    #[test]
+    fn critique_scores_against_the_text() {
+        use rstructor::Instructor;
+        let config = Config::default();
+        let summary = Summary::new("T".into(), "Conclusion".into(), vec![], vec![], vec![]);
+        let prompt = build_critique_prompt("Source text", &summary, &config).unwrap();
+        assert!(prompt.contains("\"conclusion\": \"Conclusion\""));
+        assert!(prompt.ends_with("---\n\nSource text"));
+
+        let critique = |faithfulness, coverage| Critique {
+            faithfulness,
+            coverage,
+            reason: String::new(),
+        };
+        assert_eq!(critique(4, 2).score(), 2);
+        assert!(critique(5, 5).validate().is_ok());
+        assert!(critique(0, 3).validate().is_err());
+    }
+
+    #[test]
+    fn refine_prompt_carries_the_instruction_and_text() {
+        let config = Config::default();
+        let previous = Summary::new("T".into(), "Earlier".into(), vec![], vec![], vec![]);
+        let prompt = build_refine_prompt("Source text", &previous, " shorter ", &config).unwrap();
+        assert!(prompt
+            .contains("from the reader, using the text to add or correct detail:\n\nshorter\n"));
+        assert!(prompt.contains(r#""conclusion": "Earlier""#));
+        assert!(prompt.ends_with("---\n\nSource text"));
+    }
+
+    #[test]
+    fn question_prompt_grounds_the_answer() {
+        let config = Config::default();
+        let summary = Summary::new("T".into(), "Earlier".into(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://example.com".to_string(), summary);
+        let prompt = build_question_prompt("Why?", &stored, &config).unwrap();
+        assert!(prompt.contains("Question: Why?"));
+        assert!(!prompt.contains("Source text:"));
+
+        stored = stored.with_raw_text("Because.");
+        stored.record_answer("What?", "That.");
+        let prompt = build_question_prompt("Why?", &stored, &config).unwrap();
+        assert!(prompt.contains("Earlier questions:\nQ: What?\nA: That."));
+        assert!(prompt.ends_with("Source text:\nBecause."));
+    }
+
+    #[test]
+    fn prompts_are_built_per_chunk() {
+        let mut config = Config::default();
+        assert_eq!(
+            prompts("Short text.", &config),
+            [build_prompt("Short text.", &config)]
+        );
+        config.agent.chunk_chars = 40;
+        config.agent.chunk_overlap = 0;
+        let text = "First sentence is here. Second sentence is here. Third one.";
+        let prompts = prompts(text, &config);
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[0].contains("First sentence") && !prompts[0].contains("Third one."));
+        assert!(prompts[1].ends_with("Third one."));
+    }
+
+    #[test]
+    fn library_prompt_numbers_the_sources() {
+        let mut config = Config::default();
+        config.agent.chunk_chars = 20;
+        let summary = Summary::new("T".into(), "Earlier".into(), vec![], vec![], vec![]);
+        let first = StoredSummary::new("https://a.example".to_string(), summary.clone())
+            .with_raw_text("Short.");
+        let second = StoredSummary::new("https://b.example".to_string(), summary)
+            .with_raw_text("A source text far longer than its share of the chunk.");
+        let prompt = build_library_prompt(" How? ", &[first, second], &config).unwrap();
+        assert!(prompt.contains("Question: How?\n"));
+        assert!(prompt.contains("[1] https://a.example\n\nSummary:"));
+        assert!(prompt.contains("[2] https://b.example\n\nSummary:"));
+        assert!(prompt.contains("Source text:\nShort."));
+        assert!(!prompt.contains("its share of the chunk"));
+    }
+
+    #[test]
+    fn writes_as_the_persona_preset() {
+        let mut config = Config::default();
+        assert!(build_prompt("Text", &config).starts_with(&config.agent.persona));
+        config.agent.persona_preset = Some("sceptical".parse().unwrap());
+        assert!(build_prompt("Text", &config).starts_with("You are a sceptical reviewer."));
+        assert_eq!(
+            PersonaPreset::next(Some(PersonaPreset::Skeptical)),
+            None,
+            "cycling wraps back to the configured persona"
+        );
+    }
+
+    #[test]
+    fn writes_in_the_output_language() {
+        let mut config = Config::default();
+        assert!(!build_prompt("Text", &config).contains("whatever language"));
+        config.agent.output_language = Some("sv".to_string());
+        assert!(build_prompt("Text", &config)
+            .contains("Write every part of the summary in Swedish, whatever language"));
+        assert_eq!(language_name("deu"), "German");
+        assert_eq!(language_name("Klingon"), "Klingon");
+
+        let summary = Summary::new(
+            "T".into(),
+            "Rust is a language that many teams choose for safety and speed.".into(),
+            vec![],
+            vec![],
+            vec![],
+        );
+        assert_eq!(
+            summary_language(&summary, "openai/gpt-4o", &config).as_deref(),
+            Some("Swedish")
+        );
+        // Extractive summaries quote the source, so they're in its language
+        assert_eq!(
+            summary_language(&summary, extractive::PROVIDER, &config).as_deref(),
+            Some("English")
+        );
+    }
+}
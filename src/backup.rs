@@ -0,0 +1,170 @@
+//! Backup archives of the library, made by `summera backup` or on a schedule
+//! by `summera daemon`.
+//!
+//! An archive is a zip holding `summaries.jsonl`, every record with its
+//! extracted text as `summera dump --raw` writes them (so `summera import` reads
+//! it back), the saved searches, collections, reading queue and activity log as
+//! JSON, and the config file. Archives are named after the time they were made,
+//! and all but the newest few are pruned after each backup.
+
+use crate::storage::{Storage, StorageError};
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::Serialize;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// File name format of an archive, e.g. "summera-20261015-030000.zip"
+const NAME_FORMAT: &str = "summera-%Y%m%d-%H%M%S.zip";
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("archive error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("serialization error: {0}")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("storage error: {0}")]
+    StorageError(#[from] StorageError),
+}
+
+/// Write a new archive of `storage` and `config_file` into `dir`, returning its path
+pub fn create(
+    storage: &Storage,
+    config_file: &Path,
+    dir: &Path,
+    now: DateTime<Local>,
+) -> Result<PathBuf, BackupError> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(now.format(NAME_FORMAT).to_string());
+    // Written under another name first, so a failed backup never looks like a good one
+    let partial = path.with_extension("zip.partial");
+    let mut zip = ZipWriter::new(std::fs::File::create(&partial)?);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("summaries.jsonl", options)?;
+    for stored in storage.iter() {
+        serde_json::to_writer(&mut zip, &stored?)?;
+        writeln!(zip)?;
+    }
+    add_json(&mut zip, "saved_searches.json", &storage.saved_searches()?)?;
+    add_json(&mut zip, "collections.json", &storage.collections()?)?;
+    add_json(&mut zip, "queue.json", &storage.queue_order()?)?;
+    add_json(&mut zip, "activity.json", &storage.events()?)?;
+    if config_file.exists() {
+        zip.start_file("summera.toml", options)?;
+        zip.write_all(&std::fs::read(config_file)?)?;
+    }
+    zip.finish()?;
+    std::fs::rename(&partial, &path)?;
+    Ok(path)
+}
+
+/// Add `value` to the archive as a pretty-printed JSON file called `name`
+fn add_json<W: Write + Seek>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &impl Serialize,
+) -> Result<(), BackupError> {
+    zip.start_file(name, SimpleFileOptions::default())?;
+    serde_json::to_writer_pretty(zip, value)?;
+    Ok(())
+}
+
+/// The archives in `dir` with when each was made, oldest first
+pub fn archives(dir: &Path) -> Result<Vec<(DateTime<Local>, PathBuf)>, BackupError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut archives: Vec<(DateTime<Local>, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let made = NaiveDateTime::parse_from_str(name, NAME_FORMAT).ok()?;
+            let made = made.and_local_timezone(Local).earliest()?;
+            Some((made, path))
+        })
+        .collect();
+    archives.sort();
+    Ok(archives)
+}
+
+/// When the newest archive in `dir` was made, if there is one
+pub fn last_made(dir: &Path) -> Result<Option<DateTime<Local>>, BackupError> {
+    Ok(archives(dir)?.pop().map(|(made, _)| made))
+}
+
+/// Delete all but the newest `keep` archives in `dir`, returning the paths deleted
+pub fn prune(dir: &Path, keep: usize) -> Result<Vec<PathBuf>, BackupError> {
+    let archives = archives(dir)?;
+    let excess = archives.len().saturating_sub(keep);
+    let mut pruned = Vec::new();
+    for (_, path) in archives.into_iter().take(excess) {
+        std::fs::remove_file(&path)?;
+        pruned.push(path);
+    }
+    Ok(pruned)
+}
+
+/// What a backup made and pruned
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupReport {
+    pub archive: PathBuf,
+    pub pruned: Vec<PathBuf>,
+}
+
+/// Make an archive and prune the old ones, as configured under `[backup]`
+pub fn run(
+    storage: &Storage,
+    config: &crate::Config,
+    now: DateTime<Local>,
+) -> Result<BackupReport, BackupError> {
+    let dir = config.backup.dir(&config.storage);
+    let archive = create(storage, &crate::Config::config_file_path(), &dir, now)?;
+    let pruned = prune(&dir, config.backup.keep.max(1))?;
+    Ok(BackupReport { archive, pruned })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::StoredSummary;
+    use crate::Summary;
+    use chrono::TimeZone;
+
+    #[test]
+    fn archives_the_library_and_prunes_old_archives() {
+        let data = tempfile::tempdir().unwrap();
+        let storage = Storage::open(data.path().join("db")).unwrap();
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        let stored =
+            StoredSummary::new("https://a.example".to_string(), summary).with_raw_text("The page.");
+        storage.put(&stored).unwrap();
+
+        let dir = data.path().join("backups");
+        let config_file = data.path().join("missing.toml");
+        for day in 1..=3 {
+            let made = Local.with_ymd_and_hms(2026, 10, day, 3, 0, 0).unwrap();
+            create(&storage, &config_file, &dir, made).unwrap();
+        }
+        let newest = Local.with_ymd_and_hms(2026, 10, 3, 3, 0, 0).unwrap();
+        assert_eq!(last_made(&dir).unwrap(), Some(newest));
+
+        let pruned = prune(&dir, 2).unwrap();
+        assert_eq!(pruned.len(), 1);
+        assert!(pruned[0].ends_with("summera-20261001-030000.zip"));
+        assert_eq!(archives(&dir).unwrap().len(), 2);
+
+        let file = std::fs::File::open(dir.join("summera-20261003-030000.zip")).unwrap();
+        let mut zip = zip::ZipArchive::new(file).unwrap();
+        let mut records = String::new();
+        std::io::Read::read_to_string(&mut zip.by_name("summaries.jsonl").unwrap(), &mut records)
+            .unwrap();
+        let restored: StoredSummary = serde_json::from_str(records.trim()).unwrap();
+        assert_eq!(restored.raw_text.as_deref(), Some("The page."));
+        assert!(zip.by_name("summera.toml").is_err());
+    }
+}
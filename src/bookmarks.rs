@@ -0,0 +1,157 @@
+//! Keeping the archive in step with a browser's exported bookmarks.
+//!
+//! A Netscape bookmarks file (the `bookmarks.html` every browser exports) is
+//! treated as the source of truth: bookmarks without a summary are summarised
+//! and tagged `bookmark`, and summaries whose bookmark was deleted are tagged
+//! `archived` instead of being removed, so their notes and history survive.
+//! Bookmarking a page again takes it back out of the archive.
+
+use crate::storage::{normalise_url, Storage, StorageError};
+use scraper::{Html, Selector};
+use std::collections::HashSet;
+
+/// Tag on every summary that came from the bookmarks file
+pub const BOOKMARK_TAG: &str = "bookmark";
+
+/// Tag on summaries whose bookmark was deleted
+pub const ARCHIVED_TAG: &str = "archived";
+
+/// One bookmark from the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// What syncing a bookmarks file would change
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// Bookmarks with no stored summary yet
+    pub new: Vec<Bookmark>,
+    /// Stored bookmarks missing from the file, to archive
+    pub removed: Vec<String>,
+    /// Archived bookmarks that are back in the file
+    pub restored: Vec<String>,
+}
+
+impl SyncPlan {
+    /// Whether the archive already matches the file
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty() && self.removed.is_empty() && self.restored.is_empty()
+    }
+}
+
+/// The web bookmarks in a Netscape bookmarks file, in order and without repeats.
+///
+/// `javascript:`, `place:` and other non-web links are skipped.
+pub fn parse(html: &str) -> Vec<Bookmark> {
+    let document = Html::parse_document(html);
+    let links = Selector::parse("a[href]").expect("bookmark selector is valid");
+    let mut seen = HashSet::new();
+    document
+        .select(&links)
+        .filter_map(|link| {
+            let url = link.value().attr("href")?.trim();
+            if !crate::reader::is_url(url) || !seen.insert(url.to_string()) {
+                return None;
+            }
+            let title = link.text().collect::<String>().trim().to_string();
+            Some(Bookmark {
+                url: url.to_string(),
+                title: Some(title).filter(|title| !title.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// Compare `bookmarks` with what's stored, matching URLs that name the same page
+/// (see [`normalise_url`])
+pub fn plan(bookmarks: &[Bookmark], storage: &Storage) -> Result<SyncPlan, StorageError> {
+    let bookmarked: HashSet<String> = bookmarks.iter().map(|b| normalise_url(&b.url)).collect();
+    let mut stored = HashSet::new();
+    let mut plan = SyncPlan::default();
+    for record in storage.iter() {
+        let record = record?;
+        let tagged = |tag: &str| record.tags.iter().any(|t| t == tag);
+        let url = normalise_url(&record.url);
+        if bookmarked.contains(&url) {
+            if tagged(ARCHIVED_TAG) && tagged(BOOKMARK_TAG) {
+                plan.restored.push(record.url.clone());
+            }
+        } else if tagged(BOOKMARK_TAG) && !tagged(ARCHIVED_TAG) {
+            plan.removed.push(record.url.clone());
+        }
+        stored.insert(url);
+    }
+    plan.new = bookmarks
+        .iter()
+        .filter(|b| !stored.contains(&normalise_url(&b.url)))
+        .cloned()
+        .collect();
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StoredSummary, Summary};
+
+    const EXPORT: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 PERSONAL_TOOLBAR_FOLDER="true">Bookmarks bar</H3>
+    <DL><p>
+        <DT><A HREF="https://kept.example/?utm_source=hn" ADD_DATE="1700000000">Kept &amp; read</A>
+        <DT><A HREF="https://new.example/">New</A>
+        <DT><A HREF="https://back.example/"></A>
+        <DT><A HREF="https://youtu.be/dQw4w9WgXcQ?t=42">Video</A>
+        <DT><A HREF="javascript:alert(1)">Bookmarklet</A>
+        <DT><A HREF="https://new.example/">New again</A>
+    </DL><p>
+</DL><p>"#;
+
+    #[test]
+    fn plans_new_removed_and_restored_bookmarks() {
+        let bookmarks = parse(EXPORT);
+        let urls: Vec<&str> = bookmarks.iter().map(|b| b.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://kept.example/?utm_source=hn",
+                "https://new.example/",
+                "https://back.example/",
+                "https://youtu.be/dQw4w9WgXcQ?t=42"
+            ]
+        );
+        assert_eq!(bookmarks[0].title.as_deref(), Some("Kept & read"));
+        assert_eq!(bookmarks[2].title, None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let record = |url: &str, tags: &[&str]| {
+            let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+            let mut stored = StoredSummary::new(url.to_string(), summary);
+            for tag in tags {
+                stored.add_tag(tag);
+            }
+            storage.put(&stored).unwrap();
+        };
+        // Stored without the tracking parameter the bookmark has
+        record("https://www.kept.example", &[BOOKMARK_TAG]);
+        record("https://back.example/", &[BOOKMARK_TAG, ARCHIVED_TAG]);
+        record("https://gone.example/", &[BOOKMARK_TAG]);
+        record("https://other.example/", &[]);
+        // Stored under the watch page the bookmarked link names
+        record(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            &[BOOKMARK_TAG],
+        );
+
+        let plan = plan(&bookmarks, &storage).unwrap();
+        assert_eq!(plan.new, [bookmarks[1].clone()]);
+        assert_eq!(plan.removed, ["https://gone.example/"]);
+        assert_eq!(plan.restored, ["https://back.example/"]);
+    }
+}
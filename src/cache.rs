@@ -0,0 +1,108 @@
+//! Cache of LLM summaries keyed by what was sent.
+//!
+//! Summarising the same text again with the same prompt and model gives the
+//! same answer, so the result is kept in a sled database under the cache
+//! directory and reused instead of calling the LLM. Everything here can be
+//! wiped; `summera summarise --force` skips it.
+
+use crate::agent::Summarised;
+use crate::config::Config;
+use crate::storage::StorageError;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Summaries already produced, keyed by [`SummaryCache::key`].
+///
+/// sled allows one open handle per process, so concurrent summaries share one.
+#[derive(Debug)]
+pub struct SummaryCache {
+    db: sled::Db,
+}
+
+impl SummaryCache {
+    /// Open or create the cache at the given path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+
+    /// Key for summarising `text` with `config`: a SHA-256 hash of the text and of
+    /// everything in the config that changes the prompt or who answers it.
+    ///
+    /// The hash is stable across builds and platforms, unlike std's hasher, so
+    /// the cache stays valid when summera is upgraded.
+    pub fn key(text: &str, config: &Config) -> String {
+        let agent = &config.agent;
+        // Encoded as JSON so no two sets of values run together the same way
+        let sent = serde_json::to_vec(&(
+            text,
+            agent.persona(),
+            &agent.prompt,
+            agent.length,
+            &agent.output_language,
+            &agent.provider,
+            &agent.model,
+            &agent.fields,
+        ))
+        .expect("strings always serialise");
+        format!("{:x}", Sha256::digest(sent))
+    }
+
+    /// The summary cached under `key`, if any
+    pub fn get(&self, key: &str) -> Result<Option<Summarised>, StorageError> {
+        match self.db.get(key.as_bytes())? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Cache a summary under `key`, replacing any older one
+    pub fn put(&self, key: &str, summarised: &Summarised) -> Result<(), StorageError> {
+        self.db
+            .insert(key.as_bytes(), serde_json::to_vec(summarised)?)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::Summary;
+    use tempfile::tempdir;
+
+    #[test]
+    fn keys_on_text_prompt_and_model() {
+        let dir = tempdir().unwrap();
+        let cache = SummaryCache::open(dir.path()).unwrap();
+        let mut config = Config::default();
+        let key = SummaryCache::key("some text", &config);
+        assert_eq!(key, SummaryCache::key("some text", &config));
+        assert_eq!(key.len(), 64, "a hex SHA-256 digest");
+        assert_ne!(key, SummaryCache::key("other text", &config));
+
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        let summarised = Summarised {
+            summary,
+            provider: "openai/gpt-4o-mini".to_string(),
+            critique: None,
+        };
+        cache.put(&key, &summarised).unwrap();
+        assert_eq!(
+            cache.get(&key).unwrap().unwrap().provider,
+            "openai/gpt-4o-mini"
+        );
+
+        config.agent.model = "gpt-4o".to_string();
+        let other_model = SummaryCache::key("some text", &config);
+        assert_ne!(key, other_model);
+        assert!(cache.get(&other_model).unwrap().is_none());
+
+        config
+            .agent
+            .fields
+            .insert("audience".to_string(), "Who it's for".to_string());
+        assert_ne!(other_model, SummaryCache::key("some text", &config));
+    }
+}
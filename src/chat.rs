@@ -0,0 +1,445 @@
+//! Slack and Discord discussion threads.
+//!
+//! Threads come either from the chat's API, given a link to a message
+//! (`SLACK_TOKEN`, `DISCORD_TOKEN` or `summera login`), or from an exported
+//! JSON file: a Slack export's per-day channel file, a saved
+//! `conversations.replies` response, or a DiscordChatExporter JSON export.
+//! Either way the messages are laid out as a plain transcript.
+
+use crate::config::{Config, ConfigError};
+use crate::scraper::{self, WebContent};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Prompt used instead of `agent.prompt` for chat threads
+pub const THREAD_PROMPT: &str = "Summarise this chat discussion. In the conclusion, state what \
+was decided, if anything, and by whom. List the main points as key points, and add each open \
+question as a key point starting with \"Open question:\". List agreed follow-ups as action \
+items, starting with the owner's name if one was given, e.g. \"Alice: update the runbook\". \
+Ignore greetings, reactions, jokes and bot messages.";
+
+/// Most messages read from a thread or channel
+const MAX_MESSAGES: usize = 1_000;
+
+#[derive(Error, Debug)]
+pub enum ChatError {
+    #[error("no {0} token: set {1} or run `summera login {0}`")]
+    NoToken(&'static str, &'static str, #[source] ConfigError),
+    #[error("failed to fetch the thread: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("{service} returned {message}")]
+    Rejected {
+        service: &'static str,
+        message: String,
+    },
+    #[error("not a Slack or Discord export: {0}")]
+    UnknownExport(String),
+    #[error("the thread has no messages")]
+    NoContent,
+}
+
+/// A chat message, reduced to what a summary needs
+#[derive(Debug, Clone, PartialEq)]
+struct Message {
+    author: String,
+    time: Option<DateTime<Utc>>,
+    text: String,
+}
+
+/// A thread, as named by a link to one of its messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Thread {
+    /// `https://acme.slack.com/archives/C024BE91L/p1700000000123456`
+    Slack { channel: String, ts: String },
+    /// `https://discord.com/channels/<guild>/<channel>[/<message>]`
+    Discord {
+        channel: String,
+        message: Option<String>,
+    },
+}
+
+impl Thread {
+    /// Recognise Slack message links and Discord channel or message links
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("https://")?;
+        let (host, path) = rest.split_once('/')?;
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        if host.ends_with(".slack.com") {
+            let ["archives", channel, message] = segments[..] else {
+                return None;
+            };
+            // Links to replies name the thread's parent in `thread_ts`
+            let ts = match query.split('&').find_map(|p| p.strip_prefix("thread_ts=")) {
+                Some(ts) => ts.to_string(),
+                None => {
+                    let digits = message.strip_prefix('p')?;
+                    if digits.len() <= 6 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                        return None;
+                    }
+                    let (seconds, micros) = digits.split_at(digits.len() - 6);
+                    format!("{}.{}", seconds, micros)
+                }
+            };
+            return Some(Self::Slack {
+                channel: channel.to_string(),
+                ts,
+            });
+        }
+
+        if matches!(host, "discord.com" | "discordapp.com" | "ptb.discord.com") {
+            let (channel, message) = match segments[..] {
+                ["channels", _guild, channel] => (channel, None),
+                ["channels", _guild, channel, message] => (channel, Some(message.to_string())),
+                _ => return None,
+            };
+            return Some(Self::Discord {
+                channel: channel.to_string(),
+                message,
+            });
+        }
+        None
+    }
+
+    /// Fetch the thread's messages as a transcript
+    pub async fn fetch(&self, url: &str, config: &Config) -> Result<WebContent, ChatError> {
+        let messages = match self {
+            Self::Slack { channel, ts } => {
+                let token = config
+                    .token_for("slack")
+                    .map_err(|e| ChatError::NoToken("slack", "SLACK_TOKEN", e))?;
+                fetch_slack(channel, ts, &token).await?
+            }
+            Self::Discord { channel, message } => {
+                let token = config
+                    .token_for("discord")
+                    .map_err(|e| ChatError::NoToken("discord", "DISCORD_TOKEN", e))?;
+                fetch_discord(channel, message.as_deref(), &token).await?
+            }
+        };
+        if messages.is_empty() {
+            return Err(ChatError::NoContent);
+        }
+        Ok(WebContent {
+            url: url.split('#').next().unwrap_or(url).to_string(),
+            title: None,
+            text: render(&messages),
+            description: None,
+        })
+    }
+}
+
+/// Read the replies to a Slack message, naming each author
+async fn fetch_slack(channel: &str, ts: &str, token: &str) -> Result<Vec<Message>, ChatError> {
+    let client = scraper::create_client()?;
+    let mut raw = Vec::new();
+    let mut cursor = String::new();
+    loop {
+        let response: Value = client
+            .get("https://slack.com/api/conversations.replies")
+            .bearer_auth(token)
+            .query(&[
+                ("channel", channel),
+                ("ts", ts),
+                ("limit", "200"),
+                ("cursor", &cursor),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+        slack_ok(&response)?;
+        raw.extend(response["messages"].as_array().cloned().unwrap_or_default());
+        match response["response_metadata"]["next_cursor"].as_str() {
+            Some(next) if !next.is_empty() && raw.len() < MAX_MESSAGES => cursor = next.to_string(),
+            _ => break,
+        }
+    }
+
+    // Messages only carry user IDs; look each author up once
+    let mut names: HashMap<String, String> = HashMap::new();
+    for message in &raw {
+        let Some(user) = message["user"].as_str() else {
+            continue;
+        };
+        if names.contains_key(user) {
+            continue;
+        }
+        let response: Value = client
+            .get("https://slack.com/api/users.info")
+            .bearer_auth(token)
+            .query(&[("user", user)])
+            .send()
+            .await?
+            .json()
+            .await?;
+        if let Some(name) = slack_display_name(&response["user"]) {
+            names.insert(user.to_string(), name);
+        }
+    }
+    Ok(raw
+        .iter()
+        .filter_map(|m| slack_message(m, &names))
+        .collect())
+}
+
+/// Turn Slack's `{"ok": false, "error": ...}` into an error
+fn slack_ok(response: &Value) -> Result<(), ChatError> {
+    if response["ok"].as_bool() == Some(true) {
+        return Ok(());
+    }
+    Err(ChatError::Rejected {
+        service: "Slack",
+        message: response["error"].as_str().unwrap_or("an error").to_string(),
+    })
+}
+
+/// A Slack user's display name, falling back to their real name
+fn slack_display_name(user: &Value) -> Option<String> {
+    let profile = &user["profile"];
+    [
+        &profile["display_name"],
+        &profile["real_name"],
+        &user["real_name"],
+        &user["name"],
+    ]
+    .into_iter()
+    .filter_map(Value::as_str)
+    .find(|name| !name.is_empty())
+    .map(str::to_string)
+}
+
+/// A Slack message, from the API or an export, unless it's a join or similar
+fn slack_message(message: &Value, names: &HashMap<String, String>) -> Option<Message> {
+    if matches!(
+        message["subtype"].as_str(),
+        Some("channel_join" | "channel_leave" | "channel_topic" | "channel_purpose")
+    ) {
+        return None;
+    }
+    let user = message["user"].as_str();
+    let author = user
+        .and_then(|id| names.get(id).cloned())
+        .or_else(|| slack_display_name(&serde_json::json!({ "profile": message["user_profile"] })))
+        .or_else(|| message["username"].as_str().map(str::to_string))
+        .or_else(|| user.map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string());
+    let time = message["ts"]
+        .as_str()
+        .and_then(|ts| ts.split('.').next()?.parse().ok())
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0));
+    let mut text = message["text"].as_str()?.to_string();
+    // Mentions look like <@U024BE7LH>
+    for (id, name) in names {
+        text = text.replace(&format!("<@{}>", id), &format!("@{}", name));
+    }
+    Some(Message { author, time, text })
+}
+
+/// Read a Discord thread, or the messages around a linked message
+async fn fetch_discord(
+    channel: &str,
+    message: Option<&str>,
+    token: &str,
+) -> Result<Vec<Message>, ChatError> {
+    let client = scraper::create_client()?;
+    let get = |url: String| {
+        client
+            .get(url)
+            .header(reqwest::header::AUTHORIZATION, format!("Bot {}", token))
+            .send()
+    };
+    const API: &str = "https://discord.com/api/v10";
+
+    // A thread started from a message shares that message's ID
+    let thread = message.unwrap_or(channel);
+    let mut response = get(format!("{}/channels/{}/messages?limit=100", API, thread)).await?;
+    if let (Some(message), reqwest::StatusCode::NOT_FOUND) = (message, response.status()) {
+        response = get(format!(
+            "{}/channels/{}/messages?around={}&limit=100",
+            API, channel, message
+        ))
+        .await?;
+    }
+
+    let mut raw: Vec<Value> = Vec::new();
+    loop {
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ChatError::Rejected {
+                service: "Discord",
+                message: format!("{}: {}", status, response.text().await.unwrap_or_default()),
+            });
+        }
+        let page: Vec<Value> = response.json().await?;
+        let oldest = page
+            .last()
+            .and_then(|m| m["id"].as_str())
+            .map(str::to_string);
+        let full = page.len() == 100;
+        raw.extend(page);
+        // `around` is one page by design; thread pages go back in time
+        match oldest {
+            Some(before) if full && message.is_none() && raw.len() < MAX_MESSAGES => {
+                response = get(format!(
+                    "{}/channels/{}/messages?before={}&limit=100",
+                    API, thread, before
+                ))
+                .await?;
+            }
+            _ => break,
+        }
+    }
+    // Newest first from the API
+    raw.reverse();
+    Ok(raw.iter().filter_map(discord_message).collect())
+}
+
+/// A Discord message, from the API or a DiscordChatExporter export
+fn discord_message(message: &Value) -> Option<Message> {
+    let author = &message["author"];
+    let author = [
+        &author["global_name"],
+        &author["nickname"],
+        &author["username"],
+        &author["name"],
+    ]
+    .into_iter()
+    .filter_map(Value::as_str)
+    .find(|name| !name.is_empty())
+    .unwrap_or("unknown")
+    .to_string();
+    let time = message["timestamp"]
+        .as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc));
+    let text = message["content"].as_str()?.to_string();
+    Some(Message { author, time, text })
+}
+
+/// Read an exported thread or channel, returning its title (if it has one) and transcript
+pub fn read_export(json: &str) -> Result<(Option<String>, String), ChatError> {
+    let export: Value =
+        serde_json::from_str(json).map_err(|e| ChatError::UnknownExport(e.to_string()))?;
+    let (title, messages) = match export {
+        // Slack's export has one array of messages per channel per day
+        Value::Array(ref messages) => (None, slack_messages(messages)),
+        Value::Object(ref object) if object.contains_key("guild") => {
+            let title = format!(
+                "{} #{}",
+                export["guild"]["name"].as_str().unwrap_or("Discord"),
+                export["channel"]["name"].as_str().unwrap_or("channel")
+            );
+            let messages = object["messages"].as_array().cloned().unwrap_or_default();
+            (
+                Some(title),
+                messages.iter().filter_map(discord_message).collect(),
+            )
+        }
+        // A saved conversations.replies or conversations.history response
+        Value::Object(ref object) if object.contains_key("messages") => (
+            None,
+            slack_messages(object["messages"].as_array().map_or(&[][..], Vec::as_slice)),
+        ),
+        _ => {
+            return Err(ChatError::UnknownExport(
+                "expected a Slack or DiscordChatExporter JSON file".to_string(),
+            ))
+        }
+    };
+    if messages.iter().all(|m| m.text.trim().is_empty()) {
+        return Err(ChatError::NoContent);
+    }
+    Ok((title, render(&messages)))
+}
+
+/// Slack messages in time order; exports name authors in `user_profile`
+fn slack_messages(messages: &[Value]) -> Vec<Message> {
+    let mut messages: Vec<Message> = messages
+        .iter()
+        .filter_map(|m| slack_message(m, &HashMap::new()))
+        .collect();
+    messages.sort_by_key(|m| m.time);
+    messages
+}
+
+/// One line per message: `[2024-01-02 10:00] Alice: text`
+fn render(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .filter(|m| !m.text.trim().is_empty())
+        .map(|m| match m.time {
+            Some(time) => format!(
+                "[{}] {}: {}",
+                time.format("%Y-%m-%d %H:%M"),
+                m.author,
+                m.text
+            ),
+            None => format!("{}: {}", m.author, m.text),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_thread_links() {
+        assert_eq!(
+            Thread::parse("https://acme.slack.com/archives/C024BE91L/p1700000000123456"),
+            Some(Thread::Slack {
+                channel: "C024BE91L".to_string(),
+                ts: "1700000000.123456".to_string()
+            })
+        );
+        assert_eq!(
+            Thread::parse(
+                "https://acme.slack.com/archives/C1/p1700000009000000?thread_ts=1700000000.123456&cid=C1"
+            ),
+            Some(Thread::Slack {
+                channel: "C1".to_string(),
+                ts: "1700000000.123456".to_string()
+            })
+        );
+        assert_eq!(
+            Thread::parse("https://discord.com/channels/1/22/333"),
+            Some(Thread::Discord {
+                channel: "22".to_string(),
+                message: Some("333".to_string())
+            })
+        );
+        assert_eq!(Thread::parse("https://acme.slack.com/customize"), None);
+        assert_eq!(Thread::parse("https://discord.com/invite/rust"), None);
+    }
+
+    #[test]
+    fn reads_exports() {
+        let slack = r#"[
+            {"type":"message","user":"U2","text":"Sounds good, ship it","ts":"1700000100.000200",
+             "user_profile":{"display_name":"","real_name":"Bo"}},
+            {"type":"message","subtype":"channel_join","user":"U3","text":"joined","ts":"1700000000.000050"},
+            {"type":"message","user":"U1","text":"Shall we ship?","ts":"1700000000.000100",
+             "user_profile":{"display_name":"ann","real_name":"Ann"}}
+        ]"#;
+        let (title, text) = read_export(slack).unwrap();
+        assert_eq!(title, None);
+        assert_eq!(
+            text,
+            "[2023-11-14 22:13] ann: Shall we ship?\n[2023-11-14 22:15] Bo: Sounds good, ship it"
+        );
+
+        let discord = r#"{"guild":{"name":"Rust"},"channel":{"name":"help"},"messages":[
+            {"timestamp":"2024-01-02T10:00:00+00:00","content":"Why?","author":{"name":"cy","nickname":"Cy"}}
+        ]}"#;
+        let (title, text) = read_export(discord).unwrap();
+        assert_eq!(title.as_deref(), Some("Rust #help"));
+        assert_eq!(text, "[2024-01-02 10:00] Cy: Why?");
+
+        assert!(read_export(r#"{"name":"package"}"#).is_err());
+    }
+}
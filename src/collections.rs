@@ -0,0 +1,78 @@
+//! Named collections of summaries, e.g. "job hunt" or "thesis".
+//!
+//! Unlike tags, a collection is an ordered list of URLs kept in its own sled
+//! tree, so summaries stay in the order they were added and a collection can
+//! be renamed or deleted without touching the summaries in it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A named, ordered group of stored summaries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    /// URLs of the summaries in the collection, in the order they were added
+    #[serde(default)]
+    pub urls: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Collection {
+    /// An empty collection, with surrounding whitespace trimmed from `name`
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            urls: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Add a summary at the end, returning whether it wasn't already in the collection
+    pub fn add(&mut self, url: &str) -> bool {
+        if self.contains(url) {
+            return false;
+        }
+        self.urls.push(url.to_string());
+        true
+    }
+
+    /// Take a summary out, returning whether it was in the collection
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.urls.len();
+        self.urls.retain(|u| u != url);
+        self.urls.len() != before
+    }
+
+    /// Whether the summary for `url` is in the collection
+    pub fn contains(&self, url: &str) -> bool {
+        self.urls.iter().any(|u| u == url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Storage, StoredSummary, Summary};
+
+    #[test]
+    fn keeps_members_in_order_and_forgets_deleted_summaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let mut collection = Collection::new("  job hunt ");
+        assert_eq!(collection.name, "job hunt");
+        assert!(collection.add("https://b.example"));
+        assert!(collection.add("https://a.example"));
+        assert!(!collection.add("https://b.example"));
+        storage.put_collection(&collection).unwrap();
+
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        let stored = StoredSummary::new("https://b.example".to_string(), summary);
+        storage.put(&stored).unwrap();
+        storage.delete(&stored.url).unwrap();
+
+        let loaded = storage.collection("job hunt").unwrap().unwrap();
+        assert_eq!(loaded.urls, ["https://a.example"]);
+        assert!(storage.delete_collection("job hunt").unwrap());
+        assert!(storage.collections().unwrap().is_empty());
+    }
+}
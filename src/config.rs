@@ -2,11 +2,36 @@
 //!
 //! Loads settings from `summera.toml` with environment variable overrides for sensitive data.
 //! If no config file exists, creates a default one in `~/.config/summera/summera.toml`.
+//!
+//! In portable mode (`--portable` or `SUMMA_HOME`) the config, data, cache and state
+//! all live in a single home directory instead.
+//!
+//! A `.summa/` directory in the current directory or any parent (found the same way
+//! git finds `.git`) overrides the storage paths with a per-project archive.
 
+use crate::icons::IconStyle;
+use crate::postprocess::{StyleFix, SummaryField};
+use crate::schedule::Schedule;
+use crate::tags::UrlPattern;
+use crate::tasks::TaskBackend;
+use crate::theme::ThemePreset;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
 use thiserror::Error;
 
+/// Environment variables that select a portable home directory
+const HOME_ENV_VARS: [&str; 2] = ["SUMMA_HOME", "SUMMERA_HOME"];
+
+/// Name of the per-project archive directory
+pub const PROJECT_DIR_NAME: &str = ".summa";
+
+/// Portable home selected on the command line, taking precedence over the environment
+static PORTABLE_HOME: OnceLock<PathBuf> = OnceLock::new();
+
 /// Default persona for the LLM agent
 const DEFAULT_PERSONA: &str =
     "You are a senior research assistant specializing in technical synthesis.";
@@ -25,8 +50,19 @@ pub enum ConfigError {
     SerializeError(#[from] toml::ser::Error),
     #[error("missing required API key for provider: {0}")]
     MissingApiKey(String),
+    #[error("keyring error: {0}")]
+    KeyringError(#[from] keyring::Error),
+    #[error("{0} is on a site [domains] keeps from cloud providers")]
+    Blocked(String),
+    #[error("no local provider (ollama or a localhost base_url) is configured for sites [domains] keeps from cloud providers")]
+    NoLocalProvider,
+    #[error("invalid config: {0}")]
+    Invalid(String),
 }
 
+/// Service name connector tokens are stored under in the OS keyring
+const KEYRING_SERVICE: &str = "summera";
+
 /// LLM provider configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
@@ -39,9 +75,108 @@ pub struct AgentConfig {
     /// System persona for the agent
     #[serde(default = "default_persona")]
     pub persona: String,
+    /// Built-in persona used instead of `persona`: "executive", "eli5", "technical" or "skeptical"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona_preset: Option<PersonaPreset>,
     /// Prompt template for summarisation
     #[serde(default = "default_prompt")]
     pub prompt: String,
+    /// How long summaries are: "brief", "standard" or "detailed"
+    #[serde(default)]
+    pub length: SummaryLength,
+    /// Language to write summaries in, e.g. "Swedish" or "de", whatever the source's language
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_language: Option<String>,
+    /// Longest text, in bytes, sent in one request; longer text is summarised in chunks
+    #[serde(default = "default_chunk_chars")]
+    pub chunk_chars: usize,
+    /// How much of the previous chunk, in bytes, each chunk repeats for context
+    #[serde(default = "default_chunk_overlap")]
+    pub chunk_overlap: usize,
+    /// Context window overrides in tokens, keyed by model name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub context_limits: BTreeMap<String, usize>,
+    /// How rate limited or failed requests are retried before falling back
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Providers to try, in order, when the ones before are rate limited or down
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback: Vec<ProviderConfig>,
+    /// Summarise offline by quoting key sentences when no provider can be reached
+    #[serde(default = "default_true")]
+    pub extractive_fallback: bool,
+    /// Extra summary fields to fill in, keyed by name, with what each should hold
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// Scoring each summary against its source, and rewriting poorly scored ones
+    #[serde(default)]
+    pub critique: CritiqueConfig,
+    /// Only use local providers, for a source `[domains]` keeps from cloud ones
+    #[serde(skip)]
+    pub local_only: bool,
+}
+
+/// One entry in the provider fallback chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    /// LLM provider: "gemini", "openai" or "ollama"
+    pub provider: String,
+    /// Model identifier (defaults to a sensible model for the provider)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// API endpoint, for Ollama or other OpenAI-compatible servers
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+impl ProviderConfig {
+    /// Whether requests stay on this machine: Ollama, or a server on localhost
+    pub fn is_local(&self) -> bool {
+        match self.base_url {
+            Some(ref url) => is_local_url(url),
+            None => self.provider == "ollama",
+        }
+    }
+}
+
+/// Whether `url` points at this machine
+fn is_local_url(url: &str) -> bool {
+    matches!(
+        crate::storage::url_domain(url),
+        Some("localhost" | "127.0.0.1" | "::1")
+    )
+}
+
+impl AgentConfig {
+    /// The persona to write as: the preset's, if one is chosen, else `persona`
+    pub fn persona(&self) -> &str {
+        match self.persona_preset {
+            Some(preset) => preset.persona(),
+            None => &self.persona,
+        }
+    }
+
+    /// Context window of the configured model, in tokens
+    pub fn context_limit(&self) -> usize {
+        self.context_limits
+            .get(&self.model)
+            .copied()
+            .unwrap_or_else(|| crate::tokens::default_context_limit(&self.model))
+    }
+
+    /// The configured provider followed by the fallback chain, leaving out cloud
+    /// providers when `local_only` is set
+    pub fn providers(&self) -> Vec<ProviderConfig> {
+        let primary = ProviderConfig {
+            provider: self.provider.clone(),
+            model: Some(self.model.clone()),
+            base_url: None,
+        };
+        std::iter::once(primary)
+            .chain(self.fallback.iter().cloned())
+            .filter(|provider| !self.local_only || provider.is_local())
+            .collect()
+    }
 }
 
 fn default_provider() -> String {
@@ -60,13 +195,240 @@ fn default_prompt() -> String {
     DEFAULT_PROMPT.to_string()
 }
 
+fn default_chunk_chars() -> usize {
+    48_000
+}
+
+fn default_chunk_overlap() -> usize {
+    2_000
+}
+
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
             provider: default_provider(),
             model: default_model(),
             persona: default_persona(),
+            persona_preset: None,
             prompt: default_prompt(),
+            length: SummaryLength::default(),
+            output_language: None,
+            chunk_chars: default_chunk_chars(),
+            chunk_overlap: default_chunk_overlap(),
+            context_limits: BTreeMap::new(),
+            retry: RetryConfig::default(),
+            fallback: Vec::new(),
+            extractive_fallback: true,
+            fields: BTreeMap::new(),
+            critique: CritiqueConfig::default(),
+            local_only: false,
+        }
+    }
+}
+
+/// Built-in personas, each writing for a different reader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PersonaPreset {
+    /// Impact and decisions first, for a busy reader
+    Executive,
+    /// Plain words and analogies, for a newcomer
+    Eli5,
+    /// Precise detail, for engineers
+    Technical,
+    /// What the text claims and what it fails to support
+    #[serde(alias = "sceptical")]
+    Skeptical,
+}
+
+impl PersonaPreset {
+    pub const ALL: [Self; 4] = [
+        Self::Executive,
+        Self::Eli5,
+        Self::Technical,
+        Self::Skeptical,
+    ];
+
+    /// The persona sent to the LLM
+    pub fn persona(self) -> &'static str {
+        match self {
+            Self::Executive => {
+                "You are a chief of staff briefing a busy executive. Lead with the decision, \
+                 impact or risk, keep it short and free of jargon, and make clear what needs \
+                 their attention."
+            }
+            Self::Eli5 => {
+                "You are a patient teacher explaining things to a curious newcomer. Use everyday \
+                 words and short sentences, explain any technical term you can't avoid, and use \
+                 a simple analogy where it helps."
+            }
+            Self::Technical => {
+                "You are a senior engineer writing for other engineers. Keep the precise \
+                 technical detail: names of APIs, algorithms, versions, figures and trade-offs, \
+                 and don't simplify."
+            }
+            Self::Skeptical => {
+                "You are a sceptical reviewer. Summarise what the text claims, and point out \
+                 claims without evidence, weak reasoning, conflicts of interest and what the \
+                 author leaves out."
+            }
+        }
+    }
+
+    /// The next preset, or `None` after the last one, for cycling in the TUI
+    pub fn next(preset: Option<Self>) -> Option<Self> {
+        match preset {
+            None => Some(Self::ALL[0]),
+            Some(preset) => {
+                let i = Self::ALL.iter().position(|p| *p == preset).unwrap_or(0);
+                Self::ALL.get(i + 1).copied()
+            }
+        }
+    }
+}
+
+impl FromStr for PersonaPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "executive" => Ok(Self::Executive),
+            "eli5" => Ok(Self::Eli5),
+            "technical" => Ok(Self::Technical),
+            "skeptical" | "sceptical" => Ok(Self::Skeptical),
+            other => Err(format!(
+                "unknown persona preset '{other}' (expected executive, eli5, technical or skeptical)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PersonaPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Executive => write!(f, "executive"),
+            Self::Eli5 => write!(f, "eli5"),
+            Self::Technical => write!(f, "technical"),
+            Self::Skeptical => write!(f, "skeptical"),
+        }
+    }
+}
+
+/// Summary length preset: how many key points are asked for and how much detail
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryLength {
+    /// A one-sentence conclusion and a few key points
+    Brief,
+    /// A short conclusion and a handful of key points
+    #[default]
+    Standard,
+    /// A fuller conclusion and key points with their supporting detail
+    Detailed,
+}
+
+impl SummaryLength {
+    /// Fewest and most key points asked for
+    pub fn key_points(self) -> (usize, usize) {
+        match self {
+            Self::Brief => (2, 3),
+            Self::Standard => (3, 7),
+            Self::Detailed => (8, 12),
+        }
+    }
+
+    /// Instruction added to the prompt
+    pub fn instructions(self) -> String {
+        let (min, max) = self.key_points();
+        let style = match self {
+            Self::Brief => "Keep it brief: a one-sentence conclusion and short key points.",
+            Self::Standard => "Keep the conclusion to one or two sentences.",
+            Self::Detailed => {
+                "Be thorough: a conclusion of two or three sentences, and key points that \
+                 each carry their supporting detail or example."
+            }
+        };
+        format!("{} Give between {} and {} key points.", style, min, max)
+    }
+
+    /// The next preset, for cycling through them in the TUI
+    pub fn next(self) -> Self {
+        match self {
+            Self::Brief => Self::Standard,
+            Self::Standard => Self::Detailed,
+            Self::Detailed => Self::Brief,
+        }
+    }
+}
+
+impl FromStr for SummaryLength {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "brief" => Ok(Self::Brief),
+            "standard" => Ok(Self::Standard),
+            "detailed" => Ok(Self::Detailed),
+            other => Err(format!(
+                "unknown summary length '{other}' (expected brief, standard or detailed)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SummaryLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Brief => write!(f, "brief"),
+            Self::Standard => write!(f, "standard"),
+            Self::Detailed => write!(f, "detailed"),
+        }
+    }
+}
+
+/// Retry policy for transient LLM failures (rate limits, overloaded or unreachable servers)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Attempts per provider, including the first; 1 disables retries
+    pub max_attempts: u32,
+    /// Wait before the first retry, in milliseconds; doubled on each retry after it
+    pub initial_delay_ms: u64,
+    /// Longest wait between attempts, in milliseconds
+    pub max_delay_ms: u64,
+    /// Randomise each wait to between half and all of it, so clients don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 1_000,
+            max_delay_ms: 30_000,
+            jitter: true,
+        }
+    }
+}
+
+/// Settings for the self-critique pass that scores summaries for faithfulness and coverage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CritiqueConfig {
+    /// Score every summary with a second request
+    pub enabled: bool,
+    /// Summaries scoring below this, from 1 to 5, are written again
+    pub min_score: u8,
+    /// Most times a poorly scored summary is written again; the best version is kept
+    pub regenerations: u32,
+}
+
+impl Default for CritiqueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_score: 3,
+            regenerations: 1,
         }
     }
 }
@@ -78,29 +440,537 @@ pub struct ApiConfig {
     pub gemini_key: Option<String>,
     #[serde(default)]
     pub openai_key: Option<String>,
+    /// Token for pushing action items to Todoist
+    #[serde(default)]
+    pub todoist_token: Option<String>,
+    /// Token for opening GitHub issues from action items and reading issue and PR threads
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// Stack Exchange API key, which raises the daily quota for reading questions
+    #[serde(default)]
+    pub stackexchange_key: Option<String>,
+    /// Atlassian API token (Cloud) or personal access token (Data Center) for Confluence
+    #[serde(default)]
+    pub confluence_token: Option<String>,
+    /// Notion internal integration secret
+    #[serde(default)]
+    pub notion_token: Option<String>,
+    /// Slack bot or user token with the `channels:history` and `users:read` scopes
+    #[serde(default)]
+    pub slack_token: Option<String>,
+    /// Discord bot token; the bot needs the Read Message History permission
+    #[serde(default)]
+    pub discord_token: Option<String>,
+    /// Readwise access token, for exporting highlights
+    #[serde(default)]
+    pub readwise_token: Option<String>,
+    /// X API bearer token for reading threads; without it they're read through Nitter
+    #[serde(default)]
+    pub x_bearer_token: Option<String>,
+    /// Secret the compliance ledger is signed with
+    #[serde(default)]
+    pub ledger_key: Option<String>,
 }
 
-/// Storage paths configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Storage paths configuration.
+///
+/// Follows the XDG split: `path` holds the data worth backing up (summaries),
+/// `cache_path` holds anything that can be rebuilt and wiped safely (the search
+/// index, HTTP and LLM caches), and `state_path` holds session state and history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StorageConfig {
     /// Base path for data storage
     pub path: PathBuf,
+    /// Directory for rebuildable caches (defaults to the platform cache dir)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_path: Option<PathBuf>,
+    /// Directory for session state and history (defaults to the platform state dir)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_path: Option<PathBuf>,
+}
+
+/// Fallback base directory when the platform doesn't define one
+fn home_fallback() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".summera")
 }
 
 impl Default for StorageConfig {
     fn default() -> Self {
         let default_path = dirs::data_dir()
-            .unwrap_or_else(|| {
-                dirs::home_dir()
-                    .unwrap_or_else(|| PathBuf::from("."))
-                    .join(".summera")
-            })
+            .unwrap_or_else(home_fallback)
             .join("summera_data");
 
-        Self { path: default_path }
+        Self {
+            path: default_path,
+            cache_path: None,
+            state_path: None,
+        }
+    }
+}
+
+impl StorageConfig {
+    /// Directory for rebuildable caches
+    pub fn cache_dir(&self) -> PathBuf {
+        self.cache_path.clone().unwrap_or_else(|| {
+            dirs::cache_dir()
+                .unwrap_or_else(|| home_fallback().join("cache"))
+                .join("summera")
+        })
+    }
+
+    /// Directory for session state and history
+    pub fn state_dir(&self) -> PathBuf {
+        self.state_path.clone().unwrap_or_else(|| {
+            // Only Linux defines a state dir; elsewhere keep it next to local data
+            dirs::state_dir()
+                .or_else(dirs::data_local_dir)
+                .unwrap_or_else(|| home_fallback().join("state"))
+                .join("summera")
+        })
+    }
+
+    /// Paths relative to a portable home directory
+    fn portable() -> Self {
+        Self {
+            path: PathBuf::from("data"),
+            cache_path: Some(PathBuf::from("cache")),
+            state_path: Some(PathBuf::from("state")),
+        }
+    }
+
+    /// Storage kept inside a per-project `.summa/` directory
+    pub fn project(dir: &Path) -> Self {
+        Self {
+            path: dir.join("data"),
+            cache_path: Some(dir.join("cache")),
+            state_path: Some(dir.join("state")),
+        }
+    }
+
+    /// Make relative paths absolute by joining them onto `base`
+    fn resolve_relative_to(&mut self, base: &Path) {
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        };
+        resolve(&mut self.path);
+        if let Some(ref mut cache_path) = self.cache_path {
+            resolve(cache_path);
+        }
+        if let Some(ref mut state_path) = self.state_path {
+            resolve(state_path);
+        }
+    }
+
+    /// Location of the tantivy search index
+    pub fn search_index_path(&self) -> PathBuf {
+        self.cache_dir().join("search_index")
+    }
+
+    /// Location of the cache of LLM summaries
+    pub fn summary_cache_path(&self) -> PathBuf {
+        self.cache_dir().join("llm_cache")
+    }
+}
+
+/// Where `summera push` sends action items
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TasksConfig {
+    /// Backend used when `--to` isn't given
+    #[serde(default)]
+    pub default: Option<TaskBackend>,
+    #[serde(default)]
+    pub todoist: TodoistConfig,
+    #[serde(default)]
+    pub github: GithubTasksConfig,
+    #[serde(default)]
+    pub taskwarrior: TaskwarriorConfig,
+}
+
+/// Where Confluence lives; the token comes from `CONFLUENCE_API_TOKEN` or the keyring
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ConfluenceConfig {
+    /// Base URL of a self-hosted instance, e.g. "https://wiki.example.com";
+    /// `*.atlassian.net` sites are recognised without it
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Atlassian account email, for Cloud; without it the token is sent as a bearer token
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+/// Wikipedia article settings
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WikipediaConfig {
+    /// Keep the external links an article cites with its summary
+    #[serde(default)]
+    pub references: bool,
+}
+
+/// Social thread settings; the X API token comes from `X_BEARER_TOKEN`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialConfig {
+    /// Nitter instances to read X threads from when there's no API token, tried in order
+    #[serde(default = "default_nitter_instances")]
+    pub nitter_instances: Vec<String>,
+}
+
+impl Default for SocialConfig {
+    fn default() -> Self {
+        Self {
+            nitter_instances: default_nitter_instances(),
+        }
+    }
+}
+
+fn default_nitter_instances() -> Vec<String> {
+    vec!["https://nitter.net".to_string()]
+}
+
+/// Personal data masked in extracted text before it's sent to an LLM
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RedactConfig {
+    /// Mask email addresses
+    #[serde(default)]
+    pub emails: bool,
+    /// Mask phone numbers
+    #[serde(default)]
+    pub phone_numbers: bool,
+    /// Words and names to mask wherever they appear as whole words, ignoring case
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub terms: Vec<String>,
+    /// Regular expressions to mask, e.g. employee or customer numbers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+/// Which sites' pages may be sent to cloud LLM providers.
+///
+/// Local files and text have no site and are never blocked.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DomainsConfig {
+    /// Sites kept from cloud providers; subdomains match too
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// When not empty, the only sites that may be sent to cloud providers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// What happens to a page kept from cloud providers: "refuse" or "local"
+    #[serde(default)]
+    pub blocked: BlockedAction,
+}
+
+/// What happens to a page `[domains]` keeps from cloud providers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockedAction {
+    /// Don't send it to any LLM
+    #[default]
+    Refuse,
+    /// Send it to local providers only
+    Local,
+}
+
+impl DomainsConfig {
+    /// Whether the text at `url` may be sent to a cloud provider
+    pub fn allows(&self, url: &str) -> bool {
+        let Some(host) = crate::storage::url_domain(url) else {
+            return true;
+        };
+        let host = host.to_lowercase();
+        let listed = |sites: &[String]| {
+            sites.iter().any(|site| {
+                let site = site.trim_start_matches("www.").to_lowercase();
+                host == site || host.ends_with(&format!(".{}", site))
+            })
+        };
+        !listed(&self.deny) && (self.allow.is_empty() || listed(&self.allow))
+    }
+
+    /// Whether the text at `url` may be sent to a provider that is local or not
+    pub fn allows_provider(&self, url: &str, local: bool) -> bool {
+        self.allows(url) || (local && self.blocked == BlockedAction::Local)
+    }
+}
+
+/// Where new summaries are mirrored outside the archive
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SyncConfig {
+    /// Path of a daily note to append each new summary to, with `strftime`
+    /// fields for the date, e.g. "~/notes/%Y-%m-%d.md"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_note: Option<String>,
+    /// Exported browser bookmarks (`bookmarks.html`) that `summera bookmarks` syncs from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bookmarks: Option<PathBuf>,
+    /// Append-only file every summary made or refined is signed into, for audits
+    /// (see [`crate::ledger`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<PathBuf>,
+}
+
+/// Backup archives made by `summera backup` and on a schedule by `summera daemon`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// When the daemon makes a backup: five cron fields, or @daily, @weekly or @monthly
+    #[serde(default = "default_backup_schedule")]
+    pub schedule: Schedule,
+    /// How many archives to keep; older ones are pruned after each backup
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+    /// Directory for the archives (defaults to `summera_backups` next to the storage)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+}
+
+fn default_backup_schedule() -> Schedule {
+    "@weekly".parse().expect("default schedule is valid")
+}
+
+fn default_backup_keep() -> usize {
+    8
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            schedule: default_backup_schedule(),
+            keep: default_backup_keep(),
+            dir: None,
+        }
     }
 }
 
+impl BackupConfig {
+    /// Directory the archives are written to
+    pub fn dir(&self, storage: &StorageConfig) -> PathBuf {
+        self.dir.clone().unwrap_or_else(|| {
+            storage
+                .path
+                .parent()
+                .unwrap_or(&storage.path)
+                .join("summera_backups")
+        })
+    }
+}
+
+/// Settings for `summera daemon`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DaemonConfig {
+    /// Address to answer `GET /healthz` on, e.g. "127.0.0.1:8787" (off when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_addr: Option<String>,
+}
+
+/// Embedding model for semantic search; the API key is the provider's from `[api]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsConfig {
+    /// Embedding provider: "openai", "gemini" or "ollama"
+    #[serde(default = "default_embeddings_provider")]
+    pub provider: String,
+    /// Model identifier (defaults to a sensible model for the provider)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// API endpoint, for Ollama or other OpenAI-compatible servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_embeddings_provider(),
+            model: None,
+            base_url: None,
+        }
+    }
+}
+
+impl EmbeddingsConfig {
+    /// The configured model, or the provider's default
+    pub fn model(&self) -> &str {
+        match (&self.model, self.provider.as_str()) {
+            (Some(model), _) => model,
+            (None, "gemini") => "text-embedding-004",
+            (None, "ollama") => "nomic-embed-text",
+            (None, _) => "text-embedding-3-small",
+        }
+    }
+
+    /// Whether embeddings are made on this machine, as with Ollama
+    pub fn is_local(&self) -> bool {
+        match self.base_url {
+            Some(ref url) => is_local_url(url),
+            None => self.provider == "ollama",
+        }
+    }
+}
+
+fn default_embeddings_provider() -> String {
+    "openai".to_string()
+}
+
+/// Todoist settings; the token comes from `TODOIST_API_TOKEN`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TodoistConfig {
+    /// Project to add tasks to (the inbox if unset)
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// GitHub Issues settings; the token comes from `GITHUB_TOKEN`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GithubTasksConfig {
+    /// Repository to open issues in, as "owner/name"
+    #[serde(default)]
+    pub repo: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// Taskwarrior settings
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TaskwarriorConfig {
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Notification settings for saved search alerts and background jobs
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NotificationConfig {
+    /// URL that receives a JSON POST for every alert
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// More webhooks, each with its own payload shape
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Desktop notification settings
+    #[serde(default)]
+    pub desktop: DesktopNotificationConfig,
+}
+
+/// A webhook whose request body is rendered from a template, so one alert can
+/// be shaped for Zapier, IFTTT, Slack and the like without middleware
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Body with `{{field}}` placeholders (JSON-escaped) or `{{{field}}}` (inserted as-is);
+    /// without one the default JSON payload is sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Content-Type of the rendered body
+    #[serde(default = "default_webhook_content_type")]
+    pub content_type: String,
+}
+
+fn default_webhook_content_type() -> String {
+    "application/json".to_string()
+}
+
+/// Which events raise a desktop notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesktopNotificationConfig {
+    /// Master switch for desktop notifications (off by default)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Notify when a summarisation job completes
+    #[serde(default = "default_true")]
+    pub job_completed: bool,
+    /// Notify when a summarisation job fails
+    #[serde(default = "default_true")]
+    pub job_failed: bool,
+    /// Notify when a saved search alert fires
+    #[serde(default = "default_true")]
+    pub alert: bool,
+    /// Notify when snoozed summaries come back
+    #[serde(default = "default_true")]
+    pub reminder: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DesktopNotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            job_completed: true,
+            job_failed: true,
+            alert: true,
+            reminder: true,
+        }
+    }
+}
+
+/// TUI appearance settings
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UiConfig {
+    /// Colour theme preset: "default", "high-contrast" or "mono"
+    #[serde(default)]
+    pub theme: ThemePreset,
+    /// Use ASCII section markers instead of emoji
+    #[serde(default)]
+    pub ascii: bool,
+    /// Source icons before list entries: "none" or "nerd-font"
+    #[serde(default)]
+    pub icons: IconStyle,
+    /// List starred summaries first, in a group of their own
+    #[serde(default)]
+    pub starred_first: bool,
+}
+
+/// A named archive that can be switched to without restarting
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub name: String,
+    pub storage: StorageConfig,
+}
+
+/// Tags applied to a summary at store time when every condition given matches.
+///
+/// A rule without conditions never matches.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TagRule {
+    /// Tags to add
+    pub tags: Vec<String>,
+    /// Source domain; subdomains match too
+    pub domain: Option<String>,
+    /// Regular expression matched against the source URL or file key
+    pub url: Option<UrlPattern>,
+    /// Detected language of the text, as an ISO 639-3 code ("deu") or English name ("German")
+    pub language: Option<String>,
+    /// Entity the summary must mention, ignoring case
+    pub entity: Option<String>,
+}
+
+/// A house-style edit applied to new summaries before they're stored.
+///
+/// Set one of `command`, `builtin` or `pattern`, checked in that order; a rule
+/// with none of them does nothing.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessRule {
+    /// Regular expression to replace
+    pub pattern: Option<String>,
+    /// Replacement for `pattern`, which may refer to groups as `$1` or `${name}`
+    #[serde(default)]
+    pub replace: String,
+    /// One of the built-in fixes
+    pub builtin: Option<StyleFix>,
+    /// Program that reads the summary as JSON on stdin and prints the edited JSON
+    pub command: Option<String>,
+    /// Fields `pattern` and `builtin` edit; all of them when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<SummaryField>,
+}
+
 /// Root configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -110,11 +980,71 @@ pub struct Config {
     pub api: ApiConfig,
     #[serde(default)]
     pub storage: StorageConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub tasks: TasksConfig,
+    #[serde(default)]
+    pub confluence: ConfluenceConfig,
+    #[serde(default)]
+    pub wikipedia: WikipediaConfig,
+    #[serde(default)]
+    pub social: SocialConfig,
+    #[serde(default)]
+    pub redact: RedactConfig,
+    #[serde(default)]
+    pub domains: DomainsConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub embeddings: EmbeddingsConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// Extra archives by name, each laid out like a `.summa/` directory
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspaces: BTreeMap<String, PathBuf>,
+    /// Rules that tag new summaries automatically when they're stored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_rules: Vec<TagRule>,
+    /// House-style edits applied to new summaries, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub postprocess: Vec<PostProcessRule>,
+    /// Output templates by name, for `--template` (see [`crate::template`])
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, String>,
+    /// The configured storage when a project archive has replaced it
+    #[serde(skip)]
+    global_storage: Option<StorageConfig>,
 }
 
 impl Config {
+    /// Keep everything in `home` for the rest of the process (used by `--portable`).
+    ///
+    /// Has no effect if a portable home was already set.
+    pub fn use_portable_home(home: PathBuf) {
+        let _ = PORTABLE_HOME.set(home);
+    }
+
+    /// The portable home directory, if portable mode is active
+    pub fn portable_home() -> Option<PathBuf> {
+        PORTABLE_HOME.get().cloned().or_else(|| {
+            HOME_ENV_VARS
+                .iter()
+                .filter_map(std::env::var_os)
+                .find(|value| !value.is_empty())
+                .map(PathBuf::from)
+        })
+    }
+
     /// Get the default config directory path
     pub fn config_dir() -> PathBuf {
+        if let Some(home) = Self::portable_home() {
+            return home;
+        }
         dirs::config_dir()
             .unwrap_or_else(|| {
                 dirs::home_dir()
@@ -132,7 +1062,46 @@ impl Config {
     /// Load configuration from the default location, creating it if it doesn't exist
     pub fn load() -> Result<Self, ConfigError> {
         let config_path = Self::find_config_file()?;
-        Self::load_from(&config_path)
+        let mut config = Self::load_from(&config_path)?;
+        if let Some(home) = Self::portable_home() {
+            config.storage.resolve_relative_to(&home);
+            for dir in config.workspaces.values_mut() {
+                if dir.is_relative() {
+                    *dir = home.join(&*dir);
+                }
+            }
+        }
+        if let Some(project) = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_project_dir(&cwd))
+        {
+            let global = std::mem::replace(&mut config.storage, StorageConfig::project(&project));
+            config.global_storage = Some(global);
+        }
+        Ok(config)
+    }
+
+    /// Every archive available to switch between: the global one, the current
+    /// project's `.summa/` (if any) and the configured `[workspaces]`
+    pub fn available_workspaces(&self) -> Vec<Workspace> {
+        let mut workspaces = vec![Workspace {
+            name: "global".to_string(),
+            storage: self
+                .global_storage
+                .clone()
+                .unwrap_or_else(|| self.storage.clone()),
+        }];
+        if self.global_storage.is_some() {
+            workspaces.push(Workspace {
+                name: "project".to_string(),
+                storage: self.storage.clone(),
+            });
+        }
+        workspaces.extend(self.workspaces.iter().map(|(name, dir)| Workspace {
+            name: name.clone(),
+            storage: StorageConfig::project(dir),
+        }));
+        workspaces
     }
 
     /// Load configuration from a specific path
@@ -147,15 +1116,58 @@ impl Config {
         if let Ok(key) = std::env::var("OPENAI_API_KEY") {
             config.api.openai_key = Some(key);
         }
+        if let Ok(token) = std::env::var("TODOIST_API_TOKEN") {
+            config.api.todoist_token = Some(token);
+        }
+        if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+            config.api.github_token = Some(token);
+        }
+        if let Ok(key) = std::env::var("STACKEXCHANGE_KEY") {
+            config.api.stackexchange_key = Some(key);
+        }
+        if let Ok(token) = std::env::var("CONFLUENCE_API_TOKEN") {
+            config.api.confluence_token = Some(token);
+        }
+        if let Ok(token) = std::env::var("NOTION_TOKEN") {
+            config.api.notion_token = Some(token);
+        }
+        if let Ok(token) = std::env::var("SLACK_TOKEN") {
+            config.api.slack_token = Some(token);
+        }
+        if let Ok(token) = std::env::var("DISCORD_TOKEN") {
+            config.api.discord_token = Some(token);
+        }
+        if let Ok(token) = std::env::var("READWISE_TOKEN") {
+            config.api.readwise_token = Some(token);
+        }
+        if let Ok(token) = std::env::var("X_BEARER_TOKEN") {
+            config.api.x_bearer_token = Some(token);
+        }
+        if let Ok(key) = std::env::var("SUMMA_LEDGER_KEY") {
+            config.api.ledger_key = Some(key);
+        }
 
+        config.validate()?;
         Ok(config)
     }
 
-    /// Find the config file, creating a default one if it doesn't exist
-    fn find_config_file() -> Result<PathBuf, ConfigError> {
-        // Check current directory first
+    /// Check settings that parse but can't be used, so a mistake is reported when
+    /// the config is loaded rather than when the setting is first needed
+    fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(ref template) = self.sync.daily_note {
+            crate::daily_note::check_template(template)
+                .map_err(|e| ConfigError::Invalid(format!("[sync] daily_note {}", e)))?;
+        }
+        crate::postprocess::check(&self.postprocess)
+            .map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Find the config file [`Config::load`] reads, creating a default one if it doesn't exist
+    pub fn find_config_file() -> Result<PathBuf, ConfigError> {
+        // Check current directory first, unless everything is kept in a portable home
         let local_config = PathBuf::from("summera.toml");
-        if local_config.exists() {
+        if Self::portable_home().is_none() && local_config.exists() {
             return Ok(local_config);
         }
 
@@ -175,7 +1187,11 @@ impl Config {
         let config_dir = Self::config_dir();
         std::fs::create_dir_all(&config_dir)?;
 
-        let default_config = Config::default();
+        let mut default_config = Config::default();
+        if Self::portable_home().is_some() {
+            // Relative paths keep the home movable (e.g. a USB stick with a new drive letter)
+            default_config.storage = StorageConfig::portable();
+        }
         let config_content = toml::to_string_pretty(&default_config)?;
 
         let config_path = Self::config_file_path();
@@ -186,9 +1202,21 @@ impl Config {
         Ok(())
     }
 
+    /// Create a `.summa/` project archive in `dir`, returning its path
+    pub fn init_project(dir: &Path) -> Result<PathBuf, ConfigError> {
+        let project = dir.join(PROJECT_DIR_NAME);
+        std::fs::create_dir_all(&project)?;
+        Ok(project)
+    }
+
     /// Get the API key for the configured provider
     pub fn api_key(&self) -> Result<&str, ConfigError> {
-        match self.agent.provider.as_str() {
+        self.api_key_for(&self.agent.provider)
+    }
+
+    /// Get the API key for a provider
+    pub fn api_key_for(&self, provider: &str) -> Result<&str, ConfigError> {
+        match provider {
             "gemini" => self
                 .api
                 .gemini_key
@@ -202,4 +1230,180 @@ impl Config {
             other => Err(ConfigError::MissingApiKey(other.to_string())),
         }
     }
+
+    /// This config with the agent prompt swapped, for sources such as discussion
+    /// threads that need different instructions than articles
+    pub fn with_prompt(&self, prompt: &str) -> Config {
+        let mut config = self.clone();
+        config.agent.prompt = prompt.to_string();
+        config
+    }
+
+    /// This config for sending the text of `urls` to an LLM, following `[domains]`:
+    /// as it is, limited to local providers, or refused
+    pub fn for_sources<'a>(
+        &self,
+        urls: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Config, ConfigError> {
+        let mut config = self.clone();
+        for url in urls {
+            if self.domains.allows(url) {
+                continue;
+            }
+            match self.domains.blocked {
+                BlockedAction::Refuse => return Err(ConfigError::Blocked(url.to_string())),
+                BlockedAction::Local => config.agent.local_only = true,
+            }
+        }
+        if config.agent.providers().is_empty() {
+            return Err(ConfigError::NoLocalProvider);
+        }
+        Ok(config)
+    }
+
+    /// Get the token for a connector ("confluence", "notion", "slack", "discord" or "readwise"),
+    /// or the ledger key, from the config or environment, or else from the OS keyring
+    /// (see `summera login`)
+    pub fn token_for(&self, connector: &str) -> Result<String, ConfigError> {
+        let configured = match connector {
+            "confluence" => self.api.confluence_token.as_ref(),
+            "notion" => self.api.notion_token.as_ref(),
+            "slack" => self.api.slack_token.as_ref(),
+            "discord" => self.api.discord_token.as_ref(),
+            "readwise" => self.api.readwise_token.as_ref(),
+            "ledger" => self.api.ledger_key.as_ref(),
+            _ => None,
+        };
+        if let Some(token) = configured {
+            return Ok(token.clone());
+        }
+        match keyring::Entry::new(KEYRING_SERVICE, connector)?.get_password() {
+            Ok(token) => Ok(token),
+            Err(keyring::Error::NoEntry) => Err(ConfigError::MissingApiKey(connector.to_string())),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Save a connector token in the OS keyring
+pub fn store_token(connector: &str, token: &str) -> Result<(), ConfigError> {
+    keyring::Entry::new(KEYRING_SERVICE, connector)?.set_password(token)?;
+    Ok(())
+}
+
+/// Remove a connector token from the OS keyring, returning whether there was one
+pub fn remove_token(connector: &str) -> Result<bool, ConfigError> {
+    match keyring::Entry::new(KEYRING_SERVICE, connector)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Find the nearest `.summa/` directory in `start` or one of its parents
+pub fn find_project_dir(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_DIR_NAME))
+        .find(|candidate| candidate.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_project_dir_in_parent() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("notes").join("drafts");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_dir(&nested), None);
+
+        let project = Config::init_project(root.path()).unwrap();
+        assert_eq!(find_project_dir(&nested), Some(project.clone()));
+
+        let storage = StorageConfig::project(&project);
+        assert_eq!(storage.path, project.join("data"));
+        assert_eq!(
+            storage.search_index_path(),
+            project.join("cache/search_index")
+        );
+    }
+
+    #[test]
+    fn lists_configured_workspaces_after_global() {
+        let config: Config = toml::from_str(
+            r#"
+            [storage]
+            path = "/archive/data"
+
+            [workspaces]
+            thesis = "/research/thesis"
+            "#,
+        )
+        .unwrap();
+
+        let workspaces = config.available_workspaces();
+        let names: Vec<&str> = workspaces.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, ["global", "thesis"]);
+        assert_eq!(workspaces[0].storage, config.storage);
+        assert_eq!(
+            workspaces[1].storage.path,
+            PathBuf::from("/research/thesis/data")
+        );
+    }
+
+    #[test]
+    fn keeps_blocked_sites_from_cloud_providers() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [agent]
+            provider = "openai"
+
+            [[agent.fallback]]
+            provider = "ollama"
+
+            [domains]
+            deny = ["intranet.example.com"]
+            "#,
+        )
+        .unwrap();
+        let page = "https://wiki.intranet.example.com/page";
+        assert!(matches!(
+            config.for_sources(["/notes/today.md", page]),
+            Err(ConfigError::Blocked(url)) if url == page
+        ));
+        assert_eq!(
+            config
+                .for_sources(["https://example.com/"])
+                .unwrap()
+                .agent
+                .providers()
+                .len(),
+            2
+        );
+
+        config.domains.blocked = BlockedAction::Local;
+        let local = config.for_sources([page]).unwrap();
+        let providers: Vec<String> = local
+            .agent
+            .providers()
+            .into_iter()
+            .map(|p| p.provider)
+            .collect();
+        assert_eq!(providers, ["ollama"]);
+        assert!(config.domains.allows_provider(page, true));
+        assert!(!config.domains.allows_provider(page, false));
+        config.embeddings.base_url = Some("http://[::1]:11434/v1".into());
+        assert!(config.embeddings.is_local());
+
+        config.domains.allow = vec!["example.org".into()];
+        assert!(config.domains.allows("https://docs.example.org/"));
+        assert!(!config.domains.allows("https://example.net/"));
+        config.agent.fallback.clear();
+        assert!(matches!(
+            config.for_sources(["https://example.net/"]),
+            Err(ConfigError::NoLocalProvider)
+        ));
+    }
 }
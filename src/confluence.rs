@@ -0,0 +1,173 @@
+//! Confluence pages via the REST API.
+//!
+//! Confluence renders pages in the browser, so scraping them yields little but
+//! navigation. Pages are read from the content API instead, authenticated with
+//! an API token (Cloud, together with the account email) or a personal access
+//! token (Data Center).
+
+use crate::config::{Config, ConfigError, ConfluenceConfig};
+use crate::scraper::{self, WebContent};
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfluenceError {
+    #[error("no Confluence token: set CONFLUENCE_API_TOKEN or run `summera login confluence`")]
+    NoToken(#[source] ConfigError),
+    #[error("failed to fetch from Confluence: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("Confluence returned {status} for {url}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        url: String,
+    },
+    #[error("Confluence page is empty")]
+    NoContent,
+}
+
+/// A Confluence page, as named by its URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// Root of the site, which the REST API hangs off, e.g. "https://acme.atlassian.net/wiki"
+    pub base_url: String,
+    pub id: String,
+}
+
+impl Page {
+    /// Recognise `.../spaces/KEY/pages/123/Title` and `.../pages/viewpage.action?pageId=123`
+    /// links on `*.atlassian.net` or the configured `confluence.base_url`
+    pub fn parse(url: &str, config: &ConfluenceConfig) -> Option<Self> {
+        let (page_url, query) = url.split_once('?').unwrap_or((url, ""));
+        let page_url = page_url.split('#').next()?;
+        let (base_url, id) = if let Some(pos) = page_url.find("/spaces/") {
+            let mut segments = page_url[pos..].split('/').skip(3);
+            if segments.next()? != "pages" {
+                return None;
+            }
+            (&page_url[..pos], segments.next()?.to_string())
+        } else if let Some(base_url) = page_url.strip_suffix("/pages/viewpage.action") {
+            let id = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("pageId="))?;
+            (base_url, id.to_string())
+        } else {
+            return None;
+        };
+        if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let known = match config.base_url {
+            Some(ref configured) => base_url.starts_with(configured.trim_end_matches('/')),
+            None => {
+                let rest = base_url.strip_prefix("https://")?;
+                let host = rest.split('/').next()?;
+                host.ends_with(".atlassian.net")
+            }
+        };
+        known.then(|| Self {
+            base_url: base_url.to_string(),
+            id,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    title: String,
+    space: Option<Space>,
+    body: Body,
+}
+
+#[derive(Debug, Deserialize)]
+struct Space {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Body {
+    view: Rendered,
+}
+
+/// Page body as HTML
+#[derive(Debug, Deserialize)]
+struct Rendered {
+    value: String,
+}
+
+/// Fetch a page as text, with macros rendered
+pub async fn fetch_page(page: &Page, config: &Config) -> Result<WebContent, ConfluenceError> {
+    let token = config
+        .token_for("confluence")
+        .map_err(ConfluenceError::NoToken)?;
+    let url = format!(
+        "{}/rest/api/content/{}?expand=body.view,space",
+        page.base_url, page.id
+    );
+    let request = scraper::create_client()?.get(&url);
+    let request = match config.confluence.email {
+        Some(ref email) => request.basic_auth(email, Some(&token)),
+        None => request.bearer_auth(&token),
+    };
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ConfluenceError::Rejected { status, url });
+    }
+    let content: Content = response.json().await?;
+
+    let text = scraper::html_to_text(&content.body.view.value);
+    if text.trim().is_empty() {
+        return Err(ConfluenceError::NoContent);
+    }
+    let title = match content.space {
+        Some(space) => format!("{} ({})", content.title, space.name),
+        None => content.title,
+    };
+    Ok(WebContent {
+        url: format!("{}/pages/viewpage.action?pageId={}", page.base_url, page.id),
+        title: Some(title),
+        text,
+        description: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_page_urls() {
+        let cloud = ConfluenceConfig::default();
+        let page = Page::parse(
+            "https://acme.atlassian.net/wiki/spaces/ENG/pages/98304/On-call+guide#Escalation",
+            &cloud,
+        )
+        .unwrap();
+        assert_eq!(page.base_url, "https://acme.atlassian.net/wiki");
+        assert_eq!(page.id, "98304");
+        assert_eq!(
+            Page::parse(
+                "https://wiki.example.com/pages/viewpage.action?pageId=42",
+                &cloud
+            ),
+            None
+        );
+
+        let server = ConfluenceConfig {
+            base_url: Some("https://wiki.example.com/".to_string()),
+            email: None,
+        };
+        let page = Page::parse(
+            "https://wiki.example.com/pages/viewpage.action?spaceKey=OPS&pageId=42",
+            &server,
+        )
+        .unwrap();
+        assert_eq!(page.base_url, "https://wiki.example.com");
+        assert_eq!(page.id, "42");
+        assert_eq!(
+            Page::parse("https://wiki.example.com/spaces/OPS/overview", &server),
+            None
+        );
+    }
+}
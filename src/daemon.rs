@@ -0,0 +1,474 @@
+//! `summera daemon`: jobs that run in the background on a timer.
+//!
+//! Once a minute a worker thread runs a round of jobs: it wakes snoozed
+//! summaries that are due, with a desktop notification, keeps the search index
+//! in step with storage, and makes a backup when the `[backup]` schedule says
+//! one is due. Storage is only opened while a round runs, since sled allows one
+//! process at a time; a round that finds it busy (say, with the TUI open) is
+//! skipped and tried again on the next tick.
+//!
+//! A watchdog reports a worker that stays stuck in a round. The thread can't be
+//! stopped and keeps storage open, so it's left to finish while `/healthz` turns
+//! unhealthy, letting a service manager restart the daemon. Failing jobs, stuck
+//! workers and panics are recorded as incidents in the activity log.
+//! With `[daemon] health_addr` set, `GET /healthz` reports how the jobs are
+//! doing, answering 503 when the daemon is unhealthy.
+//!
+//! Edits to the config file are picked up between rounds, except for the
+//! storage location and the `[daemon]` section, which need a restart.
+
+use crate::activity::Event;
+use crate::backup;
+use crate::reload::ConfigWatcher;
+use crate::storage::StorageError;
+use crate::{notify, snooze, Config, SearchIndex, Storage};
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// How often the daemon checks for due jobs
+const TICK: Duration = Duration::from_secs(60);
+
+/// How long a round may run before its worker counts as stuck
+const STUCK_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// How long to wait before trying a failed backup again
+const BACKUP_RETRY_MINUTES: i64 = 60;
+
+/// Failures in a row after which a job makes the daemon unhealthy
+const FAILING_AFTER: u32 = 3;
+
+/// A job and how it went: the lines it has to report, or why it failed
+type Outcome = (Job, Result<Vec<String>, String>);
+
+/// A round of jobs running on its own thread, handing back the jobs and how they went
+type Worker = JoinHandle<(Jobs, Vec<Outcome>)>;
+
+/// Jobs the daemon runs each round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    /// Waking snoozed summaries that are due
+    Snooze,
+    /// Keeping the search index in step with storage
+    Index,
+    /// Scheduled backups
+    Backup,
+    /// The worker thread running the other jobs
+    Worker,
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Snooze => write!(f, "snooze"),
+            Self::Index => write!(f, "index"),
+            Self::Backup => write!(f, "backup"),
+            Self::Worker => write!(f, "worker"),
+        }
+    }
+}
+
+/// How a job went the last times it ran
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobHealth {
+    pub last_ok: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Failures since it last succeeded
+    pub failures: u32,
+}
+
+/// What `/healthz` reports
+#[derive(Debug, Clone, Serialize)]
+pub struct Health {
+    pub started_at: DateTime<Utc>,
+    /// When the last round of jobs finished, or found storage busy
+    pub last_round: Option<DateTime<Utc>>,
+    /// Whether the last round skipped its jobs because another process, such as
+    /// the TUI, had storage open
+    pub storage_busy: bool,
+    pub next_backup: Option<DateTime<Local>>,
+    /// By job name
+    pub jobs: BTreeMap<String, JobHealth>,
+    /// Incidents since the daemon started
+    pub incidents: u32,
+}
+
+impl Health {
+    fn new(next_backup: Option<DateTime<Local>>) -> Self {
+        Self {
+            started_at: Utc::now(),
+            last_round: None,
+            storage_busy: false,
+            next_backup,
+            jobs: BTreeMap::new(),
+            incidents: 0,
+        }
+    }
+
+    /// Whether rounds are finishing and no job keeps failing
+    pub fn is_healthy(&self, now: DateTime<Utc>) -> bool {
+        let since = self.last_round.unwrap_or(self.started_at);
+        let overdue = now - since > chrono::Duration::from_std(STUCK_AFTER + TICK * 2).unwrap();
+        !overdue && self.jobs.values().all(|job| job.failures < FAILING_AFTER)
+    }
+
+    /// Note how `job` went, returning an incident if it has just started failing
+    fn record(&mut self, job: Job, result: &Result<Vec<String>, String>) -> Option<Event> {
+        let health = self.jobs.entry(job.to_string()).or_default();
+        match result {
+            Ok(_) => {
+                health.last_ok = Some(Utc::now());
+                health.failures = 0;
+                None
+            }
+            Err(e) => {
+                health.last_error = Some(e.clone());
+                health.failures += 1;
+                (health.failures == 1).then(|| self.incident(job, e.clone()))
+            }
+        }
+    }
+
+    fn incident(&mut self, job: Job, detail: String) -> Event {
+        self.incidents += 1;
+        Event::incident(job, detail)
+    }
+}
+
+/// Background jobs and what they carry from one round to the next
+#[derive(Clone)]
+struct Jobs {
+    config: Config,
+    /// When the next backup is due, or `None` if the schedule never fires again
+    next_backup: Option<DateTime<Local>>,
+    /// Incidents waiting to be written to the activity log
+    incidents: Vec<Event>,
+}
+
+impl Jobs {
+    /// Run the jobs due at `now`, with the lines each one has to report, or nothing
+    /// if storage is busy
+    fn round(&mut self, now: DateTime<Local>) -> Vec<Outcome> {
+        let storage = match Storage::open(&self.config.storage.path) {
+            Ok(storage) => storage,
+            Err(StorageError::DbError(e)) if is_locked(&e) => return Vec::new(),
+            Err(e) => return vec![(Job::Worker, Err(format!("can't open storage: {}", e)))],
+        };
+        let mut logged = Vec::new();
+        for incident in &self.incidents {
+            if storage.log_event(incident).is_err() {
+                break;
+            }
+            logged.push(incident.clone());
+        }
+        self.incidents.retain(|incident| !logged.contains(incident));
+
+        let mut results = vec![
+            (Job::Snooze, self.wake_snoozed(&storage, now)),
+            (Job::Index, self.sync_index(&storage)),
+        ];
+        if self.next_backup.is_some_and(|due| due <= now) {
+            results.push((Job::Backup, self.back_up(&storage, now)));
+        }
+        results
+    }
+
+    fn wake_snoozed(&self, storage: &Storage, now: DateTime<Local>) -> Result<Vec<String>, String> {
+        let woken =
+            snooze::wake_due(storage, now.with_timezone(&Utc)).map_err(|e| e.to_string())?;
+        if woken.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut lines = vec![format!("{} snoozed summaries are back", woken.len())];
+        if let Err(e) = notify::remind(&self.config.notifications, &woken) {
+            lines.push(format!("Reminder notification failed: {}", e));
+        }
+        Ok(lines)
+    }
+
+    fn sync_index(&self, storage: &Storage) -> Result<Vec<String>, String> {
+        let index = SearchIndex::open_synced(self.config.storage.search_index_path(), storage)
+            .map_err(|e| e.to_string())?;
+        Ok(match index.rebuilt() {
+            true => vec![format!(
+                "Rebuilt the search index from {} summaries",
+                storage.count()
+            )],
+            false => Vec::new(),
+        })
+    }
+
+    /// Switch to an edited `config`, keeping the storage and `[daemon]` settings the
+    /// daemon started with
+    fn reload(&mut self, mut config: Config) -> Result<(), backup::BackupError> {
+        config.storage = self.config.storage.clone();
+        config.daemon = self.config.daemon.clone();
+        self.next_backup = next_backup(&config)?;
+        self.config = config;
+        Ok(())
+    }
+
+    fn back_up(&mut self, storage: &Storage, now: DateTime<Local>) -> Result<Vec<String>, String> {
+        match backup::run(storage, &self.config, now) {
+            Ok(report) => {
+                self.next_backup = self.config.backup.schedule.next_after(now);
+                let mut lines = vec![format!("Backed up to {}", report.archive.display())];
+                for path in report.pruned {
+                    lines.push(format!("Pruned {}", path.display()));
+                }
+                Ok(lines)
+            }
+            Err(e) => {
+                self.next_backup = Some(now + chrono::Duration::minutes(BACKUP_RETRY_MINUTES));
+                Err(e.to_string())
+            }
+        }
+    }
+}
+
+/// Runs background jobs until Ctrl-C
+pub struct Daemon {
+    jobs: Jobs,
+    health: Arc<Mutex<Health>>,
+}
+
+impl Daemon {
+    /// A daemon for `config`, with a backup due at once if none has been made yet
+    pub fn new(config: Config) -> Result<Self, backup::BackupError> {
+        let next_backup = next_backup(&config)?;
+        Ok(Self {
+            jobs: Jobs {
+                config,
+                next_backup,
+                incidents: Vec::new(),
+            },
+            health: Arc::new(Mutex::new(Health::new(next_backup))),
+        })
+    }
+
+    /// When the next backup is due
+    pub fn next_backup(&self) -> Option<DateTime<Local>> {
+        self.jobs.next_backup
+    }
+
+    /// Run rounds of jobs until Ctrl-C, passing each line they report to `report`
+    ///
+    /// The config file is reloaded when it changes, after the round in progress.
+    pub async fn run(self, report: impl Fn(&str)) {
+        let Self { mut jobs, health } = self;
+        if let Some(ref addr) = jobs.config.daemon.health_addr {
+            match TcpListener::bind(addr).await {
+                Ok(listener) => {
+                    report(&format!("Serving http://{}/healthz", addr));
+                    tokio::spawn(serve_health(listener, health.clone()));
+                }
+                Err(e) => report(&format!("Can't serve health checks on {}: {}", addr, e)),
+            }
+        }
+
+        let mut watcher = Config::find_config_file()
+            .ok()
+            .and_then(|path| ConfigWatcher::new(&path).ok());
+        if watcher.is_none() {
+            report("Can't watch the config file; restart to apply changes to it");
+        }
+        // An edited config, checked and waiting for the round in progress to finish
+        let mut reloaded: Option<Config> = None;
+
+        let mut interval = tokio::time::interval(TICK);
+        let mut worker: Option<(Instant, Worker)> = None;
+        let mut stuck_reported = false;
+        let incident = |jobs: &mut Jobs, detail: String| {
+            report(&format!("Incident: {}", detail));
+            let event = health.lock().unwrap().incident(Job::Worker, detail);
+            jobs.incidents.push(event);
+        };
+        loop {
+            if worker.is_none() {
+                if let Some(config) = reloaded.take() {
+                    match jobs.reload(config) {
+                        Ok(()) => {
+                            report("Config reloaded");
+                            health.lock().unwrap().next_backup = jobs.next_backup;
+                        }
+                        Err(e) => {
+                            report(&format!("Config not reloaded, keeping the old one: {}", e))
+                        }
+                    }
+                }
+            }
+            tokio::select! {
+                _ = interval.tick() => {
+                    match worker {
+                        // Let a round in progress finish, unless it has been going too long
+                        Some((started, _)) if started.elapsed() <= STUCK_AFTER => continue,
+                        Some((started, _)) => {
+                            // The thread can't be stopped, and a new worker would only
+                            // find the storage it holds busy, so it's waited for
+                            if !stuck_reported {
+                                stuck_reported = true;
+                                let minutes = started.elapsed().as_secs() / 60;
+                                incident(
+                                    &mut jobs,
+                                    format!("a round of jobs has been stuck for {} minutes; no jobs run until it finishes or the daemon restarts", minutes),
+                                );
+                            }
+                            continue;
+                        }
+                        None => {}
+                    }
+                    stuck_reported = false;
+                    let mut round = jobs.clone();
+                    worker = Some((
+                        Instant::now(),
+                        tokio::task::spawn_blocking(move || {
+                            let results = round.round(Local::now());
+                            (round, results)
+                        }),
+                    ));
+                }
+                finished = async { (&mut worker.as_mut().unwrap().1).await }, if worker.is_some() => {
+                    worker = None;
+                    match finished {
+                        Ok((next, results)) => {
+                            jobs = next;
+                            record_round(&health, &mut jobs, results, &report);
+                        }
+                        Err(e) => incident(&mut jobs, format!("a round of jobs panicked: {}", e)),
+                    }
+                }
+                _ = async { watcher.as_mut().unwrap().changed().await }, if watcher.is_some() => {
+                    match Config::load() {
+                        Ok(config) => reloaded = Some(config),
+                        Err(e) => report(&format!("Config not reloaded, keeping the old one: {}", e)),
+                    }
+                }
+                _ = tokio::signal::ctrl_c() => return,
+            }
+        }
+    }
+}
+
+/// When the next backup is due under `config`: at once if none has been made yet
+fn next_backup(config: &Config) -> Result<Option<DateTime<Local>>, backup::BackupError> {
+    let dir = config.backup.dir(&config.storage);
+    Ok(match backup::last_made(&dir)? {
+        Some(made) => config.backup.schedule.next_after(made),
+        None => Some(Local::now()),
+    })
+}
+
+/// Note the results of a round in the health report, and report what the jobs did
+fn record_round(
+    health: &Mutex<Health>,
+    jobs: &mut Jobs,
+    results: Vec<Outcome>,
+    report: &impl Fn(&str),
+) {
+    let mut health = health.lock().unwrap();
+    // Nothing ran, but the daemon is only waiting its turn, not stuck
+    health.storage_busy = results.is_empty();
+    for (job, result) in results {
+        if let Some(incident) = health.record(job, &result) {
+            jobs.incidents.push(incident);
+        }
+        match result {
+            Ok(lines) => lines.iter().for_each(|line| report(line)),
+            Err(e) => report(&format!("{} failed: {}", job, e)),
+        }
+    }
+    health.last_round = Some(Utc::now());
+    health.next_backup = jobs.next_backup;
+}
+
+/// Answer `GET /healthz` with the health report as JSON
+async fn serve_health(listener: TcpListener, health: Arc<Mutex<Health>>) {
+    while let Ok((mut stream, _)) = listener.accept().await {
+        let health = health.clone();
+        tokio::spawn(async move {
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&request[..read]);
+            let (status, body) = match request.split_whitespace().nth(1) {
+                Some("/healthz") => {
+                    let health = health.lock().unwrap().clone();
+                    let healthy = health.is_healthy(Utc::now());
+                    let mut body = serde_json::to_value(&health).unwrap_or_default();
+                    body["status"] = if healthy { "ok" } else { "unhealthy" }.into();
+                    let status = match healthy {
+                        true => "200 OK",
+                        false => "503 Service Unavailable",
+                    };
+                    (status, body.to_string())
+                }
+                _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// Whether sled failed because another process holds the database
+fn is_locked(error: &sled::Error) -> bool {
+    // sled reports it only in the message of an I/O error
+    matches!(error, sled::Error::Io(e) if e.to_string().contains("could not acquire lock"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+
+    #[test]
+    fn rounds_back_up_and_record_failing_jobs_as_incidents() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.storage = crate::config::StorageConfig::project(dir.path());
+        let daemon = Daemon::new(config).unwrap();
+        let mut jobs = daemon.jobs.clone();
+
+        let results = jobs.round(Local::now());
+        let ran: Vec<Job> = results.iter().map(|(job, _)| *job).collect();
+        assert_eq!(ran, [Job::Snooze, Job::Index, Job::Backup]);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert!(jobs.next_backup.unwrap() > Local::now());
+
+        let mut health = daemon.health.lock().unwrap();
+        let failed = Err("disk full".to_string());
+        assert!(health.record(Job::Backup, &failed).is_some());
+        assert!(health.record(Job::Backup, &failed).is_none());
+        assert!(health.is_healthy(Utc::now()));
+        health.record(Job::Backup, &failed);
+        assert!(!health.is_healthy(Utc::now()));
+        health.record(Job::Backup, &Ok(Vec::new()));
+        assert!(health.is_healthy(Utc::now()));
+
+        jobs.incidents
+            .push(Event::incident(Job::Backup, "disk full"));
+        jobs.round(Local::now());
+        assert!(jobs.incidents.is_empty());
+        let storage = Storage::open(&jobs.config.storage.path).unwrap();
+        let events = storage.events().unwrap();
+        assert_eq!(events[0].action, Action::Incident);
+        assert_eq!(events[0].url, "daemon:backup");
+
+        // A round that finds storage busy still counts as finished
+        let health = Mutex::new(Health::new(None));
+        health.lock().unwrap().started_at -= chrono::Duration::hours(1);
+        record_round(&health, &mut jobs, Vec::new(), &|_| {});
+        let health = health.lock().unwrap();
+        assert!(health.storage_busy && health.is_healthy(Utc::now()));
+    }
+}
@@ -0,0 +1,115 @@
+//! Appending new summaries to a daily note.
+//!
+//! With `[sync] daily_note` set to a path template such as
+//! `~/notes/%Y-%m-%d.md`, every new summary is added to that day's note as a
+//! Markdown bullet linking to the source, followed by its conclusion. The
+//! note and its directory are created when they don't exist yet.
+
+use crate::storage::StoredSummary;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Check that every `strftime` field in `template` is one chrono knows, since
+/// filling in an unknown one panics
+pub fn check_template(template: &str) -> Result<(), String> {
+    if StrftimeItems::new(template).any(|item| item == Item::Error) {
+        return Err(format!("'{}' has an invalid strftime field", template));
+    }
+    Ok(())
+}
+
+/// Path of the daily note for `now`: `~/` expanded, then `strftime` fields filled in.
+///
+/// `template` must have passed [`check_template`].
+pub fn note_path(template: &str, now: DateTime<Local>) -> PathBuf {
+    let path = now.format(template).to_string();
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// The Markdown bullet a summary is added as
+pub fn entry(stored: &StoredSummary) -> String {
+    let title = stored.summary.title.replace(['[', ']'], "");
+    let link = if stored.url.contains("://") {
+        format!("[{}]({})", title, stored.url.replace(' ', "%20"))
+    } else {
+        format!("{} (`{}`)", title, stored.url)
+    };
+    let conclusion = stored
+        .summary
+        .conclusion
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    if conclusion.is_empty() {
+        format!("- {}\n", link)
+    } else {
+        format!("- {}: {}\n", link, conclusion.join(" "))
+    }
+}
+
+/// Append `stored` to the daily note for `now`, returning the note's path
+pub fn append(
+    template: &str,
+    stored: &StoredSummary,
+    now: DateTime<Local>,
+) -> std::io::Result<PathBuf> {
+    let path = note_path(template, now);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Start on a line of our own if the note doesn't end with a newline
+    let needs_newline = std::fs::read(&path)
+        .map(|note| !note.is_empty() && !note.ends_with(b"\n"))
+        .unwrap_or(false);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    if needs_newline {
+        writeln!(file)?;
+    }
+    file.write_all(entry(stored).as_bytes())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+    use chrono::TimeZone;
+
+    #[test]
+    fn appends_a_bullet_to_the_days_note() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = format!("{}/%Y/%Y-%m-%d.md", dir.path().display());
+        let now = Local.with_ymd_and_hms(2026, 3, 9, 8, 0, 0).unwrap();
+        let summary = Summary::new(
+            "Async [Rust]".into(),
+            "Use Tokio\nfor I/O.".into(),
+            vec![],
+            vec![],
+            vec![],
+        );
+        let stored = StoredSummary::new("https://example.com/a".to_string(), summary);
+
+        let path = dir.path().join("2026/2026-03-09.md");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "# Monday").unwrap();
+        assert_eq!(append(&template, &stored, now).unwrap(), path);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Monday\n- [Async Rust](https://example.com/a): Use Tokio for I/O.\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_strftime_fields() {
+        assert!(check_template("~/notes/%Y-%m-%d.md").is_ok());
+        assert!(check_template("~/notes/%Q.md").is_err());
+        assert!(check_template("~/notes/%").is_err());
+    }
+}
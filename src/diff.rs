@@ -0,0 +1,74 @@
+//! Line diffs between two versions of a page's extracted text.
+//!
+//! Used by `summera diff` to find what changed since a page was last fetched,
+//! so only the changes are sent to the LLM.
+
+use similar::{ChangeTag, TextDiff};
+
+/// Lines added and removed between two versions of a text
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl LineDiff {
+    /// Diff `old` against `new`, ignoring blank lines and surrounding whitespace
+    pub fn between(old: &str, new: &str) -> Self {
+        let old = significant_lines(old);
+        let new = significant_lines(new);
+        let mut diff = Self::default();
+        for change in TextDiff::from_slices(&old, &new).iter_all_changes() {
+            let line = change.value();
+            match change.tag() {
+                ChangeTag::Insert => diff.added.push(line.to_string()),
+                ChangeTag::Delete => diff.removed.push(line.to_string()),
+                ChangeTag::Equal => {}
+            }
+        }
+        diff
+    }
+
+    /// Whether the two versions are the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Render as `-`/`+` prefixed lines, removals first
+    pub fn to_patch(&self) -> String {
+        let removed = self.removed.iter().map(|line| format!("- {}", line));
+        let added = self.added.iter().map(|line| format!("+ {}", line));
+        removed.chain(added).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Trimmed, non-blank lines of `text`
+fn significant_lines(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_added_and_removed_lines() {
+        let old = "Intro\nVersion 1.0 is out\n\nFooter\n";
+        let new = "Intro\nVersion 2.0 is out\nNew pricing\nFooter\n";
+        let diff = LineDiff::between(old, new);
+        assert_eq!(diff.removed, ["Version 1.0 is out"]);
+        assert_eq!(diff.added, ["Version 2.0 is out", "New pricing"]);
+        assert_eq!(
+            diff.to_patch(),
+            "- Version 1.0 is out\n+ Version 2.0 is out\n+ New pricing"
+        );
+    }
+
+    #[test]
+    fn ignores_whitespace_only_changes() {
+        assert!(LineDiff::between("a\n\nb\n", "a\nb  \n").is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+//! Embedding vectors for semantic search.
+//!
+//! Each summary's title, conclusion, key points and entities are embedded with
+//! the model configured under `[embeddings]` and kept in a sled tree next to the
+//! summary. Queries are embedded the same way and ranked by cosine similarity,
+//! so "memory safety" finds a summary about the borrow checker that shares no
+//! words with it. Vectors are computed lazily, for summaries that have none or
+//! whose text or model changed since.
+
+use crate::config::{Config, ConfigError};
+use crate::storage::{Storage, StorageError, StoredSummary};
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+use thiserror::Error;
+
+/// Default endpoint for OpenAI embeddings
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Default endpoint for Ollama's OpenAI-compatible API
+const OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
+
+/// Gemini API endpoint
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Texts sent per embedding request
+const BATCH_SIZE: usize = 64;
+
+/// Longest document embedded, in characters; embedding models have short contexts
+const MAX_DOCUMENT_CHARS: usize = 8000;
+
+#[derive(Error, Debug)]
+pub enum EmbeddingError {
+    #[error("request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("embedding provider rejected the request ({status}): {body}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    #[error("unknown embedding provider: {0}")]
+    UnknownProvider(String),
+    #[error("unexpected embedding response: {0}")]
+    Malformed(String),
+    #[error("config error: {0}")]
+    ConfigError(#[from] ConfigError),
+    #[error("storage error: {0}")]
+    StorageError(#[from] StorageError),
+}
+
+/// The embedding of one stored summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Embedding {
+    pub url: String,
+    /// Model that produced the vector; vectors from different models don't compare
+    pub model: String,
+    /// Hash of the embedded text, to notice when a summary was refined or edited
+    pub digest: String,
+    pub vector: Vec<f32>,
+}
+
+/// A summary's URL and how similar it is to a query, from -1 to 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranked {
+    pub url: String,
+    pub score: f32,
+}
+
+/// The text a summary is embedded as
+pub fn document(stored: &StoredSummary) -> String {
+    let summary = &stored.summary;
+    let mut parts = vec![summary.title.clone(), summary.conclusion.clone()];
+    parts.extend(summary.key_points.iter().cloned());
+    if !summary.entities.is_empty() {
+        parts.push(summary.entity_names().join(", "));
+    }
+    let text = parts
+        .into_iter()
+        .filter(|part| !part.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.chars().take(MAX_DOCUMENT_CHARS).collect()
+}
+
+/// Hash of `text` as embedded by `model`
+pub fn digest(text: &str, model: &str) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    model.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Cosine similarity of two vectors, or 0 when either is empty or their lengths differ
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Rank `embeddings` by similarity to `query`, most similar first, skipping `exclude`
+pub fn rank(
+    query: &[f32],
+    embeddings: &[Embedding],
+    exclude: Option<&str>,
+    limit: usize,
+) -> Vec<Ranked> {
+    let mut ranked: Vec<Ranked> = embeddings
+        .iter()
+        .filter(|e| Some(e.url.as_str()) != exclude && e.vector.len() == query.len())
+        .map(|e| Ranked {
+            url: e.url.clone(),
+            score: cosine(query, &e.vector),
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Embed `texts` with the configured provider, one vector per text in order
+pub async fn embed(texts: &[String], config: &Config) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+    let settings = &config.embeddings;
+    let client = reqwest::Client::new();
+    let mut vectors = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(BATCH_SIZE) {
+        let embedded = match settings.provider.as_str() {
+            "openai" => {
+                let base_url = settings.base_url.as_deref().unwrap_or(OPENAI_BASE_URL);
+                let key = config.api_key_for("openai")?;
+                embed_openai(&client, base_url, Some(key), settings.model(), batch).await?
+            }
+            "ollama" => {
+                let base_url = settings.base_url.as_deref().unwrap_or(OLLAMA_BASE_URL);
+                embed_openai(&client, base_url, None, settings.model(), batch).await?
+            }
+            "gemini" => {
+                let key = config.api_key_for("gemini")?;
+                embed_gemini(&client, key, settings.model(), batch).await?
+            }
+            other => return Err(EmbeddingError::UnknownProvider(other.to_string())),
+        };
+        if embedded.len() != batch.len() {
+            return Err(EmbeddingError::Malformed(format!(
+                "{} vectors for {} texts",
+                embedded.len(),
+                batch.len()
+            )));
+        }
+        vectors.extend(embedded);
+    }
+    Ok(vectors)
+}
+
+/// Embed every stored summary that has no up-to-date embedding, returning how many were.
+///
+/// Summaries of sites `[domains]` keeps from the embedding provider are left out,
+/// so semantic search doesn't find them.
+pub async fn sync(storage: &Storage, config: &Config) -> Result<usize, EmbeddingError> {
+    let model = config.embeddings.model();
+    let local = config.embeddings.is_local();
+    let mut stale = Vec::new();
+    for stored in storage.iter() {
+        let stored = stored?;
+        if !config.domains.allows_provider(&stored.url, local) {
+            continue;
+        }
+        let text = document(&stored);
+        let digest = digest(&text, model);
+        let current = storage
+            .embedding(&stored.url)?
+            .is_some_and(|e| e.digest == digest);
+        if !current && !text.is_empty() {
+            stale.push((stored.url, text, digest));
+        }
+    }
+
+    let texts: Vec<String> = stale.iter().map(|(_, text, _)| text.clone()).collect();
+    let vectors = embed(&texts, config).await?;
+    for ((url, _, digest), vector) in stale.into_iter().zip(vectors) {
+        storage.put_embedding(&Embedding {
+            url,
+            model: model.to_string(),
+            digest,
+            vector,
+        })?;
+    }
+    Ok(texts.len())
+}
+
+/// Stored summaries ranked by how close their meaning is to `query`
+pub async fn semantic_search(
+    storage: &Storage,
+    query: &str,
+    config: &Config,
+    limit: usize,
+) -> Result<Vec<Ranked>, EmbeddingError> {
+    let vector = embed(&[query.to_string()], config).await?.remove(0);
+    Ok(rank(&vector, &current(storage, config)?, None, limit))
+}
+
+/// Stored summaries ranked by how close they are to the one for `url`
+pub fn related(
+    storage: &Storage,
+    url: &str,
+    config: &Config,
+    limit: usize,
+) -> Result<Vec<Ranked>, EmbeddingError> {
+    let Some(embedding) = storage.embedding(url)? else {
+        return Ok(Vec::new());
+    };
+    Ok(rank(
+        &embedding.vector,
+        &current(storage, config)?,
+        Some(url),
+        limit,
+    ))
+}
+
+/// Stored embeddings made with the configured model
+fn current(storage: &Storage, config: &Config) -> Result<Vec<Embedding>, EmbeddingError> {
+    let model = config.embeddings.model();
+    Ok(storage
+        .embeddings()?
+        .into_iter()
+        .filter(|e| e.model == model)
+        .collect())
+}
+
+/// Embed through an OpenAI-compatible `/embeddings` endpoint (OpenAI, Ollama, ...)
+async fn embed_openai(
+    client: &reqwest::Client,
+    base_url: &str,
+    key: Option<&str>,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+    #[derive(Deserialize)]
+    struct Response {
+        data: Vec<Item>,
+    }
+    #[derive(Deserialize)]
+    struct Item {
+        index: usize,
+        embedding: Vec<f32>,
+    }
+
+    let mut request = client
+        .post(format!("{}/embeddings", base_url.trim_end_matches('/')))
+        .json(&serde_json::json!({ "model": model, "input": texts }));
+    if let Some(key) = key {
+        request = request.bearer_auth(key);
+    }
+    let response = check(request.send().await?).await?;
+    let mut items = response.json::<Response>().await?.data;
+    items.sort_by_key(|item| item.index);
+    Ok(items.into_iter().map(|item| item.embedding).collect())
+}
+
+/// Embed through Gemini's `batchEmbedContents`
+async fn embed_gemini(
+    client: &reqwest::Client,
+    key: &str,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, EmbeddingError> {
+    #[derive(Deserialize)]
+    struct Response {
+        embeddings: Vec<Values>,
+    }
+    #[derive(Deserialize)]
+    struct Values {
+        values: Vec<f32>,
+    }
+
+    let requests: Vec<_> = texts
+        .iter()
+        .map(|text| {
+            serde_json::json!({
+                "model": format!("models/{}", model),
+                "content": { "parts": [{ "text": text }] },
+            })
+        })
+        .collect();
+    let response = client
+        .post(format!(
+            "{}/models/{}:batchEmbedContents",
+            GEMINI_BASE_URL, model
+        ))
+        .query(&[("key", key)])
+        .json(&serde_json::json!({ "requests": requests }))
+        .send()
+        .await?;
+    let response = check(response).await?;
+    Ok(response
+        .json::<Response>()
+        .await?
+        .embeddings
+        .into_iter()
+        .map(|e| e.values)
+        .collect())
+}
+
+/// Turn an unsuccessful response into [`EmbeddingError::Rejected`]
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, EmbeddingError> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        Err(EmbeddingError::Rejected {
+            status,
+            body: response.text().await.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::Summary;
+    use tempfile::tempdir;
+
+    fn embedding(url: &str, vector: Vec<f32>) -> Embedding {
+        Embedding {
+            url: url.to_string(),
+            model: "m".to_string(),
+            digest: String::new(),
+            vector,
+        }
+    }
+
+    #[test]
+    fn ranks_by_cosine_similarity() {
+        let embeddings = [
+            embedding("https://a.example", vec![1.0, 0.0]),
+            embedding("https://b.example", vec![0.6, 0.8]),
+            embedding("https://c.example", vec![0.0, 1.0]),
+            embedding("https://d.example", vec![1.0, 0.0, 0.0]),
+        ];
+        let ranked = rank(&[0.0, 2.0], &embeddings, Some("https://c.example"), 5);
+        let urls: Vec<&str> = ranked.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, ["https://b.example", "https://a.example"]);
+        assert!((ranked[0].score - 0.8).abs() < 1e-6);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn embeddings_are_stored_and_deleted_with_their_summary() {
+        let dir = tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let summary = Summary::new("T".into(), "C".into(), vec!["P".into()], vec![], vec![]);
+        let stored = StoredSummary::new("https://a.example".to_string(), summary);
+        assert_eq!(document(&stored), "T\nC\nP");
+        storage.put(&stored).unwrap();
+        storage
+            .put_embedding(&embedding(&stored.url, vec![1.0]))
+            .unwrap();
+        assert_eq!(storage.embeddings().unwrap().len(), 1);
+
+        storage.delete(&stored.url).unwrap();
+        assert!(storage.embedding(&stored.url).unwrap().is_none());
+    }
+}
@@ -0,0 +1,235 @@
+//! Exporting stored summaries to files: JSON, Markdown, CSV or HTML.
+//!
+//! JSON, CSV and HTML put every summary in one document. Markdown writes one
+//! file per summary, with YAML front matter for note-taking apps such as
+//! Obsidian, so an export can be dropped straight into a vault.
+
+use crate::storage::StoredSummary;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// File formats `summera export` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Markdown,
+    Csv,
+    Html,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Markdown => write!(f, "markdown"),
+            Self::Csv => write!(f, "csv"),
+            Self::Html => write!(f, "html"),
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "csv" => Ok(Self::Csv),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "unknown format '{}'; expected json, markdown, csv or html",
+                other
+            )),
+        }
+    }
+}
+
+/// Every summary as a pretty-printed JSON array of records
+pub fn to_json(summaries: &[StoredSummary]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(summaries)
+}
+
+/// One summary as a Markdown note with YAML front matter
+pub fn to_markdown(stored: &StoredSummary) -> String {
+    let summary = &stored.summary;
+    // JSON strings and arrays are valid YAML, and quote whatever needs quoting
+    let quote = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    let list = |values: &[String]| serde_json::to_string(values).unwrap_or_default();
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: {}\n", quote(&summary.title)));
+    out.push_str(&format!("url: {}\n", quote(&stored.url)));
+    out.push_str(&format!("created: {}\n", stored.created_at.to_rfc3339()));
+    out.push_str(&format!("tags: {}\n", list(&stored.tags)));
+    out.push_str(&format!("topics: {}\n", list(&summary.topics)));
+    if let Some(ref provider) = stored.provider {
+        out.push_str(&format!("provider: {}\n", quote(provider)));
+    }
+    if let Some(ref language) = stored.language {
+        out.push_str(&format!("language: {}\n", quote(language)));
+    }
+    if stored.starred {
+        out.push_str("starred: true\n");
+    }
+    out.push_str("---\n\n");
+
+    out.push_str(&summary.to_markdown());
+    if let Some(ref notes) = stored.notes {
+        out.push_str(&format!("\n## Notes\n\n{}\n", notes.trim_end()));
+    }
+    out
+}
+
+/// File name for a summary's Markdown note, e.g. "2026-10-15-async-rust.md"
+pub fn file_name(stored: &StoredSummary) -> String {
+    let slug = stored
+        .summary
+        .title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(8)
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() { "summary" } else { &slug };
+    format!("{}-{}.md", stored.created_at.format("%Y-%m-%d"), slug)
+}
+
+/// Write one Markdown note per summary into `dir`, returning the paths written.
+///
+/// Summaries that would share a file name get a number added instead of
+/// overwriting each other.
+pub fn write_markdown(summaries: &[StoredSummary], dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written: Vec<PathBuf> = Vec::new();
+    for stored in summaries {
+        let name = file_name(stored);
+        let mut path = dir.join(&name);
+        let stem = name.trim_end_matches(".md");
+        let mut n = 2;
+        while written.contains(&path) {
+            path = dir.join(format!("{}-{}.md", stem, n));
+            n += 1;
+        }
+        std::fs::write(&path, to_markdown(stored))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Every summary as CSV, one row each, with lists joined by "; "
+pub fn to_csv(summaries: &[StoredSummary]) -> String {
+    let mut out = String::from("url,title,created_at,conclusion,key_points,tags,topics,notes\n");
+    for stored in summaries {
+        let summary = &stored.summary;
+        let row = [
+            stored.url.clone(),
+            summary.title.clone(),
+            stored.created_at.to_rfc3339(),
+            summary.conclusion.clone(),
+            summary.key_points.join("; "),
+            stored.tags.join("; "),
+            summary.topics.join("; "),
+            stored.notes.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a CSV field if it holds a comma, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Every summary on one standalone HTML page
+pub fn to_html(summaries: &[StoredSummary]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Summaries</title>\n\
+         <style>body{max-width:48rem;margin:2rem auto;font-family:sans-serif;line-height:1.5}\
+         article{border-bottom:1px solid #ccc;padding-bottom:1rem}.meta{color:#666}</style>\n\
+         </head>\n<body>\n",
+    );
+    for stored in summaries {
+        let summary = &stored.summary;
+        out.push_str("<article>\n");
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&summary.title)));
+        out.push_str(&format!(
+            "<p class=\"meta\"><a href=\"{0}\">{0}</a> &middot; {1}</p>\n",
+            escape_html(&stored.url),
+            stored.created_at.format("%Y-%m-%d")
+        ));
+        out.push_str(&format!("<p>{}</p>\n", escape_html(&summary.conclusion)));
+        if !summary.key_points.is_empty() {
+            out.push_str("<ul>\n");
+            for point in &summary.key_points {
+                out.push_str(&format!("<li>{}</li>\n", escape_html(point)));
+            }
+            out.push_str("</ul>\n");
+        }
+        if !stored.tags.is_empty() {
+            let tags: Vec<String> = stored.tags.iter().map(|t| format!("#{}", t)).collect();
+            out.push_str(&format!(
+                "<p class=\"meta\">{}</p>\n",
+                escape_html(&tags.join(" "))
+            ));
+        }
+        if let Some(ref notes) = stored.notes {
+            out.push_str(&format!(
+                "<blockquote>{}</blockquote>\n",
+                escape_html(notes)
+            ));
+        }
+        out.push_str("</article>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::{Entity, EntityKind, Summary};
+
+    #[test]
+    fn writes_markdown_notes_and_quoted_csv() {
+        let summary = Summary::new(
+            "Async Rust: \"in practice\"".into(),
+            "Tokio, mostly.".into(),
+            vec!["Futures are lazy".into()],
+            vec![Entity::new("Tokio", EntityKind::Technology)],
+            vec!["Try it".into()],
+        );
+        let mut stored = StoredSummary::new("https://a.example".to_string(), summary);
+        stored.tags = vec!["rust".to_string()];
+
+        let note = to_markdown(&stored);
+        assert!(note.starts_with("---\ntitle: \"Async Rust: \\\"in practice\\\"\"\n"));
+        assert!(note.contains("tags: [\"rust\"]\n"));
+        assert!(note.contains("- **Technologies**: Tokio\n"));
+        assert!(note.contains("- [ ] Try it\n"));
+        assert!(file_name(&stored).ends_with("-async-rust-in-practice.md"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_markdown(&[stored.clone(), stored.clone()], dir.path()).unwrap();
+        assert!(paths[1].to_string_lossy().ends_with("-in-practice-2.md"));
+
+        let csv = to_csv(&[stored]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with("https://a.example,\"Async Rust: \"\"in practice\"\"\","));
+        assert!(row.contains(",\"Tokio, mostly.\",Futures are lazy,rust,,"));
+    }
+}
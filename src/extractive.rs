@@ -0,0 +1,241 @@
+//! Offline extractive summariser.
+//!
+//! Used when no LLM can be reached. Sentences are ranked with TextRank (PageRank
+//! over a graph of sentences linked by shared words) and the best ones are
+//! quoted verbatim, so the result is only as good as the source's own sentences.
+
+use crate::summary::{Entity, EntityKind, Summary};
+use std::collections::{HashMap, HashSet};
+
+/// Provider name recorded on summaries produced here
+pub const PROVIDER: &str = "extractive";
+
+/// Entities listed; only names mentioned at least twice count
+const ENTITIES: usize = 8;
+
+/// Sentences ranked; a long document's later sentences are ignored
+const MAX_SENTENCES: usize = 400;
+
+/// PageRank damping factor and iterations
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 30;
+
+/// Common English words that say nothing about what a sentence is about
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "could", "do", "does", "for", "from", "had", "has", "have", "he", "her",
+    "his", "how", "i", "if", "in", "into", "is", "it", "its", "just", "may", "more", "most", "no",
+    "not", "of", "on", "one", "only", "or", "other", "our", "out", "over", "she", "so", "some",
+    "such", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this",
+    "those", "to", "up", "us", "was", "we", "were", "what", "when", "which", "who", "will", "with",
+    "would", "you", "your",
+];
+
+/// Words that mark a sentence as something the reader should do
+const ACTION_CUES: &[&str] = &[
+    "should",
+    "must",
+    "need to",
+    "needs to",
+    "make sure",
+    "remember to",
+];
+
+/// Summarise `text` by quoting its `key_points` most central sentences
+pub fn summarize(text: &str, key_points: usize) -> Summary {
+    let sentences: Vec<String> = split_sentences(text)
+        .into_iter()
+        .take(MAX_SENTENCES)
+        .collect();
+    let words: Vec<HashSet<String>> = sentences.iter().map(|s| content_words(s)).collect();
+    let scores = text_rank(&words);
+
+    let mut ranked: Vec<usize> = (0..sentences.len()).collect();
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+    let mut top: Vec<usize> = ranked.iter().copied().take(key_points).collect();
+    // Quote them in the order the source makes them
+    top.sort_unstable();
+
+    let action_items = sentences
+        .iter()
+        .filter(|s| {
+            let lower = s.to_lowercase();
+            ACTION_CUES.iter().any(|cue| lower.contains(cue))
+        })
+        .take(key_points)
+        .cloned()
+        .collect();
+
+    Summary::new(
+        // A placeholder, so the page's own title is used where there is one
+        "Untitled".to_string(),
+        ranked
+            .first()
+            .map(|&i| sentences[i].clone())
+            .unwrap_or_default(),
+        top.into_iter().map(|i| sentences[i].clone()).collect(),
+        entities(&sentences),
+        action_items,
+    )
+}
+
+/// Split text into sentences at `.`, `!` or `?` followed by whitespace, dropping
+/// headings and fragments too short to stand alone.
+///
+/// Lines wrapped inside a paragraph are joined first; list items stand alone
+/// and fenced code blocks are skipped.
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_code = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("```") {
+            in_code = !in_code;
+            paragraphs.push(std::mem::take(&mut current));
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let item = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker));
+        if line.is_empty() || line.starts_with('#') || item.is_some() {
+            paragraphs.push(std::mem::take(&mut current));
+            paragraphs.extend(item.map(str::to_string));
+            continue;
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(line);
+    }
+    paragraphs.push(current);
+
+    let mut sentences = Vec::new();
+    for paragraph in &paragraphs {
+        let mut start = 0;
+        let mut chars = paragraph.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at_break = matches!(c, '.' | '!' | '?')
+                && chars.peek().is_some_and(|(_, next)| next.is_whitespace());
+            if at_break {
+                sentences.push(&paragraph[start..=i]);
+                start = i + 1;
+            }
+        }
+        sentences.push(&paragraph[start..]);
+    }
+    sentences
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| s.split_whitespace().count() >= 4)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Lowercased words of a sentence, without stopwords or punctuation
+fn content_words(sentence: &str) -> HashSet<String> {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 1)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// PageRank over sentences, weighting each pair by the words they share
+/// relative to their lengths (the TextRank similarity)
+fn text_rank(words: &[HashSet<String>]) -> Vec<f64> {
+    let n = words.len();
+    let mut weights = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let shared = words[i].intersection(&words[j]).count();
+            if shared == 0 {
+                continue;
+            }
+            let norm = (words[i].len() as f64).ln_1p() + (words[j].len() as f64).ln_1p();
+            weights[i][j] = shared as f64 / norm;
+            weights[j][i] = weights[i][j];
+        }
+    }
+    let totals: Vec<f64> = weights.iter().map(|row| row.iter().sum()).collect();
+
+    let mut scores = vec![1.0; n];
+    for _ in 0..ITERATIONS {
+        scores = (0..n)
+            .map(|i| {
+                let incoming: f64 = (0..n)
+                    .filter(|&j| totals[j] > 0.0)
+                    .map(|j| weights[j][i] / totals[j] * scores[j])
+                    .sum();
+                (1.0 - DAMPING) + DAMPING * incoming
+            })
+            .collect();
+    }
+    scores
+}
+
+/// Capitalised names that aren't just starting a sentence, most mentioned first
+fn entities(sentences: &[String]) -> Vec<Entity> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for sentence in sentences {
+        let mut name: Vec<&str> = Vec::new();
+        // Skip the first word, which is capitalised anyway
+        for word in sentence.split_whitespace().skip(1) {
+            let trimmed = word.trim_matches(|c: char| !c.is_alphanumeric());
+            let capitalised = trimmed.chars().next().is_some_and(char::is_uppercase)
+                && !STOPWORDS.contains(&trimmed.to_lowercase().as_str());
+            if capitalised {
+                name.push(trimmed);
+            }
+            // Punctuation after a word ends the name there
+            if (!capitalised || trimmed.len() != word.len()) && !name.is_empty() {
+                *counts.entry(name.join(" ")).or_default() += 1;
+                name.clear();
+            }
+        }
+        if !name.is_empty() {
+            *counts.entry(name.join(" ")).or_default() += 1;
+        }
+    }
+    let mut names: Vec<(String, usize)> = counts.into_iter().filter(|(_, n)| *n >= 2).collect();
+    names.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    names
+        .into_iter()
+        .take(ENTITIES)
+        .map(|(name, _)| Entity::new(name, EntityKind::Other))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_central_sentences() {
+        let text = "# Why Rust\n\
+            Rust is a systems programming language focused on safety.\n\
+            The borrow checker in Rust enforces memory safety at\ncompile time. \
+            Many teams adopt Rust for memory safety without a garbage collector. \
+            My cat enjoys sleeping in the sun all day long.\n\n\
+            - Teams should measure compile times before they adopt Rust widely.";
+        let summary = summarize(text, 5);
+        assert_eq!(summary.key_points.len(), 5);
+        assert!(summary.conclusion.contains("Rust"));
+        assert!(!summary.conclusion.contains("cat"));
+        assert!(summary.has_placeholder_title());
+        assert_eq!(summary.entity_names(), ["Rust"]);
+        assert_eq!(
+            summary.action_items,
+            vec!["Teams should measure compile times before they adopt Rust widely."]
+        );
+    }
+
+    #[test]
+    fn handles_empty_text() {
+        let summary = summarize("", 5);
+        assert!(summary.is_empty());
+    }
+}
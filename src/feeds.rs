@@ -0,0 +1,382 @@
+//! RSS and Atom feed subscriptions, for reading feeds as summaries.
+//!
+//! Subscribed feeds are kept in their own sled tree with the links of the
+//! entries already seen. Syncing fetches each feed and summarises the entries
+//! that are new since, tagging each summary with the feed's name. The first sync
+//! of a feed only takes its latest entries, so subscribing to a busy feed
+//! doesn't summarise its whole history.
+
+use crate::scraper::{self, ScraperError};
+use crate::storage::{normalise_tag, StoredSummary};
+use chrono::{DateTime, Utc};
+use quick_xml::escape::resolve_xml_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FeedError {
+    #[error("failed to fetch feed: {0}")]
+    Fetch(#[from] ScraperError),
+    #[error("{0} isn't an RSS or Atom feed")]
+    NotAFeed(String),
+    #[error("feed XML error: {0}")]
+    Xml(#[from] quick_xml::Error),
+}
+
+/// A subscribed feed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feed {
+    pub name: String,
+    pub url: String,
+    /// Store lite captures of new entries, with only their title and description,
+    /// rather than summarising each one
+    #[serde(default = "default_lite")]
+    pub lite: bool,
+    pub added_at: DateTime<Utc>,
+    #[serde(default)]
+    pub synced_at: Option<DateTime<Utc>>,
+    /// Links of the entries already summarised or skipped, among those still in the feed
+    #[serde(default)]
+    pub seen: Vec<String>,
+}
+
+fn default_lite() -> bool {
+    true
+}
+
+/// One entry of a feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The article the entry links to
+    pub url: String,
+    pub title: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+}
+
+/// A feed as fetched
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Document {
+    pub title: Option<String>,
+    /// Entries with a web link, newest first
+    pub entries: Vec<Entry>,
+}
+
+impl Feed {
+    /// A feed never synced, with surrounding whitespace trimmed from `name`
+    pub fn new(name: &str, url: &str, lite: bool) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            url: url.to_string(),
+            lite,
+            added_at: Utc::now(),
+            synced_at: None,
+            seen: Vec::new(),
+        }
+    }
+
+    /// Tag on every summary of the feed's entries
+    pub fn tag(&self) -> String {
+        normalise_tag(&self.name)
+    }
+
+    /// The entries to summarise, newest first and at most `limit` of them.
+    ///
+    /// On the first sync, entries past the limit are marked seen, so they're
+    /// skipped rather than left for later syncs.
+    pub fn unseen(&mut self, entries: &[Entry], limit: usize) -> Vec<Entry> {
+        let seen: HashSet<&str> = self.seen.iter().map(String::as_str).collect();
+        let mut new: Vec<Entry> = entries
+            .iter()
+            .filter(|entry| !seen.contains(entry.url.as_str()))
+            .cloned()
+            .collect();
+        let backlog = new.split_off(limit.min(new.len()));
+        if self.synced_at.is_none() {
+            self.seen.extend(backlog.into_iter().map(|entry| entry.url));
+        }
+        new
+    }
+
+    /// Prepare the summary of `entry` for storing: titled from the feed if the page
+    /// had no title, tagged with the feed's name and left pending for review
+    pub fn adopt(&self, stored: &mut StoredSummary, entry: &Entry) {
+        stored.summary.apply_title(None, entry.title.clone());
+        stored.add_tag(&self.tag());
+        stored.pending = true;
+    }
+
+    /// Mark an entry's link as seen
+    pub fn mark_seen(&mut self, url: &str) {
+        if !self.seen.iter().any(|seen| seen == url) {
+            self.seen.push(url.to_string());
+        }
+    }
+
+    /// Finish a sync, forgetting seen links that have dropped out of the feed
+    pub fn synced(&mut self, entries: &[Entry]) {
+        let current: HashSet<&str> = entries.iter().map(|entry| entry.url.as_str()).collect();
+        self.seen.retain(|url| current.contains(url.as_str()));
+        self.synced_at = Some(Utc::now());
+    }
+}
+
+/// Fetch and parse the feed at `url`
+pub async fn fetch(url: &str) -> Result<Document, FeedError> {
+    let xml = scraper::fetch_html(url).await?;
+    parse(&xml)?.ok_or_else(|| FeedError::NotAFeed(url.to_string()))
+}
+
+/// An entry being read, before it's known to have a link
+#[derive(Default)]
+struct Partial {
+    url: Option<String>,
+    guid: Option<String>,
+    title: Option<String>,
+    published: Option<DateTime<Utc>>,
+}
+
+/// An RSS or Atom document's title and entries, or `None` if `xml` is neither.
+///
+/// Entries without an http(s) link are skipped. An RSS `guid` stands in for a
+/// missing `link` when it's a URL.
+pub fn parse(xml: &str) -> Result<Option<Document>, FeedError> {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut document = Document::default();
+    let mut is_feed = false;
+    let mut entry: Option<Partial> = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) => {
+                let name = local_name(e);
+                is_feed |= path.is_empty() && matches!(name.as_str(), "rss" | "feed" | "RDF");
+                if matches!(name.as_str(), "item" | "entry") {
+                    entry = Some(Partial::default());
+                }
+                if let Some(ref mut entry) = entry {
+                    atom_link(e, entry);
+                }
+                path.push(name);
+                text.clear();
+            }
+            Event::Empty(ref e) => {
+                if let Some(ref mut entry) = entry {
+                    atom_link(e, entry);
+                }
+            }
+            Event::Text(ref e) => text.push_str(&e.xml_content().unwrap_or_default()),
+            Event::CData(ref e) => text.push_str(&e.decode().unwrap_or_default()),
+            Event::GeneralRef(ref e) => {
+                if let Ok(Some(c)) = e.resolve_char_ref() {
+                    text.push(c);
+                } else if let Some(resolved) = e
+                    .decode()
+                    .ok()
+                    .and_then(|name| resolve_xml_entity(&name).map(str::to_string))
+                {
+                    text.push_str(&resolved);
+                }
+            }
+            Event::End(_) => {
+                let Some(name) = path.pop() else {
+                    break;
+                };
+                let value = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                text.clear();
+                match (name.as_str(), entry.as_mut()) {
+                    ("item" | "entry", _) => {
+                        if let Some(done) = entry.take().and_then(Partial::finish) {
+                            document.entries.push(done);
+                        }
+                    }
+                    // Media RSS titles inside an entry come after its own
+                    ("title", Some(entry)) if entry.title.is_none() => {
+                        entry.title = Some(value).filter(|t| !t.is_empty())
+                    }
+                    ("title", None) if document.title.is_none() => {
+                        document.title = Some(value).filter(|t| !t.is_empty())
+                    }
+                    ("link", Some(entry)) if entry.url.is_none() && !value.is_empty() => {
+                        entry.url = Some(value)
+                    }
+                    ("guid" | "id", Some(entry)) => entry.guid = Some(value),
+                    ("pubDate" | "published" | "date", Some(entry)) => {
+                        entry.published = parse_date(&value).or(entry.published)
+                    }
+                    // Atom entries may only say when they were last updated
+                    ("updated", Some(entry)) if entry.published.is_none() => {
+                        entry.published = parse_date(&value)
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !is_feed {
+        return Ok(None);
+    }
+    // Newest first; entries without a date keep their place after the dated ones
+    document
+        .entries
+        .sort_by_key(|entry| std::cmp::Reverse(entry.published));
+    Ok(Some(document))
+}
+
+impl Partial {
+    fn finish(self) -> Option<Entry> {
+        let url = self
+            .url
+            .filter(|url| crate::reader::is_url(url))
+            .or(self.guid.filter(|guid| crate::reader::is_url(guid)))?;
+        Some(Entry {
+            url,
+            title: self.title,
+            published: self.published,
+        })
+    }
+}
+
+/// An element's name without its namespace prefix
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).into_owned()
+}
+
+/// Take an entry's link from an Atom `<link href="..."/>`, the alternate one if
+/// there are several
+fn atom_link(e: &BytesStart, entry: &mut Partial) {
+    if local_name(e) != "link" {
+        return;
+    }
+    let attribute = |name: &str| {
+        e.try_get_attribute(name)
+            .ok()
+            .flatten()
+            .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+    };
+    let Some(href) = attribute("href") else {
+        return;
+    };
+    let alternate = attribute("rel").is_none_or(|rel| rel == "alternate");
+    if alternate || entry.url.is_none() {
+        entry.url = Some(href);
+    }
+}
+
+/// An RFC 2822 (RSS) or RFC 3339 (Atom) date
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+  <title>This Week in Rust</title>
+  <link>https://this-week-in-rust.org/</link>
+  <item>
+    <title>Issue 1 &amp; more</title>
+    <link>https://this-week-in-rust.org/blog/1/</link>
+    <pubDate>Mon, 01 Jan 2024 00:00:00 +0000</pubDate>
+  </item>
+  <item>
+    <title><![CDATA[Issue <2>]]></title>
+    <guid isPermaLink="true">https://this-week-in-rust.org/blog/2/</guid>
+    <pubDate>Mon, 08 Jan 2024 00:00:00 +0000</pubDate>
+  </item>
+  <item><title>No link</title></item>
+</channel></rss>"#;
+
+    const ATOM: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Inside Rust</title>
+  <link href="https://blog.rust-lang.org/inside-rust/"/>
+  <entry>
+    <title>Compiler team update</title>
+    <link rel="self" href="https://blog.rust-lang.org/feed.xml"/>
+    <link rel="alternate" href="https://blog.rust-lang.org/inside-rust/update.html"/>
+    <published>2024-03-05T12:00:00Z</published>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn parses_rss_and_atom_newest_first() {
+        let rss = parse(RSS).unwrap().unwrap();
+        assert_eq!(rss.title.as_deref(), Some("This Week in Rust"));
+        let entries: Vec<(&str, Option<&str>)> = rss
+            .entries
+            .iter()
+            .map(|e| (e.url.as_str(), e.title.as_deref()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("https://this-week-in-rust.org/blog/2/", Some("Issue <2>")),
+                (
+                    "https://this-week-in-rust.org/blog/1/",
+                    Some("Issue 1 & more")
+                ),
+            ]
+        );
+
+        let atom = parse(ATOM).unwrap().unwrap();
+        assert_eq!(atom.title.as_deref(), Some("Inside Rust"));
+        assert_eq!(
+            atom.entries[0].url,
+            "https://blog.rust-lang.org/inside-rust/update.html"
+        );
+        assert!(atom.entries[0].published.is_some());
+
+        assert_eq!(parse("<html><body>Hi</body></html>").unwrap(), None);
+    }
+
+    #[test]
+    fn first_sync_skips_the_backlog_and_later_syncs_take_what_is_new() {
+        let entry = |n: usize| Entry {
+            url: format!("https://blog.example/{}", n),
+            title: None,
+            published: None,
+        };
+        let mut feed = Feed::new(" Example blog ", "https://blog.example/feed", false);
+        assert_eq!(feed.tag(), "example-blog");
+
+        let entries: Vec<Entry> = (1..=5).rev().map(entry).collect();
+        let new = feed.unseen(&entries, 2);
+        assert_eq!(new, [entry(5), entry(4)]);
+        feed.mark_seen(&new[0].url);
+        // Entry 4 failed, so it's tried again next time
+        feed.synced(&entries);
+
+        let entries: Vec<Entry> = (2..=7).rev().map(entry).collect();
+        let new = feed.unseen(&entries, 10);
+        assert_eq!(new, [entry(7), entry(6), entry(4)]);
+        feed.synced(&entries);
+        assert_eq!(feed.seen.len(), 3, "entry 1 dropped out of the feed");
+    }
+
+    #[test]
+    fn synced_entries_wait_for_review() {
+        let feed = Feed::new("Example blog", "https://blog.example/feed", true);
+        let entry = Entry {
+            url: "https://blog.example/1".to_string(),
+            title: Some("First post".to_string()),
+            published: None,
+        };
+        let summary = crate::Summary::new(String::new(), String::new(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new(entry.url.clone(), summary);
+        feed.adopt(&mut stored, &entry);
+        assert!(stored.awaits_review(Utc::now()));
+        assert_eq!(stored.tags, ["example-blog"]);
+        assert_eq!(stored.summary.title, "First post");
+    }
+}
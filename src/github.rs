@@ -0,0 +1,431 @@
+//! GitHub issue and pull request threads.
+//!
+//! Issue and PR pages are mostly JavaScript and chrome, so instead of scraping
+//! them the thread is fetched from the REST API (description, comments, reviews
+//! and diff stats) and laid out as plain text. `GITHUB_TOKEN` is sent when set,
+//! which raises the rate limit and makes private repositories readable.
+
+use crate::scraper::{self, WebContent};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Base URL of the GitHub REST API
+const API_URL: &str = "https://api.github.com";
+
+/// Most comments or reviews read from a thread; GitHub's largest page
+const PAGE_SIZE: usize = 100;
+
+/// Prompt used instead of `agent.prompt` for issue and PR threads
+pub const THREAD_PROMPT: &str =
+    "Summarise this GitHub discussion thread. In the conclusion, state \
+where the decision stands: agreed, rejected, still open, or merged, and who decided. List the \
+main arguments and any blockers as key points, and the changes reviewers or maintainers have \
+requested, that are still outstanding, as action items. Ignore bot comments, thanks and +1s.";
+
+#[derive(Error, Debug)]
+pub enum GithubError {
+    #[error("failed to fetch from GitHub: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("GitHub returned {status} for {url}{hint}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        url: String,
+        hint: &'static str,
+    },
+}
+
+/// Whether a thread is an issue or a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreadKind {
+    Issue,
+    PullRequest,
+}
+
+impl std::fmt::Display for ThreadKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Issue => write!(f, "issue"),
+            Self::PullRequest => write!(f, "pull request"),
+        }
+    }
+}
+
+/// An issue or pull request, as named by its URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thread {
+    pub owner: String,
+    pub repo: String,
+    pub number: u64,
+    pub kind: ThreadKind,
+}
+
+impl Thread {
+    /// Recognise `https://github.com/owner/repo/issues/12` and `.../pull/12`,
+    /// including deeper links such as `/pull/12/files` or `#issuecomment-1`
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let rest = rest.strip_prefix("www.").unwrap_or(rest);
+        let path = rest.strip_prefix("github.com/")?;
+        let path = path.split(['?', '#']).next()?;
+        let mut segments = path.split('/');
+        let owner = segments.next().filter(|s| !s.is_empty())?;
+        let repo = segments.next().filter(|s| !s.is_empty())?;
+        let kind = match segments.next()? {
+            "issues" => ThreadKind::Issue,
+            "pull" => ThreadKind::PullRequest,
+            _ => return None,
+        };
+        let number = segments.next()?.parse().ok()?;
+        Some(Self {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            number,
+            kind,
+        })
+    }
+
+    /// The canonical web URL, so every link into a thread is stored as one summary
+    pub fn url(&self) -> String {
+        let segment = match self.kind {
+            ThreadKind::Issue => "issues",
+            ThreadKind::PullRequest => "pull",
+        };
+        format!(
+            "https://github.com/{}/{}/{}/{}",
+            self.owner, self.repo, segment, self.number
+        )
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!(
+            "{}/repos/{}/{}/{}/{}{}",
+            API_URL,
+            self.owner,
+            self.repo,
+            match self.kind {
+                ThreadKind::Issue => "issues",
+                ThreadKind::PullRequest => "pulls",
+            },
+            self.number,
+            path
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    title: String,
+    state: String,
+    state_reason: Option<String>,
+    user: Option<User>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    body: Option<String>,
+    /// Present when the issue is really a pull request
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    user: Option<User>,
+    body: Option<String>,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pull {
+    merged: bool,
+    #[serde(default)]
+    draft: bool,
+    commits: u64,
+    additions: u64,
+    deletions: u64,
+    changed_files: u64,
+    head: Branch,
+    base: Branch,
+}
+
+#[derive(Debug, Deserialize)]
+struct Review {
+    user: Option<User>,
+    state: String,
+    body: Option<String>,
+    submitted_at: Option<String>,
+}
+
+/// Fetch an issue or pull request thread from the GitHub API as plain text
+pub async fn fetch_thread(thread: &Thread, token: Option<&str>) -> Result<WebContent, GithubError> {
+    let client = scraper::create_client()?;
+    // A PR's conversation lives on the issue endpoints, like any issue's
+    let issue_thread = Thread {
+        kind: ThreadKind::Issue,
+        ..thread.clone()
+    };
+    let issue: Issue = get_json(&client, &issue_thread.api_url(""), token).await?;
+    let comments: Vec<Comment> = get_json(
+        &client,
+        &issue_thread.api_url(&format!("/comments?per_page={}", PAGE_SIZE)),
+        token,
+    )
+    .await?;
+    let pull = if issue.pull_request.is_some() {
+        let pr_thread = Thread {
+            kind: ThreadKind::PullRequest,
+            ..thread.clone()
+        };
+        let pull: Pull = get_json(&client, &pr_thread.api_url(""), token).await?;
+        let reviews: Vec<Review> = get_json(
+            &client,
+            &pr_thread.api_url(&format!("/reviews?per_page={}", PAGE_SIZE)),
+            token,
+        )
+        .await?;
+        Some((pull, reviews))
+    } else {
+        None
+    };
+
+    let kind = if pull.is_some() {
+        ThreadKind::PullRequest
+    } else {
+        ThreadKind::Issue
+    };
+    let thread = Thread {
+        kind,
+        ..thread.clone()
+    };
+    Ok(WebContent {
+        title: Some(format!(
+            "{}/{}#{}: {}",
+            thread.owner, thread.repo, thread.number, issue.title
+        )),
+        text: render(&thread, &issue, &comments, pull.as_ref()),
+        url: thread.url(),
+        description: None,
+    })
+}
+
+/// GET a JSON document, explaining the failures a token would fix
+async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    token: Option<&str>,
+) -> Result<T, GithubError> {
+    let mut request = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let hint = match status.as_u16() {
+            403 | 429 if token.is_none() => " (rate limited; set GITHUB_TOKEN for a higher limit)",
+            404 if token.is_none() => " (set GITHUB_TOKEN to read private repositories)",
+            _ => "",
+        };
+        return Err(GithubError::Rejected {
+            status,
+            url: url.to_string(),
+            hint,
+        });
+    }
+    Ok(response.json().await?)
+}
+
+/// Lay out a thread as text: a header of facts, the description, then the
+/// comments and reviews in the order they were written
+fn render(
+    thread: &Thread,
+    issue: &Issue,
+    comments: &[Comment],
+    pull: Option<&(Pull, Vec<Review>)>,
+) -> String {
+    let mut header = vec![format!(
+        "GitHub {} {}/{}#{}: {}",
+        thread.kind, thread.owner, thread.repo, thread.number, issue.title
+    )];
+    let state = match (pull, issue.state_reason.as_deref()) {
+        (Some((pull, _)), _) if pull.merged => "merged".to_string(),
+        (Some((pull, _)), _) if pull.draft && issue.state == "open" => "open (draft)".to_string(),
+        (_, Some(reason)) if issue.state == "closed" => format!("closed ({})", reason),
+        _ => issue.state.clone(),
+    };
+    header.push(format!("State: {}", state));
+    header.push(format!("Author: {}", login(&issue.user)));
+    if !issue.labels.is_empty() {
+        let labels: Vec<&str> = issue.labels.iter().map(|l| l.name.as_str()).collect();
+        header.push(format!("Labels: {}", labels.join(", ")));
+    }
+
+    // (when, heading, body) for every comment and review
+    let mut entries: Vec<(&str, String, &str)> = comments
+        .iter()
+        .map(|c| {
+            (
+                c.created_at.as_str(),
+                format!("Comment by {}", login(&c.user)),
+                c.body.as_deref().unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    if let Some((pull, reviews)) = pull {
+        header.push(format!(
+            "Branches: {} -> {}",
+            pull.head.name, pull.base.name
+        ));
+        header.push(format!(
+            "Diff: {} commits, {} files changed, +{} -{}",
+            pull.commits, pull.changed_files, pull.additions, pull.deletions
+        ));
+        // Each reviewer's latest verdict; plain comments don't change it
+        let mut verdicts: Vec<(String, &str)> = Vec::new();
+        for review in reviews {
+            let Some(verdict) = review_verdict(&review.state) else {
+                continue;
+            };
+            let reviewer = login(&review.user);
+            match verdicts.iter_mut().find(|(name, _)| *name == reviewer) {
+                Some(entry) => entry.1 = verdict,
+                None => verdicts.push((reviewer, verdict)),
+            }
+        }
+        if !verdicts.is_empty() {
+            let verdicts: Vec<String> = verdicts
+                .iter()
+                .map(|(name, verdict)| format!("{} {}", name, verdict))
+                .collect();
+            header.push(format!("Reviews: {}", verdicts.join(", ")));
+        }
+        for review in reviews {
+            let body = review.body.as_deref().unwrap_or_default();
+            let verdict = review_verdict(&review.state);
+            if body.trim().is_empty() && verdict.is_none() {
+                continue;
+            }
+            entries.push((
+                review.submitted_at.as_deref().unwrap_or_default(),
+                format!(
+                    "Review by {}: {}",
+                    login(&review.user),
+                    verdict.unwrap_or("commented")
+                ),
+                body,
+            ));
+        }
+    }
+    // ISO 8601 timestamps sort chronologically as strings
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut text = header.join("\n");
+    text.push_str("\n\n");
+    text.push_str(issue.body.as_deref().unwrap_or("(no description)").trim());
+    for (when, heading, body) in entries {
+        let date = when.split('T').next().unwrap_or_default();
+        text.push_str(&format!(
+            "\n\n--- {} ({}) ---\n{}",
+            heading,
+            date,
+            body.trim()
+        ));
+    }
+    text
+}
+
+/// What a review's state says about the PR, or `None` for a plain comment
+fn review_verdict(state: &str) -> Option<&'static str> {
+    match state {
+        "APPROVED" => Some("approved"),
+        "CHANGES_REQUESTED" => Some("requested changes"),
+        "DISMISSED" => Some("dismissed"),
+        _ => None,
+    }
+}
+
+/// Login of a user, or "ghost" for deleted accounts like GitHub shows
+fn login(user: &Option<User>) -> String {
+    user.as_ref()
+        .map_or_else(|| "ghost".to_string(), |u| u.login.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_thread_urls() {
+        let thread = Thread::parse("https://github.com/rust-lang/rust/pull/12/files#r1").unwrap();
+        assert_eq!(thread.kind, ThreadKind::PullRequest);
+        assert_eq!(thread.number, 12);
+        assert_eq!(thread.url(), "https://github.com/rust-lang/rust/pull/12");
+        assert_eq!(
+            Thread::parse("https://www.github.com/a/b/issues/7?x=1").map(|t| t.kind),
+            Some(ThreadKind::Issue)
+        );
+        assert_eq!(
+            Thread::parse("https://github.com/a/b/blob/main/README.md"),
+            None
+        );
+        assert_eq!(Thread::parse("https://gitlab.com/a/b/issues/7"), None);
+    }
+
+    #[test]
+    fn renders_reviews_in_order() {
+        let thread = Thread::parse("https://github.com/a/b/pull/3").unwrap();
+        let issue: Issue = serde_json::from_str(
+            r#"{"title":"Add cache","state":"open","state_reason":null,"user":{"login":"ann"},
+                "labels":[{"name":"perf"}],"body":"Adds a cache.","pull_request":{}}"#,
+        )
+        .unwrap();
+        let comments: Vec<Comment> = serde_json::from_str(
+            r#"[{"user":{"login":"bob"},"body":"Why not LRU?","created_at":"2024-01-02T10:00:00Z"}]"#,
+        )
+        .unwrap();
+        let pull: Pull = serde_json::from_str(
+            r#"{"merged":false,"draft":false,"commits":2,"additions":40,"deletions":5,
+                "changed_files":3,"head":{"ref":"cache"},"base":{"ref":"main"}}"#,
+        )
+        .unwrap();
+        let reviews: Vec<Review> = serde_json::from_str(
+            r#"[{"user":{"login":"cy"},"state":"CHANGES_REQUESTED","body":"Needs tests",
+                 "submitted_at":"2024-01-01T09:00:00Z"},
+                {"user":{"login":"cy"},"state":"APPROVED","body":"",
+                 "submitted_at":"2024-01-03T09:00:00Z"},
+                {"user":null,"state":"COMMENTED","body":"","submitted_at":"2024-01-04T09:00:00Z"}]"#,
+        )
+        .unwrap();
+
+        let text = render(&thread, &issue, &comments, Some(&(pull, reviews)));
+        assert!(text.starts_with("GitHub pull request a/b#3: Add cache\nState: open\n"));
+        assert!(text.contains("Diff: 2 commits, 3 files changed, +40 -5"));
+        assert!(text.contains("Reviews: cy approved\n"));
+        let requested = text
+            .find("Review by cy: requested changes (2024-01-01)")
+            .unwrap();
+        let comment = text.find("Comment by bob (2024-01-02)").unwrap();
+        let approved = text.find("Review by cy: approved (2024-01-03)").unwrap();
+        assert!(requested < comment && comment < approved);
+        assert!(!text.contains("ghost"));
+    }
+}
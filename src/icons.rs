@@ -0,0 +1,111 @@
+//! Source icons shown before list entries.
+//!
+//! Terminals can't reliably show favicons, so known domains are mapped to
+//! [Nerd Font](https://www.nerdfonts.com) glyphs instead. This needs a patched
+//! font, so icons are off unless `ui.icons = "nerd-font"` is set.
+
+use crate::StoredSummary;
+use serde::{Deserialize, Serialize};
+
+/// How to mark the source of each list entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconStyle {
+    /// No icons
+    #[default]
+    None,
+    /// Nerd Font glyphs for well-known sites and file types
+    NerdFont,
+}
+
+/// Glyph for pages from domains without a specific icon
+const WEB: &str = "\u{f0ac}";
+
+/// Nerd Font glyphs for well-known domains, matched on the domain or any parent
+const DOMAIN_GLYPHS: &[(&str, &str)] = &[
+    ("github.com", "\u{f09b}"),
+    ("github.io", "\u{f09b}"),
+    ("gitlab.com", "\u{f296}"),
+    ("stackoverflow.com", "\u{f16c}"),
+    ("stackexchange.com", "\u{f18d}"),
+    ("youtube.com", "\u{f16a}"),
+    ("youtu.be", "\u{f16a}"),
+    ("reddit.com", "\u{f281}"),
+    ("news.ycombinator.com", "\u{f1d4}"),
+    ("wikipedia.org", "\u{f266}"),
+    ("twitter.com", "\u{f099}"),
+    ("x.com", "\u{f099}"),
+    ("linkedin.com", "\u{f0e1}"),
+    ("medium.com", "\u{f23a}"),
+    ("rust-lang.org", "\u{e7a8}"),
+    ("docs.rs", "\u{e7a8}"),
+    ("crates.io", "\u{e7a8}"),
+    ("python.org", "\u{e73c}"),
+    ("npmjs.com", "\u{e71e}"),
+    ("apple.com", "\u{f179}"),
+    ("microsoft.com", "\u{f17a}"),
+    ("google.com", "\u{f1a0}"),
+    ("amazon.com", "\u{f270}"),
+];
+
+/// Glyphs for local files by extension
+const FILE_GLYPHS: &[(&str, &str)] = &[
+    ("pdf", "\u{f1c1}"),
+    ("pptx", "\u{f1c4}"),
+    ("md", "\u{f48a}"),
+    ("txt", "\u{f15c}"),
+];
+
+/// Glyph for local files of any other type, and pasted text
+const FILE: &str = "\u{f15b}";
+
+impl IconStyle {
+    /// The icon for a stored summary's source, if icons are enabled
+    pub fn icon(self, stored: &StoredSummary) -> Option<&'static str> {
+        match self {
+            IconStyle::None => None,
+            IconStyle::NerdFont => Some(match stored.domain() {
+                Some(domain) => domain_glyph(domain),
+                None => file_glyph(&stored.url),
+            }),
+        }
+    }
+}
+
+/// Glyph for a web domain, falling back to a globe
+fn domain_glyph(domain: &str) -> &'static str {
+    DOMAIN_GLYPHS
+        .iter()
+        .find(|(known, _)| {
+            domain == *known
+                || domain
+                    .strip_suffix(known)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        })
+        .map_or(WEB, |(_, glyph)| glyph)
+}
+
+/// Glyph for a local file, chosen by extension
+fn file_glyph(source: &str) -> &'static str {
+    let extension = source.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+    FILE_GLYPHS
+        .iter()
+        .find(|(ext, _)| extension.as_deref() == Some(*ext))
+        .map_or(FILE, |(_, glyph)| glyph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_domains_and_subdomains() {
+        assert_eq!(domain_glyph("github.com"), "\u{f09b}");
+        assert_eq!(domain_glyph("gist.github.com"), "\u{f09b}");
+        assert_eq!(domain_glyph("en.wikipedia.org"), "\u{f266}");
+        // A suffix match must fall on a label boundary
+        assert_eq!(domain_glyph("notgithub.com"), WEB);
+        assert_eq!(file_glyph("file:///home/me/Report.PDF"), "\u{f1c1}");
+        assert_eq!(file_glyph("text:0123abcd"), FILE);
+    }
+}
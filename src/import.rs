@@ -0,0 +1,309 @@
+//! Importing summaries from `summera export` and `summera dump` output.
+//!
+//! A file holds JSON records: a JSON array as written by `export --format json`,
+//! or one record per line as written by `dump`. A directory holds Markdown notes
+//! as written by `export --format markdown`, read back from their front matter
+//! and sections. Records whose URL is already stored are handled by a
+//! [`Conflict`] policy.
+
+use crate::storage::{Storage, StorageError, StoredSummary};
+use crate::summary::{Entity, EntityKind, Summary};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("invalid JSON record on line {line}: {source}")]
+    JsonError {
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("{}: {message}", path.display())]
+    MarkdownError { path: PathBuf, message: String },
+    #[error("storage error: {0}")]
+    StorageError(#[from] StorageError),
+}
+
+/// What to do with an imported record whose URL is already stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// Keep the stored summary
+    Skip,
+    /// Replace it with the imported one
+    Replace,
+    /// Keep whichever was created or last edited more recently
+    Newer,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Skip => write!(f, "skip"),
+            Self::Replace => write!(f, "replace"),
+            Self::Newer => write!(f, "newer"),
+        }
+    }
+}
+
+impl std::str::FromStr for Conflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "replace" => Ok(Self::Replace),
+            "newer" => Ok(Self::Newer),
+            other => Err(format!(
+                "unknown conflict policy '{}'; expected skip, replace or newer",
+                other
+            )),
+        }
+    }
+}
+
+/// How an import went
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub added: usize,
+    pub replaced: usize,
+    pub skipped: usize,
+    /// Every record written, for re-indexing
+    pub written: Vec<StoredSummary>,
+}
+
+/// Read the records in a JSON or JSONL file, or a directory of Markdown notes
+pub fn read_records(path: &Path) -> Result<Vec<StoredSummary>, ImportError> {
+    if path.is_dir() {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        paths.sort();
+        return paths
+            .iter()
+            .map(|path| {
+                let text = std::fs::read_to_string(path)?;
+                parse_markdown(&text).map_err(|message| ImportError::MarkdownError {
+                    path: path.clone(),
+                    message,
+                })
+            })
+            .collect();
+    }
+    let text = std::fs::read_to_string(path)?;
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(&text)
+            .map_err(|source| ImportError::JsonError { line: 1, source });
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|source| ImportError::JsonError {
+                line: i + 1,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Read a summary back from a note written by [`crate::export::to_markdown`]
+pub fn parse_markdown(text: &str) -> Result<StoredSummary, String> {
+    let rest = text
+        .strip_prefix("---\n")
+        .ok_or("no front matter (expected a leading '---' line)")?;
+    let (front, body) = rest
+        .split_once("\n---\n")
+        .ok_or("front matter isn't closed with '---'")?;
+
+    let mut url = None;
+    let mut summary = Summary::new(String::new(), String::new(), vec![], vec![], vec![]);
+    let mut created_at = None;
+    let mut tags = Vec::new();
+    let mut provider = None;
+    let mut language = None;
+    let mut starred = false;
+    for line in front.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        // Values were written as JSON; a hand-edited plain value is taken as it is
+        let text = || serde_json::from_str::<String>(value).unwrap_or_else(|_| value.to_string());
+        let list = || serde_json::from_str::<Vec<String>>(value).unwrap_or_default();
+        match key.trim() {
+            "title" => summary.title = text(),
+            "url" => url = Some(text()),
+            "created" => {
+                created_at = Some(
+                    DateTime::parse_from_rfc3339(&text())
+                        .map_err(|e| format!("invalid created date: {}", e))?
+                        .with_timezone(&Utc),
+                )
+            }
+            "tags" => tags = list(),
+            "topics" => summary.topics = list(),
+            "provider" => provider = Some(text()),
+            "language" => language = Some(text()),
+            "starred" => starred = value == "true",
+            _ => {}
+        }
+    }
+    let url = url.ok_or("no url in the front matter")?;
+
+    let mut section = "";
+    let mut conclusion = Vec::new();
+    let mut notes = Vec::new();
+    for line in body.lines() {
+        if let Some(title) = line.strip_prefix("# ") {
+            if summary.title.is_empty() {
+                summary.title = title.to_string();
+            }
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("## ") {
+            section = heading.trim();
+            continue;
+        }
+        match section {
+            "" => conclusion.push(line),
+            "Notes" => notes.push(line),
+            "Key points" => {
+                if let Some(point) = line.strip_prefix("- ") {
+                    summary.key_points.push(point.to_string());
+                }
+            }
+            "Action items" => {
+                let item = line.strip_prefix("- [ ] ").or(line.strip_prefix("- "));
+                if let Some(item) = item {
+                    summary.action_items.push(item.to_string());
+                }
+            }
+            "Entities" => {
+                let Some((kind, names)) = line
+                    .strip_prefix("- **")
+                    .and_then(|line| line.split_once("**:"))
+                else {
+                    continue;
+                };
+                let kind = EntityKind::from_plural(kind).unwrap_or_default();
+                for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    summary.entities.push(Entity::new(name, kind));
+                }
+            }
+            _ => {}
+        }
+    }
+    summary.conclusion = conclusion.join("\n").trim().to_string();
+
+    let mut stored = StoredSummary::new(url, summary);
+    if let Some(created_at) = created_at {
+        stored.created_at = created_at;
+    }
+    stored.tags = tags;
+    stored.provider = provider;
+    stored.language = language;
+    stored.starred = starred;
+    // An exported summary has been seen before
+    stored.unread = false;
+    let notes = notes.join("\n").trim().to_string();
+    stored.notes = (!notes.is_empty()).then_some(notes);
+    Ok(stored)
+}
+
+/// Store `records`, settling URLs that are already stored with `conflict`
+pub fn merge(
+    storage: &Storage,
+    records: Vec<StoredSummary>,
+    conflict: Conflict,
+) -> Result<ImportReport, StorageError> {
+    let mut report = ImportReport::default();
+    for record in records {
+        let existing = storage.get(&record.url)?;
+        let write = match (&existing, conflict) {
+            (None, _) => true,
+            (Some(_), Conflict::Skip) => false,
+            (Some(_), Conflict::Replace) => true,
+            (Some(stored), Conflict::Newer) => last_changed(&record) > last_changed(stored),
+        };
+        if !write {
+            report.skipped += 1;
+            continue;
+        }
+        storage.put(&record)?;
+        match existing {
+            Some(_) => report.replaced += 1,
+            None => report.added += 1,
+        }
+        report.written.push(record);
+    }
+    Ok(report)
+}
+
+/// When a record was created or last edited by hand
+fn last_changed(stored: &StoredSummary) -> DateTime<Utc> {
+    stored
+        .edited_at
+        .unwrap_or(stored.created_at)
+        .max(stored.created_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export;
+
+    #[test]
+    fn reads_markdown_exports_back_and_merges_by_policy() {
+        let mut summary = Summary::new(
+            "Async Rust".into(),
+            "Tokio, mostly.".into(),
+            vec!["Futures are lazy".into()],
+            vec![Entity::new("Tokio", EntityKind::Technology)],
+            vec!["Try it".into()],
+        );
+        summary.topics = vec!["rust".into()];
+        let mut stored = StoredSummary::new("https://a.example".to_string(), summary);
+        stored.tags = vec!["async".to_string()];
+        stored.notes = Some("Read again.".to_string());
+
+        let parsed = parse_markdown(&export::to_markdown(&stored)).unwrap();
+        assert_eq!(parsed.url, stored.url);
+        assert_eq!(parsed.created_at, stored.created_at);
+        assert_eq!(parsed.summary.title, stored.summary.title);
+        assert_eq!(parsed.summary.conclusion, stored.summary.conclusion);
+        assert_eq!(parsed.summary.key_points, stored.summary.key_points);
+        assert_eq!(parsed.summary.entities, stored.summary.entities);
+        assert_eq!(parsed.summary.action_items, stored.summary.action_items);
+        assert_eq!(parsed.summary.topics, stored.summary.topics);
+        assert_eq!(parsed.tags, stored.tags);
+        assert_eq!(parsed.notes, stored.notes);
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let mut older = parsed.clone();
+        older.summary.title = "Old".into();
+        older.created_at -= chrono::Duration::days(1);
+        storage.put(&older).unwrap();
+
+        let report = merge(&storage, vec![parsed.clone()], Conflict::Skip).unwrap();
+        assert_eq!((report.added, report.skipped), (0, 1));
+        let report = merge(&storage, vec![parsed], Conflict::Newer).unwrap();
+        assert_eq!(report.replaced, 1);
+        let report = merge(&storage, vec![older], Conflict::Newer).unwrap();
+        assert_eq!(report.skipped, 1);
+        assert_eq!(
+            storage
+                .get("https://a.example")
+                .unwrap()
+                .unwrap()
+                .summary
+                .title,
+            "Async Rust"
+        );
+    }
+}
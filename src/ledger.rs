@@ -0,0 +1,277 @@
+//! An append-only, signed record of what was summarised by which model, for audits.
+//!
+//! With `[sync] ledger` set, every summary that's made or refined is appended to
+//! that file as one line of JSON: when, the source, the provider and model that
+//! wrote it, a SHA-256 hash of the source text it was written from, and the
+//! summary itself.
+//!
+//! Each line is signed with HMAC-SHA256, keyed by `SUMMA_LEDGER_KEY` or the key
+//! saved with `summera login ledger`, and includes the signature of the line
+//! before it. Editing, removing or reordering lines breaks the chain, which
+//! `summera ledger` checks.
+
+use crate::activity::{Action, Event};
+use crate::config::ConfigError;
+use crate::storage::StoredSummary;
+use crate::{Config, Summary};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// What separates a line's signed JSON from its signature
+const SIGNATURE_FIELD: &str = ",\"signature\":\"";
+
+/// How much of the end of the ledger is read at a time looking for its last line
+const TAIL_CHUNK: u64 = 8192;
+
+/// The last signature this process appended to each ledger, with the ledger's
+/// length after it, so appending again doesn't read the file when nobody else has
+static LAST_SIGNATURES: Mutex<BTreeMap<PathBuf, (u64, String)>> = Mutex::new(BTreeMap::new());
+
+#[derive(Error, Debug)]
+pub enum LedgerError {
+    #[error("ledger I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("no ledger key: {0}")]
+    Key(#[from] ConfigError),
+    #[error("ledger line {0} isn't a signed entry")]
+    Malformed(usize),
+    #[error("ledger line {0} has a bad signature")]
+    Signature(usize),
+    #[error("ledger line {0} doesn't follow the line before it")]
+    Chain(usize),
+}
+
+/// One line of the ledger, without its signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Utc>,
+    pub action: Action,
+    pub url: String,
+    /// Provider and model that wrote the summary, e.g. "openai/gpt-4o-mini"
+    pub provider: Option<String>,
+    /// SHA-256 of the source text the summary was written from, after redaction,
+    /// as kept in the record's `raw_text`
+    pub text_sha256: Option<String>,
+    pub summary: Summary,
+    /// Signature of the line before, empty for the first
+    pub prev: String,
+}
+
+impl Entry {
+    /// An entry for `event` on `stored`, following the line signed `prev`
+    pub fn new(event: &Event, stored: &StoredSummary, prev: String) -> Self {
+        Self {
+            at: event.at,
+            action: event.action,
+            url: stored.url.clone(),
+            provider: stored.provider.clone(),
+            text_sha256: stored
+                .raw_text
+                .as_ref()
+                .map(|text| format!("{:x}", Sha256::digest(text.as_bytes()))),
+            summary: stored.summary.clone(),
+            prev,
+        }
+    }
+}
+
+/// HMAC-SHA256 of `json` under `key`, in hex
+fn sign(json: &str, key: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(json.as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Split a line into the exact JSON that was signed and its signature.
+///
+/// The signature is spliced onto the end of the JSON rather than serialised with
+/// it, so checking it doesn't depend on the JSON coming out byte for byte the same
+/// when written again.
+fn split_line(line: &str) -> Option<(String, &str)> {
+    let (json, signature) = line.trim_end().rsplit_once(SIGNATURE_FIELD)?;
+    let signature = signature.strip_suffix("\"}")?;
+    Some((format!("{}}}", json), signature))
+}
+
+/// The signature on the last line of the ledger at `path`, empty if there's none yet.
+///
+/// Only the end of the file is read, and not even that when this process wrote the
+/// last line.
+fn last_signature(path: &Path) -> Result<String, LedgerError> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let len = file.metadata()?.len();
+    if let Some((cached_len, signature)) = LAST_SIGNATURES
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(path).cloned())
+    {
+        if cached_len == len {
+            return Ok(signature);
+        }
+    }
+
+    // Read back from the end until the whole last line is in
+    let mut tail = Vec::new();
+    let mut start = len;
+    loop {
+        let text = String::from_utf8_lossy(&tail);
+        let text = text.trim_end();
+        if start == 0 || text.contains('\n') {
+            let Some(line) = text
+                .rsplit('\n')
+                .next()
+                .filter(|line| !line.trim().is_empty())
+            else {
+                return Ok(String::new());
+            };
+            return match split_line(line) {
+                Some((_, signature)) => Ok(signature.to_string()),
+                None => {
+                    let lines = std::fs::read_to_string(path)?.lines().count();
+                    Err(LedgerError::Malformed(lines))
+                }
+            };
+        }
+        let read = TAIL_CHUNK.min(start);
+        start -= read;
+        file.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend(tail);
+        tail = chunk;
+    }
+}
+
+/// Append `event` on `stored` to the ledger at `path`, signed with `key`
+pub fn append(
+    path: &Path,
+    event: &Event,
+    stored: &StoredSummary,
+    key: &str,
+) -> Result<(), LedgerError> {
+    let entry = Entry::new(event, stored, last_signature(path)?);
+    let json = serde_json::to_string(&entry).expect("ledger entries serialise");
+    let signature = sign(&json, key);
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(
+        file,
+        "{}{}{}\"}}",
+        &json[..json.len() - 1],
+        SIGNATURE_FIELD,
+        signature
+    )?;
+    file.sync_all()?;
+    if let Ok(mut cache) = LAST_SIGNATURES.lock() {
+        cache.insert(path.to_path_buf(), (file.metadata()?.len(), signature));
+    }
+    Ok(())
+}
+
+/// Make sure the configured ledger can be signed, before any text is sent
+pub fn check(config: &Config) -> Result<(), LedgerError> {
+    if config.sync.ledger.is_some() {
+        config.token_for("ledger")?;
+    }
+    Ok(())
+}
+
+/// Append `event` on `stored` to the configured ledger, if there is one
+pub fn record(config: &Config, event: &Event, stored: &StoredSummary) -> Result<(), LedgerError> {
+    match config.sync.ledger {
+        Some(ref path) => append(path, event, stored, &config.token_for("ledger")?),
+        None => Ok(()),
+    }
+}
+
+/// Check every signature in the ledger at `path` and that each line follows the one
+/// before it, returning the entries.
+///
+/// Lines cut off the end can't be told apart from a shorter ledger; compare the
+/// count or last signature with one noted earlier to catch that.
+pub fn verify(path: &Path, key: &str) -> Result<Vec<Entry>, LedgerError> {
+    let ledger = std::fs::read_to_string(path)?;
+    let mut prev = String::new();
+    let mut entries = Vec::new();
+    for (i, line) in ledger.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let number = i + 1;
+        let (json, signature) = split_line(line).ok_or(LedgerError::Malformed(number))?;
+        if sign(&json, key) != signature {
+            return Err(LedgerError::Signature(number));
+        }
+        let entry: Entry =
+            serde_json::from_str(&json).map_err(|_| LedgerError::Malformed(number))?;
+        if entry.prev != prev {
+            return Err(LedgerError::Chain(number));
+        }
+        prev = signature.to_string();
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_signed_entries_and_catches_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit").join("ledger.jsonl");
+        let mut stored = StoredSummary::new(
+            "https://example.com/a".into(),
+            Summary::new("A".into(), "Done.".into(), vec![], vec![], vec![]),
+        )
+        .with_provider("openai/gpt-4o-mini")
+        .with_raw_text("The text");
+        append(
+            &path,
+            &Event::new(Action::Summarised, &stored),
+            &stored,
+            "k",
+        )
+        .unwrap();
+        // Longer than the chunks the end of the ledger is read in
+        stored.summary.conclusion = "Done, at length. ".repeat(1000);
+        append(&path, &Event::new(Action::Refined, &stored), &stored, "k").unwrap();
+        let cached = last_signature(&path).unwrap();
+        LAST_SIGNATURES.lock().unwrap().clear();
+        assert_eq!(last_signature(&path).unwrap(), cached);
+
+        let entries = verify(&path, "k").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].provider.as_deref(), Some("openai/gpt-4o-mini"));
+        assert_eq!(entries[1].action, Action::Refined);
+        assert!(matches!(
+            verify(&path, "other key"),
+            Err(LedgerError::Signature(1))
+        ));
+
+        let ledger = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, ledger.replace("Done.", "Not done.")).unwrap();
+        assert!(matches!(verify(&path, "k"), Err(LedgerError::Signature(1))));
+        let second = ledger.lines().nth(1).unwrap();
+        std::fs::write(&path, format!("{}\n", second)).unwrap();
+        assert!(matches!(verify(&path, "k"), Err(LedgerError::Chain(1))));
+    }
+}
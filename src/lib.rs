@@ -9,17 +9,59 @@
 //! - **Provider Agnostic**: Supports Gemini and OpenAI via rstructor
 //! - **Local Files**: Extract text from PDF and PPTX files for summarisation
 
+pub mod activity;
 pub mod agent;
+pub mod backup;
+pub mod bookmarks;
+pub mod cache;
+pub mod chat;
+pub mod collections;
 pub mod config;
+pub mod confluence;
+pub mod daemon;
+pub mod daily_note;
 pub mod db;
+pub mod diff;
+pub mod embeddings;
+pub mod export;
+pub mod extractive;
+pub mod feeds;
+pub mod github;
+pub mod icons;
+pub mod import;
+pub mod ledger;
+pub mod mailing_list;
+pub mod markers;
+pub mod notify;
+pub mod notion;
+pub mod pipeline;
+pub mod postprocess;
+pub mod query;
+pub mod queue;
+pub mod random;
 pub mod reader;
+pub mod readwise;
+pub mod redact;
+pub mod reload;
+pub mod schedule;
 pub mod scraper;
 pub mod search;
+pub mod snooze;
+pub mod social;
+pub mod stackexchange;
 pub mod storage;
 pub mod summary;
+pub mod tags;
+pub mod tasks;
+pub mod template;
+pub mod theme;
+pub mod timeline;
+pub mod tokens;
 pub mod ui;
+pub mod wikipedia;
+pub mod youtube;
 
 pub use config::Config;
 pub use db::{SearchIndex, Storage};
 pub use storage::StoredSummary;
-pub use summary::Summary;
+pub use summary::{Entity, EntityKind, Summary};
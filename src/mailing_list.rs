@@ -0,0 +1,495 @@
+//! Mailing list threads from public-inbox archives.
+//!
+//! public-inbox (lore.kernel.org, inbox.sourceware.org, ...) serves a whole
+//! thread as a gzipped mbox next to each message, so rather than scraping one
+//! message page the thread is downloaded and every message laid out in order,
+//! with quoted text trimmed and patches cut down to their description.
+
+use crate::scraper::{self, WebContent};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use std::collections::HashSet;
+use std::io::Read;
+use thiserror::Error;
+
+/// Hosts known to run public-inbox
+const HOSTS: &[&str] = &[
+    "lore.kernel.org",
+    "inbox.sourceware.org",
+    "public-inbox.org",
+    "lists.openembedded.org",
+];
+
+/// Most messages read from a thread
+const MAX_MESSAGES: usize = 300;
+
+/// Largest mbox read once decompressed, so a huge thread can't exhaust memory
+const MAX_MBOX_BYTES: usize = 50 * 1024 * 1024;
+
+/// Lines of each quoted block kept for context
+const QUOTE_LINES: usize = 3;
+
+/// Prompt used instead of `agent.prompt` for mailing list threads
+pub const THREAD_PROMPT: &str = "Summarise this mailing list thread. In the conclusion, state \
+the outcome: applied, accepted, rejected (NAKed), superseded by a newer version, or still \
+unresolved, and who decided. Trace the arc of the discussion in order as key points, naming who \
+raised each key objection and how it was answered. List review feedback that still has to be \
+addressed as action items. Ignore list bookkeeping and quoted text that only repeats earlier \
+messages.";
+
+#[derive(Error, Debug)]
+pub enum MailingListError {
+    #[error("failed to fetch the thread: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("failed to read the thread's mbox: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("the thread has no messages")]
+    NoContent,
+    #[error("the thread is larger than {} MB", MAX_MBOX_BYTES / 1024 / 1024)]
+    TooLarge,
+}
+
+/// A thread, as named by a link to one of its messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thread {
+    /// The list's root, e.g. "https://lore.kernel.org/linux-mm"
+    pub list_url: String,
+    pub message_id: String,
+}
+
+impl Thread {
+    /// Recognise `https://lore.kernel.org/<list>/<message-id>/` links, and
+    /// deeper ones such as `.../T/#u` or `.../raw`
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("https://")?;
+        let (host, path) = rest.split_once('/')?;
+        if !HOSTS.contains(&host) {
+            return None;
+        }
+        let path = path.split(['?', '#']).next()?;
+        let mut segments = path.split('/').filter(|s| !s.is_empty());
+        let list = segments.next()?;
+        let message_id = segments.next()?;
+        // Every message ID has an @; list pages (`/linux-mm/?q=...`) don't
+        if !message_id.contains('@') && !message_id.contains("%40") {
+            return None;
+        }
+        Some(Self {
+            list_url: format!("https://{}/{}", host, list),
+            message_id: message_id.replace("%40", "@"),
+        })
+    }
+
+    /// Link to the message the thread was named by
+    pub fn url(&self) -> String {
+        format!("{}/{}/", self.list_url, self.message_id)
+    }
+
+    /// The list's name, e.g. "linux-mm"
+    pub fn list(&self) -> &str {
+        self.list_url.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+/// One message, reduced to what a summary needs
+#[derive(Debug, Clone, PartialEq)]
+struct Message {
+    from: String,
+    date: Option<DateTime<Utc>>,
+    subject: String,
+    body: String,
+}
+
+/// Fetch the whole thread a message belongs to as text
+pub async fn fetch_thread(thread: &Thread) -> Result<WebContent, MailingListError> {
+    let url = format!("{}t.mbox.gz", thread.url());
+    let compressed = scraper::create_client()?
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let mbox = gunzip(&compressed, MAX_MBOX_BYTES)?;
+
+    let mut messages = parse_mbox(&String::from_utf8_lossy(&mbox));
+    if messages.is_empty() {
+        return Err(MailingListError::NoContent);
+    }
+    messages.sort_by_key(|m| m.date);
+    messages.truncate(MAX_MESSAGES);
+    Ok(WebContent {
+        url: thread.url(),
+        title: Some(thread_subject(&messages[0].subject)),
+        text: render(thread, &messages),
+        description: None,
+    })
+}
+
+/// Decompress `compressed`, failing rather than reading past `limit` bytes
+fn gunzip(compressed: &[u8], limit: usize) -> Result<Vec<u8>, MailingListError> {
+    let mut decoded = Vec::new();
+    GzDecoder::new(compressed)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut decoded)?;
+    if decoded.len() > limit {
+        return Err(MailingListError::TooLarge);
+    }
+    Ok(decoded)
+}
+
+/// The thread's subject, without `Re:` and friends
+fn thread_subject(subject: &str) -> String {
+    let mut subject = subject.trim();
+    while let Some(rest) = ["Re:", "RE:", "re:", "Fwd:", "Aw:", "AW:"]
+        .iter()
+        .find_map(|prefix| subject.strip_prefix(prefix))
+    {
+        subject = rest.trim_start();
+    }
+    subject.to_string()
+}
+
+/// Lay out the thread: a header, then each message in date order
+fn render(thread: &Thread, messages: &[Message]) -> String {
+    let people: HashSet<&str> = messages.iter().map(|m| m.from.as_str()).collect();
+    let mut text = vec![
+        format!(
+            "Mailing list thread on {}: {}",
+            thread.list(),
+            thread_subject(&messages[0].subject)
+        ),
+        format!("Messages: {} from {} people", messages.len(), people.len()),
+    ];
+    for message in messages {
+        let when = message
+            .date
+            .map(|d| format!(" ({})", d.format("%Y-%m-%d %H:%M")))
+            .unwrap_or_default();
+        text.push(String::new());
+        text.push(format!(
+            "--- {}{}: {} ---",
+            message.from, when, message.subject
+        ));
+        text.push(clean_body(&message.body));
+    }
+    text.join("\n")
+}
+
+/// Split an mboxrd file into messages
+fn parse_mbox(mbox: &str) -> Vec<Message> {
+    let mut raw: Vec<String> = Vec::new();
+    let mut current: Option<String> = None;
+    let mut after_blank = true;
+    for line in mbox.lines() {
+        if after_blank && line.starts_with("From ") {
+            raw.extend(current.take());
+            current = Some(String::new());
+        } else if let Some(ref mut message) = current {
+            // mboxrd escapes body lines starting with "From " as ">From "
+            let escaped =
+                line.starts_with('>') && line.trim_start_matches('>').starts_with("From ");
+            let unescaped = if escaped { &line[1..] } else { line };
+            message.push_str(unescaped);
+            message.push('\n');
+        }
+        after_blank = line.is_empty();
+    }
+    raw.extend(current);
+    raw.iter().filter_map(|m| parse_message(m)).collect()
+}
+
+/// Parse one RFC 5322 message, keeping its plain text body
+fn parse_message(raw: &str) -> Option<Message> {
+    let (headers, body) = split_headers(raw);
+    let from = header(&headers, "from").map(|from| sender_name(&from))?;
+    let date = header(&headers, "date").and_then(|date| parse_date(&date));
+    let subject = header(&headers, "subject").unwrap_or_default();
+    let body = plain_text(&headers, body).unwrap_or_default();
+    Some(Message {
+        from,
+        date,
+        subject,
+        body,
+    })
+}
+
+/// Unfolded `(lowercased name, value)` headers, and the body after them
+fn split_headers(raw: &str) -> (Vec<(String, String)>, &str) {
+    let (head, body) = raw.split_once("\n\n").unwrap_or((raw, ""));
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, body)
+}
+
+/// A header's value, with encoded words decoded
+fn header(headers: &[(String, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| decode_words(value))
+}
+
+/// The display name in `"Ann Example" <ann@example.com>`, else the address
+fn sender_name(from: &str) -> String {
+    match from.split_once('<') {
+        Some((name, _)) if !name.trim().is_empty() => name.trim().trim_matches('"').to_string(),
+        Some((_, address)) => address.trim_end_matches('>').to_string(),
+        None => from.trim().to_string(),
+    }
+}
+
+/// Parse an RFC 2822 date, ignoring a trailing `(UTC)`-style comment
+fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.split(" (").next()?.trim();
+    DateTime::parse_from_rfc2822(date)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+/// The body's text/plain content, looking inside multipart messages
+fn plain_text(headers: &[(String, String)], body: &str) -> Option<String> {
+    let content_type = header(headers, "content-type").unwrap_or_default();
+    let lower = content_type.to_lowercase();
+    if lower.starts_with("multipart/") {
+        let boundary = content_type
+            .split(';')
+            .find_map(|param| param.trim().strip_prefix("boundary="))?
+            .trim_matches('"');
+        let delimiter = format!("--{}", boundary);
+        return body
+            .split(delimiter.as_str())
+            .skip(1)
+            .take_while(|part| !part.starts_with("--"))
+            .find_map(|part| {
+                let (headers, body) = split_headers(part.trim_start_matches(['\r', '\n']));
+                plain_text(&headers, body)
+            });
+    }
+    if !(lower.is_empty() || lower.starts_with("text/plain")) {
+        return None;
+    }
+    let encoding = header(headers, "content-transfer-encoding")
+        .unwrap_or_default()
+        .to_lowercase();
+    Some(match encoding.as_str() {
+        "quoted-printable" => decode_quoted_printable(body),
+        "base64" => {
+            let compact: String = body.split_whitespace().collect();
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .ok()?;
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        _ => body.to_string(),
+    })
+}
+
+/// Decode a quoted-printable body: `=XX` bytes and `=` soft line breaks
+fn decode_quoted_printable(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        let (line, soft_break) = match line.strip_suffix('=') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        bytes.extend(decode_hex_escapes(line, '='));
+        if !soft_break && lines.peek().is_some() {
+            bytes.push(b'\n');
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Bytes of `text` with `<escape>XX` hex escapes decoded
+fn decode_hex_escapes(text: &str, escape: char) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let raw = text.as_bytes();
+    let mut i = 0;
+    while i < raw.len() {
+        let byte = match raw.get(i + 1..i + 3) {
+            Some(hex) if raw[i] == escape as u8 => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+    bytes
+}
+
+/// Decode RFC 2047 encoded words (`=?UTF-8?Q?J=C3=B6rg?=`) in a header
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].splitn(3, '?').collect::<Vec<_>>();
+        let word = match decoded[..] {
+            [charset, encoding, text] => text.find("?=").and_then(|end| {
+                let bytes = match encoding {
+                    "Q" | "q" => decode_hex_escapes(&text[..end].replace('_', " "), '='),
+                    "B" | "b" => base64::engine::general_purpose::STANDARD
+                        .decode(&text[..end])
+                        .ok()?,
+                    _ => return None,
+                };
+                // Length of `=?charset?X?text?=`
+                let len = 2 + charset.len() + 1 + encoding.len() + 1 + end + 2;
+                Some((String::from_utf8_lossy(&bytes).into_owned(), len))
+            }),
+            _ => None,
+        };
+        let Some((word, len)) = word else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        // Whitespace between two encoded words is not part of the text
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&word);
+        rest = &rest[start + len..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The part of a message body worth summarising: quoted blocks cut to their
+/// first lines, and the signature and any patch dropped
+fn clean_body(body: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut quoted = 0;
+    for line in body.lines() {
+        let line = line.trim_end();
+        if line == "--" || line == "-- " {
+            break;
+        }
+        if line.starts_with("diff --git ") || line.starts_with("Index: ") {
+            lines.push("[patch omitted]");
+            break;
+        }
+        if line.starts_with('>') {
+            quoted += 1;
+            match quoted {
+                n if n <= QUOTE_LINES => lines.push(line),
+                n if n == QUOTE_LINES + 1 => lines.push("> [...]"),
+                _ => {}
+            }
+            continue;
+        }
+        quoted = 0;
+        // Collapse runs of blank lines
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_thread_urls() {
+        let thread =
+            Thread::parse("https://lore.kernel.org/linux-mm/20240102.abc@kernel.org/T/#u").unwrap();
+        assert_eq!(thread.list(), "linux-mm");
+        assert_eq!(thread.message_id, "20240102.abc@kernel.org");
+        assert_eq!(
+            thread.url(),
+            "https://lore.kernel.org/linux-mm/20240102.abc@kernel.org/"
+        );
+        assert_eq!(
+            Thread::parse("https://lore.kernel.org/linux-mm/?q=folio"),
+            None
+        );
+        assert_eq!(Thread::parse("https://example.com/list/a@b/"), None);
+    }
+
+    #[test]
+    fn reads_mbox_threads() {
+        let mbox = "From mboxrd@z Thu Jan  1 00:00:00 1970\n\
+            From: =?UTF-8?Q?J=C3=B6rg?= Example <jorg@example.com>\n\
+            Subject: [PATCH v2] mm: fix\n \
+            the thing\n\
+            Date: Tue, 2 Jan 2024 10:00:00 +0100\n\
+            \n\
+            This fixes the thing.\n\
+            >From here on it works.\n\
+            \n\
+            diff --git a/mm/x.c b/mm/x.c\n\
+            +fix\n\
+            \n\
+            From mboxrd@z Thu Jan  1 00:00:00 1970\n\
+            From: ann@example.com\n\
+            Subject: Re: [PATCH v2] mm: fix the thing\n\
+            Date: Wed, 3 Jan 2024 09:00:00 +0000 (UTC)\n\
+            Content-Transfer-Encoding: quoted-printable\n\
+            \n\
+            > a\n> b\n> c\n> d\n> e\n\
+            NAK, this breaks hugetlb=\n\
+            fs.\n\
+            -- \n\
+            Ann\n";
+        let messages = parse_mbox(mbox);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].from, "Jörg Example");
+        assert_eq!(messages[0].subject, "[PATCH v2] mm: fix the thing");
+        assert_eq!(
+            clean_body(&messages[0].body),
+            "This fixes the thing.\nFrom here on it works.\n\n[patch omitted]"
+        );
+        assert_eq!(messages[1].from, "ann@example.com");
+        assert!(messages[1].date.is_some());
+        assert_eq!(
+            clean_body(&messages[1].body),
+            "> a\n> b\n> c\n> [...]\nNAK, this breaks hugetlbfs."
+        );
+        assert_eq!(
+            thread_subject(&messages[1].subject),
+            "[PATCH v2] mm: fix the thing"
+        );
+    }
+
+    #[test]
+    fn stops_decompressing_past_the_limit() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[b'x'; 1000]).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(gunzip(&compressed, 1000).unwrap().len(), 1000);
+        assert!(matches!(
+            gunzip(&compressed, 999),
+            Err(MailingListError::TooLarge)
+        ));
+    }
+}
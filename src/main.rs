@@ -3,204 +3,3064 @@
 //! The application logic is contained in lib.rs, and this file is responsible
 //! for parsing arguments and handling top-level errors.
 
-use clap::{Parser, Subcommand};
-use summera::{agent, reader, scraper, ui, Config, SearchIndex, Storage};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use colored::Colorize;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+use summera::activity::{Action, Event};
+use summera::backup;
+use summera::collections::Collection;
+use summera::config::{PersonaPreset, SummaryLength};
+use summera::daemon::Daemon;
+use summera::export::ExportFormat;
+use summera::feeds::{self, Feed};
+use summera::icons::IconStyle;
+use summera::import::Conflict;
+use summera::markers::Markers;
+use summera::random::{self, RandomFilter};
+use summera::search::{SavedSearch, SearchOptions, SearchScope};
+use summera::snooze;
+use summera::storage::{normalise_tag, PushedTask};
+use summera::tasks::TaskBackend;
+use summera::timeline;
+use summera::{
+    agent, bookmarks, embeddings, export, import, pipeline, query, queue, reader, readwise,
+    scraper, search, tags, tasks, tokens, ui, Config, EntityKind, SearchIndex, Storage,
+    StoredSummary, Summary,
+};
+
+/// `println!` for progress, headings and confirmations, silenced by `-q`
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if verbosity() >= Verbosity::Normal {
+            if output_format() != OutputFormat::Text {
+                eprintln!($($arg)*);
+            } else {
+                println!($($arg)*);
+            }
+        }
+    };
+}
+
+/// Timings and provider details on stderr, shown with `-v`
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if verbosity() >= Verbosity::Verbose {
+            eprintln!("{}", format!($($arg)*).dimmed());
+        }
+    };
+}
+
+/// Paths and cache keys on stderr, shown with `-vv`
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if verbosity() >= Verbosity::Debug {
+            eprintln!("{}", format!($($arg)*).dimmed());
+        }
+    };
+}
 
 #[derive(Parser)]
 #[command(name = "summera")]
 #[command(author, version, about = "TUI for intelligent webpage summarisation", long_about = None)]
 struct Cli {
+    /// Disable coloured output (also honours the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Use ASCII section markers instead of emoji
+    #[arg(long, global = true)]
+    ascii: bool,
+    /// Keep config, data and caches next to the summera binary (see also SUMMA_HOME)
+    #[arg(long, global = true)]
+    portable: bool,
+    /// Print only results: the summary, or just URLs in listings
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Like -q, and print summaries as bare text without colour, emoji or headings
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    plain: bool,
+    /// Print just the conclusion of each summary (implies --plain)
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    conclusion_only: bool,
+    /// Also print timings and provider details; twice for paths and cache keys
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Summarise a webpage by URL or a local file (PDF, PPTX)
+    /// Summarise webpages by URL or local files (PDF, PPTX)
     Summarise {
-        /// URL or local file path to summarise
-        source: String,
+        /// URLs or local file paths to summarise, or `-` to read them from stdin one per
+        /// line; with several, a failure doesn't stop the rest but makes the exit code
+        /// non-zero
+        #[arg(
+            value_name = "SOURCE",
+            required_unless_present_any = ["text_file", "from_file", "stdin_html"]
+        )]
+        sources: Vec<String>,
+        /// Also summarise the URLs or paths in this file, one per line (# starts a comment)
+        #[arg(long, value_name = "PATH", conflicts_with = "text_file")]
+        from_file: Option<std::path::PathBuf>,
+        /// How many sources to summarise at once
+        #[arg(short, long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: u16,
+        /// Summarise a plain text file (notes, emails, Markdown) as-is, or `-` for stdin
+        #[arg(long, value_name = "PATH", conflicts_with = "sources")]
+        text_file: Option<String>,
+        /// Summarise a page's HTML read from stdin, already rendered by a browser or
+        /// another tool, e.g. behind a login
+        #[arg(long, requires = "url", conflicts_with_all = ["sources", "from_file", "text_file"])]
+        stdin_html: bool,
+        /// Canonical URL of the page given with --stdin-html, which it's stored under
+        #[arg(long, requires = "stdin_html")]
+        url: Option<String>,
+        /// What kind of text --text-file holds, e.g. "meeting notes" or "email"
+        #[arg(long, requires = "text_file")]
+        kind: Option<String>,
+        /// Store the summary under this title instead of the generated one
+        #[arg(long)]
+        title: Option<String>,
         /// Show raw extracted text instead of summary
         #[arg(long)]
         raw: bool,
+        /// Summarise even if the page is already stored, or this text was summarised
+        /// before with the same prompt and model
+        #[arg(long, conflicts_with = "raw")]
+        force: bool,
+        /// Store only the title, description and auto-tags, without calling the LLM;
+        /// summarising the source again later upgrades it to a full summary
+        #[arg(long, conflicts_with_all = ["raw", "dry_run"])]
+        lite: bool,
+        /// How long the summary is: brief, standard or detailed (default from agent.length)
+        #[arg(long, conflicts_with = "raw")]
+        length: Option<SummaryLength>,
+        /// Write the summary in this language, e.g. "Swedish" or "sv" (default from agent.output_language)
+        #[arg(long, value_name = "LANGUAGE", conflicts_with = "raw")]
+        lang: Option<String>,
+        /// Write for a different reader: executive, eli5, technical or skeptical
+        #[arg(long, conflicts_with = "raw")]
+        persona_preset: Option<PersonaPreset>,
+        /// Fetch and build the prompt, then stop before calling the LLM
+        #[arg(long, conflicts_with = "raw")]
+        dry_run: bool,
+        /// Print the prompt that would be sent (with --dry-run)
+        #[arg(long, requires = "dry_run")]
+        show_prompt: bool,
+        /// Print the summary through a template: a name under [templates] in the config,
+        /// or inline text with {{field}} placeholders
+        #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["raw", "dry_run"])]
+        template: Option<String>,
+        /// Output format: text, json for one record per line as `dump` writes them, or markdown
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with_all = ["raw", "dry_run", "template"])]
+        format: OutputFormat,
+    },
+    /// Show what each extraction strategy finds on a page, to debug missing or noisy text
+    Inspect {
+        /// URL of the page
+        url: String,
+        /// Lines of each extraction to preview
+        #[arg(long, default_value_t = 3)]
+        lines: usize,
     },
     /// Search stored summaries
     Search {
-        /// Search query
-        query: String,
+        /// Search query (supports AND/OR/NOT, "phrases" and field:term)
+        #[arg(required_unless_present_any = ["saved", "interactive"])]
+        query: Option<String>,
+        /// Fail on unparsable queries instead of falling back to a substring scan
+        #[arg(long)]
+        strict: bool,
+        /// Only search one part of each record: summary, notes or raw
+        #[arg(long = "in", value_name = "SCOPE")]
+        scope: Option<SearchScope>,
+        /// Save this query and its options under a name
+        #[arg(long, value_name = "NAME", conflicts_with = "saved")]
+        save: Option<String>,
+        /// Notify (see [notifications] in the config) when new summaries match the saved search
+        #[arg(long, requires = "save")]
+        alert: bool,
+        /// Run a previously saved search
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["query", "strict", "scope"])]
+        saved: Option<String>,
+        /// Rank by meaning with embeddings (see [embeddings] in the config) instead of keywords
+        #[arg(long, conflicts_with_all = ["strict", "scope", "save", "saved"])]
+        semantic: bool,
+        /// Keep prompting for queries, and print a result in full by its number
+        #[arg(short, long, conflicts_with_all = ["save", "saved", "semantic", "template"])]
+        interactive: bool,
+        /// Print through a template: a name under [templates] in the config, or inline
+        /// text with {{field}} placeholders
+        #[arg(long, value_name = "TEMPLATE")]
+        template: Option<String>,
+        /// Output format: text, json for one record per line as `dump` writes them, or markdown
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with_all = ["interactive", "template"])]
+        format: OutputFormat,
+    },
+    /// List saved searches
+    Saved {
+        /// Delete the saved search with this name
+        #[arg(long, value_name = "NAME")]
+        delete: Option<String>,
+    },
+    /// List the stored summaries most similar to one, to rediscover related reading
+    Similar {
+        /// URL of a stored summary
+        url: String,
+        /// Number of similar summaries to show
+        #[arg(long, short = 'n', default_value_t = 10)]
+        limit: usize,
+        /// Compare meaning with embeddings (see [embeddings] in the config) instead of shared terms
+        #[arg(long)]
+        semantic: bool,
+        /// Output format: text, json for one record per line as `dump` writes them, or markdown
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
     /// List all stored summaries
-    List,
+    List {
+        /// Only list summaries about this topic, e.g. "rust"
+        #[arg(long)]
+        topic: Option<String>,
+        /// List the summaries waiting for review instead of the library
+        #[arg(long)]
+        pending: bool,
+        /// Only list starred summaries
+        #[arg(long)]
+        starred: bool,
+        /// Only list summaries not opened yet
+        #[arg(long)]
+        unread: bool,
+        /// List the snoozed summaries instead of the library
+        #[arg(long, conflicts_with = "pending")]
+        snoozed: bool,
+        /// Print through a template: a name under [templates] in the config, or inline
+        /// text with {{field}} placeholders
+        #[arg(long, value_name = "TEMPLATE")]
+        template: Option<String>,
+        /// Output format: text, json for one record per line as `dump` writes them, or markdown
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "template")]
+        format: OutputFormat,
+    },
+    /// Print the summary at the top of the reading queue and mark it read
+    Next {
+        /// Leave it unread, at the top of the queue
+        #[arg(long)]
+        peek: bool,
+        /// Output format: text, json for one record per line as `dump` writes them, or markdown
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Print a random older summary, to revisit something saved long ago
+    Random {
+        /// Only pick summaries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only pick summaries made longer ago than this, e.g. 30d, 6w, 3m or 1y
+        #[arg(long, value_name = "AGE", default_value = "30d", value_parser = summera::timeline::parse_age)]
+        older_than: chrono::Duration,
+        /// Output format: text, json for one record per line as `dump` writes them, or markdown
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Hide summaries until a later date, when they come back unread
+    Snooze {
+        /// URLs of stored summaries
+        #[arg(required = true)]
+        urls: Vec<String>,
+        /// When they come back: tomorrow, a date like 2026-11-01, or e.g. 3d, 2w or 1m from now
+        #[arg(long, value_name = "WHEN", required_unless_present = "cancel")]
+        until: Option<String>,
+        /// Bring them back now instead
+        #[arg(long, conflicts_with = "until")]
+        cancel: bool,
+    },
+    /// Bring back snoozed summaries that are due, with a desktop notification (for cron)
+    Remind,
+    /// Star summaries as favourites, or unstar them
+    Star {
+        /// URLs of stored summaries
+        #[arg(required = true)]
+        urls: Vec<String>,
+        /// Remove the star instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Delete stored summaries, after asking
+    Delete {
+        /// URLs of stored summaries
+        #[arg(required_unless_present_any = ["all", "older_than"], conflicts_with_all = ["all", "older_than"])]
+        urls: Vec<String>,
+        /// Delete every stored summary
+        #[arg(long, conflicts_with = "older_than")]
+        all: bool,
+        /// Delete summaries made longer ago than this, e.g. 30d, 6w, 3m or 1y
+        #[arg(long, value_name = "AGE", value_parser = summera::timeline::parse_age)]
+        older_than: Option<chrono::Duration>,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Add or remove tags on stored summaries
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// Group summaries into named collections, e.g. "job hunt"
+    Collection {
+        #[command(subcommand)]
+        action: CollectionAction,
+    },
+    /// Send action items from a summary to a task manager (Todoist, GitHub Issues, Taskwarrior)
+    Push {
+        /// URL of a stored summary
+        url: String,
+        /// Task manager to use: todoist, github or taskwarrior (defaults to `default` under [tasks])
+        #[arg(long)]
+        to: Option<TaskBackend>,
+        /// Push every action item that hasn't been pushed yet, without asking
+        #[arg(long, conflicts_with = "items")]
+        all: bool,
+        /// Push the Nth action item, counting from 1 (repeat for more)
+        #[arg(long = "item", value_name = "N")]
+        items: Vec<usize>,
+    },
+    /// Summarise what changed on a page since it was last summarised
+    Diff {
+        /// URL of a stored summary
+        url: String,
+    },
+    /// Rewrite a stored summary to an instruction, e.g. "shorter", keeping the old version
+    Refine {
+        /// URL of a stored summary
+        url: String,
+        /// What to change, e.g. "focus on the security implications"
+        instruction: String,
+    },
+    /// Correct a stored summary by hand in $EDITOR, keeping the old version
+    Edit {
+        /// URL of a stored summary
+        url: String,
+    },
+    /// Add notes to a stored summary, or edit them in $EDITOR
+    Note {
+        /// URL of a stored summary
+        url: String,
+        /// Text to add to the end of the notes; without it, the notes open in $EDITOR
+        #[arg(conflicts_with = "clear")]
+        text: Option<String>,
+        /// Delete the notes
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Ask questions about a stored summary, or one question of the whole library
+    Ask {
+        /// URL of a stored summary, or a question to answer from every stored summary
+        target: String,
+        /// The question about the summary; without one, questions are read interactively
+        /// until an empty line
+        question: Option<String>,
+        /// Most summaries to answer a library question from
+        #[arg(long, default_value_t = 5)]
+        sources: usize,
+    },
+    /// Sync with exported browser bookmarks: summarise new ones, archive deleted ones
+    Bookmarks {
+        /// Netscape bookmarks file (default from sync.bookmarks)
+        file: Option<std::path::PathBuf>,
+        /// Show what would change without summarising or tagging anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Subscribe to RSS and Atom feeds and summarise their new entries
+    Feed {
+        #[command(subcommand)]
+        action: FeedAction,
+    },
+    /// Write summaries to files (JSON, Markdown, CSV or HTML), or send them to another tool
+    #[command(args_conflicts_with_subcommands = true)]
+    Export {
+        #[command(subcommand)]
+        target: Option<ExportTarget>,
+        /// URLs of stored summaries to export (all of them if none are given)
+        urls: Vec<String>,
+        /// Only export summaries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// File format: json, markdown (one file per summary), csv or html
+        #[arg(long, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// File to write, or the directory for markdown (default: stdout)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Load summaries from `export` or `dump` output: a JSON file or a directory of Markdown notes
+    Import {
+        /// JSON or JSONL file, or directory of Markdown notes
+        path: std::path::PathBuf,
+        /// What to do when a URL is already stored: skip, replace or newer
+        #[arg(long, value_name = "POLICY", default_value_t = Conflict::Skip)]
+        on_conflict: Conflict,
+    },
+    /// Stream every stored record to stdout for jq, DuckDB and other tools
+    Dump {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DumpFormat::Jsonl)]
+        format: DumpFormat,
+        /// Include the extracted source text of each record
+        #[arg(long)]
+        raw: bool,
+        /// Print the JSON schema of a record instead of the records
+        #[arg(long)]
+        schema: bool,
+    },
+    /// Back up the library to a zip archive, pruning old archives (see [backup] in the config)
+    Backup {
+        /// List the archives instead of making one
+        #[arg(long)]
+        list: bool,
+    },
+    /// Run scheduled backups and snooze reminders in the background until Ctrl-C
+    Daemon {
+        /// Answer `GET /healthz` on this address, e.g. 127.0.0.1:8787 (default from daemon.health_addr)
+        #[arg(long, value_name = "ADDR")]
+        health_addr: Option<String>,
+    },
+    /// Run a read-only SQL query over the archive
+    Query {
+        /// SQL to run against the `summaries` table
+        #[arg(required_unless_present = "schema")]
+        sql: Option<String>,
+        /// Print the definition of the `summaries` table
+        #[arg(long, conflicts_with = "sql")]
+        schema: bool,
+    },
+    /// Show the activity log: what was summarised, refined, edited, exported or deleted
+    Log {
+        /// Only show events whose URL, title or detail contains this
+        query: Option<String>,
+        /// Only show one kind of event, e.g. "deleted"
+        #[arg(long)]
+        action: Option<Action>,
+        /// Most recent events to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Create a per-project archive (.summa/) in the current directory
+    Init,
+    /// Check the signatures of the compliance ledger ([sync] ledger)
+    Ledger {
+        /// Ledger file to check instead of the configured one
+        #[arg(long, value_name = "PATH")]
+        path: Option<std::path::PathBuf>,
+    },
+    /// Store a Confluence, Notion, Slack, Discord or Readwise token, or the ledger key,
+    /// in the OS keyring
+    Login {
+        /// Which connector the token is for
+        #[arg(value_parser = ["confluence", "notion", "slack", "discord", "readwise", "ledger"])]
+        connector: String,
+        /// Remove the stored token instead
+        #[arg(long)]
+        remove: bool,
+    },
     /// Update summera to the latest version.
     #[command(name = "update", hide = true)] // Hidden from help
     Update,
 }
 
+/// Tag operations, applied to the given URLs and/or everything matching a search
+#[derive(Subcommand)]
+enum TagAction {
+    /// Add a tag
+    Add {
+        tag: String,
+        /// URLs of stored summaries to tag
+        #[arg(required_unless_present = "query")]
+        urls: Vec<String>,
+        /// Also tag every summary matching this search query
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// Remove a tag
+    Remove {
+        tag: String,
+        /// URLs of stored summaries to untag
+        #[arg(required_unless_present = "query")]
+        urls: Vec<String>,
+        /// Also untag every summary matching this search query
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// Rename a tag everywhere it's used
+    Rename { old: String, new: String },
+    /// Fold one tag into another, which keeps existing
+    Merge { from: String, into: String },
+    /// Show how many summaries use each tag, and when it was last used
+    Stats,
+}
+
+/// Collection operations
+#[derive(Subcommand)]
+enum CollectionAction {
+    /// List collections and how many summaries each holds
+    List,
+    /// Show the summaries in a collection, in the order they were added
+    Show { name: String },
+    /// Add summaries to a collection, creating it if needed
+    Add {
+        name: String,
+        /// URLs of stored summaries to add
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Take summaries out of a collection, keeping the summaries themselves
+    Remove {
+        name: String,
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Rename a collection
+    Rename { old: String, new: String },
+    /// Delete a collection, keeping the summaries in it
+    Delete { name: String },
+}
+
+/// Feed operations
+#[derive(Subcommand)]
+enum FeedAction {
+    /// Subscribe to an RSS or Atom feed
+    Add {
+        url: String,
+        /// Name to tag its summaries with (default: the feed's title)
+        #[arg(long)]
+        name: Option<String>,
+        /// Summarise new entries in full instead of storing lite captures of them
+        #[arg(long)]
+        full: bool,
+    },
+    /// List feeds and when each was last synced
+    List,
+    /// Unsubscribe from a feed, keeping the summaries already made
+    Remove { name: String },
+    /// Summarise new entries in every feed, or only the one named
+    Sync {
+        name: Option<String>,
+        /// Most entries to summarise from each feed
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// Show the new entries without summarising anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Where `export` sends summaries
+#[derive(Subcommand)]
+enum ExportTarget {
+    /// Send conclusions and key points to Readwise as highlights
+    Readwise {
+        /// URLs of stored summaries to export (all of them if none are given)
+        urls: Vec<String>,
+        /// Only export summaries with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+}
+
+/// How much the CLI prints besides the results themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Verbosity {
+    /// Results only (`-q`)
+    Quiet,
+    /// Results with progress and confirmations
+    Normal,
+    /// Also timings and provider details (`-v`)
+    Verbose,
+    /// Also paths and cache keys (`-vv`)
+    Debug,
+}
+
+/// Set once from the command line, before anything is printed
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Debug,
+    }
+}
+
+/// How much of each summary `print_summary` prints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Body {
+    /// Every section under coloured headings
+    Full,
+    /// The conclusion and key points as bare text (`--plain`)
+    Plain,
+    /// Only the conclusion (`--conclusion-only`)
+    Conclusion,
+}
+
+/// Set once from the command line, before anything is printed
+static BODY: AtomicU8 = AtomicU8::new(Body::Full as u8);
+
+fn body() -> Body {
+    match BODY.load(Ordering::Relaxed) {
+        0 => Body::Full,
+        1 => Body::Plain,
+        _ => Body::Conclusion,
+    }
+}
+
+/// Set once from the command line; unless it's text, messages for people go to stderr
+static OUTPUT_FORMAT: AtomicU8 = AtomicU8::new(OutputFormat::Text as u8);
+
+fn output_format() -> OutputFormat {
+    match OUTPUT_FORMAT.load(Ordering::Relaxed) {
+        0 => OutputFormat::Text,
+        1 => OutputFormat::Json,
+        _ => OutputFormat::Markdown,
+    }
+}
+
+/// Output formats for commands that print summaries
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// For reading in a terminal
+    Text,
+    /// One JSON record per line
+    Json,
+    /// Clean Markdown, for pasting into notes apps
+    Markdown,
+}
+
+impl Commands {
+    /// The `--format` of a command that prints summaries
+    fn output_format(&self) -> OutputFormat {
+        match self {
+            Self::Summarise { format, .. }
+            | Self::Search { format, .. }
+            | Self::Similar { format, .. }
+            | Self::List { format, .. }
+            | Self::Next { format, .. }
+            | Self::Random { format, .. } => *format,
+            _ => OutputFormat::Text,
+        }
+    }
+}
+
+/// Output formats for `dump`
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpFormat {
+    /// One JSON object per line
+    Jsonl,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    let body = match (cli.plain, cli.conclusion_only) {
+        (_, true) => Body::Conclusion,
+        (true, false) => Body::Plain,
+        (false, false) => Body::Full,
+    };
+    BODY.store(body as u8, Ordering::Relaxed);
+    if body != Body::Full {
+        cli.quiet = true;
+        cli.no_color = true;
+        cli.ascii = true;
+    }
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+    if cli.portable {
+        let exe = std::env::current_exe()?;
+        let home = exe.parent().unwrap_or_else(|| std::path::Path::new("."));
+        Config::use_portable_home(home.to_path_buf());
+    }
+    // Legacy Windows consoles need ANSI escape processing switched on explicitly
+    #[cfg(windows)]
+    let _ = colored::control::set_virtual_terminal(true);
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (false, 0) => Verbosity::Normal,
+        (false, 1) => Verbosity::Verbose,
+        (false, _) => Verbosity::Debug,
+    };
+    VERBOSITY.store(level as u8, Ordering::Relaxed);
+    let format = cli
+        .command
+        .as_ref()
+        .map_or(OutputFormat::Text, Commands::output_format);
+    OUTPUT_FORMAT.store(format as u8, Ordering::Relaxed);
+    debug!("Config file: {}", Config::config_file_path().display());
 
     match cli.command {
-        Some(Commands::Summarise { source, raw }) => {
-            // Detect whether the source is a URL or a local file
-            let (title, text, source_key) = if reader::is_url(&source) {
-                println!("Fetching: {}", source);
-                let content = scraper::fetch_content(&source).await?;
-                let title = content.title.unwrap_or_else(|| "No title".to_string());
-                (title, content.text, source.clone())
-            } else {
-                println!("Reading: {}", source);
-                let content = reader::extract_from_file(&source)?;
-                let title = content.title.unwrap_or_else(|| "No title".to_string());
-                // Use absolute path as the storage key for local files
-                let abs_path = std::fs::canonicalize(&source)
-                    .unwrap_or_else(|_| std::path::PathBuf::from(&source));
-                let key = format!("file://{}", abs_path.display());
-                (title, content.text, key)
+        Some(Commands::Summarise {
+            mut sources,
+            from_file,
+            jobs,
+            text_file,
+            stdin_html,
+            url,
+            kind,
+            title: title_override,
+            raw,
+            force,
+            lite,
+            length,
+            lang,
+            persona_preset,
+            dry_run,
+            show_prompt,
+            template,
+            ..
+        }) => {
+            let mut config = Config::load()?;
+            let template = template.map(|t| summera::template::resolve(&t, &config.templates));
+            debug!("Storage: {}", config.storage.path.display());
+            if let Some(length) = length {
+                config.agent.length = length;
+            }
+            if persona_preset.is_some() {
+                config.agent.persona_preset = persona_preset;
+            }
+            if lang.is_some() {
+                config.agent.output_language = lang;
+            }
+            if let Some(ref path) = from_file {
+                let listed = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow::anyhow!("failed to read {}: {}", path.display(), e))?;
+                sources.extend(reader::source_list(&listed));
+            }
+            let from_stdin = sources.iter().any(|source| source == "-");
+            if from_stdin {
+                let listed = std::io::read_to_string(std::io::stdin())
+                    .map_err(|e| anyhow::anyhow!("failed to read stdin: {}", e))?;
+                sources.retain(|source| source != "-");
+                sources.extend(reader::source_list(&listed));
+            }
+            if sources.len() > 1 || from_file.is_some() || from_stdin {
+                if raw || dry_run || title_override.is_some() {
+                    anyhow::bail!("--raw, --dry-run and --title work on a single source");
+                }
+                let batch = Batch {
+                    force,
+                    lite,
+                    jobs: jobs.into(),
+                    template: template.as_deref(),
+                    ascii: cli.ascii,
+                };
+                return summarise_many(&sources, &config, &batch).await;
+            }
+            let mut source = match url {
+                Some(url) if !reader::is_url(&url) => {
+                    anyhow::bail!("--url must be an http(s) URL, got '{}'", url)
+                }
+                Some(url) => url,
+                None => sources.into_iter().next().unwrap_or_default(),
             };
-
+            if reader::is_url(&source) && !force && !raw && !dry_run {
+                let existing = Storage::open(&config.storage.path)?.find_duplicate(&source)?;
+                if let Some(capture) = existing.as_ref().filter(|e| e.is_lite() && !lite) {
+                    // Upgrade a lite capture in place without asking
+                    say!("Upgrading the lite capture '{}'", capture.summary.title);
+                    source = capture.url.clone();
+                } else if let Some(existing) = existing {
+                    eprintln!(
+                        "{} Already stored as '{}' ({}, {})",
+                        "Warning:".yellow(),
+                        existing.summary.title,
+                        existing.url,
+                        existing.created_at.format("%Y-%m-%d")
+                    );
+                    let choice = if std::io::stdin().is_terminal() {
+                        dialoguer::Select::new()
+                            .with_prompt("What now?")
+                            .items(["Show the stored summary", "Summarise it again", "Abort"])
+                            .default(0)
+                            .interact()?
+                    } else {
+                        0
+                    };
+                    match choice {
+                        0 => {
+                            let markers = Markers::select(cli.ascii || config.ui.ascii);
+                            say!();
+                            print_stored(&existing, template.as_deref(), &markers);
+                            say!("\n{}", "Pass --force to summarise it again.".dimmed());
+                            return Ok(());
+                        }
+                        // Revise the stored record rather than adding a second one
+                        1 => source = existing.url,
+                        _ => return Ok(()),
+                    }
+                }
+            }
+            let cancel = cancel_on_ctrl_c();
+            let started = Instant::now();
+            let mut extracted = match text_file {
+                Some(path) => {
+                    say!("Reading {}", path);
+                    pipeline::Extracted::from_text_file(&path, kind, title_override.clone())?
+                }
+                None if stdin_html => {
+                    say!("Reading HTML for {} from stdin", source);
+                    let html = std::io::read_to_string(std::io::stdin())
+                        .map_err(|e| anyhow::anyhow!("failed to read stdin: {}", e))?;
+                    pipeline::Extracted::from_html(&source, &html)?
+                }
+                None => pipeline::extract(&source, &config, &report_stage, &cancel).await?,
+            };
+            verbose!(
+                "Extracted {} characters in {:.1}s",
+                extracted.text.len(),
+                started.elapsed().as_secs_f64()
+            );
             if raw {
                 // Just show raw extracted text
-                println!("\n=== {} ===\n", title);
-                println!("{}", text);
-                println!("\n--- Extracted {} characters ---", text.len());
-            } else {
-                // Summarise using LLM
-                println!("Summarising {} characters...\n", text.len());
+                let title = title_override.or(extracted.title);
+                let title = title.as_deref().unwrap_or("No title");
+                say!("\n{}\n", format!("=== {} ===", title).bold());
+                println!("{}", extracted.text);
+                say!("\n--- Extracted {} characters ---", extracted.text.len());
+                return Ok(());
+            }
+            if dry_run {
+                extracted.redact(&config.redact)?;
+                let config = extracted.config(&config);
+                let prompts = agent::prompts(&extracted.prompt_text(), &config);
+                if show_prompt {
+                    for (i, prompt) in prompts.iter().enumerate() {
+                        if prompts.len() > 1 {
+                            let header = format!("=== Prompt {} of {} ===", i + 1, prompts.len());
+                            println!("{}\n", header.bold());
+                        }
+                        println!("{}\n", prompt);
+                    }
+                }
+                let counts: Vec<String> = prompts
+                    .iter()
+                    .map(|p| format!("~{}", tokens::count_tokens(p)))
+                    .collect();
+                println!(
+                    "Would send {} characters in {} prompt(s) of {} tokens to {} ({}-token context window){}",
+                    extracted.text.len(),
+                    prompts.len(),
+                    counts.join(", "),
+                    config.agent.model,
+                    config.agent.context_limit(),
+                    if prompts.len() > 1 { ", then a merge prompt" } else { "" }
+                );
+                return Ok(());
+            }
 
-                let config = Config::load()?;
-                let summary = agent::summarize(&text, &config).await?;
+            // Summarise using LLM
+            let started = Instant::now();
+            let options = pipeline::Options {
+                title: title_override,
+                force,
+                lite,
+                ..Default::default()
+            };
+            let mut stored =
+                pipeline::summarise(extracted, &config, &options, &report_stage, &cancel).await?;
+            let provider = stored.provider.clone().unwrap_or_default();
+            verbose!(
+                "Summarised in {:.1}s by {} ({}-token context window)",
+                started.elapsed().as_secs_f64(),
+                provider,
+                config.agent.context_limit()
+            );
+            // It's printed below, so it has been read
+            stored.unread = false;
+            if !stored.references.is_empty() {
+                say!(
+                    "{}",
+                    format!("Kept {} references", stored.references.len()).dimmed()
+                );
+            }
 
-                // Persist the summary to sled storage
-                let storage = Storage::open(&config.storage.path)?;
-                storage.store(&source_key, &summary)?;
+            // Persist, index and alert on the summary
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let event = Event::new(Action::Summarised, &stored);
+            let report = pipeline::store(&mut stored, event, &storage, &config).await?;
+            print_report(&report, &markers);
 
-                // Index in tantivy for full-text search
-                let search_path = config.storage.path.join("search_index");
-                if let Ok(search_index) = SearchIndex::open(&search_path) {
-                    if let Err(e) = search_index.index_summary(&source_key, &summary) {
-                        eprintln!("Warning: Failed to index summary: {}", e);
+            say!();
+            print_stored(&stored, template.as_deref(), &markers);
+            if stored.is_lite() {
+                // There are no key points to leave a gap after
+                say!(
+                    "{}",
+                    "Lite capture, stored without an LLM; summarise it again for a full summary"
+                        .dimmed()
+                );
+            } else if provider == summera::extractive::PROVIDER {
+                say!(
+                    "\n{}",
+                    "Extractive summary, written offline without an LLM".yellow()
+                );
+            } else {
+                let language = match stored.language {
+                    Some(ref language) if config.agent.output_language.is_some() => {
+                        format!(" in {}", language)
                     }
+                    _ => String::new(),
+                };
+                say!(
+                    "\n{}",
+                    format!("Summarised by {}{}", provider, language).dimmed()
+                );
+                if let Some(ref critique) = stored.critique {
+                    say!("{}", format!("{}: {}", critique, critique.reason).dimmed());
                 }
+            }
+        }
+        Some(Commands::Inspect { url, lines }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            say!("Fetching: {}\n", url);
+            let html = scraper::fetch_html(&url).await?;
+            for extraction in scraper::inspect(&html) {
+                let used = if extraction.chosen {
+                    format!(" {} used", markers.done).green().bold().to_string()
+                } else {
+                    String::new()
+                };
+                println!("{}{}", extraction.strategy.bold(), used);
+                if extraction.text.is_empty() {
+                    println!("   {}\n", "no match".dimmed());
+                    continue;
+                }
+                println!(
+                    "   {}",
+                    format!(
+                        "{} characters, ~{} tokens",
+                        extraction.text.len(),
+                        tokens::count_tokens(&extraction.text)
+                    )
+                    .dimmed()
+                );
+                for line in extraction
+                    .text
+                    .lines()
+                    .filter(|l| !l.is_empty())
+                    .take(lines)
+                {
+                    let preview: String = line.chars().take(100).collect();
+                    let cut = if preview.len() < line.len() {
+                        markers.ellipsis
+                    } else {
+                        ""
+                    };
+                    println!("   {}{}", preview, cut);
+                }
+                println!();
+            }
+        }
+        Some(Commands::Search {
+            query,
+            strict,
+            scope,
+            save,
+            alert,
+            saved,
+            semantic,
+            interactive,
+            template,
+            ..
+        }) => {
+            let config = Config::load()?;
+            let template = template.map(|t| summera::template::resolve(&t, &config.templates));
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
 
-                println!("=== {} ===\n", summary.title);
-
-                println!("💡 Conclusion:");
-                println!("  {}\n", summary.conclusion);
-
-                println!("📌 Key Points:");
-                for point in &summary.key_points {
-                    println!("  • {}", point);
+            if semantic {
+                let query = query.unwrap_or_default();
+                let embedded = embeddings::sync(&storage, &config).await?;
+                if embedded > 0 {
+                    say!("{}", format!("Embedded {} summaries", embedded).dimmed());
+                }
+                let ranked = embeddings::semantic_search(
+                    &storage,
+                    &query,
+                    &config,
+                    SearchOptions::default().limit,
+                )
+                .await?;
+                if ranked.is_empty() {
+                    say!("No results found for: {}", query);
+                } else {
+                    say!("Semantic results for '{}':\n", query);
+                    for ranked in &ranked {
+                        if let Ok(Some(stored)) = storage.get(&ranked.url) {
+                            print_entry(
+                                &stored,
+                                template.as_deref(),
+                                &markers,
+                                icon_style(cli.ascii, &config),
+                            );
+                        }
+                    }
                 }
+                return Ok(());
+            }
 
-                if !summary.entities.is_empty() {
-                    println!("\n🏷️  Entities:");
-                    println!("  {}", summary.entities.join(", "));
+            let (query, options) = match saved {
+                Some(name) => {
+                    let saved = storage
+                        .saved_search(&name)?
+                        .ok_or_else(|| anyhow::anyhow!("no saved search named '{}'", name))?;
+                    let options = saved.options(SearchOptions::default().limit);
+                    (saved.query, options)
                 }
+                None => {
+                    let options = SearchOptions {
+                        strict,
+                        scope,
+                        ..SearchOptions::default()
+                    };
+                    (query.unwrap_or_default(), options)
+                }
+            };
+
+            if interactive {
+                let first = Some(query).filter(|q| !q.is_empty());
+                return search_repl(&storage, &config, &options, first, &markers);
+            }
+
+            if let Some(name) = save {
+                storage.save_search(&SavedSearch {
+                    name: name.clone(),
+                    query: query.clone(),
+                    scope: options.scope,
+                    strict: options.strict,
+                    alert,
+                })?;
+                say!("Saved search '{}'.\n", name);
+            }
 
-                if !summary.action_items.is_empty() {
-                    println!("\n✅ Action Items:");
-                    for item in &summary.action_items {
-                        println!("  • {}", item);
+            let search_path = config.storage.search_index_path();
+            let outcome = search::search_summaries(&storage, &search_path, &query, &options)?;
+            let results = outcome.urls;
+
+            if results.is_empty() {
+                say!("No results found for: {}", query);
+            } else {
+                say!("Search results for '{}' ({}):\n", query, outcome.backend);
+                for url in &results {
+                    if let Ok(Some(stored)) = storage.get(url) {
+                        print_entry(
+                            &stored,
+                            template.as_deref(),
+                            &markers,
+                            icon_style(cli.ascii, &config),
+                        );
                     }
                 }
             }
         }
-        Some(Commands::Search { query }) => {
+        Some(Commands::Similar {
+            url,
+            limit,
+            semantic,
+            ..
+        }) => {
             let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
             let storage = Storage::open(&config.storage.path)?;
+            let stored = storage
+                .get(&url)?
+                .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))?;
 
-            // Try tantivy first, fall back to simple search
-            let search_path = config.storage.path.join("search_index");
-            let results = if let Ok(search_index) = SearchIndex::open(&search_path) {
-                match search_index.search(&query, 20) {
-                    Ok(urls) if !urls.is_empty() => urls,
-                    _ => simple_search(&storage, &query)?,
-                }
+            let similar = if semantic {
+                embeddings::sync(&storage, &config).await?;
+                embeddings::related(&storage, &url, &config, limit)?
+                    .into_iter()
+                    .map(|ranked| ranked.url)
+                    .collect()
             } else {
-                simple_search(&storage, &query)?
+                search::similar_summaries(
+                    &storage,
+                    &config.storage.search_index_path(),
+                    &url,
+                    limit,
+                )?
             };
 
-            if results.is_empty() {
-                println!("No results found for: {}", query);
+            if similar.is_empty() {
+                say!("Nothing similar to '{}' yet.", stored.summary.title);
             } else {
-                println!("Search results for '{}':\n", query);
-                for url in &results {
+                say!("Similar to '{}':\n", stored.summary.title);
+                for url in &similar {
                     if let Ok(Some(stored)) = storage.get(url) {
-                        println!(
-                            "📄 {} ({})",
-                            stored.summary.title,
-                            stored.created_at.format("%Y-%m-%d %H:%M")
-                        );
-                        println!("   {}", stored.url);
-                        println!("   {}\n", stored.summary.conclusion);
+                        print_entry(&stored, None, &markers, icon_style(cli.ascii, &config));
                     }
                 }
             }
         }
-        Some(Commands::List) => {
+        Some(Commands::Saved { delete }) => {
             let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
             let storage = Storage::open(&config.storage.path)?;
-            let summaries = storage.list_all()?;
 
-            if summaries.is_empty() {
-                println!("No stored summaries found.");
+            if let Some(name) = delete {
+                if storage.delete_saved_search(&name)? {
+                    say!("Deleted saved search '{}'.", name);
+                } else {
+                    say!("No saved search named '{}'.", name);
+                }
+                return Ok(());
+            }
+
+            let saved = storage.saved_searches()?;
+            if saved.is_empty() {
+                say!("No saved searches. Use `summera search <QUERY> --save <NAME>`.");
             } else {
-                println!("Stored summaries ({}):\n", summaries.len());
-                for stored in summaries {
+                say!("Saved searches ({}):\n", saved.len());
+                for search in saved {
+                    let scope = search
+                        .scope
+                        .map(|s| format!(" [in {}]", s))
+                        .unwrap_or_default();
+                    let bell = if search.alert {
+                        format!(" {}", markers.alert)
+                    } else {
+                        String::new()
+                    };
                     println!(
-                        "📄 {} ({})",
-                        stored.summary.title,
-                        stored.created_at.format("%Y-%m-%d %H:%M")
+                        "{} {}: {}{}{}",
+                        markers.saved_search, search.name, search.query, scope, bell
                     );
-                    println!("   {}", stored.url);
-                    println!("   {}\n", stored.summary.conclusion);
                 }
             }
         }
-        Some(Commands::Update) => {
-            println!("--- Checking for updates ---");
-            let status = self_update::backends::github::Update::configure()
-                .repo_owner("cladam")
-                .repo_name("summa") // name of the repo, I haven't changed it....
-                .bin_name("summera")
-                .show_download_progress(true)
-                .current_version(self_update::cargo_crate_version!())
-                .build()?
-                .update()?;
-            println!("Update status: `{}`!", status.version());
+        Some(Commands::List {
+            topic,
+            pending,
+            starred,
+            unread,
+            snoozed,
+            template,
+            ..
+        }) => {
+            let config = Config::load()?;
+            let template = template.map(|t| summera::template::resolve(&t, &config.templates));
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let now = chrono::Utc::now();
+            let mut summaries = storage.list_all()?;
+            summaries.retain(|stored| stored.pending == pending);
+            if snoozed {
+                summaries.retain(|stored| stored.is_snoozed(now));
+            } else if !pending {
+                summaries.retain(|stored| !stored.is_snoozed(now));
+            }
+            if let Some(topic) = topic {
+                summaries.retain(|stored| stored.has_topic(&topic));
+            }
+            if unread {
+                summaries.retain(|stored| stored.is_unread(now));
+            }
+            let pin = config.ui.starred_first && !starred;
+            if starred {
+                summaries.retain(|stored| stored.starred);
+            } else if pin {
+                timeline::pin_starred(&mut summaries);
+            }
+
+            if summaries.is_empty() && pending {
+                say!("Nothing waiting for review.");
+            } else if summaries.is_empty() && unread {
+                say!("Nothing unread.");
+            } else if summaries.is_empty() && snoozed {
+                say!("Nothing snoozed. Use `summera snooze <URL> --until <WHEN>`.");
+            } else if summaries.is_empty() && starred {
+                say!("No starred summaries. Use `summera star <URL>`.");
+            } else if summaries.is_empty() {
+                say!("No stored summaries found.");
+            } else {
+                let heading = if pending {
+                    "Waiting for review"
+                } else if snoozed {
+                    "Snoozed"
+                } else {
+                    "Stored summaries"
+                };
+                say!("{} ({}):\n", heading, summaries.len());
+                let groups = if pin {
+                    timeline::group_pinned(&summaries, timeline::today())
+                } else {
+                    timeline::group_by_day(&summaries, timeline::today())
+                };
+                for group in groups {
+                    say!("{}\n", group.label.bold().underline());
+                    for stored in &summaries[group.range] {
+                        print_entry(
+                            stored,
+                            template.as_deref(),
+                            &markers,
+                            icon_style(cli.ascii, &config),
+                        );
+                    }
+                }
+            }
         }
-        None => {
-            // Default: Launch the TUI
-            ui::run().await?;
+        Some(Commands::Next { peek, .. }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let Some(mut stored) = queue::load(&storage)?.into_iter().next() else {
+                say!("Nothing unread.");
+                return Ok(());
+            };
+            say!("{}\n", stored.url.cyan());
+            print_stored(&stored, None, &markers);
+            if !peek {
+                stored.set_unread(false, chrono::Utc::now());
+                storage.put(&stored)?;
+            }
         }
-    }
-
-    Ok(())
-}
-
-/// Simple text-based search fallback when tantivy index is not available
-fn simple_search(storage: &Storage, query: &str) -> anyhow::Result<Vec<String>> {
-    let query_lower = query.to_lowercase();
-    let all_summaries = storage.list_all()?;
+        Some(Commands::Random {
+            tag, older_than, ..
+        }) => {
+            let now = chrono::Utc::now();
+            if now.checked_sub_signed(older_than).is_none() {
+                anyhow::bail!("--older-than reaches back past the earliest date");
+            }
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let summaries = storage.list_all()?;
+            let filter = RandomFilter {
+                tag,
+                min_age: older_than,
+            };
+            let Some(index) = random::pick(&summaries, &filter, now) else {
+                say!("Nothing that old to revisit yet; try a shorter --older-than.");
+                return Ok(());
+            };
+            let stored = &summaries[index];
+            say!(
+                "{} {}\n",
+                stored.url.cyan(),
+                format!("(saved {})", stored.created_at.format("%Y-%m-%d")).dimmed()
+            );
+            print_stored(stored, None, &markers);
+        }
+        Some(Commands::Tag { action }) => tag_command(action)?,
+        // Without --until, clap has made sure --cancel was given
+        Some(Commands::Snooze { urls, until, .. }) => {
+            let until = until
+                .map(|until| snooze::parse_until(&until, chrono::Local::now()))
+                .transpose()
+                .map_err(anyhow::Error::msg)?;
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let mut changed = 0;
+            for url in &urls {
+                let Some(mut stored) = storage.get(url)? else {
+                    eprintln!("{} No stored summary for {}", "Warning:".yellow(), url);
+                    continue;
+                };
+                match until {
+                    Some(until) => stored.snooze(until),
+                    // --cancel: wake it now, if it's still snoozed
+                    None if stored.is_snoozed(chrono::Utc::now()) => {
+                        stored.snoozed_until = None;
+                        stored.unread = true;
+                    }
+                    None => continue,
+                }
+                storage.put(&stored)?;
+                changed += 1;
+            }
+            match until {
+                Some(until) => say!(
+                    "Snoozed {} of {} summaries until {}.",
+                    changed,
+                    urls.len(),
+                    until.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                ),
+                None => say!("Brought back {} of {} summaries.", changed, urls.len()),
+            }
+        }
+        Some(Commands::Remind) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let woken = snooze::wake_due(&storage, chrono::Utc::now())?;
+            if let Err(e) = summera::notify::remind(&config.notifications, &woken) {
+                eprintln!("{} {}", "Warning:".yellow(), e);
+            }
+            if woken.is_empty() {
+                say!("Nothing snoozed is due.");
+            }
+            for stored in &woken {
+                say!("{} {}", stored.summary.title.bold(), stored.url.cyan());
+            }
+        }
+        Some(Commands::Star { urls, remove }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let mut changed = 0;
+            for url in &urls {
+                let Some(mut stored) = storage.get(url)? else {
+                    eprintln!("{} No stored summary for {}", "Warning:".yellow(), url);
+                    continue;
+                };
+                if stored.starred == remove {
+                    stored.starred = !remove;
+                    storage.put(&stored)?;
+                    changed += 1;
+                }
+            }
+            let verb = if remove { "Unstarred" } else { "Starred" };
+            say!("{} {} of {} summaries.", verb, changed, urls.len());
+        }
+        Some(Commands::Delete {
+            urls,
+            all,
+            older_than,
+            yes,
+        }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let doomed: Vec<StoredSummary> = if all {
+                storage.list_all()?
+            } else if let Some(age) = older_than {
+                let cutoff = chrono::Utc::now().checked_sub_signed(age).ok_or_else(|| {
+                    anyhow::anyhow!("--older-than reaches back past the earliest date")
+                })?;
+                storage
+                    .list_all()?
+                    .into_iter()
+                    .filter(|stored| stored.created_at < cutoff)
+                    .collect()
+            } else {
+                let mut found = Vec::new();
+                for url in &urls {
+                    match storage.get(url)? {
+                        Some(stored) => found.push(stored),
+                        None => eprintln!("{} No stored summary for {}", "Warning:".yellow(), url),
+                    }
+                }
+                found
+            };
+            if doomed.is_empty() {
+                say!("Nothing to delete.");
+                return Ok(());
+            }
+            if !yes {
+                if !std::io::stdin().is_terminal() {
+                    anyhow::bail!("refusing to delete without confirmation; pass --yes");
+                }
+                for stored in doomed.iter().take(10) {
+                    say!("  {} {}", stored.summary.title, stored.url.dimmed());
+                }
+                if doomed.len() > 10 {
+                    say!("  ... and {} more", doomed.len() - 10);
+                }
+                let confirmed = dialoguer::Confirm::new()
+                    .with_prompt(format!("Delete {} summaries?", doomed.len()))
+                    .default(false)
+                    .interact()?;
+                if !confirmed {
+                    say!("Nothing deleted.");
+                    return Ok(());
+                }
+            }
+            let index = SearchIndex::open(config.storage.search_index_path()).ok();
+            for stored in &doomed {
+                storage.delete(&stored.url)?;
+                log_or_warn(&storage, &Event::new(Action::Deleted, stored));
+                if let Some(ref index) = index {
+                    // A stale index entry is skipped when its record can't be loaded
+                    let _ = index.remove(&stored.url);
+                }
+            }
+            say!("Deleted {} summaries.", doomed.len());
+        }
+        Some(Commands::Collection { action }) => collection_command(action, cli.ascii)?,
+        Some(Commands::Feed { action }) => feed_command(action, cli.ascii).await?,
+        Some(Commands::Push {
+            url,
+            to,
+            all,
+            items,
+        }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let mut stored = storage
+                .get(&url)?
+                .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))?;
+            let backend = to.or(config.tasks.default).ok_or_else(|| {
+                anyhow::anyhow!("no task manager chosen; pass --to or set `default` under [tasks]")
+            })?;
+            let action_items = stored.summary.action_items.clone();
+            if action_items.is_empty() {
+                say!("No action items in '{}'.", stored.summary.title);
+                return Ok(());
+            }
 
-    let results: Vec<String> = all_summaries
-        .into_iter()
-        .filter(|stored| {
-            let summary = &stored.summary;
-            summary.title.to_lowercase().contains(&query_lower)
-                || summary.conclusion.to_lowercase().contains(&query_lower)
-                || summary
-                    .key_points
+            let selected: Vec<usize> = if all {
+                (0..action_items.len())
+                    .filter(|&i| stored.pushed_task(&action_items[i]).is_none())
+                    .collect()
+            } else if !items.is_empty() {
+                items
                     .iter()
-                    .any(|p| p.to_lowercase().contains(&query_lower))
-                || summary
-                    .entities
+                    .map(|&n| match n {
+                        1.. if n <= action_items.len() => Ok(n - 1),
+                        _ => Err(anyhow::anyhow!(
+                            "no action item {} (there are {})",
+                            n,
+                            action_items.len()
+                        )),
+                    })
+                    .collect::<anyhow::Result<_>>()?
+            } else if std::io::stdin().is_terminal() {
+                let labels: Vec<String> = action_items
                     .iter()
-                    .any(|e| e.to_lowercase().contains(&query_lower))
-        })
-        .map(|stored| stored.url)
-        .collect();
+                    .map(|item| match stored.pushed_task(item) {
+                        Some(task) => format!("{} (already pushed to {})", item, task.backend),
+                        None => item.clone(),
+                    })
+                    .collect();
+                dialoguer::MultiSelect::new()
+                    .with_prompt(format!(
+                        "Action items to push to {} (space to check)",
+                        backend
+                    ))
+                    .items(&labels)
+                    .interact()?
+            } else {
+                anyhow::bail!("pass --all or --item N to choose action items non-interactively");
+            };
+            if selected.is_empty() {
+                say!("Nothing to push.");
+                return Ok(());
+            }
+
+            for i in selected {
+                let item = &action_items[i];
+                match tasks::push(backend, item, &stored, &config).await {
+                    Ok(reference) => {
+                        println!(
+                            "{} {} {}",
+                            markers.bullet,
+                            item,
+                            format!("-> {}", reference).dimmed()
+                        );
+                        stored.pushed_tasks.push(PushedTask {
+                            item: item.clone(),
+                            backend: backend.to_string(),
+                            reference,
+                            pushed_at: chrono::Utc::now(),
+                        });
+                    }
+                    Err(e) => {
+                        eprintln!("{} Failed to push '{}': {}", "Warning:".yellow(), item, e)
+                    }
+                }
+            }
+            storage.put(&stored)?;
+        }
+        Some(Commands::Diff { url }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let mut stored = storage
+                .get(&url)?
+                .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))?;
+            let previous = stored.raw_text.clone().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no cached text for {}; run `summera summarise` on it first",
+                    url
+                )
+            })?;
+
+            // Compare like with like: the stored text came through the same source
+            // handlers, and was masked when it was saved
+            let cancel = cancel_on_ctrl_c();
+            let mut extracted = pipeline::extract(&url, &config, &report_stage, &cancel).await?;
+            extracted.redact(&config.redact)?;
+            let text = extracted.text;
+            let diff = summera::diff::LineDiff::between(&previous, &text);
+            if diff.is_empty() {
+                say!("No changes since it was last fetched.");
+                return Ok(());
+            }
+
+            say!(
+                "Summarising changes (+{} / -{} lines)...\n",
+                diff.added.len(),
+                diff.removed.len()
+            );
+            let change = summera::storage::PageChange {
+                detected_at: chrono::Utc::now(),
+                lines_added: diff.added.len(),
+                lines_removed: diff.removed.len(),
+                summary: pipeline::summarise_changes(&url, &diff.to_patch(), &config).await?,
+            };
+
+            // Annotate the existing entry and compare against this version next time
+            say!(
+                "{}",
+                format!("{} What changed:", markers.conclusion)
+                    .green()
+                    .bold()
+            );
+            println!("  {}", change.summary);
+            stored.changes.push(change);
+            stored.raw_text = Some(text);
+            storage.put(&stored)?;
+            if let Ok(search_index) =
+                SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+            {
+                if let Err(e) = search_index.index_stored(&stored) {
+                    eprintln!("{} Failed to index summary: {}", "Warning:".yellow(), e);
+                }
+            }
+        }
+        Some(Commands::Refine { url, instruction }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let mut stored = storage
+                .get(&url)?
+                .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))?;
+            let text = stored.raw_text.clone().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no cached text for {}; run `summera summarise` on it first",
+                    url
+                )
+            })?;
+
+            say!("Refining '{}': {}\n", stored.summary.title, instruction);
+            let agent::Summarised {
+                summary, provider, ..
+            } = pipeline::refine(&stored, &text, &instruction, &config).await?;
+            stored.language = agent::summary_language(&summary, &provider, &config);
+            stored.revise(summary, provider, &instruction);
+            let event = Event::new(Action::Refined, &stored).with_detail(instruction);
+            let recorded = summera::ledger::record(&config, &event, &stored);
+            storage.put(&stored)?;
+            log_or_warn(&storage, &event);
+            if let Err(e) = recorded {
+                eprintln!(
+                    "{} Failed to record the refinement in the ledger: {}",
+                    "Warning:".yellow(),
+                    e
+                );
+            }
+            if let Ok(search_index) =
+                SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+            {
+                if let Err(e) = search_index.index_stored(&stored) {
+                    eprintln!("{} Failed to index summary: {}", "Warning:".yellow(), e);
+                }
+            }
+
+            print_summary(&stored.summary, &markers);
+            say!(
+                "\n{}",
+                format!(
+                    "Revision {} by {}",
+                    stored.revision(),
+                    stored.provider.as_deref().unwrap_or_default()
+                )
+                .dimmed()
+            );
+        }
+        Some(Commands::Edit { url }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let mut stored = storage
+                .get(&url)?
+                .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))?;
+
+            let original = stored.summary.to_editable()?;
+            let summary = Summary::from_editable(&edit::edit(&original)?)?;
+            if summary.to_editable()? == original {
+                say!("No changes to '{}'.", stored.summary.title);
+                return Ok(());
+            }
+            stored.edit(summary);
+            storage.put(&stored)?;
+            log_or_warn(&storage, &Event::new(Action::Edited, &stored));
+            if let Ok(search_index) =
+                SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+            {
+                if let Err(e) = search_index.index_stored(&stored) {
+                    eprintln!("{} Failed to index summary: {}", "Warning:".yellow(), e);
+                }
+            }
+            say!(
+                "Saved your edit of '{}' (revision {}).",
+                stored.summary.title,
+                stored.revision()
+            );
+        }
+        Some(Commands::Note { url, text, clear }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let mut stored = storage
+                .get(&url)?
+                .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))?;
+
+            let changed = match text {
+                Some(text) => {
+                    stored.append_note(&text);
+                    true
+                }
+                None if clear => stored.set_notes(""),
+                None => {
+                    let original = stored.notes.clone().unwrap_or_default();
+                    stored.set_notes(&edit::edit(&original)?)
+                }
+            };
+            if !changed {
+                say!("No changes to the notes on '{}'.", stored.summary.title);
+                return Ok(());
+            }
+            storage.put(&stored)?;
+            log_or_warn(
+                &storage,
+                &Event::new(Action::Edited, &stored).with_detail("notes"),
+            );
+            if let Ok(search_index) =
+                SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+            {
+                if let Err(e) = search_index.index_stored(&stored) {
+                    eprintln!("{} Failed to index summary: {}", "Warning:".yellow(), e);
+                }
+            }
+            match stored.notes {
+                Some(_) => say!("Saved the notes on '{}'.", stored.summary.title),
+                None => say!("Deleted the notes on '{}'.", stored.summary.title),
+            }
+        }
+        Some(Commands::Ask {
+            target,
+            question,
+            sources,
+        }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let url = target;
+            let mut stored = match storage.get(&url)? {
+                Some(stored) => stored,
+                None if question.is_none() && !reader::is_url(&url) => {
+                    let outcome = search::search_summaries(
+                        &storage,
+                        &config.storage.search_index_path(),
+                        &url,
+                        // A question the index can't parse would only fall back to
+                        // matching its whole text, so report the mistake instead
+                        &SearchOptions {
+                            limit: sources,
+                            strict: true,
+                            ..SearchOptions::default()
+                        },
+                    )?;
+                    let found: Vec<StoredSummary> = outcome
+                        .urls
+                        .iter()
+                        .filter_map(|u| storage.get(u).ok().flatten())
+                        .collect();
+                    if found.is_empty() {
+                        anyhow::bail!("no stored summaries match '{}'", url);
+                    }
+                    let answer = pipeline::answer_from_library(&url, &found, &config).await?;
+                    println!("\n{} {}\n", markers.conclusion.green().bold(), answer);
+                    println!("{}", "Sources:".bold());
+                    for (i, stored) in found.iter().enumerate() {
+                        println!(
+                            "  [{}] {} {}",
+                            i + 1,
+                            stored.summary.title,
+                            stored.url.dimmed()
+                        );
+                    }
+                    return Ok(());
+                }
+                None => anyhow::bail!("no stored summary for {}", url),
+            };
+            if stored.raw_text.is_none() {
+                eprintln!(
+                    "{} No cached text for {}, answering from the summary alone",
+                    "Warning:".yellow(),
+                    url
+                );
+            }
+
+            // Refuse a blocked site before asking for a question
+            config.for_sources([stored.url.as_str()])?;
+            let interactive = question.is_none();
+            if interactive && !std::io::stdin().is_terminal() {
+                anyhow::bail!("pass a question to ask non-interactively");
+            }
+            if interactive {
+                say!(
+                    "Asking about '{}'. Enter an empty line to stop.\n",
+                    stored.summary.title
+                );
+            }
+            let mut question = question;
+            loop {
+                let question = match question.take() {
+                    Some(question) => question,
+                    None if interactive => dialoguer::Input::<String>::new()
+                        .with_prompt("Question")
+                        .allow_empty(true)
+                        .interact_text()?,
+                    None => break,
+                };
+                if question.trim().is_empty() {
+                    break;
+                }
+                let answer = pipeline::answer(&question, &stored, &config).await?;
+                println!("\n{} {}\n", markers.conclusion.green().bold(), answer);
+                stored.record_answer(&question, &answer);
+                storage.put(&stored)?;
+                if let Ok(search_index) =
+                    SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+                {
+                    if let Err(e) = search_index.index_stored(&stored) {
+                        eprintln!("{} Failed to index answer: {}", "Warning:".yellow(), e);
+                    }
+                }
+                if !interactive {
+                    break;
+                }
+            }
+        }
+        Some(Commands::Bookmarks { file, dry_run }) => {
+            let config = Config::load()?;
+            let markers = Markers::select(cli.ascii || config.ui.ascii);
+            let storage = Storage::open(&config.storage.path)?;
+            let file = file
+                .or_else(|| config.sync.bookmarks.clone())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("pass a bookmarks file or set `bookmarks` under [sync]")
+                })?;
+            let file = match (file.strip_prefix("~"), dirs::home_dir()) {
+                (Ok(rest), Some(home)) => home.join(rest),
+                _ => file,
+            };
+            let bookmarks = bookmarks::parse(&std::fs::read_to_string(&file)?);
+            let plan = bookmarks::plan(&bookmarks, &storage)?;
+            if plan.is_empty() {
+                say!(
+                    "All {} bookmarks in {} are summarised.",
+                    bookmarks.len(),
+                    file.display()
+                );
+                return Ok(());
+            }
+            if dry_run {
+                for bookmark in &plan.new {
+                    println!("{} {}", "summarise".green(), bookmark.url);
+                }
+                for url in &plan.removed {
+                    println!("{} {}", "archive".yellow(), url);
+                }
+                for url in &plan.restored {
+                    println!("{} {}", "restore".cyan(), url);
+                }
+                return Ok(());
+            }
+
+            let search_path = config.storage.search_index_path();
+            let search_index = SearchIndex::open_synced(&search_path, &storage).ok();
+            let reindex = |stored: &StoredSummary| {
+                if let Some(ref index) = search_index {
+                    if let Err(e) = index.index_stored(stored) {
+                        eprintln!("{} Failed to index summary: {}", "Warning:".yellow(), e);
+                    }
+                }
+            };
+            for (urls, archive) in [(&plan.removed, true), (&plan.restored, false)] {
+                for url in urls {
+                    let Some(mut stored) = storage.get(url)? else {
+                        continue;
+                    };
+                    if archive {
+                        stored.add_tag(bookmarks::ARCHIVED_TAG);
+                    } else {
+                        stored.remove_tag(bookmarks::ARCHIVED_TAG);
+                    }
+                    storage.put(&stored)?;
+                    reindex(&stored);
+                }
+            }
+
+            let cancel = cancel_on_ctrl_c();
+            let options = pipeline::Options::default();
+            let mut summarised = 0;
+            for bookmark in &plan.new {
+                say!("Summarising: {}", bookmark.url);
+                let stage = |stage: pipeline::Stage| say!("  {}", stage.to_string().dimmed());
+                let result =
+                    pipeline::summarise_source(&bookmark.url, &config, &options, &stage, &cancel)
+                        .await;
+                let mut stored = match result {
+                    Ok(stored) => stored,
+                    Err(pipeline::PipelineError::Cancelled) => {
+                        say!("{}", "Cancelled".yellow());
+                        break;
+                    }
+                    Err(e) => {
+                        eprintln!("{} {}: {}", "Warning:".yellow(), bookmark.url, e);
+                        continue;
+                    }
+                };
+                stored.summary.apply_title(None, bookmark.title.clone());
+                stored.add_tag(bookmarks::BOOKMARK_TAG);
+                stored.pending = true;
+                let event = Event::new(Action::Summarised, &stored).with_detail("from bookmarks");
+                match pipeline::store(&mut stored, event, &storage, &config).await {
+                    Ok(report) => print_report(&report, &markers),
+                    Err(e) => {
+                        eprintln!("{} {}: {}", "Warning:".yellow(), bookmark.url, e);
+                        continue;
+                    }
+                }
+                summarised += 1;
+            }
+            say!(
+                "\nSummarised {} of {} new bookmarks, archived {}, restored {}.",
+                summarised,
+                plan.new.len(),
+                plan.removed.len(),
+                plan.restored.len()
+            );
+            if summarised > 0 {
+                say!(
+                    "{}",
+                    "New summaries wait for review: press 'r' in the TUI or run `summera list --pending`."
+                        .dimmed()
+                );
+            }
+        }
+        Some(Commands::Export {
+            target: None,
+            urls,
+            tag,
+            format,
+            output,
+        }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let mut summaries = stored_or_all(&storage, &urls, tag.as_deref())?;
+            if summaries.is_empty() {
+                say!("Nothing to export.");
+                return Ok(());
+            }
+            // Source text makes exports huge; `summera dump --raw` has it
+            for stored in &mut summaries {
+                stored.raw_text = None;
+            }
+            let document = match format {
+                ExportFormat::Markdown => {
+                    let dir = output.as_deref().ok_or_else(|| {
+                        anyhow::anyhow!("markdown writes one file per summary; pass --output <DIR>")
+                    })?;
+                    let written = export::write_markdown(&summaries, dir)?;
+                    say!("Wrote {} notes to {}", written.len(), dir.display());
+                    None
+                }
+                ExportFormat::Json => Some(export::to_json(&summaries)?),
+                ExportFormat::Csv => Some(export::to_csv(&summaries)),
+                ExportFormat::Html => Some(export::to_html(&summaries)),
+            };
+            match (document, output) {
+                (Some(document), Some(path)) => {
+                    std::fs::write(&path, document)?;
+                    say!("Wrote {} summaries to {}", summaries.len(), path.display());
+                }
+                (Some(document), None) => print!("{}", document),
+                (None, _) => {}
+            }
+            let detail = format!("as {}", format);
+            for stored in &summaries {
+                log_or_warn(
+                    &storage,
+                    &Event::new(Action::Exported, stored).with_detail(&detail),
+                );
+            }
+        }
+        Some(Commands::Export {
+            target: Some(ExportTarget::Readwise { urls, tag }),
+            ..
+        }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let token = config.token_for("readwise").map_err(|_| {
+                anyhow::anyhow!(
+                    "no Readwise token; set READWISE_TOKEN or run `summera login readwise`"
+                )
+            })?;
+            let summaries = stored_or_all(&storage, &urls, tag.as_deref())?;
+            if summaries.is_empty() {
+                say!("Nothing to export.");
+                return Ok(());
+            }
+
+            say!("Exporting {} summaries to Readwise...", summaries.len());
+            let sent = readwise::export(&summaries, &token).await?;
+            for stored in &summaries {
+                log_or_warn(
+                    &storage,
+                    &Event::new(Action::Exported, stored).with_detail("to Readwise"),
+                );
+            }
+            say!("Sent {} highlights.", sent);
+        }
+        Some(Commands::Import { path, on_conflict }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let records = import::read_records(&path)?;
+            say!(
+                "Importing {} summaries from {}...",
+                records.len(),
+                path.display()
+            );
+            let report = import::merge(&storage, records, on_conflict)?;
+            let search_path = config.storage.search_index_path();
+            match SearchIndex::open_synced(&search_path, &storage) {
+                Ok(index) => {
+                    if let Err(e) = index.index_all(&report.written) {
+                        eprintln!("{} Failed to index summaries: {}", "Warning:".yellow(), e);
+                    }
+                }
+                Err(e) => eprintln!("{} Search index unavailable: {}", "Warning:".yellow(), e),
+            }
+            say!(
+                "Added {}, replaced {}, skipped {} already stored.",
+                report.added,
+                report.replaced,
+                report.skipped
+            );
+        }
+        Some(Commands::Dump {
+            format: DumpFormat::Jsonl,
+            raw,
+            schema,
+        }) => {
+            if schema {
+                let schema = schemars::schema_for!(StoredSummary);
+                println!("{}", serde_json::to_string_pretty(&schema)?);
+                return Ok(());
+            }
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            dump_jsonl(&storage, raw)?;
+        }
+        Some(Commands::Backup { list }) => {
+            let config = Config::load()?;
+            let dir = config.backup.dir(&config.storage);
+            if list {
+                let archives = backup::archives(&dir)?;
+                if archives.is_empty() {
+                    say!("No backups in {} yet.", dir.display());
+                }
+                for (made, path) in archives.iter().rev() {
+                    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+                    say!(
+                        "{}  {}",
+                        made.format("%Y-%m-%d %H:%M"),
+                        format!("{} ({} KB)", path.display(), size.div_ceil(1024)).dimmed()
+                    );
+                }
+                return Ok(());
+            }
+            let storage = Storage::open(&config.storage.path)?;
+            let report = backup::run(&storage, &config, chrono::Local::now())?;
+            say!("Backed up to {}", report.archive.display());
+            for path in &report.pruned {
+                say!("{}", format!("Pruned {}", path.display()).dimmed());
+            }
+        }
+        Some(Commands::Daemon { health_addr }) => {
+            let mut config = Config::load()?;
+            if health_addr.is_some() {
+                config.daemon.health_addr = health_addr;
+            }
+            let daemon = Daemon::new(config.clone())?;
+            say!(
+                "Running in the background, backing up to {} ({}). Ctrl-C to stop.",
+                config.backup.dir(&config.storage).display(),
+                config.backup.schedule
+            );
+            match daemon.next_backup() {
+                Some(due) => verbose!("Next backup: {}", due.format("%Y-%m-%d %H:%M")),
+                None => eprintln!(
+                    "{} The backup schedule '{}' never fires",
+                    "Warning:".yellow(),
+                    config.backup.schedule
+                ),
+            }
+            daemon
+                .run(|line| {
+                    say!(
+                        "{} {}",
+                        chrono::Local::now().format("%H:%M").to_string().dimmed(),
+                        line
+                    )
+                })
+                .await;
+        }
+        Some(Commands::Query { sql, schema }) => {
+            if schema {
+                println!("{};", query::SUMMARIES_TABLE);
+                return Ok(());
+            }
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let result = query::run_query(&storage, &sql.unwrap_or_default())?;
+            print_table(&result);
+        }
+        Some(Commands::Log {
+            query,
+            action,
+            limit,
+        }) => {
+            let config = Config::load()?;
+            let storage = Storage::open(&config.storage.path)?;
+            let mut events = storage.events()?;
+            events.retain(|event| {
+                action.is_none_or(|action| event.action == action)
+                    && query.as_deref().is_none_or(|query| event.matches(query))
+            });
+            if events.is_empty() {
+                say!("No matching activity.");
+                return Ok(());
+            }
+            for event in events.iter().rev().take(limit) {
+                let detail = event
+                    .detail
+                    .as_deref()
+                    .map(|detail| format!(" ({})", detail))
+                    .unwrap_or_default();
+                println!(
+                    "{} {:<10} {}{}",
+                    event.at.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+                    event.action.to_string(),
+                    event.title.bold(),
+                    detail.dimmed()
+                );
+                println!("                 {}", event.url.dimmed());
+            }
+        }
+        Some(Commands::Init) => {
+            let cwd = std::env::current_dir()?;
+            if let Some(existing) = summera::config::find_project_dir(&cwd) {
+                say!("Project archive already exists at {}", existing.display());
+            } else {
+                let project = Config::init_project(&cwd)?;
+                say!("Created project archive at {}", project.display());
+                say!("Summaries saved below this directory now stay in this project.");
+            }
+        }
+        Some(Commands::Ledger { path }) => {
+            let config = Config::load()?;
+            let path = path
+                .or_else(|| config.sync.ledger.clone())
+                .ok_or_else(|| anyhow::anyhow!("no ledger; set `ledger` under [sync]"))?;
+            let entries = summera::ledger::verify(&path, &config.token_for("ledger")?)?;
+            say!(
+                "{} entries in {}, every signature valid and the chain unbroken",
+                entries.len(),
+                path.display()
+            );
+            if let (Some(first), Some(last)) = (entries.first(), entries.last()) {
+                say!(
+                    "From {} to {}",
+                    first.at.format("%Y-%m-%d %H:%M"),
+                    last.at.format("%Y-%m-%d %H:%M")
+                );
+            }
+        }
+        Some(Commands::Login { connector, remove }) => {
+            if remove {
+                if summera::config::remove_token(&connector)? {
+                    say!("Removed the {} token from the keyring", connector);
+                } else {
+                    say!("No {} token in the keyring", connector);
+                }
+            } else {
+                // Prompt without echo, or read a piped token for scripts
+                let token = if std::io::stdin().is_terminal() {
+                    dialoguer::Password::new()
+                        .with_prompt(format!("{} token", connector))
+                        .interact()?
+                } else {
+                    let mut line = String::new();
+                    std::io::stdin().read_line(&mut line)?;
+                    line.trim().to_string()
+                };
+                if token.is_empty() {
+                    anyhow::bail!("no token given");
+                }
+                summera::config::store_token(&connector, &token)?;
+                say!("Saved the {} token in the keyring", connector);
+            }
+        }
+        Some(Commands::Update) => {
+            say!("--- Checking for updates ---");
+            let status = self_update::backends::github::Update::configure()
+                .repo_owner("cladam")
+                .repo_name("summa") // name of the repo, I haven't changed it....
+                .bin_name("summera")
+                .show_download_progress(true)
+                .current_version(self_update::cargo_crate_version!())
+                .build()?
+                .update()?;
+            say!("Update status: `{}`!", status.version());
+        }
+        None => {
+            // Default: Launch the TUI
+            ui::run(cli.ascii).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a `tag` subcommand
+fn tag_command(action: TagAction) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let storage = Storage::open(&config.storage.path)?;
+    let search_path = config.storage.search_index_path();
+    let parse_tag = |tag: &str| {
+        let tag = normalise_tag(tag);
+        if tag.is_empty() {
+            anyhow::bail!("tag can't be empty");
+        }
+        Ok(tag)
+    };
+    let reindex = |changed: &[StoredSummary]| {
+        if changed.is_empty() {
+            return;
+        }
+        if let Err(e) =
+            SearchIndex::open_synced(&search_path, &storage).and_then(|i| i.index_all(changed))
+        {
+            eprintln!("{} Failed to index tags: {}", "Warning:".yellow(), e);
+        }
+    };
+
+    let (adding, tag, mut urls, query) = match action {
+        TagAction::Add { tag, urls, query } => (true, tag, urls, query),
+        TagAction::Remove { tag, urls, query } => (false, tag, urls, query),
+        TagAction::Rename { old, new } => {
+            let (old, new) = (parse_tag(&old)?, parse_tag(&new)?);
+            if old != new && tags::tag_in_use(&storage, &new)? {
+                anyhow::bail!(
+                    "tag '{}' already exists; use `summera tag merge {} {}` to combine them",
+                    new,
+                    old,
+                    new
+                );
+            }
+            let changed = tags::replace_tag(&storage, &old, &new)?;
+            reindex(&changed);
+            say!(
+                "Renamed '{}' to '{}' on {} summaries.",
+                old,
+                new,
+                changed.len()
+            );
+            return Ok(());
+        }
+        TagAction::Merge { from, into } => {
+            let (from, into) = (parse_tag(&from)?, parse_tag(&into)?);
+            let changed = tags::replace_tag(&storage, &from, &into)?;
+            reindex(&changed);
+            say!(
+                "Merged '{}' into '{}' on {} summaries.",
+                from,
+                into,
+                changed.len()
+            );
+            return Ok(());
+        }
+        TagAction::Stats => {
+            let stats = tags::tag_stats(&storage)?;
+            if stats.is_empty() {
+                say!("No tags yet. Use `summera tag add <TAG> <URL>`.");
+            } else {
+                print_table(&query::QueryResult {
+                    columns: ["tag", "count", "last used"].map(String::from).to_vec(),
+                    rows: stats
+                        .iter()
+                        .map(|s| {
+                            vec![
+                                s.tag.clone(),
+                                s.count.to_string(),
+                                s.last_used.format("%Y-%m-%d").to_string(),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+            return Ok(());
+        }
+    };
+
+    let tag = parse_tag(&tag)?;
+    if let Some(query) = query {
+        // Bulk edits use the index alone; a loose substring fallback could touch anything
+        let index = SearchIndex::open_synced(&search_path, &storage)?;
+        urls.extend(index.search(&query, storage.count().max(1))?);
+    }
+    urls.sort();
+    urls.dedup();
+
+    let mut changed = Vec::new();
+    for url in &urls {
+        let Some(mut stored) = storage.get(url)? else {
+            eprintln!("{} No stored summary for {}", "Warning:".yellow(), url);
+            continue;
+        };
+        let updated = if adding {
+            stored.add_tag(&tag)
+        } else {
+            stored.remove_tag(&tag)
+        };
+        if updated {
+            storage.put(&stored)?;
+            changed.push(stored);
+        }
+    }
+    reindex(&changed);
+
+    let verb = if adding { "Tagged" } else { "Untagged" };
+    println!(
+        "{} {} of {} matching summaries with '{}'.",
+        verb,
+        changed.len(),
+        urls.len(),
+        tag
+    );
+    Ok(())
+}
+
+/// Icons to show before entries; Nerd Font glyphs aren't ASCII, so `--ascii` turns them off
+fn icon_style(ascii: bool, config: &Config) -> IconStyle {
+    if ascii || config.ui.ascii {
+        IconStyle::None
+    } else {
+        config.ui.icons
+    }
+}
+
+/// Print a full summary after summarising
+fn print_summary(summary: &Summary, markers: &Markers) {
+    match body() {
+        Body::Full => {}
+        Body::Plain => {
+            println!("{}", summary.conclusion);
+            if !summary.key_points.is_empty() {
+                println!();
+            }
+            for point in &summary.key_points {
+                println!("- {}", point);
+            }
+            return;
+        }
+        Body::Conclusion => {
+            println!("{}", summary.conclusion);
+            return;
+        }
+    }
+    println!("{}\n", format!("=== {} ===", summary.title).bold());
+
+    println!("{}", format!("{}:", markers.conclusion).green().bold());
+    println!("  {}\n", summary.conclusion);
+
+    // Lite captures have none
+    if !summary.key_points.is_empty() {
+        println!("{}", format!("{}:", markers.key_points).green().bold());
+        for point in &summary.key_points {
+            println!("  {} {}", markers.bullet, point);
+        }
+    }
+
+    if !summary.entities.is_empty() {
+        println!("\n{}", format!("{}:", markers.entities).green().bold());
+        let groups = summary.entities_by_kind();
+        let labelled = groups.len() > 1 || groups[0].0 != EntityKind::Other;
+        for (kind, names) in groups {
+            if labelled {
+                println!("  {}: {}", kind.plural(), names.join(", ").dimmed());
+            } else {
+                println!("  {}", names.join(", ").dimmed());
+            }
+        }
+    }
+
+    if !summary.topics.is_empty() {
+        println!("\n{}", format!("{}:", markers.topics).green().bold());
+        println!("  {}", summary.topics.join(", ").dimmed());
+    }
+
+    if let Some(ref sentiment) = summary.sentiment {
+        println!("\n{}", format!("{}:", markers.sentiment).green().bold());
+        println!("  {}", sentiment);
+    }
+
+    for (heading, lines) in summary.custom_sections() {
+        println!("\n{}", format!("{}:", heading).green().bold());
+        for line in lines {
+            println!("  {} {}", markers.bullet, line);
+        }
+    }
+
+    if !summary.action_items.is_empty() {
+        println!("\n{}", format!("{}:", markers.action_items).green().bold());
+        for item in &summary.action_items {
+            println!("  {} {}", markers.bullet, item);
+        }
+    }
+}
+
+/// Print a stored summary through `template`, or in full without one
+fn print_stored(stored: &StoredSummary, template: Option<&str>, markers: &Markers) {
+    match output_format() {
+        OutputFormat::Json => return print_json(stored),
+        OutputFormat::Markdown => return print!("{}", stored.summary.to_markdown()),
+        OutputFormat::Text => {}
+    }
+    match template {
+        Some(template) => println!("{}", summera::template::render_summary(template, stored)),
+        None => {
+            print_summary(&stored.summary, markers);
+            if let Some(stats) = stored.reading_stats().filter(|_| body() == Body::Full) {
+                println!("\n{}", stats.dimmed());
+            }
+        }
+    }
+}
+
+/// Print a stored summary as one line of JSON, without its extracted text
+fn print_json(stored: &StoredSummary) {
+    let mut stored = stored.clone();
+    stored.raw_text = None;
+    println!("{}", serde_json::to_string(&stored).unwrap_or_default());
+}
+
+/// Print a one-entry overview of a stored summary for `list` and `search`,
+/// or the summary through `template` when one is given
+fn print_entry(
+    stored: &StoredSummary,
+    template: Option<&str>,
+    markers: &Markers,
+    icons: IconStyle,
+) {
+    match output_format() {
+        OutputFormat::Json => return print_json(stored),
+        OutputFormat::Markdown => return println!("{}", stored.summary.to_markdown()),
+        OutputFormat::Text => {}
+    }
+    if let Some(template) = template {
+        println!("{}", summera::template::render_summary(template, stored));
+        return;
+    }
+    // Just the id, for scripts
+    if verbosity() == Verbosity::Quiet {
+        println!("{}", stored.url);
+        return;
+    }
+    let reading = stored
+        .reading_minutes()
+        .map(|minutes| format!(", {} min read", minutes))
+        .unwrap_or_default();
+    let star = if stored.starred {
+        format!(" {}", markers.star)
+    } else {
+        String::new()
+    };
+    println!(
+        "{} {}{} {}",
+        icons.icon(stored).unwrap_or(markers.entry),
+        stored.summary.title.bold(),
+        star,
+        format!(
+            "({}{})",
+            stored.created_at.format("%Y-%m-%d %H:%M"),
+            reading
+        )
+        .dimmed()
+    );
+    println!("   {}", stored.url.cyan());
+    if !stored.tags.is_empty() {
+        let tags: Vec<String> = stored.tags.iter().map(|t| format!("#{}", t)).collect();
+        println!("   {}", tags.join(" ").dimmed());
+    }
+    if let Some(until) = stored
+        .snoozed_until
+        .filter(|_| stored.is_snoozed(chrono::Utc::now()))
+    {
+        let until = until.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M");
+        println!("   {}", format!("Snoozed until {}", until).dimmed());
+    }
+    println!("   {}\n", stored.summary.conclusion);
+}
+
+/// Prompt for queries until an empty line, listing numbered results with snippets.
+///
+/// Entering a result's number prints that summary in full.
+fn search_repl(
+    storage: &Storage,
+    config: &Config,
+    options: &SearchOptions,
+    first: Option<String>,
+    markers: &Markers,
+) -> anyhow::Result<()> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("search --interactive needs a terminal");
+    }
+    say!(
+        "{}",
+        "Type a query, a result's number to read it, or an empty line to quit.".dimmed()
+    );
+    let search_path = config.storage.search_index_path();
+    let mut results: Vec<StoredSummary> = Vec::new();
+    let mut next = first;
+    loop {
+        let input = match next.take() {
+            Some(query) => query,
+            None => dialoguer::Input::<String>::new()
+                .with_prompt("search")
+                .allow_empty(true)
+                .interact_text()?,
+        };
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(());
+        }
+        if let Ok(number) = input.parse::<usize>() {
+            match number.checked_sub(1).and_then(|i| results.get(i)) {
+                Some(stored) => {
+                    println!("\n{}\n", stored.url.cyan());
+                    print_summary(&stored.summary, markers);
+                    println!();
+                }
+                None => eprintln!("{} No result {}", "Warning:".yellow(), number),
+            }
+            continue;
+        }
+        let outcome = match search::search_summaries(storage, &search_path, input, options) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                continue;
+            }
+        };
+        results = outcome
+            .urls
+            .iter()
+            .filter_map(|url| storage.get(url).ok().flatten())
+            .collect();
+        if results.is_empty() {
+            say!("No results found for: {}\n", input);
+            continue;
+        }
+        println!();
+        for (i, stored) in results.iter().enumerate() {
+            println!("{:>3}. {}", i + 1, stored.summary.title.bold());
+            println!("     {}", stored.url.cyan());
+            println!(
+                "     {}\n",
+                search::snippet(stored, input, 100, markers.ellipsis).dimmed()
+            );
+        }
+    }
+}
+
+/// How `summarise_many` runs
+struct Batch<'a> {
+    /// Summarise sources that are already stored again
+    force: bool,
+    /// Store lite captures instead of summarising
+    lite: bool,
+    /// Most sources summarised at once
+    jobs: usize,
+    template: Option<&'a str>,
+    ascii: bool,
+}
+
+/// Summarise several sources, `batch.jobs` at a time, printing each summary as it's
+/// ready and a report of what failed at the end.
+///
+/// A source that fails is reported and skipped; the error returned at the end
+/// makes the exit code non-zero so scripts notice.
+async fn summarise_many(
+    sources: &[String],
+    config: &Config,
+    batch: &Batch<'_>,
+) -> anyhow::Result<()> {
+    let markers = Markers::select(batch.ascii || config.ui.ascii);
+    let storage = Storage::open(&config.storage.path)?;
+    let total = sources.len();
+    let label = |i: usize| format!("[{}/{}]", i + 1, total);
+
+    // Skip what's already stored before starting, so only real work is queued
+    let mut queue = Vec::new();
+    let mut skipped = 0;
+    for (i, source) in sources.iter().enumerate() {
+        let existing = if reader::is_url(source) {
+            storage.find_duplicate(source)?
+        } else {
+            None
+        };
+        match existing {
+            // Lite captures are upgraded unless this is another lite pass
+            Some(existing) if !batch.force && (batch.lite || !existing.is_lite()) => {
+                say!(
+                    "{} {}\n",
+                    label(i).bold(),
+                    format!(
+                        "Already stored as '{}' ({}); --force to summarise it again",
+                        existing.summary.title, existing.url
+                    )
+                    .dimmed()
+                );
+                skipped += 1;
+            }
+            // Summarising again revises the stored record rather than adding a second one
+            Some(existing) => queue.push((i, existing.url)),
+            None => queue.push((i, source.clone())),
+        }
+    }
+
+    let config = std::sync::Arc::new(config.clone());
+    let options = std::sync::Arc::new(pipeline::Options {
+        force: batch.force,
+        lite: batch.lite,
+        // Opened once: sled refuses a second handle in the same process
+        cache: summera::cache::SummaryCache::open(config.storage.summary_cache_path())
+            .ok()
+            .map(std::sync::Arc::new),
+        ..Default::default()
+    });
+    let cancel = cancel_on_ctrl_c();
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(batch.jobs.max(1)));
+    let concurrent = batch.jobs > 1;
+    let mut running = tokio::task::JoinSet::new();
+    for (i, source) in queue {
+        let (config, options) = (config.clone(), options.clone());
+        let (cancel, permits) = (cancel.clone(), permits.clone());
+        let label = label(i);
+        running.spawn(async move {
+            // Held until the summary is printed, so one job at a time reads in order
+            let permit = permits.acquire_owned().await.ok();
+            if cancel.is_cancelled() {
+                return (
+                    label,
+                    source,
+                    Err(pipeline::PipelineError::Cancelled),
+                    permit,
+                );
+            }
+            say!("{} {}", label.bold(), source);
+            // Stages of sources summarised at once are told apart by their number
+            let prefix = if concurrent {
+                format!("{} ", label)
+            } else {
+                String::new()
+            };
+            let stage = |stage: pipeline::Stage| say!("  {}{}", prefix, stage.to_string().dimmed());
+            let started = Instant::now();
+            let result =
+                pipeline::summarise_source(&source, &config, &options, &stage, &cancel).await;
+            verbose!(
+                "{}Summarised in {:.1}s",
+                prefix,
+                started.elapsed().as_secs_f64()
+            );
+            (label, source, result, permit)
+        });
+    }
+
+    let mut summarised = 0;
+    let mut failed = Vec::new();
+    let mut cancelled = 0;
+    while let Some(finished) = running.join_next().await {
+        let (label, source, result, _permit) = finished?;
+        let mut stored = match result {
+            Ok(stored) => stored,
+            Err(pipeline::PipelineError::Cancelled) => {
+                if cancelled == 0 {
+                    say!("{}\n", "Cancelled".yellow());
+                }
+                cancelled += 1;
+                continue;
+            }
+            Err(e) => {
+                eprintln!("{} {} {}: {}\n", "Error:".red(), label, source, e);
+                failed.push((source, e.to_string()));
+                continue;
+            }
+        };
+        let event = Event::new(Action::Summarised, &stored);
+        stored.unread = false;
+        let report = pipeline::store(&mut stored, event, &storage, &config).await?;
+        if concurrent {
+            say!("{} {}", label.bold(), source);
+        }
+        print_report(&report, &markers);
+        say!();
+        print_stored(&stored, batch.template, &markers);
+        say!();
+        summarised += 1;
+    }
+
+    say!(
+        "Summarised {} of {} sources, {} already stored, {} failed.",
+        summarised,
+        total,
+        skipped,
+        failed.len()
+    );
+    for (source, error) in &failed {
+        eprintln!("  {} {}: {}", markers.bullet, source, error);
+    }
+    if !failed.is_empty() {
+        let sources: Vec<&str> = failed.iter().map(|(source, _)| source.as_str()).collect();
+        anyhow::bail!("failed to summarise {}", sources.join(", "));
+    }
+    if cancelled > 0 {
+        anyhow::bail!("cancelled with {} source(s) left", cancelled);
+    }
+    Ok(())
+}
+
+/// Print what storing a new summary did besides saving it
+fn print_report(report: &pipeline::Stored, markers: &Markers) {
+    if !report.tagged.is_empty() {
+        say!(
+            "{}",
+            format!("Auto-tagged: {}", report.tagged.join(", ")).dimmed()
+        );
+    }
+    if let Some(ref path) = report.daily_note {
+        say!("{}", format!("Added to {}", path.display()).dimmed());
+    }
+    for saved in &report.alerts {
+        say!("{} Matches saved search '{}'", markers.alert, saved.name);
+    }
+    for warning in &report.warnings {
+        eprintln!("{} {}", "Warning:".yellow(), warning);
+    }
+}
+
+/// Log `event` to the activity log, warning rather than failing: by now the change
+/// it records has already been made
+fn log_or_warn(storage: &Storage, event: &Event) {
+    if let Err(e) = storage.log_event(event) {
+        eprintln!("{} Failed to log the event: {}", "Warning:".yellow(), e);
+    }
+}
+
+/// Print a pipeline stage of a single summary as progress, or as a warning
+fn report_stage(stage: pipeline::Stage) {
+    match stage {
+        pipeline::Stage::Extracted { budget, .. } if !budget.fits() => {
+            say!("{}", stage);
+            eprintln!(
+                "{} Prompt exceeds the context window, summarising in chunks",
+                "Warning:".yellow()
+            );
+        }
+        pipeline::Stage::Cached => say!(
+            "{}",
+            "Using the cached summary of identical text (--force to summarise again)".dimmed()
+        ),
+        pipeline::Stage::Summarising(agent::Progress::Extractive { .. })
+        | pipeline::Stage::Unprocessed(_) => eprintln!("{} {}", "Warning:".yellow(), stage),
+        pipeline::Stage::Summarising(_) => verbose!("{}", stage),
+        _ => say!("{}", stage),
+    }
+}
+
+/// A token cancelled by Ctrl-C, so a summary in progress stops without storing
+/// anything; a second Ctrl-C exits at once
+fn cancel_on_ctrl_c() -> pipeline::CancellationToken {
+    let cancel = pipeline::CancellationToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            token.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
+    cancel
+}
+
+/// Run a `feed` subcommand
+async fn feed_command(action: FeedAction, ascii: bool) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let storage = Storage::open(&config.storage.path)?;
+
+    match action {
+        FeedAction::Add { url, name, full } => {
+            if !reader::is_url(&url) {
+                anyhow::bail!("feed URL must start with http:// or https://");
+            }
+            if let Some(feed) = storage.feeds()?.into_iter().find(|feed| feed.url == url) {
+                anyhow::bail!("already subscribed to {} as '{}'", url, feed.name);
+            }
+            let document = feeds::fetch(&url).await?;
+            let name = name
+                .or(document.title)
+                .filter(|name| !name.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("the feed has no title; pass --name"))?;
+            if storage.feed(name.trim())?.is_some() {
+                anyhow::bail!("feed '{}' already exists; pass --name", name.trim());
+            }
+            let feed = Feed::new(&name, &url, !full);
+            storage.put_feed(&feed)?;
+            say!(
+                "Subscribed to '{}' ({} entries). Run `summera feed sync` to summarise the latest.",
+                feed.name,
+                document.entries.len()
+            );
+        }
+        FeedAction::List => {
+            let feeds = storage.feeds()?;
+            if feeds.is_empty() {
+                say!("No feeds. Use `summera feed add <URL>`.");
+            } else {
+                print_table(&query::QueryResult {
+                    columns: ["feed", "tag", "url", "synced"].map(String::from).to_vec(),
+                    rows: feeds
+                        .iter()
+                        .map(|feed| {
+                            vec![
+                                feed.name.clone(),
+                                feed.tag(),
+                                feed.url.clone(),
+                                feed.synced_at.map_or_else(
+                                    || "never".to_string(),
+                                    |at| at.format("%Y-%m-%d %H:%M").to_string(),
+                                ),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+        }
+        FeedAction::Remove { name } => {
+            if storage.delete_feed(name.trim())? {
+                say!("Unsubscribed from '{}'.", name.trim());
+            } else {
+                say!("No feed named '{}'.", name.trim());
+            }
+        }
+        FeedAction::Sync {
+            name,
+            limit,
+            dry_run,
+        } => {
+            let feeds = match name {
+                Some(name) => vec![storage
+                    .feed(name.trim())?
+                    .ok_or_else(|| anyhow::anyhow!("no feed named '{}'", name.trim()))?],
+                None => storage.feeds()?,
+            };
+            if feeds.is_empty() {
+                say!("No feeds. Use `summera feed add <URL>`.");
+                return Ok(());
+            }
+            let markers = Markers::select(ascii || config.ui.ascii);
+            let cancel = cancel_on_ctrl_c();
+            let mut summarised = 0;
+            'feeds: for mut feed in feeds {
+                let document = match feeds::fetch(&feed.url).await {
+                    Ok(document) => document,
+                    Err(e) => {
+                        eprintln!("{} {}: {}", "Warning:".yellow(), feed.name, e);
+                        continue;
+                    }
+                };
+                let mut new = feed.unseen(&document.entries, limit);
+                // Already summarised some other way
+                for entry in &new {
+                    if storage.find_duplicate(&entry.url)?.is_some() {
+                        feed.mark_seen(&entry.url);
+                    }
+                }
+                new.retain(|entry| !feed.seen.contains(&entry.url));
+                if dry_run {
+                    for entry in &new {
+                        println!("{} {} {}", feed.name.cyan(), "summarise".green(), entry.url);
+                    }
+                    continue;
+                }
+                say!("{}: {} new", feed.name.bold(), new.len());
+                let options = pipeline::Options {
+                    lite: feed.lite,
+                    ..Default::default()
+                };
+                let verb = if feed.lite {
+                    "Capturing"
+                } else {
+                    "Summarising"
+                };
+                for entry in &new {
+                    say!("{}: {}", verb, entry.url);
+                    let stage = |stage: pipeline::Stage| say!("  {}", stage.to_string().dimmed());
+                    let result =
+                        pipeline::summarise_source(&entry.url, &config, &options, &stage, &cancel)
+                            .await;
+                    let mut stored = match result {
+                        Ok(stored) => stored,
+                        Err(pipeline::PipelineError::Cancelled) => {
+                            say!("{}", "Cancelled".yellow());
+                            storage.put_feed(&feed)?;
+                            break 'feeds;
+                        }
+                        Err(e) => {
+                            eprintln!("{} {}: {}", "Warning:".yellow(), entry.url, e);
+                            continue;
+                        }
+                    };
+                    feed.adopt(&mut stored, entry);
+                    let event = Event::new(Action::Summarised, &stored)
+                        .with_detail(format!("from feed {}", feed.name));
+                    match pipeline::store(&mut stored, event, &storage, &config).await {
+                        Ok(report) => print_report(&report, &markers),
+                        Err(e) => {
+                            eprintln!("{} {}: {}", "Warning:".yellow(), entry.url, e);
+                            continue;
+                        }
+                    }
+                    feed.mark_seen(&entry.url);
+                    summarised += 1;
+                }
+                feed.synced(&document.entries);
+                storage.put_feed(&feed)?;
+            }
+            if !dry_run && summarised > 0 {
+                say!(
+                    "\nAdded {} new entries. They wait for review: press 'r' in the TUI or run `summera list --pending`.",
+                    summarised
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run a `collection` subcommand
+fn collection_command(action: CollectionAction, ascii: bool) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let storage = Storage::open(&config.storage.path)?;
+    let find = |name: &str| {
+        storage
+            .collection(name.trim())?
+            .ok_or_else(|| anyhow::anyhow!("no collection named '{}'", name.trim()))
+    };
+
+    match action {
+        CollectionAction::List => {
+            let collections = storage.collections()?;
+            if collections.is_empty() {
+                say!("No collections. Use `summera collection add <NAME> <URL>`.");
+            } else {
+                print_table(&query::QueryResult {
+                    columns: ["collection", "summaries", "created"]
+                        .map(String::from)
+                        .to_vec(),
+                    rows: collections
+                        .iter()
+                        .map(|c| {
+                            vec![
+                                c.name.clone(),
+                                c.urls.len().to_string(),
+                                c.created_at.format("%Y-%m-%d").to_string(),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+        }
+        CollectionAction::Show { name } => {
+            let collection = find(&name)?;
+            let markers = Markers::select(ascii || config.ui.ascii);
+            if collection.urls.is_empty() {
+                say!("'{}' is empty.", collection.name);
+                return Ok(());
+            }
+            say!("{} ({}):\n", collection.name, collection.urls.len());
+            for url in &collection.urls {
+                if let Some(stored) = storage.get(url)? {
+                    print_entry(&stored, None, &markers, icon_style(ascii, &config));
+                }
+            }
+        }
+        CollectionAction::Add { name, urls } => {
+            let mut collection = match storage.collection(name.trim())? {
+                Some(collection) => collection,
+                None if name.trim().is_empty() => anyhow::bail!("collection name can't be empty"),
+                None => Collection::new(&name),
+            };
+            let mut added = 0;
+            for url in &urls {
+                if storage.get(url)?.is_none() {
+                    eprintln!("{} No stored summary for {}", "Warning:".yellow(), url);
+                } else if collection.add(url) {
+                    added += 1;
+                }
+            }
+            storage.put_collection(&collection)?;
+            say!(
+                "Added {} of {} summaries to '{}'.",
+                added,
+                urls.len(),
+                collection.name
+            );
+        }
+        CollectionAction::Remove { name, urls } => {
+            let mut collection = find(&name)?;
+            let removed = urls.iter().filter(|url| collection.remove(url)).count();
+            storage.put_collection(&collection)?;
+            say!(
+                "Removed {} of {} summaries from '{}'.",
+                removed,
+                urls.len(),
+                collection.name
+            );
+        }
+        CollectionAction::Rename { old, new } => {
+            let mut collection = find(&old)?;
+            let new = new.trim();
+            if new.is_empty() {
+                anyhow::bail!("collection name can't be empty");
+            }
+            if new != collection.name && storage.collection(new)?.is_some() {
+                anyhow::bail!("collection '{}' already exists", new);
+            }
+            storage.delete_collection(&collection.name)?;
+            let old = std::mem::replace(&mut collection.name, new.to_string());
+            storage.put_collection(&collection)?;
+            say!("Renamed '{}' to '{}'.", old, new);
+        }
+        CollectionAction::Delete { name } => {
+            if storage.delete_collection(name.trim())? {
+                say!("Deleted collection '{}'.", name.trim());
+            } else {
+                say!("No collection named '{}'.", name.trim());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print query results as an aligned table
+fn print_table(result: &query::QueryResult) {
+    let mut widths: Vec<usize> = result.columns.iter().map(|c| c.chars().count()).collect();
+    for row in &result.rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    let format_row = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(&result.columns).bold());
+    for row in &result.rows {
+        println!("{}", format_row(row));
+    }
+    say!(
+        "{}",
+        format!(
+            "({} row{})",
+            result.rows.len(),
+            if result.rows.len() == 1 { "" } else { "s" }
+        )
+        .dimmed()
+    );
+}
+
+/// Write one JSON record per line to stdout, stopping quietly if the reader goes away
+fn dump_jsonl(storage: &Storage, raw: bool) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    let result = storage.iter().try_for_each(|stored| -> anyhow::Result<()> {
+        let mut stored = stored?;
+        if !raw {
+            stored.raw_text = None;
+        }
+        serde_json::to_writer(&mut out, &stored)?;
+        writeln!(out)?;
+        Ok(())
+    });
+    match result.and_then(|()| Ok(out.flush()?)) {
+        // `summera dump | head` closes the pipe early; that's not an error
+        Err(e) if is_broken_pipe(&e) => Ok(()),
+        other => other,
+    }
+}
+
+/// The stored summaries for `urls`, or every one when none are given, optionally
+/// only those tagged `tag`
+fn stored_or_all(
+    storage: &Storage,
+    urls: &[String],
+    tag: Option<&str>,
+) -> anyhow::Result<Vec<StoredSummary>> {
+    let mut summaries = if urls.is_empty() {
+        storage.list_all()?
+    } else {
+        urls.iter()
+            .map(|url| {
+                storage
+                    .get(url)?
+                    .ok_or_else(|| anyhow::anyhow!("no stored summary for {}", url))
+            })
+            .collect::<anyhow::Result<_>>()?
+    };
+    if let Some(tag) = tag {
+        let tag = normalise_tag(tag);
+        summaries.retain(|stored| stored.tags.contains(&tag));
+    }
+    Ok(summaries)
+}
 
-    Ok(results)
+/// Whether an error was caused by writing to a closed pipe
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    let kind = error
+        .downcast_ref::<std::io::Error>()
+        .map(|e| e.kind())
+        .or_else(|| {
+            error
+                .downcast_ref::<serde_json::Error>()
+                .and_then(|e| e.io_error_kind())
+        });
+    kind == Some(std::io::ErrorKind::BrokenPipe)
 }
@@ -0,0 +1,126 @@
+//! Section markers and glyphs used when rendering summaries.
+//!
+//! The default markers use emoji, which corrupt output on terminals and fonts
+//! without emoji support and confuse some tools when piped. The ASCII set is
+//! selected with `--ascii` or `ascii = true` under `[ui]` in the config, and
+//! automatically on the legacy Windows console, which draws emoji at the wrong
+//! width and garbles the TUI layout.
+
+/// The glyphs used for headings, bullets and list decorations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Markers {
+    pub conclusion: &'static str,
+    pub key_points: &'static str,
+    pub entities: &'static str,
+    pub topics: &'static str,
+    pub sentiment: &'static str,
+    pub action_items: &'static str,
+    /// Prefix for a stored summary in CLI listings
+    pub entry: &'static str,
+    /// Prefix for a saved search in CLI listings
+    pub saved_search: &'static str,
+    /// Saved search alert indicator
+    pub alert: &'static str,
+    /// Active smart collection indicator
+    pub star: &'static str,
+    /// Selected list row indicator
+    pub selected: &'static str,
+    pub bullet: &'static str,
+    pub ellipsis: &'static str,
+    /// Up/down navigation keys in help text
+    pub arrows: &'static str,
+    /// Expanded and collapsed date group headers
+    pub expanded: &'static str,
+    pub collapsed: &'static str,
+    /// Separator before the active quick filter in list titles
+    pub breadcrumb: &'static str,
+    /// Finished stage of a running job
+    pub done: &'static str,
+    /// Animation frames for the stage in progress
+    pub spinner: &'static [&'static str],
+}
+
+impl Markers {
+    /// Emoji and Unicode markers (the default)
+    pub const UNICODE: Self = Self {
+        conclusion: "💡 Conclusion",
+        key_points: "📌 Key Points",
+        entities: "🏷️  Entities",
+        topics: "🧭 Topics",
+        sentiment: "🎭 Stance",
+        action_items: "✅ Action Items",
+        entry: "📄",
+        saved_search: "🔎",
+        alert: "🔔",
+        star: "★",
+        selected: "▶ ",
+        bullet: "•",
+        ellipsis: "…",
+        arrows: "↑↓",
+        expanded: "▾",
+        collapsed: "▸",
+        breadcrumb: "›",
+        done: "✓",
+        spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+    };
+
+    /// Plain ASCII markers
+    pub const ASCII: Self = Self {
+        conclusion: "[*] Conclusion",
+        key_points: "[#] Key Points",
+        entities: "[@] Entities",
+        topics: "[~] Topics",
+        sentiment: "[=] Stance",
+        action_items: "[x] Action Items",
+        entry: "-",
+        saved_search: "?",
+        alert: "(!)",
+        star: "*",
+        selected: "> ",
+        bullet: "-",
+        ellipsis: "~",
+        arrows: "Up/Down",
+        expanded: "v",
+        collapsed: ">",
+        breadcrumb: ">",
+        done: "+",
+        spinner: &["|", "/", "-", "\\"],
+    };
+
+    /// Pick the marker set, falling back to ASCII where emoji render badly
+    pub fn select(ascii: bool) -> Self {
+        let has_env = |key: &str| std::env::var_os(key).is_some();
+        if ascii || !emoji_supported(cfg!(windows), has_env) {
+            Self::ASCII
+        } else {
+            Self::UNICODE
+        }
+    }
+}
+
+impl Default for Markers {
+    fn default() -> Self {
+        Self::UNICODE
+    }
+}
+
+/// Whether the current terminal is expected to render emoji at the right width.
+///
+/// Windows Terminal (`WT_SESSION`) and terminals that announce themselves via
+/// `TERM_PROGRAM` (VS Code, WezTerm, ...) are fine; plain conhost is not.
+fn emoji_supported(is_windows: bool, has_env: impl Fn(&str) -> bool) -> bool {
+    !is_windows || has_env("WT_SESSION") || has_env("TERM_PROGRAM")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emoji_supported() {
+        assert!(emoji_supported(false, |_| false));
+        assert!(!emoji_supported(true, |_| false));
+        assert!(emoji_supported(true, |key| key == "WT_SESSION"));
+        assert!(emoji_supported(true, |key| key == "TERM_PROGRAM"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Notifications for saved search alerts and background job results.
+//!
+//! Alerts are delivered as a JSON POST to the configured webhook, and to any
+//! templated webhooks in the shape each one asks for. Desktop notifications
+//! (via notify-rust) are opt-in and configurable per event type.
+
+use crate::config::NotificationConfig;
+use crate::search::SavedSearch;
+use crate::storage::StoredSummary;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum NotifyError {
+    #[error("webhook request failed: {0}")]
+    WebhookError(#[from] reqwest::Error),
+    #[error("desktop notification failed: {0}")]
+    DesktopError(#[from] notify_rust::error::Error),
+}
+
+/// Events that can raise a desktop notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// A summarisation job finished successfully
+    JobCompleted,
+    /// A summarisation job failed
+    JobFailed,
+    /// A saved search matched a newly stored summary
+    Alert,
+    /// Snoozed summaries came back
+    Reminder,
+}
+
+impl NotifyEvent {
+    /// Whether desktop notifications are enabled for this event
+    fn enabled(self, config: &NotificationConfig) -> bool {
+        let desktop = &config.desktop;
+        desktop.enabled
+            && match self {
+                Self::JobCompleted => desktop.job_completed,
+                Self::JobFailed => desktop.job_failed,
+                Self::Alert => desktop.alert,
+                Self::Reminder => desktop.reminder,
+            }
+    }
+}
+
+/// Payload sent to the webhook when a saved search matches a new summary
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertPayload<'a> {
+    pub event: &'static str,
+    pub saved_search: &'a str,
+    pub query: &'a str,
+    pub url: &'a str,
+    pub title: &'a str,
+    pub conclusion: &'a str,
+}
+
+impl<'a> AlertPayload<'a> {
+    /// Build the payload for a saved search matching a stored summary
+    pub fn new(saved: &'a SavedSearch, stored: &'a StoredSummary) -> Self {
+        Self {
+            event: "saved_search_match",
+            saved_search: &saved.name,
+            query: &saved.query,
+            url: &stored.url,
+            title: &stored.summary.title,
+            conclusion: &stored.summary.conclusion,
+        }
+    }
+}
+
+/// Render a webhook template with the fields of `payload`.
+///
+/// `{{field}}` is JSON-escaped so it can sit inside a quoted JSON string;
+/// `{{{field}}}` is inserted as-is. Unknown fields render as nothing.
+pub fn render(template: &str, payload: &impl Serialize) -> String {
+    crate::template::render_json(template, payload)
+}
+
+/// Show a desktop notification if enabled for `event` in the config.
+pub fn desktop(
+    config: &NotificationConfig,
+    event: NotifyEvent,
+    summary: &str,
+    body: &str,
+) -> Result<(), NotifyError> {
+    if event.enabled(config) {
+        notify_rust::Notification::new()
+            .appname("summera")
+            .summary(summary)
+            .body(body)
+            .show()?;
+    }
+    Ok(())
+}
+
+/// Show a desktop notification, if enabled, for summaries whose snooze is over
+pub fn remind(config: &NotificationConfig, woken: &[StoredSummary]) -> Result<(), NotifyError> {
+    let summary = match woken {
+        [] => return Ok(()),
+        [_] => "A snoozed summary is back".to_string(),
+        _ => format!("{} snoozed summaries are back", woken.len()),
+    };
+    let titles: Vec<&str> = woken.iter().map(|s| s.summary.title.as_str()).collect();
+    desktop(config, NotifyEvent::Reminder, &summary, &titles.join("\n"))
+}
+
+/// Notify every configured channel that `saved` matched `stored`, returning what
+/// failed.
+///
+/// Each channel is tried whether or not the others went through. Does nothing
+/// when no channel is configured.
+pub async fn send_alert(
+    config: &NotificationConfig,
+    saved: &SavedSearch,
+    stored: &StoredSummary,
+) -> Vec<NotifyError> {
+    let mut errors = Vec::new();
+    if let Err(e) = desktop(
+        config,
+        NotifyEvent::Alert,
+        &format!("Saved search '{}' matched", saved.name),
+        &stored.summary.title,
+    ) {
+        errors.push(e);
+    }
+    let payload = AlertPayload::new(saved, stored);
+    let client = reqwest::Client::new();
+    if let Some(ref webhook_url) = config.webhook_url {
+        errors.extend(post(client.post(webhook_url).json(&payload)).await.err());
+    }
+    for webhook in &config.webhooks {
+        let request = client.post(&webhook.url);
+        let request = match webhook.template {
+            Some(ref template) => request
+                .header(reqwest::header::CONTENT_TYPE, &webhook.content_type)
+                .body(render(template, &payload)),
+            None => request.json(&payload),
+        };
+        errors.extend(post(request).await.err());
+    }
+    errors
+}
+
+/// Send a webhook request, failing on an error status
+async fn post(request: reqwest::RequestBuilder) -> Result<(), NotifyError> {
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::Summary;
+
+    fn saved_search() -> SavedSearch {
+        SavedSearch {
+            name: "wasm".to_string(),
+            query: "wasm".to_string(),
+            scope: None,
+            strict: false,
+            alert: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn tries_every_webhook_and_reports_each_failure() {
+        // Nothing listens on a port freed straight after binding it
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let unreachable = format!("http://127.0.0.1:{}/hook", port);
+        let mut config = NotificationConfig {
+            webhook_url: Some(unreachable.clone()),
+            ..Default::default()
+        };
+        config.webhooks = ["{{title}}", "{{url}}"]
+            .into_iter()
+            .map(|template| crate::config::WebhookConfig {
+                url: unreachable.clone(),
+                template: Some(template.to_string()),
+                content_type: "text/plain".to_string(),
+            })
+            .collect();
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        let stored = StoredSummary::new("https://example.com".to_string(), summary);
+
+        let errors = send_alert(&config, &saved_search(), &stored).await;
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn renders_templates_with_escaped_and_raw_fields() {
+        let saved = saved_search();
+        let summary = Summary::new("Say \"hi\"".into(), "C".into(), vec![], vec![], vec![]);
+        let stored = StoredSummary::new("https://example.com".to_string(), summary);
+        let payload = AlertPayload::new(&saved, &stored);
+
+        let body = render(
+            r#"{"value1": "{{ title }}", "value2": "{{url}}{{missing}}"}"#,
+            &payload,
+        );
+        assert_eq!(
+            body,
+            r#"{"value1": "Say \"hi\"", "value2": "https://example.com"}"#
+        );
+        assert_eq!(
+            render("{{{title}}} ({{event}})", &payload),
+            r#"Say "hi" (saved_search_match)"#
+        );
+    }
+}
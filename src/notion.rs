@@ -0,0 +1,243 @@
+//! Notion pages via the REST API.
+//!
+//! Notion pages are rendered in the browser from blocks, so there's nothing to
+//! scrape. The page's blocks are read from the API with an internal integration
+//! secret instead; the integration has to be invited to the page (Share →
+//! Connections) before it can be read.
+
+use crate::config::{Config, ConfigError};
+use crate::scraper::{self, WebContent};
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use thiserror::Error;
+
+/// Base URL of the Notion API
+const API_URL: &str = "https://api.notion.com/v1";
+
+/// API version the block layout below was written against
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// How deep nested blocks (toggles, list items) are followed
+const MAX_DEPTH: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum NotionError {
+    #[error("no Notion token: set NOTION_TOKEN or run `summera login notion`")]
+    NoToken(#[source] ConfigError),
+    #[error("failed to fetch from Notion: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("Notion returned {status} for {url}{hint}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        url: String,
+        hint: &'static str,
+    },
+    #[error("Notion page is empty")]
+    NoContent,
+}
+
+/// The page ID in a `notion.so` or `*.notion.site` link, as a hyphenated UUID.
+///
+/// Links end in the title and the ID, e.g. `https://www.notion.so/acme/Roadmap-1a2b...`.
+pub fn page_id(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://")?;
+    let (host, path) = rest.split_once('/')?;
+    if !(host == "notion.so" || host == "www.notion.so" || host.ends_with(".notion.site")) {
+        return None;
+    }
+    let last = path.split(['?', '#']).next()?.rsplit('/').next()?;
+    let hex: String = last.chars().filter(|c| *c != '-').collect();
+    let id = hex.get(hex.len().checked_sub(32)?..)?;
+    if !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &id[..8],
+        &id[8..12],
+        &id[12..16],
+        &id[16..20],
+        &id[20..]
+    ))
+}
+
+/// Fetch a page and its blocks as text
+pub async fn fetch_page(url: &str, id: &str, config: &Config) -> Result<WebContent, NotionError> {
+    let token = config.token_for("notion").map_err(NotionError::NoToken)?;
+    let client = Client {
+        http: scraper::create_client()?,
+        token,
+    };
+    let page = client.get(&format!("{}/pages/{}", API_URL, id)).await?;
+    let mut lines = Vec::new();
+    client.collect_blocks(id, 0, &mut lines).await?;
+    let text = lines.join("\n");
+    if text.trim().is_empty() {
+        return Err(NotionError::NoContent);
+    }
+    Ok(WebContent {
+        url: url.split(['?', '#']).next().unwrap_or(url).to_string(),
+        title: page_title(&page),
+        text,
+        description: None,
+    })
+}
+
+/// HTTP client with the headers every Notion request needs
+struct Client {
+    http: reqwest::Client,
+    token: String,
+}
+
+impl Client {
+    async fn get(&self, url: &str) -> Result<Value, NotionError> {
+        let response = self
+            .http
+            .get(url)
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let hint = match status.as_u16() {
+                404 => " (is the integration connected to the page?)",
+                _ => "",
+            };
+            return Err(NotionError::Rejected {
+                status,
+                url: url.to_string(),
+                hint,
+            });
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Append the text of a block's children, and theirs, to `lines`
+    fn collect_blocks<'a>(
+        &'a self,
+        id: &'a str,
+        depth: usize,
+        lines: &'a mut Vec<String>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), NotionError>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut cursor: Option<String> = None;
+            loop {
+                let mut url = format!("{}/blocks/{}/children?page_size=100", API_URL, id);
+                if let Some(ref cursor) = cursor {
+                    url.push_str("&start_cursor=");
+                    url.push_str(cursor);
+                }
+                let batch = self.get(&url).await?;
+                for block in batch["results"].as_array().into_iter().flatten() {
+                    if let Some(line) = render_block(block, depth) {
+                        lines.push(line);
+                    }
+                    // Subpages are separate documents; don't inline them
+                    let nested = block["has_children"].as_bool() == Some(true)
+                        && !matches!(
+                            block["type"].as_str(),
+                            Some("child_page" | "child_database")
+                        );
+                    if nested && depth < MAX_DEPTH {
+                        if let Some(child) = block["id"].as_str() {
+                            self.collect_blocks(child, depth + 1, lines).await?;
+                        }
+                    }
+                }
+                match batch["next_cursor"].as_str() {
+                    Some(next) if batch["has_more"].as_bool() == Some(true) => {
+                        cursor = Some(next.to_string())
+                    }
+                    _ => return Ok(()),
+                }
+            }
+        })
+    }
+}
+
+/// The page's title property
+fn page_title(page: &Value) -> Option<String> {
+    let properties = page["properties"].as_object()?;
+    let title = properties
+        .values()
+        .find(|property| property["type"] == "title")?;
+    let title = plain_text(&title["title"]);
+    (!title.is_empty()).then_some(title)
+}
+
+/// One block as a line of Markdown-ish text, indented by nesting depth
+fn render_block(block: &Value, depth: usize) -> Option<String> {
+    let kind = block["type"].as_str()?;
+    let data = &block[kind];
+    let text = plain_text(&data["rich_text"]);
+    let line = match kind {
+        "heading_1" => format!("# {}", text),
+        "heading_2" => format!("## {}", text),
+        "heading_3" => format!("### {}", text),
+        "bulleted_list_item" | "toggle" => format!("- {}", text),
+        "numbered_list_item" => format!("1. {}", text),
+        "to_do" if data["checked"].as_bool() == Some(true) => format!("[x] {}", text),
+        "to_do" => format!("[ ] {}", text),
+        "quote" | "callout" => format!("> {}", text),
+        "paragraph" | "code" => text,
+        "table_row" => data["cells"]
+            .as_array()?
+            .iter()
+            .map(plain_text)
+            .collect::<Vec<_>>()
+            .join(" | "),
+        "child_page" => format!("Subpage: {}", data["title"].as_str()?),
+        _ => return None,
+    };
+    (!line.trim().is_empty()).then(|| format!("{}{}", "  ".repeat(depth), line))
+}
+
+/// Concatenate the plain text of a rich text array
+fn plain_text(rich_text: &Value) -> String {
+    rich_text
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|span| span["plain_text"].as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_page_ids() {
+        assert_eq!(
+            page_id("https://www.notion.so/acme/Roadmap-0123456789abcdef0123456789abcdef?pvs=4")
+                .as_deref(),
+            Some("01234567-89ab-cdef-0123-456789abcdef")
+        );
+        assert_eq!(
+            page_id("https://acme.notion.site/01234567-89ab-cdef-0123-456789abcdef").as_deref(),
+            Some("01234567-89ab-cdef-0123-456789abcdef")
+        );
+        assert_eq!(page_id("https://www.notion.so/acme/Roadmap"), None);
+        assert_eq!(
+            page_id("https://example.com/0123456789abcdef0123456789abcdef"),
+            None
+        );
+    }
+
+    #[test]
+    fn renders_blocks() {
+        let text = |s: &str| json!([{ "plain_text": s }]);
+        let heading = json!({ "type": "heading_2", "heading_2": { "rich_text": text("Goals") } });
+        assert_eq!(render_block(&heading, 0).as_deref(), Some("## Goals"));
+        let todo =
+            json!({ "type": "to_do", "to_do": { "rich_text": text("Ship it"), "checked": true } });
+        assert_eq!(render_block(&todo, 1).as_deref(), Some("  [x] Ship it"));
+        let row = json!({ "type": "table_row", "table_row": { "cells": [text("a"), text("b")] } });
+        assert_eq!(render_block(&row, 0).as_deref(), Some("a | b"));
+        let divider = json!({ "type": "divider", "divider": {} });
+        assert_eq!(render_block(&divider, 0), None);
+    }
+}
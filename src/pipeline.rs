@@ -0,0 +1,712 @@
+//! Fetching, extracting, summarising and storing a source.
+//!
+//! The one sequence behind every way of summarising: `summarise` on the command
+//! line, one source or many, bookmark syncing and the TUI. It runs in three typed
+//! steps, each of which can be used on its own:
+//!
+//! 1. [`extract`] reads the source the way its module knows best (GitHub, chat,
+//!    social, Stack Exchange, mailing list, Wikipedia, YouTube, Confluence, Notion,
+//!    web page or local file) into an [`Extracted`] text.
+//! 2. [`summarise`] turns that into a record ready to store, through the summary
+//!    cache, with the providers `[domains]` allows for the source, and applies the
+//!    house-style post-processing rules.
+//! 3. [`store`] applies the tag rules, signs the record into the ledger, saves and
+//!    logs it, appends it to the daily note, indexes it and sends the alerts it
+//!    matches.
+//!
+//! Progress is reported as [`Stage`]s, and the first two steps stop early, writing
+//! nothing, when their [`CancellationToken`] is cancelled.
+//!
+//! The LLM calls on stored records, [`refine`], [`answer`], [`answer_from_library`]
+//! and [`summarise_changes`], go through here too, so `[domains]` decides in one
+//! place which providers may read a source.
+
+use crate::activity::Event;
+use crate::agent::{self, AgentError, Progress, TextMetadata};
+use crate::cache::SummaryCache;
+use crate::config::{ConfigError, RedactConfig};
+use crate::ledger::{self, LedgerError};
+use crate::postprocess;
+use crate::reader::ReaderError;
+use crate::search::{self, SavedSearch};
+use crate::storage::{StorageError, VideoDetails, LITE_PROVIDER};
+use crate::tokens::Budget;
+use crate::{
+    chat, confluence, daily_note, github, mailing_list, notify, notion, reader, redact, scraper,
+    social, stackexchange, tags, wikipedia, youtube, Config, Entity, EntityKind, SearchIndex,
+    Storage, StoredSummary, Summary,
+};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+pub use tokio_util::sync::CancellationToken;
+
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    /// The source couldn't be fetched; the message comes from its module
+    #[error("{0}")]
+    Fetch(String),
+    #[error("Failed to read file: {0}")]
+    Read(#[from] ReaderError),
+    #[error("Summarisation failed: {0}")]
+    Summarise(#[from] AgentError),
+    /// `[domains]` keeps the source from the configured providers
+    #[error("{0}")]
+    Blocked(ConfigError),
+    #[error("invalid pattern in [redact]: {0}")]
+    Redact(regex::Error),
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("{0}")]
+    Ledger(#[from] LedgerError),
+    #[error("cancelled")]
+    Cancelled,
+}
+
+/// What the pipeline is doing, reported as each step starts
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+    /// Fetching a source over the network, e.g. "GitHub issue <url>"
+    Fetching(String),
+    /// Reading a local file
+    Reading(String),
+    /// About to summarise this much text, and how it compares to the context window
+    Extracted { chars: usize, budget: Budget },
+    /// Reusing the cached summary of identical text
+    Cached,
+    /// Summarising, as reported by the agent
+    Summarising(Progress),
+    /// A `[[postprocess]]` rule failed, so the summary is stored as written
+    Unprocessed(String),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fetching(source) => write!(f, "Fetching {}", source),
+            Self::Reading(path) => write!(f, "Reading {}", path),
+            Self::Extracted { chars, budget } => {
+                write!(f, "Extracted {} characters ({})", chars, budget)
+            }
+            Self::Cached => write!(f, "Using the cached summary of identical text"),
+            Self::Summarising(progress) => write!(f, "{}", progress),
+            Self::Unprocessed(reason) => {
+                write!(f, "Storing the summary without house style: {}", reason)
+            }
+        }
+    }
+}
+
+/// Callback receiving [`Stage`] updates
+pub type OnStage<'a> = &'a (dyn Fn(Stage) + Sync);
+
+/// A source read into text, ready to summarise
+#[derive(Debug, Clone)]
+pub struct Extracted {
+    /// Title found in the source, used when the LLM doesn't give a usable one
+    pub title: Option<String>,
+    pub text: String,
+    /// Key the record is stored under: the canonical URL, an absolute path, or a
+    /// hash for text without a location
+    pub source_key: String,
+    /// Prompt of its own, for threads and questions
+    pub prompt: Option<&'static str>,
+    /// Links kept from the source, e.g. a Wikipedia article's references
+    pub references: Vec<String>,
+    /// Who wrote a social thread, named as an entity of its summary
+    pub author: Option<String>,
+    /// The page's description of itself, kept by lite captures
+    pub description: Option<String>,
+    /// Channel and length of a video summarised from its transcript
+    pub video: Option<VideoDetails>,
+    /// Context for text that didn't come from a page or document
+    pub metadata: TextMetadata,
+}
+
+impl Extracted {
+    fn new(title: Option<String>, text: String, source_key: String) -> Self {
+        Self {
+            title,
+            text,
+            source_key,
+            prompt: None,
+            references: Vec::new(),
+            author: None,
+            description: None,
+            video: None,
+            metadata: TextMetadata::default(),
+        }
+    }
+
+    /// Read a plain text file (notes, emails, Markdown) as-is, or stdin for `-`.
+    ///
+    /// `kind` and `title` are passed on to the LLM as context.
+    pub fn from_text_file(
+        path: &str,
+        kind: Option<String>,
+        title: Option<String>,
+    ) -> Result<Self, PipelineError> {
+        let content = reader::read_text_file(path)?;
+        // Text without a stable location is keyed by its content
+        let source_key = if path == "-" {
+            format!("text:{:016x}", hash_text(&content.text))
+        } else {
+            reader::file_source_key(path)
+        };
+        let mut extracted = Self::new(content.title, content.text, source_key.clone());
+        extracted.metadata = TextMetadata {
+            title,
+            kind,
+            source: Some(source_key),
+        };
+        Ok(extracted)
+    }
+
+    /// Extract the text of a page from HTML rendered elsewhere, such as by a browser
+    /// behind a login, storing it under `url`
+    pub fn from_html(url: &str, html: &str) -> Result<Self, PipelineError> {
+        let content = scraper::parse_content(url, html)
+            .map_err(|e| PipelineError::Fetch(format!("Failed to read HTML: {}", e)))?;
+        let title = content.title.or_else(|| scraper::title_from_url(url));
+        let mut extracted = Self::new(title, content.text, url.to_string());
+        extracted.description = content.description;
+        Ok(extracted)
+    }
+
+    /// The text as sent to the LLM, headed by whatever metadata is known
+    pub fn prompt_text(&self) -> String {
+        self.metadata.with_context(&self.text)
+    }
+
+    /// Mask the personal data `[redact]` asks for, before any of the text is sent
+    /// or stored
+    pub fn redact(&mut self, config: &RedactConfig) -> Result<(), PipelineError> {
+        if !config.is_enabled() {
+            return Ok(());
+        }
+        let masked = |text: &str| redact::redact(text, config).map_err(PipelineError::Redact);
+        self.text = masked(&self.text)?;
+        if let Some(ref title) = self.metadata.title {
+            self.metadata.title = Some(masked(title)?);
+        }
+        if let Some(ref title) = self.title {
+            self.title = Some(masked(title)?);
+        }
+        Ok(())
+    }
+
+    /// `config`, with the source's own prompt if it has one
+    pub fn config(&self, config: &Config) -> Config {
+        match self.prompt {
+            Some(prompt) => config.with_prompt(prompt),
+            None => config.clone(),
+        }
+    }
+}
+
+/// Stable key for text that has no URL or path
+fn hash_text(text: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// How [`summarise`] treats a source
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Store the summary under this title instead of the generated one
+    pub title: Option<String>,
+    /// Summarise again even if identical text is in the summary cache
+    pub force: bool,
+    /// Store a lite capture, with only the title and description, instead of calling
+    /// the LLM
+    pub lite: bool,
+    /// Summary cache shared by summaries run at once; opened for each summary if unset
+    pub cache: Option<Arc<SummaryCache>>,
+}
+
+/// Run `step`, or stop with [`PipelineError::Cancelled`] when `cancel` fires first
+async fn cancellable<T, E>(
+    cancel: &CancellationToken,
+    step: impl Future<Output = Result<T, E>>,
+) -> Result<T, PipelineError>
+where
+    PipelineError: From<E>,
+{
+    match cancel.run_until_cancelled(step).await {
+        Some(result) => Ok(result?),
+        None => Err(PipelineError::Cancelled),
+    }
+}
+
+/// Fetch or read a URL or local file into text
+pub async fn extract(
+    input: &str,
+    config: &Config,
+    on_stage: OnStage<'_>,
+    cancel: &CancellationToken,
+) -> Result<Extracted, PipelineError> {
+    cancellable(cancel, extract_source(input, config, on_stage)).await
+}
+
+async fn extract_source(
+    input: &str,
+    config: &Config,
+    on_stage: OnStage<'_>,
+) -> Result<Extracted, PipelineError> {
+    let fetch = |e: &dyn fmt::Display| PipelineError::Fetch(e.to_string());
+    let extracted = if let Some(thread) = github::Thread::parse(input) {
+        on_stage(Stage::Fetching(format!(
+            "GitHub {} {}",
+            thread.kind,
+            thread.url()
+        )));
+        let content = github::fetch_thread(&thread, config.api.github_token.as_deref())
+            .await
+            .map_err(|e| fetch(&e))?;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.prompt = Some(github::THREAD_PROMPT);
+        extracted
+    } else if let Some(thread) = chat::Thread::parse(input) {
+        on_stage(Stage::Fetching(format!("chat thread {}", input)));
+        let content = thread.fetch(input, config).await.map_err(|e| fetch(&e))?;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.prompt = Some(chat::THREAD_PROMPT);
+        extracted
+    } else if let Some(thread) = social::Thread::parse(input) {
+        on_stage(Stage::Fetching(format!(
+            "{} thread {}",
+            thread.network(),
+            input
+        )));
+        let unrolled = social::fetch_thread(&thread, config)
+            .await
+            .map_err(|e| fetch(&e))?;
+        let content = unrolled.content;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.prompt = Some(social::THREAD_PROMPT);
+        extracted.author = Some(unrolled.author);
+        extracted
+    } else if let Some(question) = stackexchange::Question::parse(input) {
+        on_stage(Stage::Fetching(format!(
+            "{} question {}",
+            question.site, input
+        )));
+        let content =
+            stackexchange::fetch_question(&question, config.api.stackexchange_key.as_deref())
+                .await
+                .map_err(|e| fetch(&e))?;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.prompt = Some(stackexchange::QUESTION_PROMPT);
+        extracted
+    } else if let Some(thread) = mailing_list::Thread::parse(input) {
+        on_stage(Stage::Fetching(format!(
+            "{} thread {}",
+            thread.list(),
+            thread.url()
+        )));
+        let content = mailing_list::fetch_thread(&thread)
+            .await
+            .map_err(|e| fetch(&e))?;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.prompt = Some(mailing_list::THREAD_PROMPT);
+        extracted
+    } else if let Some(article) = wikipedia::Article::parse(input) {
+        on_stage(Stage::Fetching(format!(
+            "Wikipedia article {}",
+            article.url()
+        )));
+        let (content, links) = wikipedia::fetch_article(&article, config.wikipedia.references)
+            .await
+            .map_err(|e| fetch(&e))?;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.references = links;
+        extracted
+    } else if let Some(video) = youtube::Video::parse(input) {
+        on_stage(Stage::Fetching(format!(
+            "YouTube transcript {}",
+            video.url()
+        )));
+        let transcript = youtube::fetch_transcript(&video)
+            .await
+            .map_err(|e| fetch(&e))?;
+        let content = transcript.content;
+        let mut extracted = Extracted::new(content.title, content.text, content.url);
+        extracted.prompt = Some(youtube::TRANSCRIPT_PROMPT);
+        extracted.description = content.description;
+        extracted.video = Some(transcript.details);
+        extracted
+    } else if let Some(page) = confluence::Page::parse(input, &config.confluence) {
+        on_stage(Stage::Fetching(format!("Confluence page {}", input)));
+        let content = confluence::fetch_page(&page, config)
+            .await
+            .map_err(|e| fetch(&e))?;
+        Extracted::new(content.title, content.text, content.url)
+    } else if let Some(id) = notion::page_id(input) {
+        on_stage(Stage::Fetching(format!("Notion page {}", input)));
+        let content = notion::fetch_page(input, &id, config)
+            .await
+            .map_err(|e| fetch(&e))?;
+        Extracted::new(content.title, content.text, content.url)
+    } else if reader::is_url(input) {
+        on_stage(Stage::Fetching(input.to_string()));
+        let content = scraper::fetch_content(input)
+            .await
+            .map_err(|e| PipelineError::Fetch(format!("Failed to fetch URL: {}", e)))?;
+        let title = content.title.or_else(|| scraper::title_from_url(input));
+        let mut extracted = Extracted::new(title, content.text, input.to_string());
+        extracted.description = content.description;
+        extracted
+    } else {
+        on_stage(Stage::Reading(input.to_string()));
+        let content = reader::extract_from_file(input)?;
+        let mut extracted =
+            Extracted::new(content.title, content.text, reader::file_source_key(input));
+        if reader::is_chat_export(input) {
+            extracted.prompt = Some(chat::THREAD_PROMPT);
+        }
+        extracted
+    };
+    Ok(extracted)
+}
+
+/// Summarise extracted text into a record ready to store, using the summary cache
+pub async fn summarise(
+    mut extracted: Extracted,
+    config: &Config,
+    options: &Options,
+    on_stage: OnStage<'_>,
+    cancel: &CancellationToken,
+) -> Result<StoredSummary, PipelineError> {
+    // A summary that can't be signed into the ledger is refused before it's paid for
+    ledger::check(config)?;
+    if options.lite {
+        return lite(extracted, config, options, on_stage).await;
+    }
+    let config = allowed(&extracted.config(config), [extracted.source_key.as_str()])?;
+    extracted.redact(&config.redact)?;
+    let text = extracted.prompt_text();
+    on_stage(Stage::Extracted {
+        chars: extracted.text.len(),
+        budget: agent::prompt_budget(&text, &config),
+    });
+    // The cache is best-effort: another summera may hold its lock
+    let opened;
+    let cache = match options.cache {
+        Some(ref cache) => Some(cache.as_ref()),
+        None => {
+            opened = SummaryCache::open(config.storage.summary_cache_path()).ok();
+            opened.as_ref()
+        }
+    };
+    let cache_key = SummaryCache::key(&text, &config);
+    let cached = match cache {
+        Some(cache) if !options.force => cache.get(&cache_key).ok().flatten(),
+        _ => None,
+    };
+    let agent::Summarised {
+        mut summary,
+        provider,
+        critique,
+    } = match cached {
+        Some(summarised) => {
+            on_stage(Stage::Cached);
+            summarised
+        }
+        None => {
+            let on_progress = |progress| on_stage(Stage::Summarising(progress));
+            let summarised = cancellable(
+                cancel,
+                agent::summarize_with_progress(&text, &config, &on_progress),
+            )
+            .await?;
+            // Don't let an offline summary stand in for a real one later
+            if let Some(cache) = cache.filter(|_| !summarised.is_extractive()) {
+                // A failed write only costs a repeat request next time
+                let _ = cache.put(&cache_key, &summarised);
+            }
+            summarised
+        }
+    };
+    // After the cache, so editing the rules applies to cached summaries too, and
+    // before the title, so an explicit `--title` is kept as given. A failing rule
+    // doesn't throw away a summary that's already been paid for
+    if let Err(e) = postprocess::apply(&mut summary, &config.postprocess).await {
+        on_stage(Stage::Unprocessed(e.to_string()));
+    }
+    summary.apply_title(options.title.clone(), extracted.title);
+    if let Some(ref author) = extracted.author {
+        summary.add_entity(Entity::new(author, EntityKind::Person));
+    }
+
+    let language = agent::summary_language(&summary, &provider, &config);
+    Ok(StoredSummary::new(extracted.source_key, summary)
+        .with_provider(provider)
+        .with_critique(critique)
+        .with_language(language)
+        .with_references(extracted.references)
+        .with_video(extracted.video)
+        .with_raw_text(extracted.text))
+}
+
+/// Longest description a lite capture takes from the start of the text, in bytes
+const LEAD_BYTES: usize = 300;
+
+/// A lite capture of extracted text: its title and description, without an LLM.
+///
+/// Sources without a description of their own are described by the start of
+/// their text. Summarising the source again later replaces it with a full summary.
+async fn lite(
+    mut extracted: Extracted,
+    config: &Config,
+    options: &Options,
+    on_stage: OnStage<'_>,
+) -> Result<StoredSummary, PipelineError> {
+    extracted.redact(&config.redact)?;
+    let description = match extracted.description.take() {
+        Some(description) => description,
+        None => lead(&extracted.text),
+    };
+    let title = extracted
+        .title
+        .unwrap_or_else(|| extracted.source_key.clone());
+    let mut summary = Summary::new(title, description, vec![], vec![], vec![]);
+    if let Err(e) = postprocess::apply(&mut summary, &config.postprocess).await {
+        on_stage(Stage::Unprocessed(e.to_string()));
+    }
+    if let Some(ref title) = options.title {
+        summary.title = title.clone();
+    }
+    Ok(StoredSummary::new(extracted.source_key, summary)
+        .with_provider(LITE_PROVIDER)
+        .with_video(extracted.video)
+        .with_raw_text(extracted.text))
+}
+
+/// The first paragraph of `text`, cut at a sentence or word end to at most
+/// [`LEAD_BYTES`]
+fn lead(text: &str) -> String {
+    let first = text
+        .split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|paragraph| !paragraph.is_empty())
+        .unwrap_or_default();
+    if first.len() <= LEAD_BYTES {
+        return first;
+    }
+    let mut end = LEAD_BYTES;
+    while !first.is_char_boundary(end) {
+        end -= 1;
+    }
+    let cut = &first[..end];
+    match cut.rfind(". ") {
+        Some(sentence) => cut[..=sentence].to_string(),
+        None => format!("{}...", cut[..cut.rfind(' ').unwrap_or(end)].trim_end()),
+    }
+}
+
+/// [`extract`] and [`summarise`] a URL or local file
+pub async fn summarise_source(
+    input: &str,
+    config: &Config,
+    options: &Options,
+    on_stage: OnStage<'_>,
+    cancel: &CancellationToken,
+) -> Result<StoredSummary, PipelineError> {
+    let extracted = extract(input, config, on_stage, cancel).await?;
+    summarise(extracted, config, options, on_stage, cancel).await
+}
+
+/// `config` limited to the providers `[domains]` allows for `urls`
+fn allowed<'a>(
+    config: &Config,
+    urls: impl IntoIterator<Item = &'a str>,
+) -> Result<Config, PipelineError> {
+    config.for_sources(urls).map_err(PipelineError::Blocked)
+}
+
+/// Rewrite the summary of `stored` following `instruction`, from its source `text`
+pub async fn refine(
+    stored: &StoredSummary,
+    text: &str,
+    instruction: &str,
+    config: &Config,
+) -> Result<agent::Summarised, PipelineError> {
+    ledger::check(config)?;
+    let config = allowed(config, [stored.url.as_str()])?;
+    Ok(agent::refine(text, &stored.summary, instruction, &config).await?)
+}
+
+/// Answer `question` about `stored`
+pub async fn answer(
+    question: &str,
+    stored: &StoredSummary,
+    config: &Config,
+) -> Result<String, PipelineError> {
+    let config = allowed(config, [stored.url.as_str()])?;
+    Ok(agent::answer(question, stored, &config).await?)
+}
+
+/// Answer `question` from several stored records, citing them in the order given
+pub async fn answer_from_library(
+    question: &str,
+    sources: &[StoredSummary],
+    config: &Config,
+) -> Result<String, PipelineError> {
+    let config = allowed(config, sources.iter().map(|s| s.url.as_str()))?;
+    Ok(agent::answer_from_library(question, sources, &config).await?)
+}
+
+/// Summarise what changed on the page at `url`, given as a `-`/`+` patch
+pub async fn summarise_changes(
+    url: &str,
+    patch: &str,
+    config: &Config,
+) -> Result<String, PipelineError> {
+    let config = allowed(config, [url])?;
+    Ok(agent::summarize_changes(patch, &config).await?)
+}
+
+/// What [`store`] did besides saving the record
+#[derive(Debug, Default)]
+pub struct Stored {
+    /// Tags added by the tag rules
+    pub tagged: Vec<String>,
+    /// The daily note the summary was appended to
+    pub daily_note: Option<PathBuf>,
+    /// Alerting saved searches the summary matches, notified already
+    pub alerts: Vec<SavedSearch>,
+    /// Steps that failed without stopping the record being saved
+    pub warnings: Vec<String>,
+}
+
+/// Save a newly summarised record after applying the tag rules and signing it into
+/// the ledger, then log `event`, append it to the daily note, index it and send the
+/// alerts it matches.
+///
+/// A record already stored under the same URL is revised rather than replaced, so
+/// `stored` becomes that record with the new summary and keeps the user's notes,
+/// tags and history.
+///
+/// Only reading the existing record and saving can fail; the ledger and anything
+/// after saving, including the activity log, are reported in [`Stored::warnings`].
+pub async fn store(
+    stored: &mut StoredSummary,
+    event: Event,
+    storage: &Storage,
+    config: &Config,
+) -> Result<Stored, PipelineError> {
+    let mut report = Stored::default();
+    if let Some(existing) = storage.get(&stored.url)? {
+        let fresh = std::mem::replace(stored, existing);
+        stored.resummarise(fresh);
+    }
+    report.tagged = tags::apply_rules(stored, &config.tag_rules);
+    // The text was sent either way, so the ledger records it even if saving fails,
+    // and a ledger that fails anyway doesn't cost the summary
+    let recorded = ledger::record(config, &event, stored);
+    storage.put(stored)?;
+    // The summary is saved by now, so a gap in the activity log is only a warning
+    if let Err(e) = storage.log_event(&event) {
+        report
+            .warnings
+            .push(format!("Failed to log the event: {}", e));
+    }
+    if let Err(e) = recorded {
+        report
+            .warnings
+            .push(format!("Failed to record the summary in the ledger: {}", e));
+    }
+    if let Some(ref template) = config.sync.daily_note {
+        match daily_note::append(template, stored, chrono::Local::now()) {
+            Ok(path) => report.daily_note = Some(path),
+            Err(e) => report
+                .warnings
+                .push(format!("Failed to append to the daily note: {}", e)),
+        }
+    }
+
+    let search_path = config.storage.search_index_path();
+    if let Err(e) =
+        SearchIndex::open_synced(&search_path, storage).and_then(|i| i.index_stored(stored))
+    {
+        report
+            .warnings
+            .push(format!("Failed to index summary: {}", e));
+    }
+    match search::matching_alerts(storage, &search_path, &stored.url) {
+        Ok(alerts) => report.alerts = alerts,
+        Err(e) => report
+            .warnings
+            .push(format!("Failed to match saved searches: {}", e)),
+    }
+    for saved in &report.alerts {
+        for e in notify::send_alert(&config.notifications, saved, stored).await {
+            report.warnings.push(format!("Failed to send alert: {}", e));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stops_a_cancelled_step_without_finishing_it() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let step = std::future::pending::<Result<(), PipelineError>>();
+        assert!(matches!(
+            cancellable(&cancel, step).await,
+            Err(PipelineError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn describes_the_extracted_text_and_its_budget() {
+        let extracted = Extracted::new(Some("T".into()), "Some text".into(), "text:1".into());
+        assert_eq!(extracted.prompt_text(), "Some text");
+        let stage = Stage::Extracted {
+            chars: extracted.text.len(),
+            budget: Budget {
+                tokens: 3,
+                limit: 128_000,
+            },
+        };
+        assert_eq!(stage.to_string(), "Extracted 9 characters (~3 tokens)");
+    }
+
+    #[tokio::test]
+    async fn stores_a_lite_capture_from_the_description_or_the_lead() {
+        let config = Config::default();
+        let mut extracted = Extracted::new(
+            Some("Release notes".into()),
+            format!(
+                "\n\nFirst   paragraph. {}\n\nSecond.",
+                "Long sentence ".repeat(30)
+            ),
+            "https://example.com/notes".into(),
+        );
+        let stored = lite(extracted.clone(), &config, &Options::default(), &|_| {})
+            .await
+            .unwrap();
+        assert!(stored.is_lite());
+        assert_eq!(stored.summary.title, "Release notes");
+        assert_eq!(stored.summary.conclusion, "First paragraph.");
+        assert!(stored.summary.key_points.is_empty());
+
+        extracted.description = Some("What changed in 2.0".into());
+        let options = Options {
+            title: Some("Notes 2.0".into()),
+            lite: true,
+            ..Default::default()
+        };
+        let stored = lite(extracted, &config, &options, &|_| {}).await.unwrap();
+        assert_eq!(stored.summary.title, "Notes 2.0");
+        assert_eq!(stored.summary.conclusion, "What changed in 2.0");
+    }
+}
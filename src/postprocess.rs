@@ -0,0 +1,297 @@
+//! House-style edits applied to every new summary before it's stored.
+//!
+//! Rules come from `[[postprocess]]` in the config and run in order. Each one
+//! either replaces a regular expression, applies one of the built-in [`StyleFix`]es,
+//! or hands the whole summary to an external command as JSON and takes back
+//! the edited JSON it prints.
+
+use crate::config::PostProcessRule;
+use crate::Summary;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PostProcessError {
+    #[error("invalid pattern in [[postprocess]]: {0}")]
+    Pattern(#[from] regex::Error),
+    #[error("post-processing command '{command}' failed: {reason}")]
+    Command { command: String, reason: String },
+}
+
+/// Built-in edits for a `[[postprocess]]` rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StyleFix {
+    /// Start with a capital letter; the rest is left alone, to keep names intact
+    SentenceCase,
+    /// Drop a full stop at the end, leaving "..." and "etc." alone
+    StripTrailingPeriod,
+    /// Write dates like "March 5, 2024" or "5th March 2024" as 2024-03-05
+    IsoDates,
+}
+
+/// Parts of a summary a `[[postprocess]]` rule can edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryField {
+    Title,
+    Conclusion,
+    KeyPoints,
+    ActionItems,
+}
+
+impl SummaryField {
+    /// Every field, edited by rules that don't name any
+    pub const ALL: [Self; 4] = [
+        Self::Title,
+        Self::Conclusion,
+        Self::KeyPoints,
+        Self::ActionItems,
+    ];
+
+    /// The text of this field in `summary`, one string per list item
+    fn texts_mut(self, summary: &mut Summary) -> Vec<&mut String> {
+        match self {
+            Self::Title => vec![&mut summary.title],
+            Self::Conclusion => vec![&mut summary.conclusion],
+            Self::KeyPoints => summary.key_points.iter_mut().collect(),
+            Self::ActionItems => summary.action_items.iter_mut().collect(),
+        }
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Month names and abbreviations, standing in for `MONTH` in the date patterns
+const MONTH_PATTERN: &str = "(?:Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?|Aug(?:ust)?|Sep(?:t(?:ember)?)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?)";
+
+/// "March 5, 2024" and "Mar 5th 2024"
+const MONTH_FIRST: &str =
+    r"\b(?P<month>MONTH)\.? (?P<day>\d{1,2})(?:st|nd|rd|th)?,? (?P<year>\d{4})\b";
+
+/// "5 March 2024" and "5th of March, 2024"
+const DAY_FIRST: &str =
+    r"\b(?P<day>\d{1,2})(?:st|nd|rd|th)?(?: of)? (?P<month>MONTH)\.?,? (?P<year>\d{4})\b";
+
+/// Check that every rule's pattern compiles and every command names a program
+pub fn check(rules: &[PostProcessRule]) -> Result<(), PostProcessError> {
+    for rule in rules {
+        if let Some(ref pattern) = rule.pattern {
+            Regex::new(pattern)?;
+        }
+        if let Some(ref command) = rule.command.as_ref().filter(|c| c.trim().is_empty()) {
+            return Err(PostProcessError::Command {
+                command: command.to_string(),
+                reason: "empty command".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Apply `rules` to `summary`, in order, leaving it as it was if any rule fails
+pub async fn apply(
+    summary: &mut Summary,
+    rules: &[PostProcessRule],
+) -> Result<(), PostProcessError> {
+    let mut edited = summary.clone();
+    for rule in rules {
+        if let Some(ref command) = rule.command {
+            edited = run_command(command, &edited).await?;
+            continue;
+        }
+        let pattern = rule.pattern.as_deref().map(Regex::new).transpose()?;
+        let edit = |text: &str| match (rule.builtin, &pattern) {
+            (Some(fix), _) => fix.apply(text),
+            (None, Some(pattern)) => pattern.replace_all(text, &rule.replace).into_owned(),
+            (None, None) => text.to_string(),
+        };
+        let fields = match rule.fields.as_slice() {
+            [] => &SummaryField::ALL[..],
+            fields => fields,
+        };
+        for field in fields {
+            for text in field.texts_mut(&mut edited) {
+                *text = edit(text);
+            }
+        }
+    }
+    *summary = edited;
+    Ok(())
+}
+
+impl StyleFix {
+    /// `text` with this fix applied
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Self::SentenceCase => {
+                let mut chars = text.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            Self::StripTrailingPeriod => {
+                let trimmed = text.trim_end();
+                let abbreviation = trimmed.ends_with("..") || trimmed.ends_with("etc.");
+                match trimmed.strip_suffix('.') {
+                    Some(stripped) if !abbreviation => stripped.to_string(),
+                    _ => text.to_string(),
+                }
+            }
+            Self::IsoDates => {
+                let iso = |caps: &Captures| {
+                    let month = caps["month"].to_lowercase();
+                    let month = MONTHS
+                        .iter()
+                        .position(|m| month.starts_with(m))
+                        .unwrap_or(0)
+                        + 1;
+                    let day: u32 = caps["day"].parse().unwrap_or(0);
+                    match chrono::NaiveDate::from_ymd_opt(
+                        caps["year"].parse().unwrap_or(0),
+                        month as u32,
+                        day,
+                    ) {
+                        Some(date) => date.format("%Y-%m-%d").to_string(),
+                        // Not a real date, e.g. "February 30"; leave it as written
+                        None => caps[0].to_string(),
+                    }
+                };
+                let date = |pattern: &str| {
+                    Regex::new(&pattern.replace("MONTH", MONTH_PATTERN))
+                        .expect("date pattern is valid")
+                };
+                let text = date(MONTH_FIRST).replace_all(text, iso);
+                date(DAY_FIRST).replace_all(&text, iso).into_owned()
+            }
+        }
+    }
+}
+
+/// Pass `summary` as JSON to `command` on stdin and read the edited summary from stdout.
+///
+/// The command line is split on whitespace, without a shell. It runs on the
+/// blocking pool, so a slow command doesn't hold up the runtime.
+async fn run_command(command: &str, summary: &Summary) -> Result<Summary, PostProcessError> {
+    let failed = |reason: String| PostProcessError::Command {
+        command: command.to_string(),
+        reason,
+    };
+    let input = serde_json::to_vec(summary).map_err(|e| failed(e.to_string()))?;
+    let owned = command.to_string();
+    tokio::task::spawn_blocking(move || run_blocking(&owned, input))
+        .await
+        .map_err(|e| failed(e.to_string()))?
+}
+
+fn run_blocking(command: &str, input: Vec<u8>) -> Result<Summary, PostProcessError> {
+    let failed = |reason: String| PostProcessError::Command {
+        command: command.to_string(),
+        reason,
+    };
+    let mut words = command.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| failed("empty command".to_string()))?;
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+    // Written from its own thread while stdout is read, so a command that prints
+    // before it has read everything can't fill both pipes and stall
+    let writer = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            // A command that exits without reading all of it is judged by its output
+            let _ = stdin.write_all(&input);
+        })
+    });
+    let output = child
+        .wait_with_output()
+        .map_err(|e| failed(e.to_string()))?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        return Err(failed(output.status.to_string()));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| failed(format!("didn't print a summary as JSON: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn applies_rules_in_order_to_the_fields_named() {
+        let mut summary = Summary::new(
+            "release notes".into(),
+            "Shipped on March 5th, 2024.".into(),
+            vec!["faster builds.".into(), "See the docs etc.".into()],
+            vec![],
+            vec!["upgrade by 1 April 2024.".into()],
+        );
+        let rules = [
+            PostProcessRule {
+                builtin: Some(StyleFix::IsoDates),
+                ..Default::default()
+            },
+            PostProcessRule {
+                builtin: Some(StyleFix::StripTrailingPeriod),
+                fields: vec![SummaryField::KeyPoints, SummaryField::ActionItems],
+                ..Default::default()
+            },
+            PostProcessRule {
+                builtin: Some(StyleFix::SentenceCase),
+                ..Default::default()
+            },
+            PostProcessRule {
+                pattern: Some(r"\bdocs\b".into()),
+                replace: "documentation".into(),
+                ..Default::default()
+            },
+        ];
+        check(&rules).unwrap();
+        apply(&mut summary, &rules).await.unwrap();
+        assert_eq!(summary.title, "Release notes");
+        assert_eq!(summary.conclusion, "Shipped on 2024-03-05.");
+        assert_eq!(
+            summary.key_points,
+            ["Faster builds", "See the documentation etc."]
+        );
+        assert_eq!(summary.action_items, ["Upgrade by 2024-04-01"]);
+
+        assert_eq!(
+            StyleFix::IsoDates.apply("February 30, 2024"),
+            "February 30, 2024"
+        );
+        let invalid = PostProcessRule {
+            pattern: Some("(".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            check(std::slice::from_ref(&invalid)),
+            Err(PostProcessError::Pattern(_))
+        ));
+
+        // A rule that fails leaves the edits of the rules before it undone
+        let rules = [
+            PostProcessRule {
+                pattern: Some("Release".into()),
+                replace: "Changelog".into(),
+                ..Default::default()
+            },
+            invalid,
+        ];
+        assert!(apply(&mut summary, &rules).await.is_err());
+        assert_eq!(summary.title, "Release notes");
+    }
+}
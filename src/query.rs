@@ -0,0 +1,160 @@
+//! Read-only SQL over the archive.
+//!
+//! Copies every stored summary into an in-memory SQLite database with a single
+//! `summaries` table, then runs the query with `PRAGMA query_only` so nothing
+//! can be written back. List fields are stored as JSON arrays, so SQLite's
+//! `json_each` and `json_array_length` work on them.
+
+use crate::storage::{Storage, StorageError};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error("SQL error: {0}")]
+    SqlError(#[from] rusqlite::Error),
+    #[error("storage error: {0}")]
+    StorageError(#[from] StorageError),
+    #[error("failed to encode field: {0}")]
+    EncodeError(#[from] serde_json::Error),
+}
+
+/// Definition of the `summaries` table the queries run against
+pub const SUMMARIES_TABLE: &str = "CREATE TABLE summaries (
+    url TEXT PRIMARY KEY,
+    domain TEXT,
+    created_at TEXT NOT NULL,
+    provider TEXT,
+    language TEXT,
+    title TEXT NOT NULL,
+    conclusion TEXT NOT NULL,
+    key_points TEXT NOT NULL,
+    entities TEXT NOT NULL,
+    action_items TEXT NOT NULL,
+    topics TEXT NOT NULL,
+    custom TEXT NOT NULL,
+    notes TEXT,
+    tags TEXT NOT NULL,
+    raw_text TEXT
+)";
+
+/// The rows returned by a query, with every value rendered as text
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Run a read-only SQL query against every stored summary
+pub fn run_query(storage: &Storage, sql: &str) -> Result<QueryResult, QueryError> {
+    let conn = load_archive(storage)?;
+    conn.pragma_update(None, "query_only", true)?;
+
+    let mut stmt = conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = Vec::new();
+    let mut cursor = stmt.query([])?;
+    while let Some(row) = cursor.next()? {
+        let values = (0..columns.len())
+            .map(|i| row.get_ref(i).map(render_value))
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(values);
+    }
+
+    Ok(QueryResult { columns, rows })
+}
+
+/// Build the in-memory database from storage
+fn load_archive(storage: &Storage) -> Result<Connection, QueryError> {
+    let mut conn = Connection::open_in_memory()?;
+    conn.execute(SUMMARIES_TABLE, [])?;
+
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO summaries VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        for stored in storage.iter() {
+            let stored = stored?;
+            insert.execute(params![
+                stored.url,
+                stored.domain(),
+                stored.created_at.to_rfc3339(),
+                stored.provider,
+                stored.language,
+                stored.summary.title,
+                stored.summary.conclusion,
+                serde_json::to_string(&stored.summary.key_points)?,
+                serde_json::to_string(&stored.summary.entities)?,
+                serde_json::to_string(&stored.summary.action_items)?,
+                serde_json::to_string(&stored.summary.topics)?,
+                serde_json::to_string(&stored.summary.custom)?,
+                stored.notes,
+                serde_json::to_string(&stored.tags)?,
+                stored.raw_text,
+            ])?;
+        }
+    }
+    tx.commit()?;
+
+    Ok(conn)
+}
+
+/// Render a SQLite value for display
+fn render_value(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StoredSummary, Summary};
+
+    fn storage_with(urls: &[&str]) -> (tempfile::TempDir, Storage) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        for url in urls {
+            let summary = Summary::new(
+                "Title".to_string(),
+                String::new(),
+                vec![],
+                vec!["Rust".into()],
+                vec![],
+            );
+            storage
+                .put(&StoredSummary::new(url.to_string(), summary))
+                .unwrap();
+        }
+        (dir, storage)
+    }
+
+    #[test]
+    fn groups_by_domain() {
+        let (_dir, storage) = storage_with(&[
+            "https://example.com/a",
+            "https://www.example.com/b",
+            "https://other.org/c",
+        ]);
+        let result = run_query(
+            &storage,
+            "SELECT domain, count(*) FROM summaries GROUP BY 1 ORDER BY 2 DESC",
+        )
+        .unwrap();
+        assert_eq!(result.columns, ["domain", "count(*)"]);
+        assert_eq!(result.rows[0], ["example.com", "2"]);
+        assert_eq!(result.rows[1], ["other.org", "1"]);
+    }
+
+    #[test]
+    fn rejects_writes() {
+        let (_dir, storage) = storage_with(&["https://example.com/a"]);
+        assert!(run_query(&storage, "DELETE FROM summaries").is_err());
+    }
+}
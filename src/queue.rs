@@ -0,0 +1,57 @@
+//! The reading queue: unread summaries in the order you mean to read them.
+//!
+//! The order is a list of URLs kept in its own sled tree and rewritten whenever
+//! an entry is moved. Unread summaries that aren't in it yet, such as ones just
+//! made, join the end of the queue oldest first, and summaries leave the queue
+//! once they're read or while they're snoozed.
+
+use crate::storage::{Storage, StorageError, StoredSummary};
+use chrono::Utc;
+
+/// Put `unread` in queue order: those in `order` first, as listed, then the rest oldest first
+pub fn arrange(mut unread: Vec<StoredSummary>, order: &[String]) -> Vec<StoredSummary> {
+    unread.sort_by_key(|stored| {
+        let position = order.iter().position(|url| *url == stored.url);
+        (position.unwrap_or(usize::MAX), stored.created_at)
+    });
+    unread
+}
+
+/// The unread summaries in the library, in queue order
+pub fn load(storage: &Storage) -> Result<Vec<StoredSummary>, StorageError> {
+    let unread = storage
+        .list_all()?
+        .into_iter()
+        .filter(|stored| stored.in_queue(Utc::now()))
+        .collect();
+    Ok(arrange(unread, &storage.queue_order()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+
+    #[test]
+    fn keeps_the_saved_order_and_appends_new_unread_summaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        for (i, unread) in [true, true, false, true].into_iter().enumerate() {
+            let summary = Summary::new(i.to_string(), "C".into(), vec![], vec![], vec![]);
+            let mut stored = StoredSummary::new(format!("https://{}.example", i), summary);
+            stored.created_at -= chrono::Duration::hours(10 - i as i64);
+            stored.unread = unread;
+            storage.put(&stored).unwrap();
+        }
+        storage
+            .put_queue_order(&["https://1.example".to_string()])
+            .unwrap();
+
+        let titles: Vec<String> = load(&storage)
+            .unwrap()
+            .into_iter()
+            .map(|stored| stored.summary.title)
+            .collect();
+        assert_eq!(titles, ["1", "0", "3"]);
+    }
+}
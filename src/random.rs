@@ -0,0 +1,93 @@
+//! Picking a random older summary to revisit, for `summera random` and 'R' in the TUI.
+
+use crate::storage::{normalise_tag, StoredSummary};
+use chrono::{DateTime, Duration, Utc};
+use std::hash::{BuildHasher, Hasher};
+
+/// How old a summary has to be to come up by default
+pub const DEFAULT_MIN_AGE_DAYS: i64 = 30;
+
+/// Which summaries may come up
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomFilter {
+    /// Only summaries with this tag
+    pub tag: Option<String>,
+    /// Only summaries made at least this long ago
+    pub min_age: Duration,
+}
+
+impl Default for RandomFilter {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            min_age: Duration::days(DEFAULT_MIN_AGE_DAYS),
+        }
+    }
+}
+
+impl RandomFilter {
+    /// Whether `stored` may come up at `now`; pending summaries never do
+    pub fn matches(&self, stored: &StoredSummary, now: DateTime<Utc>) -> bool {
+        !stored.pending
+            && !stored.is_snoozed(now)
+            // Nothing is older than the earliest date there is
+            && now
+                .checked_sub_signed(self.min_age)
+                .is_some_and(|cutoff| stored.created_at <= cutoff)
+            && self
+                .tag
+                .as_deref()
+                .is_none_or(|tag| stored.tags.contains(&normalise_tag(tag)))
+    }
+}
+
+/// Index into `summaries` of a random one matching `filter`, if any match
+pub fn pick(
+    summaries: &[StoredSummary],
+    filter: &RandomFilter,
+    now: DateTime<Utc>,
+) -> Option<usize> {
+    let candidates: Vec<usize> = (0..summaries.len())
+        .filter(|&i| filter.matches(&summaries[i], now))
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    // Anything random will do; RandomState is seeded differently every time
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    Some(candidates[random as usize % candidates.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+
+    #[test]
+    fn picks_only_old_enough_summaries_with_the_tag() {
+        let now = Utc::now();
+        let summaries: Vec<StoredSummary> = [(400, "rust"), (400, "go"), (2, "rust")]
+            .into_iter()
+            .map(|(days, tag)| {
+                let summary = Summary::new(tag.into(), "C".into(), vec![], vec![], vec![]);
+                let mut stored = StoredSummary::new(format!("https://{}.example", tag), summary);
+                stored.created_at = now - Duration::days(days);
+                stored.add_tag(tag);
+                stored
+            })
+            .collect();
+        let filter = RandomFilter {
+            tag: Some("Rust".to_string()),
+            ..RandomFilter::default()
+        };
+        assert_eq!(pick(&summaries, &filter, now), Some(0));
+
+        let recent = RandomFilter {
+            min_age: Duration::days(1000),
+            ..RandomFilter::default()
+        };
+        assert_eq!(pick(&summaries, &recent, now), None);
+    }
+}
@@ -1,8 +1,9 @@
-//! Local file reader module for text extraction from PDF and PPTX files.
+//! Local file reader module for text extraction from PDF, PPTX and chat export files.
 //!
 //! Supports:
 //! - **PDF** via `pdf-extract`
 //! - **PPTX** (Office Open XML) via `zip` + `quick-xml`
+//! - **JSON** Slack and Discord exports, via [`crate::chat`]
 //!
 //! The legacy binary `.ppt` format is not supported — only `.pptx`.
 
@@ -18,12 +19,14 @@ use thiserror::Error;
 pub enum ReaderError {
     #[error("file not found: {0}")]
     FileNotFound(String),
-    #[error("unsupported file format: {0}. Supported formats: pdf, pptx")]
+    #[error("unsupported file format: {0}. Supported formats: pdf, pptx, json (chat exports)")]
     UnsupportedFormat(String),
     #[error("failed to extract text from PDF: {0}")]
     PdfError(String),
     #[error("failed to extract text from PPTX: {0}")]
     PptxError(String),
+    #[error("failed to read chat export: {0}")]
+    ChatError(#[from] crate::chat::ChatError),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("no text content found in file")]
@@ -48,6 +51,8 @@ pub enum FileFormat {
     Pdf,
     /// Office Open XML Presentation
     Pptx,
+    /// Slack or Discord thread exported as JSON
+    ChatExport,
 }
 
 impl FileFormat {
@@ -58,16 +63,151 @@ impl FileFormat {
         match ext.to_lowercase().as_str() {
             "pdf" => Some(Self::Pdf),
             "pptx" => Some(Self::Pptx),
+            "json" => Some(Self::ChatExport),
             _ => None,
         }
     }
 }
 
+/// Check whether a local path is a chat export, to be summarised as a discussion.
+pub fn is_chat_export(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(FileFormat::from_extension)
+        == Some(FileFormat::ChatExport)
+}
+
 /// Check whether a string looks like a URL (starts with http:// or https://).
 pub fn is_url(source: &str) -> bool {
     source.starts_with("http://") || source.starts_with("https://")
 }
 
+/// Turn what was typed or pasted into the open dialogue into sources to summarise.
+///
+/// An existing file path is kept whole, even with spaces in it. Anything else is
+/// split on whitespace so several URLs can be pasted at once, and a bare host
+/// such as `example.com/post` gets `https://` in front of it.
+pub fn parse_sources(input: &str) -> Result<Vec<String>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Enter a URL or a file path".to_string());
+    }
+    if Path::new(input).is_file() {
+        return Ok(vec![input.to_string()]);
+    }
+    input.split_whitespace().map(parse_source).collect()
+}
+
+/// Sources listed in a batch file, one per line, skipping blank lines and `#` comments
+pub fn source_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Check one URL or file path, adding the scheme to a bare host
+fn parse_source(source: &str) -> Result<String, String> {
+    if Path::new(source).is_file() {
+        return Ok(source.to_string());
+    }
+    let first_segment = source.split(['/', '?', '#']).next().unwrap_or_default();
+    let looks_like_file = source.starts_with(['.', '/', '~'])
+        || source.contains('\\')
+        || source.get(1..2) == Some(":")
+        || Path::new(first_segment)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(FileFormat::from_extension)
+            .is_some();
+    let url = if is_url(source) {
+        source.to_string()
+    } else if let Some((scheme, _)) = source.split_once("://") {
+        return Err(format!(
+            "{}:// links aren't supported, only http and https",
+            scheme
+        ));
+    } else if looks_like_file {
+        return Err(format!("No such file: {}", source));
+    } else if is_host(first_segment) {
+        format!("https://{}", source)
+    } else {
+        return Err(format!("'{}' isn't a URL or a file path", source));
+    };
+    let host = url
+        .split_once("://")
+        .map_or("", |(_, rest)| rest)
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    if is_host(host) {
+        Ok(url)
+    } else {
+        Err(format!("'{}' has no valid host", source))
+    }
+}
+
+/// Whether `host` (with an optional port) could be a web server: a dotted name,
+/// `localhost` or a bracketed IPv6 address
+fn is_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if !host.ends_with(']') => {
+            if port.is_empty() || !port.chars().all(|c| c.is_ascii_digit()) {
+                return false;
+            }
+            name
+        }
+        _ => host,
+    };
+    if name.starts_with('[') && name.ends_with(']') {
+        return name.len() > 2;
+    }
+    name == "localhost"
+        || (name.contains('.')
+            && !name.starts_with('.')
+            && !name.ends_with('.')
+            && !name.contains("..")
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '.'))
+}
+
+/// Build the storage key for a local file: an absolute `file://` URL.
+///
+/// Falls back to the path as given when it can't be canonicalised (e.g. it no
+/// longer exists).
+pub fn file_source_key(path: &str) -> String {
+    let abs_path = std::fs::canonicalize(path).unwrap_or_else(|_| std::path::PathBuf::from(path));
+    path_to_file_url(&abs_path.display().to_string())
+}
+
+/// Convert an absolute path string into a `file://` URL.
+///
+/// Windows paths lose the `\\?\` verbatim prefix that `canonicalize` adds and
+/// use forward slashes, so `C:\docs\a.pdf` becomes `file:///C:/docs/a.pdf`.
+/// Unix paths are kept as-is, matching keys stored by earlier versions.
+fn path_to_file_url(path: &str) -> String {
+    let path = if let Some(unc) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{unc}")
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    };
+    let path = path.replace('\\', "/");
+
+    if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        format!("file:///{path}")
+    }
+}
+
+/// Normalise Windows (`\r\n`) and classic Mac (`\r`) line endings to `\n`.
+pub fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
 /// Extract text content from a local file.
 ///
 /// Detects the format from the file extension and delegates to the appropriate
@@ -93,7 +233,7 @@ pub fn extract_from_file(path: &str) -> Result<FileContent, ReaderError> {
     let format = FileFormat::from_extension(extension)
         .ok_or_else(|| ReaderError::UnsupportedFormat(extension.to_string()))?;
 
-    let title = file_path
+    let mut title = file_path
         .file_stem()
         .and_then(|s| s.to_str())
         .map(|s| s.replace(['_', '-'], " "));
@@ -101,7 +241,47 @@ pub fn extract_from_file(path: &str) -> Result<FileContent, ReaderError> {
     let text = match format {
         FileFormat::Pdf => extract_pdf(file_path)?,
         FileFormat::Pptx => extract_pptx(file_path)?,
+        FileFormat::ChatExport => {
+            let (export_title, text) =
+                crate::chat::read_export(&std::fs::read_to_string(file_path)?)?;
+            // Discord exports name the server and channel; prefer that to the filename
+            title = export_title.or(title);
+            text
+        }
     };
+    let text = normalize_line_endings(&text);
+
+    if text.trim().is_empty() {
+        return Err(ReaderError::NoContent);
+    }
+
+    Ok(FileContent {
+        path: path.to_string(),
+        title,
+        text,
+    })
+}
+
+/// Read a plain text file (Markdown notes, saved emails, ...) as-is.
+///
+/// Unlike [`extract_from_file`] any extension is accepted, and `-` reads stdin.
+pub fn read_text_file(path: &str) -> Result<FileContent, ReaderError> {
+    let (title, text) = if path == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        (None, text)
+    } else {
+        let file_path = Path::new(path);
+        if !file_path.exists() {
+            return Err(ReaderError::FileNotFound(path.to_string()));
+        }
+        let title = file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.replace(['_', '-'], " "));
+        (title, std::fs::read_to_string(file_path)?)
+    };
+    let text = normalize_line_endings(&text);
 
     if text.trim().is_empty() {
         return Err(ReaderError::NoContent);
@@ -121,6 +301,14 @@ fn extract_pdf(path: &Path) -> Result<String, ReaderError> {
     Ok(clean_extracted_text(&text))
 }
 
+/// Extract plain text from a PDF held in memory, such as one fetched from a URL.
+pub fn extract_pdf_bytes(bytes: &[u8]) -> Result<String, ReaderError> {
+    let text = pdf_extract::extract_text_from_mem(bytes)
+        .map_err(|e| ReaderError::PdfError(e.to_string()))?;
+
+    Ok(normalize_line_endings(&clean_extracted_text(&text)))
+}
+
 /// Extract plain text from a PPTX file.
 ///
 /// PPTX is an Office Open XML format — a ZIP archive containing XML slides
@@ -226,12 +414,50 @@ mod tests {
         assert!(!is_url("relative/path.pdf"));
     }
 
+    #[test]
+    fn test_source_list() {
+        assert_eq!(
+            source_list("# reading\nhttps://a.example\n\n  ./slides.pptx  \n"),
+            ["https://a.example", "./slides.pptx"]
+        );
+    }
+
+    #[test]
+    fn test_parse_sources() {
+        assert_eq!(
+            parse_sources("  example.com/post\nhttps://a.example/b?x=1 ").unwrap(),
+            ["https://example.com/post", "https://a.example/b?x=1"]
+        );
+        assert_eq!(
+            parse_sources("localhost:8080/notes").unwrap(),
+            ["https://localhost:8080/notes"]
+        );
+        assert!(parse_sources("   ").is_err());
+        assert!(parse_sources("hello").is_err());
+        assert!(parse_sources("https://").is_err());
+        assert!(parse_sources("ftp://example.com/file").is_err());
+        assert_eq!(
+            parse_sources("missing-report.pdf").unwrap_err(),
+            "No such file: missing-report.pdf"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quarterly report.pdf");
+        std::fs::write(&path, b"").unwrap();
+        let path = path.display().to_string();
+        assert_eq!(parse_sources(&path).unwrap(), [path]);
+    }
+
     #[test]
     fn test_file_format_detection() {
         assert_eq!(FileFormat::from_extension("pdf"), Some(FileFormat::Pdf));
         assert_eq!(FileFormat::from_extension("PDF"), Some(FileFormat::Pdf));
         assert_eq!(FileFormat::from_extension("pptx"), Some(FileFormat::Pptx));
         assert_eq!(FileFormat::from_extension("PPTX"), Some(FileFormat::Pptx));
+        assert_eq!(
+            FileFormat::from_extension("json"),
+            Some(FileFormat::ChatExport)
+        );
         assert_eq!(FileFormat::from_extension("doc"), None);
         assert_eq!(FileFormat::from_extension("ppt"), None);
     }
@@ -268,6 +494,31 @@ mod tests {
         assert_eq!(result, "hello world\nfoo bar\nbaz");
     }
 
+    #[test]
+    fn test_path_to_file_url() {
+        assert_eq!(
+            path_to_file_url("/home/user/doc.pdf"),
+            "file:///home/user/doc.pdf"
+        );
+        assert_eq!(
+            path_to_file_url(r"\\?\C:\Users\me\doc.pdf"),
+            "file:///C:/Users/me/doc.pdf"
+        );
+        assert_eq!(
+            path_to_file_url(r"C:\Users\me\doc.pdf"),
+            "file:///C:/Users/me/doc.pdf"
+        );
+        assert_eq!(
+            path_to_file_url(r"\\?\UNC\server\share\doc.pdf"),
+            "file:////server/share/doc.pdf"
+        );
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        assert_eq!(normalize_line_endings("a\r\nb\rc\nd"), "a\nb\nc\nd");
+    }
+
     #[test]
     fn test_file_not_found() {
         let result = extract_from_file("/nonexistent/file.pdf");
@@ -291,4 +542,52 @@ mod tests {
             ReaderError::UnsupportedFormat(_)
         ));
     }
+
+    /// A one-page PDF showing `text`, with a correct cross-reference table
+    fn minimal_pdf(text: &str) -> Vec<u8> {
+        let stream = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] \
+             /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>"
+                .to_string(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                stream.len(),
+                stream
+            ),
+        ];
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+        pdf.into_bytes()
+    }
+
+    #[test]
+    fn test_extract_pdf_bytes() {
+        let text = extract_pdf_bytes(&minimal_pdf("Quarterly results")).unwrap();
+        assert!(text.contains("Quarterly results"), "got {:?}", text);
+        assert!(matches!(
+            extract_pdf_bytes(b"<html>not a pdf</html>"),
+            Err(ReaderError::PdfError(_))
+        ));
+    }
 }
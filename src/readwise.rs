@@ -0,0 +1,127 @@
+//! Exporting summaries to Readwise as highlights.
+//!
+//! Each summary becomes a book-like source in Readwise, named after the page,
+//! whose highlights are its conclusion and key points. Tags travel as Readwise
+//! inline tags (`.rust`) in the highlight notes. Readwise de-duplicates on
+//! text, title, author and URL, so exporting a summary again adds nothing new.
+
+use crate::storage::StoredSummary;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Endpoint that creates highlights, and their sources, in bulk
+const HIGHLIGHTS_URL: &str = "https://readwise.io/api/v2/highlights/";
+
+/// Longest highlight Readwise accepts, in characters
+const MAX_HIGHLIGHT_CHARS: usize = 8191;
+
+/// Highlights sent per request
+const BATCH_SIZE: usize = 100;
+
+#[derive(Error, Debug)]
+pub enum ReadwiseError {
+    #[error("request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Readwise rejected the export ({status}): {body}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+}
+
+/// One highlight in the shape the Readwise API takes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Highlight {
+    pub text: String,
+    pub title: String,
+    /// The page's domain, or "summera" for local files
+    pub author: String,
+    pub source_url: String,
+    pub source_type: &'static str,
+    pub category: &'static str,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    pub highlighted_at: String,
+}
+
+/// The highlights a summary exports as: its conclusion, then each key point
+pub fn highlights(stored: &StoredSummary) -> Vec<Highlight> {
+    let tags: Vec<String> = stored.tags.iter().map(|tag| format!(".{}", tag)).collect();
+    let highlight = |text: &str, label: &str| Highlight {
+        text: text.chars().take(MAX_HIGHLIGHT_CHARS).collect(),
+        title: stored.summary.title.clone(),
+        author: stored.domain().unwrap_or("summera").to_string(),
+        source_url: stored.url.clone(),
+        source_type: "summera",
+        category: "articles",
+        note: std::iter::once(label.to_string())
+            .chain(tags.iter().cloned())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        highlighted_at: stored.created_at.to_rfc3339(),
+    };
+
+    let summary = &stored.summary;
+    std::iter::once((summary.conclusion.as_str(), "Conclusion"))
+        .chain(summary.key_points.iter().map(|point| (point.as_str(), "")))
+        .filter(|(text, _)| !text.trim().is_empty())
+        .map(|(text, label)| highlight(text, label))
+        .collect()
+}
+
+/// Send the highlights of `summaries` to Readwise, returning how many were sent
+pub async fn export(summaries: &[StoredSummary], token: &str) -> Result<usize, ReadwiseError> {
+    let highlights: Vec<Highlight> = summaries.iter().flat_map(highlights).collect();
+    let client = reqwest::Client::new();
+    for batch in highlights.chunks(BATCH_SIZE) {
+        let response = client
+            .post(HIGHLIGHTS_URL)
+            .header("Authorization", format!("Token {}", token))
+            .json(&serde_json::json!({ "highlights": batch }))
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ReadwiseError::Rejected {
+                status,
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+    }
+    Ok(highlights.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+
+    #[test]
+    fn exports_conclusion_and_key_points_with_tags() {
+        let summary = Summary::new(
+            "Async Rust".into(),
+            "Use Tokio.".into(),
+            vec!["Futures are lazy.".into(), " ".into()],
+            vec![],
+            vec![],
+        );
+        let mut stored = StoredSummary::new("https://www.example.com/async".to_string(), summary);
+        stored.add_tag("rust");
+
+        let highlights = highlights(&stored);
+        let texts: Vec<(&str, &str)> = highlights
+            .iter()
+            .map(|h| (h.text.as_str(), h.note.as_str()))
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                ("Use Tokio.", "Conclusion .rust"),
+                ("Futures are lazy.", ".rust")
+            ]
+        );
+        assert_eq!(highlights[0].author, "example.com");
+        assert_eq!(highlights[0].title, "Async Rust");
+    }
+}
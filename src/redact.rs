@@ -0,0 +1,106 @@
+//! Masking personal data in extracted text before it's sent to an LLM.
+//!
+//! What's masked is set under `[redact]` in the config: email addresses, phone
+//! numbers, terms such as names, and patterns of your own. Each match is replaced
+//! by a placeholder saying what was there, so the summary still reads sensibly.
+
+use crate::config::RedactConfig;
+use regex::{Captures, Regex};
+
+/// Placeholder for an email address
+const EMAIL_MASK: &str = "[email]";
+/// Placeholder for a phone number
+const PHONE_MASK: &str = "[phone]";
+/// Placeholder for a configured term or pattern
+const MASK: &str = "[redacted]";
+
+const EMAIL: &str = r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+";
+
+/// Digits in groups, with an optional country code and area code in parentheses
+const PHONE: &str = r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?|\b)\d{2,4}(?:[ .-]\d{2,4}){1,4}\b";
+
+/// Dates that would otherwise pass for phone numbers
+const DATE: &str = r"^\d{4}[-.]\d{1,2}[-.]\d{1,2}$";
+
+impl RedactConfig {
+    /// Whether anything is masked at all
+    pub fn is_enabled(&self) -> bool {
+        self.emails || self.phone_numbers || !self.terms.is_empty() || !self.patterns.is_empty()
+    }
+}
+
+/// `text` with everything `config` asks for masked.
+///
+/// Fails on an invalid pattern rather than sending the text unmasked.
+pub fn redact(text: &str, config: &RedactConfig) -> Result<String, regex::Error> {
+    let mut text = text.to_string();
+    for pattern in &config.patterns {
+        text = Regex::new(pattern)?.replace_all(&text, MASK).into_owned();
+    }
+    if config.emails {
+        let email = Regex::new(EMAIL).expect("email pattern is valid");
+        text = email.replace_all(&text, EMAIL_MASK).into_owned();
+    }
+    if config.phone_numbers {
+        let phone = Regex::new(PHONE).expect("phone pattern is valid");
+        let date = Regex::new(DATE).expect("date pattern is valid");
+        text = phone
+            .replace_all(&text, |caps: &Captures| {
+                let found = &caps[0];
+                let digits = found.chars().filter(char::is_ascii_digit).count();
+                if (7..=15).contains(&digits) && !date.is_match(found) {
+                    PHONE_MASK.to_string()
+                } else {
+                    found.to_string()
+                }
+            })
+            .into_owned();
+    }
+    let mut terms: Vec<&str> = config
+        .terms
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .collect();
+    if !terms.is_empty() {
+        // Longest first, so "Ann Lee" is masked whole rather than leaving "Lee"
+        // behind a match for "Ann"
+        terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+        let alternatives: Vec<String> = terms.iter().map(|term| regex::escape(term)).collect();
+        let terms = Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|")))?;
+        text = terms.replace_all(&text, MASK).into_owned();
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_what_is_configured_and_leaves_dates_alone() {
+        let config = RedactConfig {
+            emails: true,
+            phone_numbers: true,
+            terms: vec!["Ann".into(), "ann lee".into()],
+            patterns: vec![r"EMP-\d{6}".into()],
+        };
+        let text = "Ann Lee (EMP-104233, ann.lee@example.com, +46 70 123 45 67) \
+                    met Annika on 2024-03-05 about 3 issues; call (08) 555 0199.";
+        assert_eq!(
+            redact(text, &config).unwrap(),
+            "[redacted] ([redacted], [email], [phone]) \
+             met Annika on 2024-03-05 about 3 issues; call [phone]."
+        );
+
+        let off = RedactConfig::default();
+        assert!(!off.is_enabled());
+        assert_eq!(redact(text, &off).unwrap(), text);
+
+        let invalid = RedactConfig {
+            patterns: vec!["(".into()],
+            ..Default::default()
+        };
+        assert!(redact(text, &invalid).is_err());
+    }
+}
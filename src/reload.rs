@@ -0,0 +1,121 @@
+//! Watching the config file, so the TUI and `summera daemon` pick up edits
+//! without a restart.
+//!
+//! The directory is watched rather than the file, since many editors save by
+//! writing a new file and renaming it over the old one. Editors also tend to
+//! write in several steps, so a change is only reported once the file has been
+//! quiet for a moment; reading it half-written would report a parse error for a
+//! config that's fine.
+
+use ::notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// How long the file must go without changes before it's reloaded
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// Reports changes to one file
+pub struct ConfigWatcher {
+    /// Stops watching when dropped
+    _watcher: RecommendedWatcher,
+    changes: UnboundedReceiver<()>,
+    /// When the latest change came in, while waiting for the writes to settle
+    pending: Option<Instant>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, which may be replaced rather than written in place
+    pub fn new(path: &Path) -> ::notify::Result<Self> {
+        let path = std::path::absolute(path)?;
+        let dir = path
+            .parent()
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        let name = path.file_name().map(|name| name.to_os_string());
+        let (sender, changes) = mpsc::unbounded_channel();
+        let mut watcher =
+            ::notify::recommended_watcher(move |event: ::notify::Result<::notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let ours = event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == name.as_deref());
+                if ours && !event.kind.is_access() {
+                    // Nobody is listening once the watcher is being dropped
+                    let _ = sender.send(());
+                }
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+            pending: None,
+        })
+    }
+
+    /// Whether the file changed and has settled since, without waiting
+    pub fn poll(&mut self) -> bool {
+        while self.changes.try_recv().is_ok() {
+            self.pending = Some(Instant::now());
+        }
+        match self.pending {
+            Some(at) if at.elapsed() >= SETTLE => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Wait until the file changes and settles.
+    ///
+    /// Safe to use in `tokio::select!`: a change seen before the wait is dropped
+    /// is still reported by the next one.
+    pub async fn changed(&mut self) {
+        loop {
+            let Some(at) = self.pending else {
+                if self.changes.recv().await.is_none() {
+                    std::future::pending::<()>().await;
+                }
+                self.pending = Some(Instant::now());
+                continue;
+            };
+            tokio::select! {
+                change = self.changes.recv() => {
+                    if change.is_some() {
+                        self.pending = Some(Instant::now());
+                    }
+                }
+                _ = tokio::time::sleep_until((at + SETTLE).into()) => {
+                    self.pending = None;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_a_settled_change_to_the_file_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("summera.toml");
+        std::fs::write(&path, "").unwrap();
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+
+        std::fs::write(dir.path().join("other.toml"), "x = 1").unwrap();
+        tokio::time::sleep(SETTLE * 2).await;
+        assert!(!watcher.poll());
+
+        std::fs::write(&path, "[ui]\nascii = true\n").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), watcher.changed())
+            .await
+            .expect("the change is reported");
+        assert!(!watcher.poll());
+    }
+}
@@ -0,0 +1,211 @@
+//! Cron-like schedules for jobs run by `summera daemon`.
+//!
+//! A schedule is the five cron fields, minute, hour, day of month, month and
+//! day of week, each `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`, or a
+//! list of those. `@hourly`, `@daily`, `@weekly` and `@monthly` are shorthands.
+//! Times are local. As in cron, when both the day of month and the day of week
+//! are restricted, a day matching either one is enough.
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How far ahead to look for the next run before giving up, e.g. on "0 0 31 2 *"
+const SEARCH_YEARS: u32 = 5;
+
+/// When a job runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Schedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Both day fields are restricted, so either may match
+    either_day: bool,
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = String;
+
+    fn try_from(expression: String) -> Result<Self, Self::Error> {
+        expression.parse()
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(schedule: Schedule) -> Self {
+        schedule.expression
+    }
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = s.trim();
+        let fields = match expression {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            fields => fields,
+        };
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "invalid schedule '{}'; expected five cron fields or @daily, @weekly, ...",
+                expression
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7).map_err(|e| field_error("weekday", e))?;
+        // 7 is Sunday as well as 0
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: parse_field(minute, 0, 59).map_err(|e| field_error("minute", e))?,
+            hours: parse_field(hour, 0, 23).map_err(|e| field_error("hour", e))?,
+            days: parse_field(day, 1, 31).map_err(|e| field_error("day", e))?,
+            months: parse_field(month, 1, 12).map_err(|e| field_error("month", e))?,
+            weekdays,
+            either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl Schedule {
+    /// The first time after `after` that the schedule fires, to the minute
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)?;
+        let mut time = start + chrono::Duration::minutes(1);
+        // Not `with_year`, which has no answer for 29 February
+        let end = start.checked_add_months(chrono::Months::new(12 * SEARCH_YEARS))?;
+        while time < end {
+            if !has(self.months, time.month()) {
+                time = start_of_next_month(time)?;
+            } else if !self.day_matches(time) {
+                time = (time.date() + chrono::Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + chrono::Duration::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += chrono::Duration::minutes(1);
+            } else if let Some(local) = time.and_local_timezone(Local).earliest() {
+                return Some(local);
+            } else {
+                // Skipped by a daylight saving change
+                time += chrono::Duration::minutes(1);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, time: NaiveDateTime) -> bool {
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn start_of_next_month(time: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (year, month) = match time.month() {
+        12 => (time.year() + 1, 1),
+        month => (time.year(), month + 1),
+    };
+    chrono::NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+fn field_error(name: &str, message: String) -> String {
+    format!("invalid {} field: {}", name, message)
+}
+
+/// The values a field allows, as bits of a set
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|&step| step > 0)
+                    .ok_or_else(|| format!("'{}' has an invalid step", part))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let number = |text: &str| {
+            text.parse::<u32>()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| format!("'{}' isn't a number from {} to {}", text, min, max))
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((from, to)) => (number(from)?, number(to)?),
+                None => {
+                    let n = number(range)?;
+                    // "5/15" means from 5 on, every 15
+                    (n, if step > 1 { max } else { n })
+                }
+            },
+        };
+        if from > to {
+            return Err(format!("'{}' runs backwards", part));
+        }
+        for value in (from..=to).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn finds_the_next_run_of_cron_expressions() {
+        let at = |y, mo, d, h, mi| Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap();
+        // Thursday 15 October 2026, 10:30
+        let now = at(2026, 10, 15, 10, 30);
+
+        let weekly: Schedule = "@weekly".parse().unwrap();
+        assert_eq!(weekly.next_after(now), Some(at(2026, 10, 18, 0, 0)));
+        let nightly: Schedule = "15 3 * * *".parse().unwrap();
+        assert_eq!(nightly.next_after(now), Some(at(2026, 10, 16, 3, 15)));
+        let quarterly: Schedule = "0 9 1 */3 *".parse().unwrap();
+        assert_eq!(quarterly.next_after(now), Some(at(2027, 1, 1, 9, 0)));
+        let weekdays: Schedule = "*/20 9-17 * * 1-5".parse().unwrap();
+        assert_eq!(weekdays.next_after(now), Some(at(2026, 10, 15, 10, 40)));
+        let never: Schedule = "0 0 31 2 *".parse().unwrap();
+        assert_eq!(never.next_after(now), None);
+        assert_eq!(
+            nightly.next_after(at(2028, 2, 29, 3, 15)),
+            Some(at(2028, 3, 1, 3, 15))
+        );
+
+        assert!("0 0 * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("* * * * 5-1".parse::<Schedule>().is_err());
+    }
+}
@@ -1,7 +1,10 @@
 //! Web scraping module for content extraction.
 //!
-//! Uses reqwest for fetching and scraper for HTML parsing.
+//! Uses reqwest for fetching and scraper for HTML parsing. URLs that serve a PDF
+//! are read with the same PDF extraction as local files.
 
+use crate::reader::{self, ReaderError};
+use reqwest::header::CONTENT_TYPE;
 use reqwest::Client;
 use scraper::{Html, Selector};
 use std::time::Duration;
@@ -17,12 +20,19 @@ const USER_AGENT: &str = concat!(
 /// Default timeout for HTTP requests
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// Largest response body read, so a huge download can't exhaust memory
+const MAX_BODY_BYTES: usize = 50 * 1024 * 1024;
+
 #[derive(Error, Debug)]
 pub enum ScraperError {
     #[error("failed to fetch URL: {0}")]
     FetchError(#[from] reqwest::Error),
     #[error("no content found at URL")]
     NoContent,
+    #[error("{0}")]
+    Pdf(#[from] ReaderError),
+    #[error("{0} is larger than {limit} MB", limit = MAX_BODY_BYTES / 1024 / 1024)]
+    TooLarge(String),
 }
 
 /// Extracted content from a webpage
@@ -34,27 +44,149 @@ pub struct WebContent {
     pub title: Option<String>,
     /// Main text content
     pub text: String,
+    /// The page's own description of itself, from its meta tags
+    pub description: Option<String>,
 }
 
 /// Create a configured HTTP client for scraping
-fn create_client() -> Result<Client, reqwest::Error> {
+pub(crate) fn create_client() -> Result<Client, reqwest::Error> {
     Client::builder()
         .user_agent(USER_AGENT)
         .timeout(REQUEST_TIMEOUT)
         .build()
 }
 
-/// Fetch and extract content from a URL
-pub async fn fetch_content(url: &str) -> Result<WebContent, ScraperError> {
+/// Containers tried in order for a page's main content, before falling back to the body
+const MAIN_SELECTORS: [&str; 5] = ["article", "main", "[role='main']", ".content", "#content"];
+
+/// What one extraction strategy finds on a page, for `summera inspect`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extraction {
+    /// The strategy: a content selector, or "body" for the whole page
+    pub strategy: String,
+    /// Extracted text; empty when the selector matched nothing
+    pub text: String,
+    /// Whether this is the text summarising the page would use
+    pub chosen: bool,
+}
+
+/// A response body, with the content type it was served as
+struct Download {
+    content_type: String,
+    body: Vec<u8>,
+}
+
+impl Download {
+    /// The body as text, in the charset the response declared or else UTF-8
+    fn text(&self) -> String {
+        let encoding = self
+            .content_type
+            .split(';')
+            .find_map(|param| param.trim().strip_prefix("charset="))
+            .and_then(|charset| {
+                encoding_rs::Encoding::for_label(charset.trim_matches('"').as_bytes())
+            })
+            .unwrap_or(encoding_rs::UTF_8);
+        encoding.decode(&self.body).0.into_owned()
+    }
+}
+
+/// GET `url`, rejecting 4xx/5xx responses and bodies over [`MAX_BODY_BYTES`]
+async fn download(url: &str) -> Result<Download, ScraperError> {
     let client = create_client()?;
+    let mut response = client.get(url).send().await?.error_for_status()?;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let too_large = || ScraperError::TooLarge(url.to_string());
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_BODY_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    // The declared length may be missing or wrong, so count what arrives too
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Download { content_type, body })
+}
+
+/// Fetch a page's HTML, with the same limits as [`fetch_content`]
+pub async fn fetch_html(url: &str) -> Result<String, ScraperError> {
+    Ok(download(url).await?.text())
+}
+
+/// Run every extraction strategy on `html`, in the order summarising tries them
+pub fn inspect(html: &str) -> Vec<Extraction> {
+    let document = Html::parse_document(html);
+    let mut chosen = false;
+    MAIN_SELECTORS
+        .into_iter()
+        .map(|selector| {
+            (
+                selector,
+                select_text(&document, selector).unwrap_or_default(),
+            )
+        })
+        .chain(std::iter::once((
+            "body",
+            extract_text_from_element(&document),
+        )))
+        .map(|(strategy, text)| {
+            let first = !chosen && !text.trim().is_empty();
+            chosen |= first;
+            Extraction {
+                strategy: strategy.to_string(),
+                text,
+                chosen: first,
+            }
+        })
+        .collect()
+}
+
+/// Fetch and extract content from a URL, whether it serves a web page or a PDF
+pub async fn fetch_content(url: &str) -> Result<WebContent, ScraperError> {
+    let download = download(url).await?;
+    // Servers label PDFs either way; the body says whether it really is one
+    let maybe_pdf = download.content_type.starts_with("application/pdf")
+        || download
+            .content_type
+            .starts_with("application/octet-stream");
+    if !maybe_pdf || !download.body.starts_with(b"%PDF-") {
+        return parse_content(url, &download.text());
+    }
+    // Extraction is CPU-bound and can take a while on a long PDF
+    let text = tokio::task::spawn_blocking(move || reader::extract_pdf_bytes(&download.body))
+        .await
+        .map_err(|e| ReaderError::PdfError(e.to_string()))??;
+    if text.trim().is_empty() {
+        return Err(ScraperError::NoContent);
+    }
+    // PDFs rarely carry a usable title; the caller falls back to the file name
+    Ok(WebContent {
+        url: url.to_string(),
+        title: None,
+        text,
+        description: None,
+    })
+}
 
-    // Fetch the HTML, rejecting 4xx/5xx responses
-    let response = client.get(url).send().await?.error_for_status()?;
-    let html = response.text().await?;
-    let document = Html::parse_document(&html);
+/// Extract content from a page's HTML that was fetched some other way, such as by
+/// a browser behind a login
+pub fn parse_content(url: &str, html: &str) -> Result<WebContent, ScraperError> {
+    let document = Html::parse_document(html);
 
     // Extract title
     let title = extract_title(&document);
+    let description = extract_description(&document);
 
     // Extract main content
     let text = extract_text(&document);
@@ -67,50 +199,98 @@ pub async fn fetch_content(url: &str) -> Result<WebContent, ScraperError> {
         url: url.to_string(),
         title,
         text,
+        description,
     })
 }
 
-/// Extract the page title from <title> or <h1>
-fn extract_title(document: &Html) -> Option<String> {
-    // Try <title> first
-    let title_selector = Selector::parse("title").unwrap();
-    if let Some(element) = document.select(&title_selector).next() {
-        let title: String = element.text().collect();
-        if !title.trim().is_empty() {
-            return Some(title.trim().to_string());
-        }
-    }
+/// Derive a readable title from a URL's last path segment, or its host.
+///
+/// Used when a page has neither `<title>` nor `<h1>`, e.g.
+/// `https://example.com/blog/rust-async-in-2024.html` becomes "Rust async in 2024".
+pub fn title_from_url(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = rest.split(['?', '#']).next()?;
+    let mut segments = path.split('/');
+    let host = segments.next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
 
-    // Fall back to first <h1>
-    let h1_selector = Selector::parse("h1").unwrap();
-    if let Some(element) = document.select(&h1_selector).next() {
-        let title: String = element.text().collect();
-        if !title.trim().is_empty() {
-            return Some(title.trim().to_string());
+    // Purely numeric segments (IDs, dates) make poor titles
+    let slug = segments.rfind(|s| !s.is_empty() && !s.chars().all(|c| c.is_ascii_digit()));
+    let words = match slug {
+        Some(slug) => {
+            let stem = match slug.rsplit_once('.') {
+                Some((stem, ext)) if !stem.is_empty() && ext.len() <= 5 => stem,
+                _ => slug,
+            };
+            stem.replace("%20", " ")
+                .split(['-', '_', '+', ' '])
+                .filter(|w| !w.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
         }
-    }
+        None => host.to_string(),
+    };
 
-    None
+    let mut chars = words.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// Extract the page title from <title> or <h1>
+fn extract_title(document: &Html) -> Option<String> {
+    ["title", "h1"].into_iter().find_map(|tag| {
+        let selector = Selector::parse(tag).unwrap();
+        let element = document.select(&selector).next()?;
+        // Collapse whitespace so CRLF-formatted markup doesn't leak into the title
+        let title = element
+            .text()
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!title.is_empty()).then_some(title)
+    })
+}
+
+/// Extract the page's description from its meta tags, preferring the standard one
+fn extract_description(document: &Html) -> Option<String> {
+    [
+        "meta[name='description']",
+        "meta[property='og:description']",
+        "meta[name='twitter:description']",
+    ]
+    .into_iter()
+    .find_map(|tag| {
+        let selector = Selector::parse(tag).unwrap();
+        let content = document.select(&selector).next()?.value().attr("content")?;
+        let description = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!description.is_empty()).then_some(description)
+    })
 }
 
 /// Extract readable text content from the page
 fn extract_text(document: &Html) -> String {
     // Try to find main content areas first
-    let main_selectors = ["article", "main", "[role='main']", ".content", "#content"];
-
-    for selector_str in main_selectors {
-        if let Ok(selector) = Selector::parse(selector_str) {
-            if let Some(element) = document.select(&selector).next() {
-                let text = extract_text_from_element(&Html::parse_fragment(&element.html()));
-                if !text.trim().is_empty() {
-                    return text;
-                }
-            }
-        }
-    }
+    MAIN_SELECTORS
+        .into_iter()
+        .filter_map(|selector| select_text(document, selector))
+        .find(|text| !text.trim().is_empty())
+        // Fall back to extracting from body, excluding scripts/styles
+        .unwrap_or_else(|| extract_text_from_element(document))
+}
+
+/// Text of the first element matching `selector`, or `None` if nothing matches
+fn select_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).ok()?;
+    let element = document.select(&selector).next()?;
+    Some(extract_text_from_element(&Html::parse_fragment(
+        &element.html(),
+    )))
+}
 
-    // Fall back to extracting from body, excluding scripts/styles
-    extract_text_from_element(document)
+/// Extract readable text from an HTML fragment, such as a page body returned by an API
+pub(crate) fn html_to_text(html: &str) -> String {
+    extract_text_from_element(&Html::parse_fragment(html))
 }
 
 /// Extract text from paragraphs and headings, excluding scripts and styles
@@ -130,3 +310,82 @@ fn extract_text_from_element(document: &Html) -> String {
 
     paragraphs.join("\n\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspect_marks_the_strategy_summarising_uses() {
+        let html = r#"<html><body>
+            <nav><li>Home, About, Contact and more links</li></nav>
+            <main><p>An empty article tag came first, so main content wins.</p></main>
+            <article></article>
+        </body></html>"#;
+        let extractions = inspect(html);
+        let strategies: Vec<(&str, bool, bool)> = extractions
+            .iter()
+            .map(|e| (e.strategy.as_str(), e.text.is_empty(), e.chosen))
+            .collect();
+        assert_eq!(
+            strategies,
+            [
+                ("article", true, false),
+                ("main", false, true),
+                ("[role='main']", true, false),
+                (".content", true, false),
+                ("#content", true, false),
+                ("body", false, false),
+            ]
+        );
+        let document = Html::parse_document(html);
+        assert_eq!(extract_text(&document), extractions[1].text);
+    }
+
+    #[test]
+    fn parse_content_reads_the_meta_description() {
+        let html = r#"<html><head><title>Notes</title>
+            <meta property="og:description" content="From Open Graph">
+            <meta name="description" content="  What changed
+              in 2.0 ">
+        </head><body><p>Body text long enough to count</p></body></html>"#;
+        let content = parse_content("https://example.com/", html).unwrap();
+        assert_eq!(content.description.as_deref(), Some("What changed in 2.0"));
+        let bare = parse_content(
+            "https://example.com/",
+            "<p>Body text long enough to count</p>",
+        )
+        .unwrap();
+        assert_eq!(bare.description, None);
+    }
+
+    #[test]
+    fn decodes_downloads_in_their_declared_charset() {
+        let latin1 = Download {
+            content_type: "text/html; charset=\"iso-8859-1\"".to_string(),
+            body: b"caf\xe9".to_vec(),
+        };
+        assert_eq!(latin1.text(), "café");
+        let undeclared = Download {
+            content_type: "text/html".to_string(),
+            body: "café".as_bytes().to_vec(),
+        };
+        assert_eq!(undeclared.text(), "café");
+    }
+
+    #[test]
+    fn derives_title_from_url_slug() {
+        assert_eq!(
+            title_from_url("https://example.com/blog/rust-async_in-2024.html?ref=x").as_deref(),
+            Some("Rust async in 2024")
+        );
+        assert_eq!(
+            title_from_url("https://news.example.org/item/12345/").as_deref(),
+            Some("Item")
+        );
+        assert_eq!(
+            title_from_url("https://www.example.com/").as_deref(),
+            Some("Example.com")
+        );
+    }
+}
@@ -1,10 +1,19 @@
 //! Tantivy-based full-text search index.
+//!
+//! Queries use tantivy's query syntax: terms are OR-ed by default, `AND`/`OR`/`NOT`
+//! (or `+term`/`-term`) build boolean queries, `"quoted text"` matches a phrase and
+//! `field:term` restricts a term to one field (e.g. `title:rust`). `domain:` and
+//! `tag:` match a whole source domain or tag, e.g. `domain:blog.rust-lang.org`, and
+//! `topic:` a topic the agent classified the summary under, e.g. `topic:security`.
 
-use crate::summary::Summary;
+use crate::storage::{normalise_tag, Storage, StorageError, StoredSummary};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
-use tantivy::schema::{Schema, Value, STORED, TEXT};
+use tantivy::query::{MoreLikeThisQuery, QueryParser};
+use tantivy::schema::{OwnedValue, Schema, Value, STORED, STRING, TEXT};
 use tantivy::{doc, Index, IndexWriter, ReloadPolicy};
 use thiserror::Error;
 
@@ -16,24 +25,185 @@ pub enum SearchError {
     QueryError(#[from] tantivy::query::QueryParserError),
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("storage error: {0}")]
+    StorageError(#[from] StorageError),
 }
 
+/// Which backend produced a set of search results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchBackend {
+    /// The tantivy full-text index
+    Tantivy,
+    /// Case-insensitive substring scan over stored summaries
+    Fallback,
+}
+
+impl fmt::Display for SearchBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tantivy => write!(f, "tantivy"),
+            Self::Fallback => write!(f, "substring fallback"),
+        }
+    }
+}
+
+/// The URLs matching a query, along with the backend that found them.
+#[derive(Debug, Clone)]
+pub struct SearchOutcome {
+    pub backend: SearchBackend,
+    pub urls: Vec<String>,
+}
+
+/// Which part of a stored record a query should look at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    /// Title, conclusion, key points and entities
+    Summary,
+    /// User notes and Q&A history
+    Notes,
+    /// The extracted source text
+    Raw,
+}
+
+impl SearchScope {
+    /// Index fields covered by this scope
+    fn fields(self) -> &'static [&'static str] {
+        match self {
+            Self::Summary => &["title", "conclusion", "key_points", "entities"],
+            Self::Notes => &["notes", "qa"],
+            Self::Raw => &["raw"],
+        }
+    }
+
+    /// Whether a stored record contains `needle` (already lowercased) within this scope
+    fn matches(self, stored: &StoredSummary, needle: &str) -> bool {
+        let contains = |text: &str| text.to_lowercase().contains(needle);
+        match self {
+            Self::Summary => {
+                let summary = &stored.summary;
+                contains(&summary.title)
+                    || contains(&summary.conclusion)
+                    || summary.key_points.iter().any(|p| contains(p))
+                    || summary.entities.iter().any(|e| contains(&e.name))
+                    || summary.topics.iter().any(|t| contains(t))
+                    || contains(&stored.url)
+            }
+            Self::Notes => {
+                stored.notes.as_deref().is_some_and(contains)
+                    || stored
+                        .qa_history
+                        .iter()
+                        .any(|qa| contains(&qa.question) || contains(&qa.answer))
+            }
+            Self::Raw => stored.raw_text.as_deref().is_some_and(contains),
+        }
+    }
+}
+
+impl FromStr for SearchScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "summary" => Ok(Self::Summary),
+            "notes" => Ok(Self::Notes),
+            "raw" => Ok(Self::Raw),
+            other => Err(format!(
+                "unknown search scope '{other}' (expected summary, notes or raw)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SearchScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Summary => write!(f, "summary"),
+            Self::Notes => write!(f, "notes"),
+            Self::Raw => write!(f, "raw"),
+        }
+    }
+}
+
+/// Options controlling a library search.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Maximum number of results
+    pub limit: usize,
+    /// Return query errors instead of falling back to a substring scan
+    pub strict: bool,
+    /// Restrict matching to one part of each record; `None` searches everything
+    pub scope: Option<SearchScope>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            limit: 20,
+            strict: false,
+            scope: None,
+        }
+    }
+}
+
+/// A named query, re-evaluated whenever it is run.
+///
+/// Shown in the TUI sidebar as a smart collection and runnable from the CLI with
+/// `summera search --saved <name>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub scope: Option<SearchScope>,
+    #[serde(default)]
+    pub strict: bool,
+    /// Send a notification when a newly stored summary matches
+    #[serde(default)]
+    pub alert: bool,
+}
+
+impl SavedSearch {
+    /// Search options for running this saved search with the given result limit
+    pub fn options(&self, limit: usize) -> SearchOptions {
+        SearchOptions {
+            limit,
+            strict: self.strict,
+            scope: self.scope,
+        }
+    }
+}
+
+/// Every scope, used when a search isn't restricted
+const ALL_SCOPES: [SearchScope; 3] = [SearchScope::Summary, SearchScope::Notes, SearchScope::Raw];
+
 /// Tantivy-based search index for summaries.
 pub struct SearchIndex {
     index: Index,
     schema: Schema,
+    /// Set when the on-disk index was just created and holds no documents yet
+    rebuilt: bool,
 }
 
 impl SearchIndex {
     /// Build the expected schema for the search index.
     fn build_schema() -> Schema {
         let mut schema_builder = Schema::builder();
-        schema_builder.add_text_field("url", TEXT | STORED);
+        // Untokenised so that a whole URL can be used as a delete term
+        schema_builder.add_text_field("url", STRING | STORED);
         schema_builder.add_text_field("title", TEXT | STORED);
         schema_builder.add_text_field("conclusion", TEXT);
         schema_builder.add_text_field("key_points", TEXT);
         schema_builder.add_text_field("entities", TEXT);
         schema_builder.add_text_field("action_items", TEXT);
+        schema_builder.add_text_field("notes", TEXT);
+        schema_builder.add_text_field("qa", TEXT);
+        schema_builder.add_text_field("raw", TEXT);
+        // Exact-match filters, outside the default search fields
+        schema_builder.add_text_field("domain", STRING);
+        schema_builder.add_text_field("tag", STRING);
+        schema_builder.add_text_field("topic", STRING);
         schema_builder.build()
     }
 
@@ -47,6 +217,9 @@ impl SearchIndex {
         let index_path = path.as_ref();
         std::fs::create_dir_all(index_path)?;
 
+        // A freshly created index is empty, so callers should populate it
+        let mut rebuilt = true;
+
         // Try creating a fresh index first (fast path for new installs).
         let index = match Index::create_in_dir(index_path, schema.clone()) {
             Ok(idx) => idx,
@@ -54,6 +227,7 @@ impl SearchIndex {
                 // Index already exists – open it and verify its schema.
                 let existing = Index::open_in_dir(index_path)?;
                 if existing.schema() == schema {
+                    rebuilt = false;
                     existing
                 } else {
                     // Schema mismatch – rebuild the index from scratch.
@@ -65,39 +239,99 @@ impl SearchIndex {
             }
         };
 
-        Ok(Self { index, schema })
+        Ok(Self {
+            index,
+            schema,
+            rebuilt,
+        })
+    }
+
+    /// Whether the index was just created or rebuilt, and so started out empty
+    pub fn rebuilt(&self) -> bool {
+        self.rebuilt
+    }
+
+    /// Open the index and, if it was just created or rebuilt, populate it from storage.
+    pub fn open_synced<P: AsRef<Path>>(path: P, storage: &Storage) -> Result<Self, SearchError> {
+        let index = Self::open(path)?;
+        if index.rebuilt {
+            index.index_all(&storage.list_all()?)?;
+        }
+        Ok(index)
+    }
+
+    /// Index a stored summary for searching, replacing any previous document for its URL
+    pub fn index_stored(&self, stored: &StoredSummary) -> Result<(), SearchError> {
+        self.index_all(std::slice::from_ref(stored))
     }
 
-    /// Index a summary for searching
-    pub fn index_summary(&self, url: &str, summary: &Summary) -> Result<(), SearchError> {
+    /// Index several stored summaries in a single commit
+    pub fn index_all(&self, records: &[StoredSummary]) -> Result<(), SearchError> {
         let mut index_writer: IndexWriter = self.index.writer(50_000_000)?;
+        let field = |name: &str| self.schema.get_field(name).unwrap();
 
-        let url_field = self.schema.get_field("url").unwrap();
-        let title_field = self.schema.get_field("title").unwrap();
-        let conclusion_field = self.schema.get_field("conclusion").unwrap();
-        let key_points_field = self.schema.get_field("key_points").unwrap();
-        let entities_field = self.schema.get_field("entities").unwrap();
-        let action_items_field = self.schema.get_field("action_items").unwrap();
-
-        // Delete any existing document with this URL first
-        let url_term = tantivy::Term::from_field_text(url_field, url);
-        index_writer.delete_term(url_term);
-
-        index_writer.add_document(doc!(
-            url_field => url,
-            title_field => summary.title.clone(),
-            conclusion_field => summary.conclusion.clone(),
-            key_points_field => summary.key_points.join(" "),
-            entities_field => summary.entities.join(" "),
-            action_items_field => summary.action_items.join(" "),
-        ))?;
+        for stored in records {
+            let summary = &stored.summary;
+
+            // Delete any existing document with this URL first
+            let url_term = tantivy::Term::from_field_text(field("url"), &stored.url);
+            index_writer.delete_term(url_term);
+
+            let qa_text = stored
+                .qa_history
+                .iter()
+                .map(|qa| format!("{} {}", qa.question, qa.answer))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let mut document = doc!(
+                field("url") => stored.url.as_str(),
+                field("title") => summary.title.clone(),
+                field("conclusion") => summary.conclusion.clone(),
+                field("key_points") => summary.key_points.join(" "),
+                field("entities") => summary.entity_names().join(" "),
+                field("action_items") => summary.action_items.join(" "),
+                field("notes") => stored.notes.clone().unwrap_or_default(),
+                field("qa") => qa_text,
+                field("raw") => stored.raw_text.clone().unwrap_or_default(),
+            );
+            if let Some(domain) = stored.domain() {
+                document.add_text(field("domain"), domain);
+            }
+            for tag in &stored.tags {
+                document.add_text(field("tag"), tag);
+            }
+            for topic in &summary.topics {
+                document.add_text(field("topic"), normalise_tag(topic));
+            }
+            index_writer.add_document(document)?;
+        }
+
+        index_writer.commit()?;
+        Ok(())
+    }
 
+    /// Drop the document for `url`, e.g. after its summary was deleted
+    pub fn remove(&self, url: &str) -> Result<(), SearchError> {
+        let mut index_writer: IndexWriter = self.index.writer(50_000_000)?;
+        let url_field = self.schema.get_field("url").unwrap();
+        index_writer.delete_term(tantivy::Term::from_field_text(url_field, url));
         index_writer.commit()?;
         Ok(())
     }
 
-    /// Search for summaries matching the query
+    /// Search for summaries matching the query across every scope
     pub fn search(&self, query_str: &str, limit: usize) -> Result<Vec<String>, SearchError> {
+        self.search_in(query_str, limit, None)
+    }
+
+    /// Search for summaries matching the query, optionally restricted to one scope
+    pub fn search_in(
+        &self,
+        query_str: &str,
+        limit: usize,
+        scope: Option<SearchScope>,
+    ) -> Result<Vec<String>, SearchError> {
         let reader = self
             .index
             .reader_builder()
@@ -105,20 +339,14 @@ impl SearchIndex {
             .try_into()?;
 
         let searcher = reader.searcher();
-        let title_field = self.schema.get_field("title").unwrap();
-        let conclusion_field = self.schema.get_field("conclusion").unwrap();
-        let key_points_field = self.schema.get_field("key_points").unwrap();
-        let entities_field = self.schema.get_field("entities").unwrap();
-
-        let query_parser = QueryParser::for_index(
-            &self.index,
-            vec![
-                title_field,
-                conclusion_field,
-                key_points_field,
-                entities_field,
-            ],
-        );
+        let scopes = scope.as_ref().map_or(&ALL_SCOPES[..], std::slice::from_ref);
+        let default_fields = scopes
+            .iter()
+            .flat_map(|scope| scope.fields())
+            .map(|name| self.schema.get_field(name).unwrap())
+            .collect();
+
+        let query_parser = QueryParser::for_index(&self.index, default_fields);
         let query = query_parser.parse_query(query_str)?;
 
         let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
@@ -137,3 +365,370 @@ impl SearchIndex {
         Ok(results)
     }
 }
+
+impl SearchIndex {
+    /// URLs of the summaries sharing the most distinctive terms with `stored`'s
+    /// title, conclusion, key points and entities, excluding `stored` itself
+    pub fn more_like(
+        &self,
+        stored: &StoredSummary,
+        limit: usize,
+    ) -> Result<Vec<String>, SearchError> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let searcher = reader.searcher();
+        let field = |name: &str| self.schema.get_field(name).unwrap();
+        let summary = &stored.summary;
+        let text = |value: String| vec![OwnedValue::Str(value)];
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_min_word_length(3)
+            .with_document_fields(vec![
+                (field("title"), text(summary.title.clone())),
+                (field("conclusion"), text(summary.conclusion.clone())),
+                (field("key_points"), text(summary.key_points.join(" "))),
+                (field("entities"), text(summary.entity_names().join(" "))),
+            ]);
+
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit + 1))?;
+        let mut results = Vec::new();
+        for (_score, doc_address) in top_docs {
+            let retrieved_doc = searcher.doc::<tantivy::TantivyDocument>(doc_address)?;
+            if let Some(url) = retrieved_doc
+                .get_first(field("url"))
+                .and_then(|v| v.as_str())
+            {
+                if url != stored.url {
+                    results.push(url.to_string());
+                }
+            }
+        }
+        results.truncate(limit);
+        Ok(results)
+    }
+}
+
+/// Stored summaries most like the one for `url`, from the tantivy index at `index_path`
+pub fn similar_summaries(
+    storage: &Storage,
+    index_path: &Path,
+    url: &str,
+    limit: usize,
+) -> Result<Vec<String>, SearchError> {
+    let stored = storage
+        .get(url)?
+        .ok_or_else(|| StorageError::NotFound(url.to_string()))?;
+    SearchIndex::open_synced(index_path, storage)?.more_like(&stored, limit)
+}
+
+/// Search stored summaries, preferring the tantivy index at `index_path`.
+///
+/// Falls back to [`substring_search`] when the index is unavailable, the query
+/// cannot be parsed, or the index returns nothing. With `strict` set, index and
+/// query errors are returned instead so that boolean/phrase syntax mistakes are
+/// not silently masked by the fallback.
+pub fn search_summaries(
+    storage: &Storage,
+    index_path: &Path,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<SearchOutcome, SearchError> {
+    let indexed = SearchIndex::open_synced(index_path, storage)
+        .and_then(|index| index.search_in(query, options.limit, options.scope));
+
+    match indexed {
+        Ok(urls) if !urls.is_empty() => {
+            return Ok(SearchOutcome {
+                backend: SearchBackend::Tantivy,
+                urls,
+            })
+        }
+        Err(e) if options.strict => return Err(e),
+        _ => {}
+    }
+
+    let mut urls = substring_search(storage, query, options.scope)?;
+    urls.truncate(options.limit);
+    Ok(SearchOutcome {
+        backend: SearchBackend::Fallback,
+        urls,
+    })
+}
+
+/// Saved searches with alerts enabled that match the newly stored record `url`.
+pub fn matching_alerts(
+    storage: &Storage,
+    index_path: &Path,
+    url: &str,
+) -> Result<Vec<SavedSearch>, SearchError> {
+    let limit = storage.count().max(1);
+    let mut matches = Vec::new();
+
+    for saved in storage.saved_searches()?.into_iter().filter(|s| s.alert) {
+        let outcome = search_summaries(storage, index_path, &saved.query, &saved.options(limit))?;
+        if outcome.urls.iter().any(|u| u == url) {
+            matches.push(saved);
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Simple case-insensitive text search over every stored summary.
+pub fn substring_search(
+    storage: &Storage,
+    query: &str,
+    scope: Option<SearchScope>,
+) -> Result<Vec<String>, StorageError> {
+    let query_lower = query.to_lowercase();
+    let scopes = scope.as_ref().map_or(&ALL_SCOPES[..], std::slice::from_ref);
+
+    let results = storage
+        .list_all()?
+        .into_iter()
+        .filter(|stored| scopes.iter().any(|s| s.matches(stored, &query_lower)))
+        .map(|stored| stored.url)
+        .collect();
+
+    Ok(results)
+}
+
+/// Up to `width` characters of a result around the first query term it mentions.
+///
+/// Looks in the conclusion, key points, notes and source text, in that order,
+/// and falls back to the start of the conclusion when no term is found.
+pub fn snippet(stored: &StoredSummary, query: &str, width: usize, ellipsis: &str) -> String {
+    let terms: Vec<Vec<char>> = query
+        .split_whitespace()
+        .filter(|term| !matches!(*term, "AND" | "OR" | "NOT") && !term.starts_with('-'))
+        .map(|term| term.rsplit(':').next().unwrap_or(term))
+        .map(|term| term.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|term| term.chars().count() > 1)
+        .map(|term| term.to_lowercase().chars().collect())
+        .collect();
+    let texts = std::iter::once(&stored.summary.conclusion)
+        .chain(&stored.summary.key_points)
+        .chain(&stored.notes)
+        .chain(&stored.raw_text);
+    for text in texts {
+        let chars: Vec<char> = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .collect();
+        let lower: Vec<char> = chars
+            .iter()
+            .map(|c| c.to_lowercase().next().unwrap_or(*c))
+            .collect();
+        let found = terms
+            .iter()
+            .filter_map(|term| lower.windows(term.len()).position(|w| w == &term[..]))
+            .min();
+        if let Some(at) = found {
+            return window(&chars, at.saturating_sub(width / 3), width, ellipsis);
+        }
+    }
+    let conclusion: Vec<char> = stored.summary.conclusion.chars().collect();
+    window(&conclusion, 0, width, ellipsis)
+}
+
+/// `width` characters of `chars` from `start`, marking cut ends with `ellipsis`
+fn window(chars: &[char], start: usize, width: usize, ellipsis: &str) -> String {
+    let end = (start + width).min(chars.len());
+    let start = end.saturating_sub(width).min(start);
+    let mut text: String = chars[start..end].iter().collect();
+    if start > 0 {
+        text = format!("{}{}", ellipsis, text.trim_start());
+    }
+    if end < chars.len() {
+        text = format!("{}{}", text.trim_end(), ellipsis);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::Summary;
+
+    fn sample_record(url: &str) -> StoredSummary {
+        let summary = Summary::new(
+            "Async Rust in practice".to_string(),
+            "Tokio makes async ergonomic.".to_string(),
+            vec!["Futures are lazy".to_string()],
+            vec!["Tokio".into()],
+            vec![],
+        );
+        StoredSummary::new(url.to_string(), summary)
+            .with_raw_text("The linker pulls in wasm imports at build time.")
+    }
+
+    fn strict() -> SearchOptions {
+        SearchOptions {
+            strict: true,
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_strict_search_rejects_unparsable_query() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let storage = Storage::open(dir.path().join("db")).expect("failed to open storage");
+        let index_path = dir.path().join("search_index");
+
+        let result = search_summaries(&storage, &index_path, "nosuchfield:rust", &strict());
+        assert!(matches!(result, Err(SearchError::QueryError(_))));
+    }
+
+    #[test]
+    fn test_domain_and_tag_filters_match_exactly() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let index = SearchIndex::open(dir.path()).expect("failed to open index");
+        let mut tagged = sample_record("https://www.blog.rust-lang.org/async");
+        tagged.add_tag("rust");
+        tagged.summary.topics = vec!["Machine Learning".to_string()];
+        assert!(tagged.has_topic("machine learning"));
+        index
+            .index_all(&[tagged, sample_record("https://example.com/async")])
+            .expect("failed to index");
+
+        let urls = index.search("domain:blog.rust-lang.org", 10).unwrap();
+        assert_eq!(urls, ["https://www.blog.rust-lang.org/async"]);
+        assert_eq!(index.search("tag:rust", 10).unwrap().len(), 1);
+        assert!(index.search("domain:rust-lang.org", 10).unwrap().is_empty());
+        assert_eq!(index.search("topic:machine-learning", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search_reports_backend() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let storage = Storage::open(dir.path().join("db")).expect("failed to open storage");
+        let index_path = dir.path().join("search_index");
+        let stored = sample_record("https://example.com/async");
+
+        storage.put(&stored).expect("failed to store");
+        SearchIndex::open(&index_path)
+            .expect("failed to open index")
+            .index_stored(&stored)
+            .expect("failed to index");
+
+        let outcome = search_summaries(&storage, &index_path, "tokio AND futures", &strict())
+            .expect("search failed");
+        assert_eq!(outcome.backend, SearchBackend::Tantivy);
+        assert_eq!(outcome.urls, vec![stored.url.clone()]);
+
+        let lenient = SearchOptions::default();
+        let outcome = search_summaries(&storage, &index_path, "nosuchfield:rust", &lenient)
+            .expect("search failed");
+        assert_eq!(outcome.backend, SearchBackend::Fallback);
+    }
+
+    #[test]
+    fn test_search_scope_restricts_fields() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let storage = Storage::open(dir.path().join("db")).expect("failed to open storage");
+        let index_path = dir.path().join("search_index");
+        let mut stored = sample_record("https://example.com/async");
+        stored.notes = Some("Remember the executor benchmarks".to_string());
+
+        storage.put(&stored).expect("failed to store");
+        SearchIndex::open(&index_path)
+            .expect("failed to open index")
+            .index_stored(&stored)
+            .expect("failed to index");
+
+        let in_scope = |scope| SearchOptions {
+            scope: Some(scope),
+            ..strict()
+        };
+        let search = |query, scope| {
+            search_summaries(&storage, &index_path, query, &in_scope(scope))
+                .expect("search failed")
+                .urls
+        };
+
+        assert_eq!(search("wasm", SearchScope::Raw).len(), 1);
+        assert!(search("wasm", SearchScope::Summary).is_empty());
+        assert_eq!(search("executor", SearchScope::Notes).len(), 1);
+        assert!(search("executor", SearchScope::Raw).is_empty());
+    }
+
+    #[test]
+    fn test_similar_summaries_share_terms() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let storage = Storage::open(dir.path().join("db")).unwrap();
+        let record = |url: &str, title: &str| {
+            let summary = Summary::new(title.into(), title.into(), vec![], vec![], vec![]);
+            StoredSummary::new(url.to_string(), summary)
+        };
+        for stored in [
+            record("https://a.example", "Borrow checker lifetimes explained"),
+            record(
+                "https://b.example",
+                "Lifetimes and the borrow checker in practice",
+            ),
+            record("https://c.example", "Sourdough baking at home"),
+        ] {
+            storage.put(&stored).unwrap();
+        }
+
+        let similar =
+            similar_summaries(&storage, &dir.path().join("index"), "https://a.example", 5).unwrap();
+        assert_eq!(similar, ["https://b.example"]);
+    }
+
+    #[test]
+    fn test_matching_alerts_only_fires_for_alerting_matches() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let storage = Storage::open(dir.path().join("db")).expect("failed to open storage");
+        let index_path = dir.path().join("search_index");
+        let stored = sample_record("https://example.com/async");
+
+        storage.put(&stored).expect("failed to store");
+        SearchIndex::open(&index_path)
+            .expect("failed to open index")
+            .index_stored(&stored)
+            .expect("failed to index");
+
+        let saved = |name: &str, query: &str, alert| SavedSearch {
+            name: name.to_string(),
+            query: query.to_string(),
+            scope: None,
+            strict: false,
+            alert,
+        };
+        for search in [
+            saved("tokio", "tokio", true),
+            saved("quiet", "tokio", false),
+            saved("python", "python", true),
+        ] {
+            storage.save_search(&search).expect("failed to save search");
+        }
+
+        let names: Vec<String> = matching_alerts(&storage, &index_path, &stored.url)
+            .expect("alert matching failed")
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["tokio".to_string()]);
+    }
+
+    #[test]
+    fn test_snippet_shows_the_first_matching_term() {
+        let stored = sample_record("https://example.com/async");
+        assert_eq!(
+            snippet(&stored, "+title:TOKIO", 80, "..."),
+            "Tokio makes async ergonomic."
+        );
+        assert_eq!(
+            snippet(&stored, "wasm", 16, "..."),
+            "...s in wasm import..."
+        );
+        assert_eq!(snippet(&stored, "python", 10, "..."), "Tokio make...");
+    }
+}
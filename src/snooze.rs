@@ -0,0 +1,93 @@
+//! Snoozing summaries until a later date, like a tickler file.
+//!
+//! A snoozed summary is left out of the library list, the reading queue and
+//! `summera random` until its date. Once it's due it's listed as unread, so it
+//! shows up in bold and at the end of the reading queue. Only the daemon and
+//! `summera remind` wake it, clearing the snooze and raising a reminder; listing
+//! summaries never writes to them.
+
+use crate::storage::{Storage, StorageError, StoredSummary};
+use crate::timeline;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+/// Parse when a snooze ends: "tomorrow", a date like "2026-11-01" (the start of
+/// that day, local time) or an age from now like "3d", "2w" or "1m"
+pub fn parse_until(text: &str, now: DateTime<Local>) -> Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    let day = match text.to_lowercase().as_str() {
+        "tomorrow" => Some(now.date_naive() + chrono::Duration::days(1)),
+        _ => NaiveDate::parse_from_str(text, "%Y-%m-%d").ok(),
+    };
+    let until = match day {
+        Some(day) => day
+            .and_hms_opt(0, 0, 0)
+            .and_then(|start| start.and_local_timezone(Local).earliest())
+            .ok_or_else(|| format!("'{}' doesn't start at a valid local time", text))?
+            .with_timezone(&Utc),
+        None => {
+            let age = timeline::parse_age(text).map_err(|_| {
+                format!(
+                    "invalid date '{}'; expected tomorrow, YYYY-MM-DD or e.g. 3d, 2w or 1m",
+                    text
+                )
+            })?;
+            now.with_timezone(&Utc)
+                .checked_add_signed(age)
+                .ok_or_else(|| format!("'{}' is too far in the future", text))?
+        }
+    };
+    if until <= now {
+        return Err(format!("'{}' isn't in the future", text));
+    }
+    Ok(until)
+}
+
+/// Wake every kept summary whose snooze is over by `now`, returning the ones that woke
+pub fn wake_due(storage: &Storage, now: DateTime<Utc>) -> Result<Vec<StoredSummary>, StorageError> {
+    let mut woken = Vec::new();
+    for mut stored in storage.list_all()? {
+        if stored.wake(now) {
+            storage.put(&stored)?;
+            woken.push(stored);
+        }
+    }
+    Ok(woken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+
+    #[test]
+    fn parses_dates_and_ages_and_wakes_due_summaries() {
+        let now = Local::now();
+        let tomorrow = parse_until("tomorrow", now).unwrap();
+        assert_eq!(
+            tomorrow.with_timezone(&Local).date_naive(),
+            now.date_naive() + chrono::Duration::days(1)
+        );
+        assert_eq!(
+            parse_until("2w", now).unwrap(),
+            now.with_timezone(&Utc) + chrono::Duration::days(14)
+        );
+        assert!(parse_until("2001-01-01", now).is_err());
+        assert!(parse_until("someday", now).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let now = Utc::now();
+        for (i, days) in [-1, 1].into_iter().enumerate() {
+            let summary = Summary::new(i.to_string(), "C".into(), vec![], vec![], vec![]);
+            let mut stored = StoredSummary::new(format!("https://{}.example", i), summary);
+            stored.snooze(now + chrono::Duration::days(days));
+            storage.put(&stored).unwrap();
+        }
+
+        let woken = wake_due(&storage, now).unwrap();
+        assert_eq!(woken.len(), 1);
+        assert_eq!(woken[0].url, "https://0.example");
+        assert!(storage.get("https://0.example").unwrap().unwrap().unread);
+        assert!(wake_due(&storage, now).unwrap().is_empty());
+    }
+}
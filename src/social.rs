@@ -0,0 +1,600 @@
+//! Twitter/X and Mastodon threads, unrolled.
+//!
+//! A thread is a chain of posts an author writes as replies to themselves.
+//! Summarising just the linked post misses most of it, so the whole chain is
+//! read, oldest first, and summarised as one document with the author named.
+//! Mastodon threads come from the instance's public API. X threads come from
+//! the v2 API when `X_BEARER_TOKEN` is set, and otherwise from the first
+//! Nitter instance in `[social] nitter_instances` that answers.
+
+use crate::config::Config;
+use crate::scraper::{self, WebContent};
+use ::scraper::{Html, Selector};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Base URL of the X API
+const X_API_URL: &str = "https://api.x.com/2";
+
+/// Fields requested for every tweet
+const TWEET_FIELDS: &str = "tweet.fields=conversation_id,created_at,referenced_tweets,note_tweet\
+&expansions=author_id&user.fields=username,name";
+
+/// Hosts that serve X links
+const X_HOSTS: &[&str] = &["x.com", "twitter.com", "mobile.twitter.com", "mobile.x.com"];
+
+/// Prompt used instead of `agent.prompt` for social threads
+pub const THREAD_PROMPT: &str = "Summarise this social media thread as a single piece of \
+writing by its author. In the conclusion, state the author's main claim or announcement. List \
+the steps of the argument, in order, as key points, and any links, calls to action or \
+recommendations as action items. Name the author first among the entities. Ignore post \
+numbering, hashtags and requests to share.";
+
+#[derive(Error, Debug)]
+pub enum SocialError {
+    #[error("failed to fetch thread: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("{url} returned {status}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        url: String,
+    },
+    #[error("post not found: {0}")]
+    NotFound(String),
+    #[error("no Nitter instance could read {0}; set X_BEARER_TOKEN to use the X API")]
+    NoInstance(String),
+}
+
+/// A post that may start, end or sit in the middle of a thread, as named by its URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Thread {
+    /// `https://<instance>/@<user>/<id>`
+    Mastodon { host: String, id: String },
+    /// `https://x.com/<handle>/status/<id>`, or the same on twitter.com
+    X { handle: String, id: String },
+}
+
+impl Thread {
+    /// Recognise X status links and Mastodon post links on any instance
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("https://")?;
+        let (host, path) = rest.split_once('/')?;
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        let path = path.split(['?', '#']).next()?;
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+        if X_HOSTS.contains(&host) {
+            return match segments[..] {
+                [handle, "status" | "statuses", id, ..] if numeric(id) => Some(Self::X {
+                    handle: handle.to_string(),
+                    id: id.to_string(),
+                }),
+                _ => None,
+            };
+        }
+        let id = match segments[..] {
+            [user, id] if user.starts_with('@') && user.len() > 1 => id,
+            ["users", _, "statuses", id] => id,
+            _ => return None,
+        };
+        numeric(id).then(|| Self::Mastodon {
+            host: host.to_string(),
+            id: id.to_string(),
+        })
+    }
+
+    /// Which network the thread is on
+    pub fn network(&self) -> &'static str {
+        match self {
+            Self::Mastodon { .. } => "Mastodon",
+            Self::X { .. } => "X",
+        }
+    }
+}
+
+/// An unrolled thread, and who wrote it
+#[derive(Debug, Clone)]
+pub struct Unrolled {
+    pub content: WebContent,
+    /// The author's name and handle, e.g. "Jane Doe (@jane@mastodon.social)"
+    pub author: String,
+}
+
+/// One post, reduced to what unrolling needs
+#[derive(Debug, Clone, PartialEq)]
+struct Post {
+    id: String,
+    author_id: String,
+    in_reply_to: Option<String>,
+    created_at: Option<String>,
+    text: String,
+}
+
+/// Fetch every post in the author's thread around `thread`, in order
+pub async fn fetch_thread(thread: &Thread, config: &Config) -> Result<Unrolled, SocialError> {
+    let client = scraper::create_client()?;
+    match thread {
+        Thread::Mastodon { host, id } => fetch_mastodon(&client, host, id).await,
+        Thread::X { handle, id } => match config.api.x_bearer_token.as_deref() {
+            Some(token) => fetch_x(&client, token, id).await,
+            None => fetch_nitter(&client, &config.social.nitter_instances, handle, id).await,
+        },
+    }
+}
+
+/// GET a JSON document, turning error statuses into [`SocialError::Rejected`]
+async fn get_json<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, SocialError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(SocialError::NotFound(response.url().to_string()));
+    }
+    if !status.is_success() {
+        return Err(SocialError::Rejected {
+            status,
+            url: response.url().to_string(),
+        });
+    }
+    Ok(response.json().await?)
+}
+
+#[derive(Debug, Deserialize)]
+struct Status {
+    id: String,
+    created_at: Option<String>,
+    in_reply_to_id: Option<String>,
+    account: Account,
+    content: String,
+    #[serde(default)]
+    spoiler_text: String,
+    #[serde(default)]
+    media_attachments: Vec<Attachment>,
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Account {
+    id: String,
+    acct: String,
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Attachment {
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Context {
+    ancestors: Vec<Status>,
+    descendants: Vec<Status>,
+}
+
+impl Status {
+    fn into_post(self) -> Post {
+        let mut text = html_text(&self.content);
+        if !self.spoiler_text.is_empty() {
+            text = format!("[CW: {}] {}", self.spoiler_text, text);
+        }
+        for description in self
+            .media_attachments
+            .into_iter()
+            .flat_map(|a| a.description)
+        {
+            text.push_str(&format!("\n[Image: {}]", description));
+        }
+        Post {
+            id: self.id,
+            author_id: self.account.id,
+            in_reply_to: self.in_reply_to_id,
+            created_at: self.created_at,
+            text,
+        }
+    }
+}
+
+/// Read a Mastodon post and its context from the instance's public API
+async fn fetch_mastodon(
+    client: &reqwest::Client,
+    host: &str,
+    id: &str,
+) -> Result<Unrolled, SocialError> {
+    let api = format!("https://{}/api/v1/statuses/{}", host, id);
+    let status: Status = get_json(client.get(&api)).await?;
+    let context: Context = get_json(client.get(format!("{}/context", api))).await?;
+
+    // Remote accounts already carry their instance in `acct`
+    let acct = if status.account.acct.contains('@') {
+        status.account.acct.clone()
+    } else {
+        format!("{}@{}", status.account.acct, host)
+    };
+    let author = author_label(&status.account.display_name, &acct);
+    let author_id = status.account.id.clone();
+    let url = status
+        .url
+        .clone()
+        .unwrap_or_else(|| format!("https://{}/@{}/{}", host, status.account.acct, id));
+
+    let posts = context
+        .ancestors
+        .into_iter()
+        .chain(std::iter::once(status))
+        .chain(context.descendants)
+        .map(Status::into_post)
+        .collect();
+    let thread = unroll(posts, &author_id, id);
+    Ok(render("Mastodon", url, author, &thread))
+}
+
+#[derive(Debug, Deserialize)]
+struct Tweets<T> {
+    data: Option<T>,
+    #[serde(default)]
+    includes: Includes,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Includes {
+    #[serde(default)]
+    users: Vec<User>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: String,
+    name: String,
+    username: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tweet {
+    id: String,
+    text: String,
+    author_id: String,
+    conversation_id: Option<String>,
+    created_at: Option<String>,
+    #[serde(default)]
+    referenced_tweets: Vec<ReferencedTweet>,
+    note_tweet: Option<NoteTweet>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReferencedTweet {
+    #[serde(rename = "type")]
+    kind: String,
+    id: String,
+}
+
+/// The full text of a tweet longer than 280 characters
+#[derive(Debug, Deserialize)]
+struct NoteTweet {
+    text: String,
+}
+
+impl Tweet {
+    fn into_post(self) -> Post {
+        let in_reply_to = self
+            .referenced_tweets
+            .into_iter()
+            .find(|t| t.kind == "replied_to")
+            .map(|t| t.id);
+        Post {
+            id: self.id,
+            author_id: self.author_id,
+            in_reply_to,
+            created_at: self.created_at,
+            text: self.note_tweet.map_or(self.text, |note| note.text),
+        }
+    }
+}
+
+/// Read a tweet, the tweet its conversation started with and the author's replies
+/// in the conversation from the X API.
+///
+/// The API's search only reaches back seven days, so older threads stop at
+/// whatever the linked tweet and its conversation's first tweet cover.
+async fn fetch_x(client: &reqwest::Client, token: &str, id: &str) -> Result<Unrolled, SocialError> {
+    let get_tweet = |id: &str| {
+        let url = format!("{}/tweets/{}?{}", X_API_URL, id, TWEET_FIELDS);
+        get_json::<Tweets<Tweet>>(client.get(url).bearer_auth(token))
+    };
+    let linked = get_tweet(id).await?;
+    let tweet = linked
+        .data
+        .ok_or_else(|| SocialError::NotFound(id.to_string()))?;
+    let user = linked
+        .includes
+        .users
+        .into_iter()
+        .find(|u| u.id == tweet.author_id)
+        .ok_or_else(|| SocialError::NotFound(id.to_string()))?;
+    let conversation = tweet
+        .conversation_id
+        .clone()
+        .unwrap_or_else(|| id.to_string());
+
+    let mut posts = Vec::new();
+    if conversation != tweet.id {
+        posts.extend(get_tweet(&conversation).await?.data.map(Tweet::into_post));
+    }
+    let search = format!(
+        "{}/tweets/search/recent?query=conversation_id:{} from:{}&max_results=100&{}",
+        X_API_URL, conversation, user.username, TWEET_FIELDS
+    );
+    let replies: Tweets<Vec<Tweet>> = get_json(client.get(search).bearer_auth(token)).await?;
+    posts.extend(replies.data.into_iter().flatten().map(Tweet::into_post));
+    posts.push(tweet.into_post());
+
+    let thread = unroll(posts, &user.id, id);
+    let url = format!("https://x.com/{}/status/{}", user.username, thread[0].id);
+    let author = author_label(&user.name, &user.username);
+    Ok(render("X", url, author, &thread))
+}
+
+/// Read a thread from the first Nitter instance that serves it.
+///
+/// Nitter shows the author's thread around a tweet in its `.main-thread`,
+/// with replies by anyone else below it.
+async fn fetch_nitter(
+    client: &reqwest::Client,
+    instances: &[String],
+    handle: &str,
+    id: &str,
+) -> Result<Unrolled, SocialError> {
+    for instance in instances {
+        let url = format!(
+            "{}/{}/status/{}",
+            instance.trim_end_matches('/'),
+            handle,
+            id
+        );
+        let Ok(response) = client.get(&url).send().await else {
+            continue;
+        };
+        if !response.status().is_success() {
+            continue;
+        }
+        let html = response.text().await?;
+        if let Some((name, thread)) = parse_nitter(&html, handle) {
+            let url = format!("https://x.com/{}/status/{}", handle, thread[0].id);
+            return Ok(render("X", url, author_label(&name, handle), &thread));
+        }
+    }
+    Err(SocialError::NoInstance(format!(
+        "https://x.com/{}/status/{}",
+        handle, id
+    )))
+}
+
+/// The author's display name and their posts in a Nitter thread page
+fn parse_nitter(html: &str, handle: &str) -> Option<(String, Vec<Post>)> {
+    let document = Html::parse_document(html);
+    let item = Selector::parse(".main-thread .timeline-item").ok()?;
+    let username = Selector::parse(".username").ok()?;
+    let fullname = Selector::parse(".fullname").ok()?;
+    let content = Selector::parse(".tweet-content").ok()?;
+    let link = Selector::parse(".tweet-link, .tweet-date a").ok()?;
+    let date = Selector::parse(".tweet-date a").ok()?;
+
+    let text_of = |element: ::scraper::ElementRef| element.text().collect::<String>();
+    let mut name = None;
+    let mut posts = Vec::new();
+    for item in document.select(&item) {
+        let Some(user) = item.select(&username).next().map(text_of) else {
+            continue;
+        };
+        if !user
+            .trim()
+            .trim_start_matches('@')
+            .eq_ignore_ascii_case(handle)
+        {
+            continue;
+        }
+        name = name.or_else(|| item.select(&fullname).next().map(text_of));
+        let id = item
+            .select(&link)
+            .filter_map(|a| a.value().attr("href"))
+            .find_map(|href| href.split("/status/").nth(1))
+            .map(|id| id.split(['#', '?']).next().unwrap_or(id).to_string())
+            .unwrap_or_default();
+        posts.push(Post {
+            id,
+            author_id: handle.to_string(),
+            in_reply_to: None,
+            created_at: item
+                .select(&date)
+                .next()
+                .and_then(|a| a.value().attr("title"))
+                .map(str::to_string),
+            text: item
+                .select(&content)
+                .next()
+                .map(text_of)
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        });
+    }
+    (!posts.is_empty()).then(|| (name.unwrap_or_else(|| handle.to_string()), posts))
+}
+
+/// Sort key putting numeric IDs, which grow over time on both networks, in order
+fn id_order(id: &str) -> (usize, &str) {
+    (id.len(), id)
+}
+
+/// The chain of the author's self-replies through `start`, oldest first.
+///
+/// Walks up while each post replies to the author, then down along the
+/// author's earliest reply to the last post, so replies by others and the
+/// author's answers to them are left out.
+fn unroll(posts: Vec<Post>, author_id: &str, start: &str) -> Vec<Post> {
+    let mut by_id: HashMap<String, Post> = posts
+        .into_iter()
+        .filter(|p| p.author_id == author_id)
+        .map(|p| (p.id.clone(), p))
+        .collect();
+    let Some(first) = by_id.remove(start) else {
+        return Vec::new();
+    };
+
+    let mut thread = vec![first];
+    while let Some(parent) = thread[0]
+        .in_reply_to
+        .as_ref()
+        .and_then(|id| by_id.remove(id))
+    {
+        thread.insert(0, parent);
+    }
+    loop {
+        let last = &thread[thread.len() - 1].id;
+        let next = by_id
+            .values()
+            .filter(|p| p.in_reply_to.as_ref() == Some(last))
+            .min_by(|a, b| id_order(&a.id).cmp(&id_order(&b.id)))
+            .map(|p| p.id.clone());
+        match next.and_then(|id| by_id.remove(&id)) {
+            Some(post) => thread.push(post),
+            None => return thread,
+        }
+    }
+}
+
+fn author_label(name: &str, handle: &str) -> String {
+    let name = name.trim();
+    if name.is_empty() {
+        format!("@{}", handle)
+    } else {
+        format!("{} (@{})", name, handle)
+    }
+}
+
+/// Lay the thread out as one document, numbering each post
+fn render(network: &str, url: String, author: String, thread: &[Post]) -> Unrolled {
+    let mut text = vec![format!(
+        "Thread on {} by {}, {} posts",
+        network,
+        author,
+        thread.len()
+    )];
+    if let Some(date) = thread.first().and_then(|p| p.created_at.as_deref()) {
+        text.push(format!("Posted {}", date));
+    }
+    for (i, post) in thread.iter().enumerate() {
+        text.push(String::new());
+        text.push(format!("[{}/{}] {}", i + 1, thread.len(), post.text));
+    }
+    let title = thread.first().map(|p| {
+        let first_line = p.text.lines().next().unwrap_or_default();
+        format!("{}: {}", author, first_line)
+    });
+    Unrolled {
+        content: WebContent {
+            url,
+            title,
+            text: text.join("\n"),
+            description: None,
+        },
+        author,
+    }
+}
+
+/// Plain text of a Mastodon post, one line per paragraph or line break
+fn html_text(html: &str) -> String {
+    let html = html.replace("<br>", "\n").replace("<br />", "\n");
+    let fragment = Html::parse_fragment(&html);
+    let paragraph = Selector::parse("p").expect("valid selector");
+    let paragraphs: Vec<String> = fragment
+        .select(&paragraph)
+        .map(|p| p.text().collect::<String>().trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if paragraphs.is_empty() {
+        fragment
+            .root_element()
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    } else {
+        paragraphs.join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(id: &str, author: &str, reply_to: Option<&str>) -> Post {
+        Post {
+            id: id.to_string(),
+            author_id: author.to_string(),
+            in_reply_to: reply_to.map(str::to_string),
+            created_at: None,
+            text: format!("post {}", id),
+        }
+    }
+
+    #[test]
+    fn parses_thread_urls() {
+        assert_eq!(
+            Thread::parse("https://x.com/rustlang/status/1790000000000000000?s=20"),
+            Some(Thread::X {
+                handle: "rustlang".to_string(),
+                id: "1790000000000000000".to_string()
+            })
+        );
+        assert_eq!(
+            Thread::parse("https://mastodon.social/@jane/112233445566"),
+            Some(Thread::Mastodon {
+                host: "mastodon.social".to_string(),
+                id: "112233445566".to_string()
+            })
+        );
+        assert!(Thread::parse("https://hachyderm.io/users/jane/statuses/42").is_some());
+        assert_eq!(Thread::parse("https://x.com/rustlang"), None);
+        assert_eq!(Thread::parse("https://medium.com/@jane/a-post-3f2a"), None);
+    }
+
+    #[test]
+    fn unrolls_only_the_authors_chain() {
+        let posts = vec![
+            post("10", "me", None),
+            post("11", "me", Some("10")),
+            post("12", "other", Some("11")),
+            post("13", "me", Some("12")), // answering someone else
+            post("14", "me", Some("11")),
+            post("15", "me", Some("14")),
+            post("9", "me", None),
+        ];
+        let ids: Vec<String> = unroll(posts, "me", "14")
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, ["10", "11", "14", "15"]);
+    }
+
+    #[test]
+    fn reads_the_authors_posts_from_nitter() {
+        let html = r#"<div class="main-thread">
+            <div class="timeline-item"><a class="tweet-link" href="/jane/status/1#m"></a>
+              <a class="fullname">Jane Doe</a><a class="username">@jane</a>
+              <div class="tweet-content">First</div></div>
+            <div class="timeline-item"><a class="tweet-link" href="/bob/status/2#m"></a>
+              <a class="username">@bob</a><div class="tweet-content">Nice</div></div>
+            <div class="timeline-item"><a class="tweet-link" href="/jane/status/3#m"></a>
+              <a class="username">@Jane</a><div class="tweet-content">Second</div></div>
+        </div>"#;
+        let (name, posts) = parse_nitter(html, "jane").unwrap();
+        assert_eq!(name, "Jane Doe");
+        let posts: Vec<(&str, &str)> = posts
+            .iter()
+            .map(|p| (p.id.as_str(), p.text.as_str()))
+            .collect();
+        assert_eq!(posts, [("1", "First"), ("3", "Second")]);
+    }
+}
@@ -0,0 +1,439 @@
+//! Stack Overflow (and other Stack Exchange) questions.
+//!
+//! A question page is mostly sidebars, vote buttons and related links, so the
+//! question, its best answers and their top comments are read from the Stack
+//! Exchange API instead and laid out in order of merit: the question, the
+//! accepted answer, then the others by score. `STACKEXCHANGE_KEY` raises the
+//! API's daily quota when set.
+
+use crate::scraper::{self, WebContent};
+use ::scraper::{Html, Node};
+use chrono::DateTime;
+use flate2::read::GzDecoder;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::io::Read;
+use thiserror::Error;
+
+/// Base URL of the Stack Exchange API
+const API_URL: &str = "https://api.stackexchange.com/2.3";
+
+/// Answers read, besides the accepted one
+const MAX_ANSWERS: usize = 5;
+
+/// Comments kept under each post
+const MAX_COMMENTS: usize = 5;
+
+/// Largest response read once decompressed
+const MAX_RESPONSE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Sites that aren't `*.stackexchange.com`
+const SITES: &[&str] = &[
+    "stackoverflow.com",
+    "superuser.com",
+    "serverfault.com",
+    "askubuntu.com",
+    "mathoverflow.net",
+    "stackapps.com",
+];
+
+/// Prompt used instead of `agent.prompt` for questions
+pub const QUESTION_PROMPT: &str = "Summarise this Q&A thread. Give the problem in a few words as \
+the title. In the conclusion, give the accepted or best-voted solution and why it works. As key \
+points, first state the problem, starting with \"Problem:\", then each caveat or pitfall raised \
+in answers or comments, starting with \"Caveat:\", then each worthwhile alternative answer, \
+starting with \"Alternative:\" and saying when to prefer it. List the concrete steps to apply \
+the solution as action items. Ignore thanks, edits and off-topic comments.";
+
+#[derive(Error, Debug)]
+pub enum StackExchangeError {
+    #[error("failed to fetch from Stack Exchange: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("Stack Exchange returned {status} for {url}: {message}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        url: String,
+        message: String,
+    },
+    #[error("unreadable Stack Exchange response: {0}")]
+    ParseError(String),
+    #[error("no such question: {0}")]
+    NotFound(String),
+}
+
+/// A question, or an answer to one, as named by its URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Question {
+    /// The site's domain, which the API takes as its `site` parameter
+    pub site: String,
+    /// Question ID, or answer ID when `is_answer` is set
+    pub id: u64,
+    pub is_answer: bool,
+}
+
+impl Question {
+    /// Recognise `/questions/<id>/<slug>`, `/q/<id>` and `/a/<id>` links
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("https://")?;
+        let (host, path) = rest.split_once('/')?;
+        let site = host.strip_prefix("www.").unwrap_or(host);
+        if !(SITES.contains(&site) || site.ends_with(".stackexchange.com")) {
+            return None;
+        }
+        let path = path.split(['?', '#']).next()?;
+        let mut segments = path.split('/');
+        let is_answer = match segments.next()? {
+            "questions" | "q" => false,
+            "a" => true,
+            _ => return None,
+        };
+        Some(Self {
+            site: site.to_string(),
+            id: segments.next()?.parse().ok()?,
+            is_answer,
+        })
+    }
+}
+
+/// Items in a Stack Exchange API response
+#[derive(Debug, Deserialize)]
+struct Wrapper<T> {
+    items: Vec<T>,
+}
+
+/// Error body of a rejected request
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    error_message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Owner {
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuestionPost {
+    question_id: u64,
+    title: String,
+    body: String,
+    link: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    score: i64,
+    view_count: u64,
+    answer_count: u64,
+    creation_date: i64,
+    accepted_answer_id: Option<u64>,
+    closed_reason: Option<String>,
+    owner: Option<Owner>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Answer {
+    answer_id: u64,
+    question_id: u64,
+    body: String,
+    score: i64,
+    is_accepted: bool,
+    creation_date: i64,
+    owner: Option<Owner>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Comment {
+    post_id: u64,
+    body: String,
+    score: i64,
+    owner: Option<Owner>,
+}
+
+/// Fetch a question with its best answers and their top comments as text
+pub async fn fetch_question(
+    question: &Question,
+    key: Option<&str>,
+) -> Result<WebContent, StackExchangeError> {
+    let client = scraper::create_client()?;
+    let api = Api {
+        client: &client,
+        site: &question.site,
+        key,
+    };
+    let question_id = if question.is_answer {
+        let answers: Vec<Answer> = api.get(&format!("answers/{}", question.id), "").await?;
+        answers
+            .first()
+            .map(|a| a.question_id)
+            .ok_or_else(|| StackExchangeError::NotFound(question.id.to_string()))?
+    } else {
+        question.id
+    };
+
+    let post: QuestionPost = api
+        .get(&format!("questions/{}", question_id), "")
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| StackExchangeError::NotFound(question_id.to_string()))?;
+    let mut answers: Vec<Answer> = api
+        .get(
+            &format!("questions/{}/answers", question_id),
+            "&sort=votes&order=desc&pagesize=30",
+        )
+        .await?;
+    // The accepted answer first, then the best of the rest
+    answers.sort_by_key(|a| (!a.is_accepted, -a.score));
+    answers.truncate(MAX_ANSWERS + 1);
+
+    let ids: Vec<String> = std::iter::once(post.question_id)
+        .chain(answers.iter().map(|a| a.answer_id))
+        .map(|id| id.to_string())
+        .collect();
+    let comments: Vec<Comment> = api
+        .get(
+            &format!("posts/{}/comments", ids.join(";")),
+            "&sort=votes&order=desc&pagesize=100",
+        )
+        .await?;
+
+    Ok(WebContent {
+        url: post.link.clone(),
+        title: Some(decode_html(&post.title)),
+        text: render(&question.site, &post, &answers, &comments),
+        description: None,
+    })
+}
+
+/// Requests to one site's API
+struct Api<'a> {
+    client: &'a reqwest::Client,
+    site: &'a str,
+    key: Option<&'a str>,
+}
+
+impl Api<'_> {
+    /// The items at `path`, with post bodies included
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &str,
+    ) -> Result<Vec<T>, StackExchangeError> {
+        let url = format!(
+            "{}/{}?site={}&filter=withbody{}",
+            API_URL, path, self.site, query
+        );
+        let mut request = self.client.get(&url);
+        if let Some(key) = self.key {
+            request = request.query(&[("key", key)]);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let body = gunzip(&response.bytes().await?)?;
+        if !status.is_success() {
+            let message = serde_json::from_slice::<ApiError>(&body)
+                .map(|e| e.error_message)
+                .unwrap_or_default();
+            return Err(StackExchangeError::Rejected {
+                status,
+                url,
+                message,
+            });
+        }
+        let wrapper: Wrapper<T> = serde_json::from_slice(&body)
+            .map_err(|e| StackExchangeError::ParseError(e.to_string()))?;
+        Ok(wrapper.items)
+    }
+}
+
+/// The API gzips every response whether asked to or not
+fn gunzip(body: &[u8]) -> Result<Vec<u8>, StackExchangeError> {
+    if !body.starts_with(&[0x1f, 0x8b]) {
+        return Ok(body.to_vec());
+    }
+    let mut decoded = Vec::new();
+    GzDecoder::new(body)
+        .take(MAX_RESPONSE_BYTES as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| StackExchangeError::ParseError(e.to_string()))?;
+    if decoded.len() > MAX_RESPONSE_BYTES {
+        return Err(StackExchangeError::ParseError(format!(
+            "response is larger than {} MB",
+            MAX_RESPONSE_BYTES / 1024 / 1024
+        )));
+    }
+    Ok(decoded)
+}
+
+/// Lay out the question, then each answer with its comments
+fn render(site: &str, post: &QuestionPost, answers: &[Answer], comments: &[Comment]) -> String {
+    let mut text = vec![format!(
+        "Question on {}: {}",
+        site,
+        decode_html(&post.title)
+    )];
+    if !post.tags.is_empty() {
+        text.push(format!("Tags: {}", post.tags.join(", ")));
+    }
+    let status = match (&post.closed_reason, post.accepted_answer_id) {
+        (Some(reason), _) => format!("closed ({})", reason),
+        (None, Some(_)) => "has an accepted answer".to_string(),
+        (None, None) => "no accepted answer".to_string(),
+    };
+    text.push(format!(
+        "Score {}, {} answers, {} views, {}",
+        post.score, post.answer_count, post.view_count, status
+    ));
+
+    let mut section = |heading: String, id: u64, body: &str| {
+        text.push(String::new());
+        text.push(format!("--- {} ---", heading));
+        text.push(post_text(body));
+        for comment in comments
+            .iter()
+            .filter(|c| c.post_id == id)
+            .take(MAX_COMMENTS)
+        {
+            text.push(format!(
+                "Comment by {} (score {}): {}",
+                name(&comment.owner),
+                comment.score,
+                decode_html(&comment.body)
+            ));
+        }
+    };
+    section(
+        format!(
+            "Question by {} ({}, score {})",
+            name(&post.owner),
+            date(post.creation_date),
+            post.score
+        ),
+        post.question_id,
+        &post.body,
+    );
+    for answer in answers {
+        let kind = if answer.is_accepted {
+            "Accepted answer"
+        } else {
+            "Answer"
+        };
+        section(
+            format!(
+                "{} by {} ({}, score {})",
+                kind,
+                name(&answer.owner),
+                date(answer.creation_date),
+                answer.score
+            ),
+            answer.answer_id,
+            &answer.body,
+        );
+    }
+    text.join("\n")
+}
+
+fn name(owner: &Option<Owner>) -> &str {
+    owner
+        .as_ref()
+        .and_then(|o| o.display_name.as_deref())
+        .unwrap_or("a deleted user")
+}
+
+fn date(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Plain text of an HTML snippet such as a title or comment
+fn decode_html(html: &str) -> String {
+    Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// A post body as text: paragraphs and list items joined up, code blocks fenced
+fn post_text(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut blocks: Vec<String> = Vec::new();
+    for node in fragment.root_element().descendants() {
+        let Node::Element(element) = node.value() else {
+            continue;
+        };
+        let text = || {
+            node.descendants()
+                .filter_map(|n| n.value().as_text().map(|t| t.to_string()))
+                .collect::<String>()
+        };
+        match element.name() {
+            "pre" => blocks.push(format!("```\n{}\n```", text().trim_end())),
+            name @ ("p" | "h1" | "h2" | "h3" | "h4" | "li") => {
+                let inside = |tags: &[&str]| {
+                    node.ancestors().any(|a| {
+                        a.value()
+                            .as_element()
+                            .is_some_and(|e| tags.contains(&e.name()))
+                    })
+                };
+                // A list item's paragraphs are read with the item
+                if inside(&["pre"]) || (name != "li" && inside(&["li"])) {
+                    continue;
+                }
+                let line = text().split_whitespace().collect::<Vec<_>>().join(" ");
+                if line.is_empty() {
+                    continue;
+                }
+                blocks.push(match name {
+                    "li" => format!("- {}", line),
+                    _ if inside(&["blockquote"]) => format!("> {}", line),
+                    _ => line,
+                });
+            }
+            _ => {}
+        }
+    }
+    blocks.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_question_urls() {
+        assert_eq!(
+            Question::parse("https://stackoverflow.com/questions/27791532/how-do-i-x?noredirect=1"),
+            Some(Question {
+                site: "stackoverflow.com".to_string(),
+                id: 27791532,
+                is_answer: false
+            })
+        );
+        assert_eq!(
+            Question::parse("https://unix.stackexchange.com/a/12345/678"),
+            Some(Question {
+                site: "unix.stackexchange.com".to_string(),
+                id: 12345,
+                is_answer: true
+            })
+        );
+        assert_eq!(Question::parse("https://stackoverflow.com/users/1/x"), None);
+        assert_eq!(Question::parse("https://example.com/questions/1"), None);
+    }
+
+    #[test]
+    fn keeps_code_blocks_and_lists() {
+        let html = "<p>Use <code>Rc&lt;T&gt;</code>:</p>\
+            <pre><code>let a = Rc::new(5);\nlet b = a.clone();\n</code></pre>\
+            <ul><li><p>cheap clones</p></li><li>no threads</li></ul>\
+            <blockquote><p>quoted docs</p></blockquote>";
+        assert_eq!(
+            post_text(html),
+            "Use Rc<T>:\n\n```\nlet a = Rc::new(5);\nlet b = a.clone();\n```\n\n\
+             - cheap clones\n\n- no threads\n\n> quoted docs"
+        );
+    }
+}
@@ -1,7 +1,13 @@
 //! Sled-based storage for summaries.
 
-use crate::summary::Summary;
+use crate::activity::Event;
+use crate::collections::Collection;
+use crate::embeddings::Embedding;
+use crate::feeds::Feed;
+use crate::search::SavedSearch;
+use crate::summary::{Critique, Summary};
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::path::Path;
@@ -17,8 +23,14 @@ pub enum StorageError {
     NotFound(String),
 }
 
-/// A stored summary with metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Provider recorded on lite captures, stored with only the page's title and
+/// description and no LLM call
+pub const LITE_PROVIDER: &str = "lite";
+
+/// A stored summary with metadata.
+///
+/// The field names are also the `summera dump` output format, so keep them stable.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StoredSummary {
     /// The source URL
     pub url: String,
@@ -26,6 +38,201 @@ pub struct StoredSummary {
     pub created_at: DateTime<Utc>,
     /// The summary itself
     pub summary: Summary,
+    /// The LLM provider and model that produced the summary, e.g. "gemini/gemini-2.0-flash"
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Language the summary is written in, by English name, e.g. "Swedish"
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Extracted source text the summary was generated from
+    #[serde(default)]
+    pub raw_text: Option<String>,
+    /// Free-form notes written by the user
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Questions asked about this summary and the agent's answers
+    #[serde(default)]
+    pub qa_history: Vec<QaExchange>,
+    /// What changed each time `summera diff` found a new version of the page
+    #[serde(default)]
+    pub changes: Vec<PageChange>,
+    /// User-defined tags, normalised with [`normalise_tag`] and kept sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Action items sent to a task manager with `summera push`
+    #[serde(default)]
+    pub pushed_tasks: Vec<PushedTask>,
+    /// Sources the page cites, e.g. a Wikipedia article's external links
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// Earlier versions of the summary replaced by `summera refine`, oldest first
+    #[serde(default)]
+    pub revisions: Vec<Revision>,
+    /// When the summary was last corrected by hand with `summera edit` or the TUI
+    #[serde(default)]
+    pub edited_at: Option<DateTime<Utc>>,
+    /// Words in the source text, counted when it was extracted
+    #[serde(default)]
+    pub word_count: Option<usize>,
+    /// Self-critique score of the current summary, when `agent.critique` is enabled
+    #[serde(default)]
+    pub critique: Option<Critique>,
+    /// Made unattended (e.g. by `summera bookmarks`) and not yet kept or discarded in review
+    #[serde(default)]
+    pub pending: bool,
+    /// Snoozed until then: a pending summary stays out of review, and a kept one
+    /// out of the library list, until it comes back unread
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// Marked as a favourite, with `*` in the TUI or `summera star`
+    #[serde(default)]
+    pub starred: bool,
+    /// Not yet opened in the TUI detail view or printed when summarised
+    #[serde(default)]
+    pub unread: bool,
+    /// Channel and length, for summaries of YouTube videos
+    #[serde(default)]
+    pub video: Option<VideoDetails>,
+}
+
+/// Instruction recorded on revisions replaced by a hand edit
+pub const EDITED_BY_HAND: &str = "edited by hand";
+
+/// Instruction recorded on revisions replaced by summarising the source again
+pub const SUMMARISED_AGAIN: &str = "summarised again";
+
+/// A version of a summary that a refinement or hand edit replaced
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Revision {
+    pub summary: Summary,
+    pub provider: Option<String>,
+    /// The instruction the replacement was written to, e.g. "shorter", or [`EDITED_BY_HAND`]
+    pub instruction: String,
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// An action item that was turned into a task elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PushedTask {
+    pub item: String,
+    /// Task manager it went to, e.g. "todoist"
+    pub backend: String,
+    /// URL or ID of the task that was created
+    pub reference: String,
+    pub pushed_at: DateTime<Utc>,
+}
+
+/// What's known about a video summarised from its transcript
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct VideoDetails {
+    /// The channel that published it
+    pub channel: String,
+    pub duration_secs: u64,
+}
+
+impl VideoDetails {
+    /// The length as a clock reading, e.g. "12:05" or "1:02:03"
+    pub fn duration(&self) -> String {
+        let (hours, minutes, seconds) = (
+            self.duration_secs / 3600,
+            self.duration_secs / 60 % 60,
+            self.duration_secs % 60,
+        );
+        if hours > 0 {
+            format!("{}:{:02}:{:02}", hours, minutes, seconds)
+        } else {
+            format!("{}:{:02}", minutes, seconds)
+        }
+    }
+}
+
+/// Average adult silent reading speed, used for reading time estimates
+const READING_WORDS_PER_MINUTE: usize = 238;
+
+/// Number of whitespace-separated words in `text`
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Canonical form of a tag: trimmed, lowercase, with inner whitespace as `-`
+pub fn normalise_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Host name of `url` without a leading `www.`, or `None` if it isn't a web URL
+pub fn url_domain(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.starts_with("http") {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    // IPv6 addresses are bracketed, so their colons aren't a port
+    let host = match host.strip_prefix('[') {
+        Some(address) => address.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    (!host.is_empty()).then_some(host)
+}
+
+/// Query parameters that track where a visitor came from rather than pick the page
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "mc_cid", "mc_eid", "ref", "ref_src"];
+
+/// What identifies a web page in `url`, for spotting the same page stored under another URL.
+///
+/// The scheme, a leading `www.`, the fragment, a trailing slash and tracking parameters
+/// (`utm_*`, `fbclid`, ...) are dropped and the host is lowercased, so
+/// `https://www.Example.com/post/?utm_source=rss#top` becomes `example.com/post`.
+/// YouTube links (`youtu.be/`, `/shorts/`, `&t=` and so on) name the video's watch
+/// page. Anything other than an http(s) URL is returned trimmed but otherwise unchanged.
+pub fn normalise_url(url: &str) -> String {
+    let canonical = crate::youtube::Video::parse(url.trim()).map(|video| video.url());
+    let url = canonical.as_deref().unwrap_or(url.trim());
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return url.to_string();
+    }
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = location.split_once('/').unwrap_or((location, ""));
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let params: Vec<&str> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !name.is_empty() && !name.starts_with("utm_") && !TRACKING_PARAMS.contains(&name)
+        })
+        .collect();
+    let mut normalised = format!("{}/{}", host, path.trim_end_matches('/'));
+    if !params.is_empty() {
+        normalised = format!("{}?{}", normalised, params.join("&"));
+    }
+    normalised.trim_end_matches('/').to_string()
+}
+
+/// A summary of how a page changed between two fetches
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PageChange {
+    pub detected_at: DateTime<Utc>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// LLM summary of only the changed content
+    pub summary: String,
+}
+
+/// A single question and answer about a stored summary
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QaExchange {
+    pub question: String,
+    pub answer: String,
+    pub asked_at: DateTime<Utc>,
 }
 
 impl StoredSummary {
@@ -35,13 +242,330 @@ impl StoredSummary {
             url,
             created_at: Utc::now(),
             summary,
+            provider: None,
+            language: None,
+            raw_text: None,
+            notes: None,
+            qa_history: Vec::new(),
+            changes: Vec::new(),
+            tags: Vec::new(),
+            pushed_tasks: Vec::new(),
+            references: Vec::new(),
+            revisions: Vec::new(),
+            edited_at: None,
+            word_count: None,
+            critique: None,
+            pending: false,
+            snoozed_until: None,
+            starred: false,
+            unread: true,
+            video: None,
+        }
+    }
+
+    /// Number of the current version of the summary, counting the first as 1
+    pub fn revision(&self) -> usize {
+        self.revisions.len() + 1
+    }
+
+    /// Replace the summary with a refined one, keeping the current version as a revision
+    pub fn revise(&mut self, summary: Summary, provider: String, instruction: &str) {
+        let previous = std::mem::replace(&mut self.summary, summary);
+        // The score was for the replaced version
+        self.critique = None;
+        self.revisions.push(Revision {
+            summary: previous,
+            provider: self.provider.replace(provider),
+            instruction: instruction.to_string(),
+            replaced_at: Utc::now(),
+        });
+    }
+
+    /// Take the summary of a fresh pass over the same source, keeping the current
+    /// version as a revision and everything the user added: notes, tags, questions,
+    /// pushed tasks, the star and the review state.
+    pub fn resummarise(&mut self, fresh: StoredSummary) {
+        let previous = std::mem::replace(&mut self.summary, fresh.summary);
+        // A lite capture holds nothing the full summary doesn't
+        if !self.is_lite() {
+            self.revisions.push(Revision {
+                summary: previous,
+                provider: self.provider.clone(),
+                instruction: SUMMARISED_AGAIN.to_string(),
+                replaced_at: Utc::now(),
+            });
+        }
+        self.provider = fresh.provider;
+        self.language = fresh.language;
+        self.raw_text = fresh.raw_text;
+        self.word_count = fresh.word_count;
+        self.critique = fresh.critique;
+        self.references = fresh.references;
+        self.video = fresh.video;
+        self.unread = fresh.unread;
+        for tag in &fresh.tags {
+            self.add_tag(tag);
+        }
+    }
+
+    /// Replace the summary with a hand-edited one, keeping the current version as a
+    /// revision and marking the record as edited
+    pub fn edit(&mut self, summary: Summary) {
+        let previous = std::mem::replace(&mut self.summary, summary);
+        self.critique = None;
+        self.revisions.push(Revision {
+            summary: previous,
+            provider: self.provider.clone(),
+            instruction: EDITED_BY_HAND.to_string(),
+            replaced_at: Utc::now(),
+        });
+        self.edited_at = Some(Utc::now());
+    }
+
+    /// Whether the summary is pending and not snoozed past `now`
+    pub fn awaits_review(&self, now: DateTime<Utc>) -> bool {
+        self.pending && self.snoozed_until.is_none_or(|until| until <= now)
+    }
+
+    /// Keep a pending summary, moving it into the library
+    pub fn accept(&mut self) {
+        self.pending = false;
+        self.snoozed_until = None;
+    }
+
+    /// Leave a summary out of review, or out of the library list, until `until`
+    pub fn snooze(&mut self, until: DateTime<Utc>) {
+        self.snoozed_until = Some(until);
+    }
+
+    /// Whether the summary is snoozed past `now`
+    pub fn is_snoozed(&self, now: DateTime<Utc>) -> bool {
+        self.snoozed_until.is_some_and(|until| until > now)
+    }
+
+    /// Whether a kept summary's snooze is over by `now`, so it's due to wake
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.pending && self.snoozed_until.is_some_and(|until| until <= now)
+    }
+
+    /// Bring a kept summary back unread once its snooze is over; returns whether it woke
+    pub fn wake(&mut self, now: DateTime<Utc>) -> bool {
+        if !self.is_due(now) {
+            return false;
         }
+        self.snoozed_until = None;
+        self.unread = true;
+        true
+    }
+
+    /// Whether the summary is unread, counting one due to wake that hasn't been woken yet
+    pub fn is_unread(&self, now: DateTime<Utc>) -> bool {
+        self.unread || self.is_due(now)
+    }
+
+    /// Mark the summary read or unread, settling a snooze that's due either way
+    pub fn set_unread(&mut self, unread: bool, now: DateTime<Utc>) {
+        if self.is_due(now) {
+            self.snoozed_until = None;
+        }
+        self.unread = unread;
+    }
+
+    /// Whether the summary belongs in the reading queue: kept, unread and not snoozed
+    pub fn in_queue(&self, now: DateTime<Utc>) -> bool {
+        self.is_unread(now) && !self.pending && !self.is_snoozed(now)
+    }
+
+    /// Replace the notes, dropping them if `text` is blank; returns whether they changed
+    pub fn set_notes(&mut self, text: &str) -> bool {
+        let notes = Some(text.trim())
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        let changed = self.notes != notes;
+        self.notes = notes;
+        changed
+    }
+
+    /// Add a paragraph to the end of the notes
+    pub fn append_note(&mut self, text: &str) {
+        let notes = match self.notes.take() {
+            Some(notes) => format!("{}\n\n{}", notes, text.trim()),
+            None => text.to_string(),
+        };
+        self.set_notes(&notes);
+    }
+
+    /// Keep a question and the agent's answer in the Q&A history
+    pub fn record_answer(&mut self, question: &str, answer: &str) {
+        self.qa_history.push(QaExchange {
+            question: question.to_string(),
+            answer: answer.to_string(),
+            asked_at: Utc::now(),
+        });
+    }
+
+    /// Where an action item was pushed to, if it has been
+    pub fn pushed_task(&self, item: &str) -> Option<&PushedTask> {
+        self.pushed_tasks.iter().find(|task| task.item == item)
+    }
+
+    /// Add a normalised tag, returning whether it was new
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        match self.tags.binary_search_by(|t| t.as_str().cmp(tag)) {
+            Ok(_) => false,
+            Err(pos) => {
+                self.tags.insert(pos, tag.to_string());
+                true
+            }
+        }
+    }
+
+    /// Remove a normalised tag, returning whether it was there
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let before = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        self.tags.len() != before
+    }
+
+    /// Whether the summary is about `topic`, compared the way tags are
+    pub fn has_topic(&self, topic: &str) -> bool {
+        let topic = normalise_tag(topic);
+        self.summary
+            .topics
+            .iter()
+            .any(|t| normalise_tag(t) == topic)
+    }
+
+    /// Whether this is a lite capture, still to be summarised in full
+    pub fn is_lite(&self) -> bool {
+        self.provider.as_deref() == Some(LITE_PROVIDER)
+    }
+
+    /// Host name of the source URL without a leading `www.`, or `None` for local files
+    pub fn domain(&self) -> Option<&str> {
+        url_domain(&self.url)
+    }
+
+    /// Record which provider produced the summary
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    /// Record the language the summary is written in
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Record the self-critique score of the summary
+    pub fn with_critique(mut self, critique: Option<Critique>) -> Self {
+        self.critique = critique;
+        self
+    }
+
+    /// Attach the sources the page cites
+    pub fn with_references(mut self, references: Vec<String>) -> Self {
+        self.references = references;
+        self
+    }
+
+    /// Attach the channel and length of a video
+    pub fn with_video(mut self, video: Option<VideoDetails>) -> Self {
+        self.video = video;
+        self
+    }
+
+    /// Attach the extracted source text
+    pub fn with_raw_text(mut self, text: impl Into<String>) -> Self {
+        let text = text.into();
+        self.word_count = Some(count_words(&text));
+        self.raw_text = Some(text);
+        self
+    }
+
+    /// Words in the source text, counted from the kept text for older records
+    pub fn source_words(&self) -> Option<usize> {
+        self.word_count
+            .or_else(|| self.raw_text.as_deref().map(count_words))
+            .filter(|&words| words > 0)
+    }
+
+    /// Minutes the source takes to read, rounded up
+    pub fn reading_minutes(&self) -> Option<usize> {
+        self.source_words()
+            .map(|words| words.div_ceil(READING_WORDS_PER_MINUTE))
+    }
+
+    /// Words in the summary's conclusion and key points
+    pub fn summary_words(&self) -> usize {
+        count_words(&self.summary.conclusion)
+            + self
+                .summary
+                .key_points
+                .iter()
+                .map(|point| count_words(point))
+                .sum::<usize>()
+    }
+
+    /// The summary's length as a fraction of the source's, in words
+    pub fn compression(&self) -> Option<f64> {
+        self.source_words()
+            .map(|words| self.summary_words() as f64 / words as f64)
+    }
+
+    /// "1820 words, 8 min read, summary 6%", or `None` when the source text wasn't kept.
+    ///
+    /// Videos are headed by their channel and length, e.g. "Fireship, 2:30 video, ...",
+    /// which are shown even without the text.
+    pub fn reading_stats(&self) -> Option<String> {
+        let video = self
+            .video
+            .as_ref()
+            .map(|video| format!("{}, {} video", video.channel, video.duration()));
+        let stats = match (
+            self.source_words(),
+            self.reading_minutes(),
+            self.compression(),
+        ) {
+            (Some(words), Some(minutes), Some(compression)) => Some(format!(
+                "{} words, {} min read, summary {:.0}%",
+                words,
+                minutes,
+                compression * 100.0
+            )),
+            _ => None,
+        };
+        let parts: Vec<String> = video.into_iter().chain(stats).collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
     }
 }
 
+/// Name of the sled tree holding saved searches
+const SAVED_SEARCHES_TREE: &str = "saved_searches";
+
+/// Name of the sled tree holding embedding vectors, keyed like the summaries
+const EMBEDDINGS_TREE: &str = "embeddings";
+
+/// Name of the sled tree holding the activity log, keyed by increasing id
+const ACTIVITY_TREE: &str = "activity";
+
+/// Name of the sled tree holding collections, keyed by name
+const COLLECTIONS_TREE: &str = "collections";
+
+/// Name of the sled tree holding feed subscriptions, keyed by name
+const FEEDS_TREE: &str = "feeds";
+
+/// Name of the sled tree holding the reading queue order
+const QUEUE_TREE: &str = "queue";
+
+/// Key of the reading queue order in [`QUEUE_TREE`]
+const QUEUE_ORDER_KEY: &str = "order";
+
 /// Sled-based storage for webpage summaries.
 ///
-/// Stores summaries keyed by URL hash for efficient retrieval.
+/// Stores summaries keyed by URL hash for efficient retrieval. Auxiliary data
+/// such as saved searches lives in separate named trees.
 pub struct Storage {
     db: sled::Db,
 }
@@ -55,9 +579,13 @@ impl Storage {
 
     /// Store a summary for a URL
     pub fn store(&self, url: &str, summary: &Summary) -> Result<(), StorageError> {
-        let key = Self::hash_url(url);
-        let stored = StoredSummary::new(url.to_string(), summary.clone());
-        let value = serde_json::to_vec(&stored)?;
+        self.put(&StoredSummary::new(url.to_string(), summary.clone()))
+    }
+
+    /// Insert or replace a full stored record, keyed by its URL
+    pub fn put(&self, stored: &StoredSummary) -> Result<(), StorageError> {
+        let key = Self::hash_url(&stored.url);
+        let value = serde_json::to_vec(stored)?;
         self.db.insert(key.as_bytes(), value)?;
         self.db.flush()?;
         Ok(())
@@ -84,23 +612,222 @@ impl Storage {
             results.push(stored);
         }
         // Sort by created_at descending (newest first)
-        results.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        results.sort_by_key(|s| std::cmp::Reverse(s.created_at));
         Ok(results)
     }
 
-    /// Delete a summary by URL
+    /// Iterate over every stored summary in key order, decoding one at a time
+    pub fn iter(&self) -> impl Iterator<Item = Result<StoredSummary, StorageError>> + '_ {
+        self.db.iter().map(|item| {
+            let (_key, value) = item?;
+            Ok(serde_json::from_slice(&value)?)
+        })
+    }
+
+    /// Delete a summary by URL, along with its embedding and its place in collections
     pub fn delete(&self, url: &str) -> Result<bool, StorageError> {
         let key = Self::hash_url(url);
         let existed = self.db.remove(key.as_bytes())?.is_some();
+        self.db.open_tree(EMBEDDINGS_TREE)?.remove(key.as_bytes())?;
+        for mut collection in self.collections()? {
+            if collection.remove(url) {
+                self.put_collection(&collection)?;
+            }
+        }
         self.db.flush()?;
         Ok(existed)
     }
 
+    /// The stored summary for `url`, or for another URL of the same page (see [`normalise_url`])
+    pub fn find_duplicate(&self, url: &str) -> Result<Option<StoredSummary>, StorageError> {
+        if let Some(stored) = self.get(url)? {
+            return Ok(Some(stored));
+        }
+        // Videos are stored under their watch page
+        if let Some(video) = crate::youtube::Video::parse(url) {
+            if let Some(stored) = self.get(&video.url())? {
+                return Ok(Some(stored));
+            }
+        }
+        let wanted = normalise_url(url);
+        for stored in self.iter() {
+            let stored = stored?;
+            if normalise_url(&stored.url) == wanted {
+                return Ok(Some(stored));
+            }
+        }
+        Ok(None)
+    }
+
     /// Get the number of stored summaries
     pub fn count(&self) -> usize {
         self.db.len()
     }
 
+    /// Save (or overwrite) a named search
+    pub fn save_search(&self, saved: &SavedSearch) -> Result<(), StorageError> {
+        let tree = self.db.open_tree(SAVED_SEARCHES_TREE)?;
+        tree.insert(saved.name.as_bytes(), serde_json::to_vec(saved)?)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    /// Look up a saved search by name
+    pub fn saved_search(&self, name: &str) -> Result<Option<SavedSearch>, StorageError> {
+        let tree = self.db.open_tree(SAVED_SEARCHES_TREE)?;
+        match tree.get(name.as_bytes())? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List all saved searches, ordered by name
+    pub fn saved_searches(&self) -> Result<Vec<SavedSearch>, StorageError> {
+        let tree = self.db.open_tree(SAVED_SEARCHES_TREE)?;
+        tree.iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    /// Delete a saved search by name
+    pub fn delete_saved_search(&self, name: &str) -> Result<bool, StorageError> {
+        let tree = self.db.open_tree(SAVED_SEARCHES_TREE)?;
+        let existed = tree.remove(name.as_bytes())?.is_some();
+        tree.flush()?;
+        Ok(existed)
+    }
+
+    /// Save (or overwrite) a collection
+    pub fn put_collection(&self, collection: &Collection) -> Result<(), StorageError> {
+        let tree = self.db.open_tree(COLLECTIONS_TREE)?;
+        tree.insert(collection.name.as_bytes(), serde_json::to_vec(collection)?)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    /// Look up a collection by name
+    pub fn collection(&self, name: &str) -> Result<Option<Collection>, StorageError> {
+        let tree = self.db.open_tree(COLLECTIONS_TREE)?;
+        match tree.get(name.as_bytes())? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List all collections, ordered by name
+    pub fn collections(&self) -> Result<Vec<Collection>, StorageError> {
+        let tree = self.db.open_tree(COLLECTIONS_TREE)?;
+        tree.iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    /// Delete a collection by name, leaving its summaries alone
+    pub fn delete_collection(&self, name: &str) -> Result<bool, StorageError> {
+        let tree = self.db.open_tree(COLLECTIONS_TREE)?;
+        let existed = tree.remove(name.as_bytes())?.is_some();
+        tree.flush()?;
+        Ok(existed)
+    }
+
+    /// Save (or overwrite) a feed subscription
+    pub fn put_feed(&self, feed: &Feed) -> Result<(), StorageError> {
+        let tree = self.db.open_tree(FEEDS_TREE)?;
+        tree.insert(feed.name.as_bytes(), serde_json::to_vec(feed)?)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    /// Look up a feed by name
+    pub fn feed(&self, name: &str) -> Result<Option<Feed>, StorageError> {
+        let tree = self.db.open_tree(FEEDS_TREE)?;
+        match tree.get(name.as_bytes())? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List all feeds, ordered by name
+    pub fn feeds(&self) -> Result<Vec<Feed>, StorageError> {
+        let tree = self.db.open_tree(FEEDS_TREE)?;
+        tree.iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    /// Unsubscribe from a feed by name, leaving its summaries alone
+    pub fn delete_feed(&self, name: &str) -> Result<bool, StorageError> {
+        let tree = self.db.open_tree(FEEDS_TREE)?;
+        let existed = tree.remove(name.as_bytes())?.is_some();
+        tree.flush()?;
+        Ok(existed)
+    }
+
+    /// URLs in the order they were last arranged in the reading queue (see [`crate::queue`])
+    pub fn queue_order(&self) -> Result<Vec<String>, StorageError> {
+        let tree = self.db.open_tree(QUEUE_TREE)?;
+        match tree.get(QUEUE_ORDER_KEY)? {
+            Some(data) => Ok(serde_json::from_slice(&data)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Replace the reading queue order
+    pub fn put_queue_order(&self, urls: &[String]) -> Result<(), StorageError> {
+        let tree = self.db.open_tree(QUEUE_TREE)?;
+        tree.insert(QUEUE_ORDER_KEY, serde_json::to_vec(urls)?)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    /// Store (or replace) the embedding of a summary
+    pub fn put_embedding(&self, embedding: &Embedding) -> Result<(), StorageError> {
+        let tree = self.db.open_tree(EMBEDDINGS_TREE)?;
+        let key = Self::hash_url(&embedding.url);
+        tree.insert(key.as_bytes(), serde_json::to_vec(embedding)?)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    /// The embedding of the summary for a URL, if one was computed
+    pub fn embedding(&self, url: &str) -> Result<Option<Embedding>, StorageError> {
+        let tree = self.db.open_tree(EMBEDDINGS_TREE)?;
+        match tree.get(Self::hash_url(url).as_bytes())? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every stored embedding, in key order
+    pub fn embeddings(&self) -> Result<Vec<Embedding>, StorageError> {
+        let tree = self.db.open_tree(EMBEDDINGS_TREE)?;
+        tree.iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    /// Append an event to the activity log
+    pub fn log_event(&self, event: &Event) -> Result<(), StorageError> {
+        let tree = self.db.open_tree(ACTIVITY_TREE)?;
+        let id = self.db.generate_id()?;
+        tree.insert(id.to_be_bytes(), serde_json::to_vec(event)?)?;
+        tree.flush()?;
+        Ok(())
+    }
+
+    /// The activity log, oldest first
+    pub fn events(&self) -> Result<Vec<Event>, StorageError> {
+        let tree = self.db.open_tree(ACTIVITY_TREE)?;
+        tree.iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
     /// Create a hash of the URL for use as a key
     fn hash_url(url: &str) -> String {
         use std::collections::hash_map::DefaultHasher;
@@ -110,3 +837,264 @@ impl Storage {
         format!("{:x}", hasher.finish())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_streams_every_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        for url in ["https://a.example", "https://b.example"] {
+            let summary = Summary::new(url.to_string(), String::new(), vec![], vec![], vec![]);
+            storage
+                .put(&StoredSummary::new(url.to_string(), summary).with_raw_text("body"))
+                .unwrap();
+        }
+
+        let mut urls: Vec<String> = storage.iter().map(|s| s.unwrap().url).collect();
+        urls.sort();
+        assert_eq!(urls, ["https://a.example", "https://b.example"]);
+    }
+
+    #[test]
+    fn notes_are_appended_and_cleared() {
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://a.example".to_string(), summary);
+        stored.append_note("Check the benchmarks ");
+        stored.append_note("Compare with tokio");
+        assert_eq!(
+            stored.notes.as_deref(),
+            Some("Check the benchmarks\n\nCompare with tokio")
+        );
+        assert!(!stored.set_notes("Check the benchmarks\n\nCompare with tokio\n"));
+        assert!(stored.set_notes("  "));
+        assert_eq!(stored.notes, None);
+    }
+
+    #[test]
+    fn reading_stats_compare_summary_and_source() {
+        let summary = Summary::new(
+            "T".into(),
+            "Two words.".into(),
+            vec!["Three more words.".into()],
+            vec![],
+            vec![],
+        );
+        let mut stored = StoredSummary::new("https://a.example".to_string(), summary);
+        assert_eq!(stored.reading_stats(), None);
+
+        stored = stored.with_raw_text("word ".repeat(500));
+        assert_eq!(stored.word_count, Some(500));
+        assert_eq!(stored.reading_minutes(), Some(3));
+        assert_eq!(
+            stored.reading_stats().as_deref(),
+            Some("500 words, 3 min read, summary 1%")
+        );
+
+        // Records stored before word counts were kept count their text instead
+        stored.word_count = None;
+        assert_eq!(stored.source_words(), Some(500));
+
+        stored = stored.with_video(Some(VideoDetails {
+            channel: "Fireship".into(),
+            duration_secs: 3723,
+        }));
+        assert_eq!(
+            stored.reading_stats().as_deref(),
+            Some("Fireship, 1:02:03 video, 500 words, 3 min read, summary 1%")
+        );
+        stored.raw_text = None;
+        assert_eq!(
+            stored.reading_stats().as_deref(),
+            Some("Fireship, 1:02:03 video")
+        );
+    }
+
+    #[test]
+    fn finds_the_same_page_under_another_url() {
+        assert_eq!(
+            normalise_url("https://www.Example.com/post/?utm_source=rss&id=3#top"),
+            "example.com/post?id=3"
+        );
+        assert_eq!(normalise_url("http://example.com/"), "example.com");
+        assert_eq!(normalise_url("/tmp/notes.pdf"), "/tmp/notes.pdf");
+        assert_eq!(
+            normalise_url("https://youtu.be/dQw4w9WgXcQ?t=42"),
+            normalise_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        storage
+            .put(&StoredSummary::new(
+                "https://example.com/post".into(),
+                summary,
+            ))
+            .unwrap();
+        let found = storage
+            .find_duplicate("http://www.example.com/post/?fbclid=x")
+            .unwrap();
+        assert_eq!(
+            found.map(|s| s.url).as_deref(),
+            Some("https://example.com/post")
+        );
+        assert!(storage
+            .find_duplicate("https://example.com/other")
+            .unwrap()
+            .is_none());
+
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        storage
+            .put(&StoredSummary::new(
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ".into(),
+                summary,
+            ))
+            .unwrap();
+        for url in [
+            "https://youtu.be/dQw4w9WgXcQ",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42s",
+        ] {
+            assert!(storage.find_duplicate(url).unwrap().is_some(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn snoozed_summaries_come_back_when_due() {
+        let summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://a.example".to_string(), summary);
+        let now = Utc::now();
+        assert!(!stored.awaits_review(now));
+
+        stored.pending = true;
+        stored.snooze(now + chrono::Duration::days(1));
+        assert!(!stored.awaits_review(now));
+        assert!(stored.awaits_review(now + chrono::Duration::days(2)));
+
+        stored.accept();
+        assert!(!stored.pending && stored.snoozed_until.is_none());
+
+        stored.snooze(now + chrono::Duration::days(1));
+        assert!(stored.is_snoozed(now) && !stored.wake(now));
+        assert!(!stored.in_queue(now));
+
+        // Due but not woken yet, it's read as unread without being written
+        stored.unread = false;
+        let later = now + chrono::Duration::days(2);
+        assert!(stored.is_unread(later) && stored.in_queue(later));
+        let mut read = stored.clone();
+        read.set_unread(false, later);
+        assert!(!read.is_unread(later) && read.snoozed_until.is_none());
+
+        assert!(stored.wake(later));
+        assert!(stored.unread && !stored.is_snoozed(now));
+    }
+
+    #[test]
+    fn domain_strips_www_port_and_credentials() {
+        let summary = Summary::new(String::new(), String::new(), vec![], vec![], vec![]);
+        let domain = |url: &str| {
+            StoredSummary::new(url.to_string(), summary.clone())
+                .domain()
+                .map(str::to_string)
+        };
+        assert_eq!(
+            domain("https://www.example.com/a?b#c").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            domain("http://user@blog.example.org:8080").as_deref(),
+            Some("blog.example.org")
+        );
+        assert_eq!(domain("file:///home/me/paper.pdf"), None);
+    }
+
+    #[test]
+    fn tags_stay_sorted_and_unique() {
+        let summary = Summary::new(String::new(), String::new(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://example.com".to_string(), summary);
+        assert_eq!(normalise_tag("  Machine   Learning "), "machine-learning");
+        assert!(stored.add_tag("rust"));
+        assert!(stored.add_tag("async"));
+        assert!(!stored.add_tag("rust"));
+        assert_eq!(stored.tags, ["async", "rust"]);
+        assert!(stored.remove_tag("async"));
+        assert!(!stored.remove_tag("async"));
+        assert_eq!(stored.tags, ["rust"]);
+    }
+
+    #[test]
+    fn revise_keeps_the_previous_version() {
+        let original = Summary::new("Long".into(), "C".into(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://example.com".to_string(), original)
+            .with_provider("gemini/gemini-2.0-flash");
+        let shorter = Summary::new("Short".into(), "C".into(), vec![], vec![], vec![]);
+        stored.revise(shorter, "openai/gpt-4o".to_string(), "shorter");
+
+        assert_eq!(stored.summary.title, "Short");
+        assert_eq!(stored.provider.as_deref(), Some("openai/gpt-4o"));
+        assert_eq!(stored.revisions.len(), 1);
+        assert_eq!(stored.revision(), 2);
+        assert_eq!(stored.revisions[0].summary.title, "Long");
+        assert_eq!(
+            stored.revisions[0].provider.as_deref(),
+            Some("gemini/gemini-2.0-flash")
+        );
+        assert_eq!(stored.revisions[0].instruction, "shorter");
+    }
+
+    #[test]
+    fn resummarising_keeps_what_the_user_added() {
+        let original = Summary::new("Old".into(), "C".into(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://example.com".to_string(), original)
+            .with_provider("gemini/gemini-2.0-flash");
+        stored.add_tag("rust");
+        stored.append_note("Read twice");
+        stored.starred = true;
+        stored.pending = true;
+
+        let fresh = Summary::new("New".into(), "C".into(), vec![], vec![], vec![]);
+        let mut fresh = StoredSummary::new(stored.url.clone(), fresh)
+            .with_provider("openai/gpt-4o")
+            .with_raw_text("new text");
+        fresh.add_tag("auto");
+        stored.resummarise(fresh);
+
+        assert_eq!(stored.summary.title, "New");
+        assert_eq!(stored.provider.as_deref(), Some("openai/gpt-4o"));
+        assert_eq!(stored.raw_text.as_deref(), Some("new text"));
+        assert_eq!(stored.revisions[0].summary.title, "Old");
+        assert_eq!(stored.revisions[0].instruction, SUMMARISED_AGAIN);
+        assert_eq!(stored.tags, ["auto", "rust"]);
+        assert_eq!(stored.notes.as_deref(), Some("Read twice"));
+        assert!(stored.starred && stored.pending);
+    }
+
+    #[test]
+    fn upgrading_a_lite_capture_keeps_its_feed_tag_and_review_state() {
+        let capture = Summary::new("Entry".into(), "Lead".into(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://blog.example/1".to_string(), capture)
+            .with_provider(LITE_PROVIDER);
+        stored.add_tag("example-blog");
+        stored.pending = true;
+        stored.append_note("Worth a full read");
+
+        let full = Summary::new("Entry".into(), "Full".into(), vec![], vec![], vec![]);
+        stored.resummarise(
+            StoredSummary::new(stored.url.clone(), full).with_provider("openai/gpt-4o"),
+        );
+
+        assert!(!stored.is_lite());
+        assert_eq!(stored.summary.conclusion, "Full");
+        assert!(
+            stored.revisions.is_empty(),
+            "the capture isn't kept as a revision"
+        );
+        assert_eq!(stored.tags, ["example-blog"]);
+        assert_eq!(stored.notes.as_deref(), Some("Worth a full read"));
+        assert!(stored.pending);
+    }
+}
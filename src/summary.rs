@@ -1,32 +1,359 @@
 //! Summary struct - the core structured output from the LLM agent.
 
+use rstructor::{Instructor, RStructorError};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
 
 /// Structured summary output from the LLM.
 ///
 /// This schema is enforced by rstructor, ensuring the LLM returns valid data.
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+/// Responses that fail [`validate_summary`] are sent back to the model to repair.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Instructor)]
+#[llm(
+    description = "A structured summary of a piece of content",
+    validate = "validate_summary"
+)]
 pub struct Summary {
     /// Title or headline for the summarized content
+    #[llm(description = "A concise title for the content")]
     pub title: String,
     /// Main conclusion or takeaway from the content
+    #[llm(description = "The main takeaway or conclusion of the content in 1-2 sentences")]
     pub conclusion: String,
     /// Main takeaways from the content
+    #[llm(description = "Key takeaways from the content")]
     pub key_points: Vec<String>,
-    /// Named entities mentioned (people, organizations, technologies, etc.)
-    pub entities: Vec<String>,
+    /// Named entities mentioned, each with what kind of thing it is
+    #[llm(
+        description = "Named entities like people, organisations, technologies and places, each with its kind"
+    )]
+    pub entities: Vec<Entity>,
     /// Actionable items or next steps identified in the content
+    #[llm(description = "Actionable items or next steps; can be empty")]
     pub action_items: Vec<String>,
+    /// Broad subject areas the content belongs to, e.g. "rust", "security" or "ml"
+    #[llm(
+        description = "One to three broad topics the content belongs to, as short lowercase labels like rust, security or ml"
+    )]
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Stance the content takes toward its subject, for opinion pieces and reviews
+    #[llm(
+        description = "The stance the content takes toward its subject (positive, negative or neutral), with a one-line justification"
+    )]
+    #[serde(default)]
+    pub sentiment: Option<Sentiment>,
+    /// Extra fields declared under `[agent.fields]` in the config, keyed by name
+    #[llm(
+        description = "Extra fields asked for in the instructions, keyed by field name; empty unless some are asked for"
+    )]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, serde_json::Value>,
+}
+
+/// What kind of thing a named entity is
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    Instructor,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Person,
+    Organisation,
+    Technology,
+    Location,
+    /// Anything else, and every entity stored before kinds were recorded
+    #[default]
+    Other,
+}
+
+impl EntityKind {
+    /// Heading for a group of entities of this kind
+    pub fn plural(&self) -> &'static str {
+        match self {
+            Self::Person => "People",
+            Self::Organisation => "Organisations",
+            Self::Technology => "Technologies",
+            Self::Location => "Locations",
+            Self::Other => "Other",
+        }
+    }
+
+    /// The kind with this [`plural`](Self::plural) heading
+    pub fn from_plural(plural: &str) -> Option<Self> {
+        match plural {
+            "People" => Some(Self::Person),
+            "Organisations" => Some(Self::Organisation),
+            "Technologies" => Some(Self::Technology),
+            "Locations" => Some(Self::Location),
+            "Other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+
+/// A named entity mentioned in the content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema, Instructor)]
+pub struct Entity {
+    #[llm(description = "The name as written in the content, e.g. Rust or Ada Lovelace")]
+    pub name: String,
+    #[llm(description = "person, organisation, technology, location or other")]
+    pub kind: EntityKind,
+}
+
+impl Entity {
+    pub fn new(name: impl Into<String>, kind: EntityKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+        }
+    }
+}
+
+/// An entity of unknown kind
+impl From<&str> for Entity {
+    fn from(name: &str) -> Self {
+        Self::new(name, EntityKind::Other)
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Reads `{ "name", "kind" }` and, for records stored before entities were typed,
+/// a bare name as an entity of kind `other`
+impl<'de> Deserialize<'de> for Entity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Name(String),
+            Typed {
+                name: String,
+                #[serde(default)]
+                kind: EntityKind,
+            },
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Name(name) => Self::new(name, EntityKind::Other),
+            Stored::Typed { name, kind } => Self::new(name, kind),
+        })
+    }
+}
+
+/// Whether content argues for, against or neither
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Instructor)]
+#[serde(rename_all = "lowercase")]
+pub enum Stance {
+    Positive,
+    Negative,
+    Neutral,
+}
+
+impl fmt::Display for Stance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Positive => write!(f, "Positive"),
+            Self::Negative => write!(f, "Negative"),
+            Self::Neutral => write!(f, "Neutral"),
+        }
+    }
+}
+
+/// The stance of a piece of content and why
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, Instructor)]
+pub struct Sentiment {
+    #[llm(description = "positive, negative or neutral")]
+    pub stance: Stance,
+    #[llm(description = "One sentence on what in the content shows this stance")]
+    pub justification: String,
+}
+
+impl fmt::Display for Sentiment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.justification.trim() {
+            "" => write!(f, "{}", self.stance),
+            justification => write!(f, "{}: {}", self.stance, justification),
+        }
+    }
+}
+
+/// How well a summary holds up against its source, scored by a second LLM pass
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, Instructor)]
+#[llm(
+    description = "A review of how well a summary represents its source text",
+    validate = "validate_critique"
+)]
+pub struct Critique {
+    #[llm(
+        description = "1 to 5: 5 when every claim in the summary is supported by the text, 1 when much of it is unsupported or wrong"
+    )]
+    pub faithfulness: u8,
+    #[llm(
+        description = "1 to 5: 5 when the summary includes everything important in the text, 1 when it misses the main points"
+    )]
+    pub coverage: u8,
+    #[llm(description = "One sentence on the biggest problem, or why the summary is good")]
+    pub reason: String,
+}
+
+impl Critique {
+    /// The lower of the two scores, since a summary is only as good as its weakest side
+    pub fn score(&self) -> u8 {
+        self.faithfulness.min(self.coverage)
+    }
+}
+
+impl fmt::Display for Critique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Quality {}/5 (faithfulness {}, coverage {})",
+            self.score(),
+            self.faithfulness,
+            self.coverage
+        )
+    }
+}
+
+/// Scores must be on the 1 to 5 scale
+fn validate_critique(critique: &Critique) -> rstructor::Result<()> {
+    for (name, score) in [
+        ("faithfulness", critique.faithfulness),
+        ("coverage", critique.coverage),
+    ] {
+        if !(1..=5).contains(&score) {
+            return Err(RStructorError::ValidationError(format!(
+                "{} must be between 1 and 5, got {}",
+                name, score
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reject summaries with nothing in them, so the model is asked to try again
+fn validate_summary(summary: &Summary) -> rstructor::Result<()> {
+    if summary.conclusion.trim().is_empty() {
+        return Err(RStructorError::ValidationError(
+            "conclusion must not be empty".to_string(),
+        ));
+    }
+    if summary
+        .key_points
+        .iter()
+        .all(|point| point.trim().is_empty())
+    {
+        return Err(RStructorError::ValidationError(
+            "key_points must contain at least one point".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Comment heading the TOML a summary is edited as
+const EDIT_HEADER: &str =
+    "# Correct the summary, then save and close. Lines starting with # are ignored.\n\n";
+
+#[derive(Error, Debug)]
+pub enum EditError {
+    #[error("failed to write the summary for editing: {0}")]
+    SerializeError(#[from] toml::ser::Error),
+    #[error("the edited summary doesn't parse: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("the edited summary is incomplete: {0}")]
+    Invalid(String),
+}
+
+/// `value` with the nulls inside it removed, or `None` if it's null itself
+fn without_nulls(value: serde_json::Value) -> Option<serde_json::Value> {
+    use serde_json::Value;
+    match value {
+        Value::Null => None,
+        Value::Array(items) => Some(Value::Array(
+            items.into_iter().filter_map(without_nulls).collect(),
+        )),
+        Value::Object(fields) => Some(Value::Object(
+            fields
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, without_nulls(value)?)))
+                .collect(),
+        )),
+        value => Some(value),
+    }
 }
 
 impl Summary {
+    /// The summary as TOML for correcting by hand in an editor
+    pub fn to_editable(&self) -> Result<String, EditError> {
+        // TOML has no null, so custom fields the LLM left empty are dropped
+        let mut editable = self.clone();
+        editable.custom = std::mem::take(&mut editable.custom)
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, without_nulls(value)?)))
+            .collect();
+        Ok(format!(
+            "{}{}",
+            EDIT_HEADER,
+            toml::to_string_pretty(&editable)?
+        ))
+    }
+
+    /// Read back a summary edited from [`Summary::to_editable`], checking it isn't empty
+    pub fn from_editable(text: &str) -> Result<Self, EditError> {
+        let summary: Summary = toml::from_str(text)?;
+        validate_summary(&summary).map_err(|e| EditError::Invalid(e.to_string()))?;
+        Ok(summary)
+    }
+
+    /// The summary as Markdown: the title as a heading, the conclusion, then key
+    /// points, entities and action items (as task checkboxes) under their own headings
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n{}\n", self.title, self.conclusion);
+        if !self.key_points.is_empty() {
+            out.push_str("\n## Key points\n\n");
+            for point in &self.key_points {
+                out.push_str(&format!("- {}\n", point));
+            }
+        }
+        if !self.entities.is_empty() {
+            out.push_str("\n## Entities\n\n");
+            for (kind, names) in self.entities_by_kind() {
+                out.push_str(&format!("- **{}**: {}\n", kind.plural(), names.join(", ")));
+            }
+        }
+        if !self.action_items.is_empty() {
+            out.push_str("\n## Action items\n\n");
+            for item in &self.action_items {
+                out.push_str(&format!("- [ ] {}\n", item));
+            }
+        }
+        out
+    }
+
     /// Create a new summary
     pub fn new(
         title: String,
         conclusion: String,
         key_points: Vec<String>,
-        entities: Vec<String>,
+        entities: Vec<Entity>,
         action_items: Vec<String>,
     ) -> Self {
         Self {
@@ -35,9 +362,72 @@ impl Summary {
             key_points,
             entities,
             action_items,
+            topics: Vec::new(),
+            sentiment: None,
+            custom: BTreeMap::new(),
         }
     }
 
+    /// Set the title from `override_title`, or from `fallback` when the LLM didn't
+    /// produce a usable one
+    pub fn apply_title(&mut self, override_title: Option<String>, fallback: Option<String>) {
+        match (override_title, fallback) {
+            (Some(title), _) => self.title = title,
+            (None, Some(title)) if self.has_placeholder_title() => self.title = title,
+            _ => {}
+        }
+    }
+
+    /// Put `entity` first among the entities, unless it's already listed
+    pub fn add_entity(&mut self, entity: Entity) {
+        if !self
+            .entities
+            .iter()
+            .any(|e| e.name.eq_ignore_ascii_case(&entity.name))
+        {
+            self.entities.insert(0, entity);
+        }
+    }
+
+    /// Names of the entities, in order
+    pub fn entity_names(&self) -> Vec<&str> {
+        self.entities.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    /// Entity names grouped by kind, in [`EntityKind`] order, skipping empty kinds
+    pub fn entities_by_kind(&self) -> Vec<(EntityKind, Vec<&str>)> {
+        let mut groups: Vec<(EntityKind, Vec<&str>)> = Vec::new();
+        for entity in &self.entities {
+            match groups.iter_mut().find(|(kind, _)| *kind == entity.kind) {
+                Some((_, names)) => names.push(&entity.name),
+                None => groups.push((entity.kind, vec![&entity.name])),
+            }
+        }
+        groups.sort_by_key(|(kind, _)| *kind);
+        groups
+    }
+
+    /// The custom fields as a heading ("open_questions" becomes "Open questions") and
+    /// one line per list item, skipping empty ones
+    pub fn custom_sections(&self) -> Vec<(String, Vec<String>)> {
+        self.custom
+            .iter()
+            .filter_map(|(name, value)| {
+                let lines = match value {
+                    serde_json::Value::Array(items) => items.iter().map(field_text).collect(),
+                    value => vec![field_text(value)],
+                };
+                let lines: Vec<String> = lines.into_iter().filter(|l| !l.is_empty()).collect();
+                (!lines.is_empty()).then(|| (field_heading(name), lines))
+            })
+            .collect()
+    }
+
+    /// Whether the title is empty or a placeholder like "No title"
+    pub fn has_placeholder_title(&self) -> bool {
+        ["", "no title", "untitled"].contains(&self.title.trim().to_lowercase().as_str())
+    }
+
     /// Check if the summary has any content
     pub fn is_empty(&self) -> bool {
         self.conclusion.is_empty()
@@ -46,3 +436,135 @@ impl Summary {
             && self.action_items.is_empty()
     }
 }
+
+/// "open_questions" as "Open questions"
+fn field_heading(name: &str) -> String {
+    let words = name.replace(['_', '-'], " ");
+    let mut chars = words.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A custom field value as one line of text: strings as they are, `null` as nothing
+fn field_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.trim().to_string(),
+        serde_json::Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_summaries_stored_before_topics_and_stance() {
+        let summary: Summary = serde_json::from_str(
+            r#"{"title": "T", "conclusion": "C", "key_points": [], "entities": [], "action_items": []}"#,
+        )
+        .unwrap();
+        assert!(summary.topics.is_empty());
+        assert_eq!(summary.sentiment, None);
+
+        let sentiment: Sentiment =
+            serde_json::from_str(r#"{"stance": "negative", "justification": "Too slow."}"#)
+                .unwrap();
+        assert_eq!(sentiment.to_string(), "Negative: Too slow.");
+    }
+
+    #[test]
+    fn reads_untyped_entities_and_groups_by_kind() {
+        let summary: Summary = serde_json::from_str(
+            r#"{"title": "T", "conclusion": "C", "key_points": [], "action_items": [],
+                "entities": ["Tokio", {"name": "Ada Lovelace", "kind": "person"},
+                             {"name": "Rust", "kind": "technology"}, {"name": "Ferris"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(summary.entities[0], Entity::from("Tokio"));
+        assert_eq!(summary.entities[1].kind, EntityKind::Person);
+        assert_eq!(
+            summary.entities_by_kind(),
+            [
+                (EntityKind::Person, vec!["Ada Lovelace"]),
+                (EntityKind::Technology, vec!["Rust"]),
+                (EntityKind::Other, vec!["Tokio", "Ferris"]),
+            ]
+        );
+        let stored = serde_json::to_value(&summary.entities[0]).unwrap();
+        assert_eq!(
+            stored,
+            serde_json::json!({"name": "Tokio", "kind": "other"})
+        );
+    }
+
+    #[test]
+    fn renders_custom_fields_generically() {
+        let mut summary = Summary::new("T".into(), "C".into(), vec![], vec![], vec![]);
+        summary.custom = serde_json::from_str(
+            r#"{"open_questions": ["Who pays?", ""], "risk_level": "high", "notes": null, "score": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            summary.custom_sections(),
+            [
+                ("Open questions".to_string(), vec!["Who pays?".to_string()]),
+                ("Risk level".to_string(), vec!["high".to_string()]),
+                ("Score".to_string(), vec!["3".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_markdown_sections_that_have_content() {
+        let summary = Summary::new(
+            "Async Rust".into(),
+            "Tokio, mostly.".into(),
+            vec!["Futures are lazy".into()],
+            vec![Entity::new("Tokio", EntityKind::Technology)],
+            vec![],
+        );
+        assert_eq!(
+            summary.to_markdown(),
+            "# Async Rust\n\nTokio, mostly.\n\n## Key points\n\n- Futures are lazy\n\n\
+             ## Entities\n\n- **Technologies**: Tokio\n"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_the_editable_form() {
+        let mut summary = Summary::new(
+            "T".into(),
+            "C".into(),
+            vec!["Kept".into(), "Hallucinated".into()],
+            vec!["Rust".into()],
+            vec![],
+        );
+        summary.sentiment = Some(Sentiment {
+            stance: Stance::Neutral,
+            justification: "Plain report.".into(),
+        });
+        let text = summary.to_editable().unwrap();
+        assert!(text.starts_with("# Correct the summary"));
+
+        let edited = Summary::from_editable(&text.replace("\"Hallucinated\",", "")).unwrap();
+        assert_eq!(edited.key_points, ["Kept"]);
+        assert_eq!(edited.sentiment, summary.sentiment);
+        assert!(matches!(
+            Summary::from_editable(&text.replace("\"C\"", "\"\"")),
+            Err(EditError::Invalid(_))
+        ));
+
+        summary.custom = BTreeMap::from([
+            ("audience".to_string(), serde_json::Value::Null),
+            ("risks".to_string(), serde_json::json!(["Lock-in", null])),
+        ]);
+        let edited = Summary::from_editable(&summary.to_editable().unwrap()).unwrap();
+        assert_eq!(
+            edited.custom,
+            BTreeMap::from([("risks".to_string(), serde_json::json!(["Lock-in"]))])
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Tag vocabulary maintenance (renaming, merging, usage statistics) and the
+//! auto-tagging rules applied when summaries are stored.
+//!
+//! Tags live on each [`StoredSummary`], so the maintenance functions walk the
+//! whole archive. The caller is responsible for re-indexing the records that
+//! come back changed.
+
+use crate::config::TagRule;
+use crate::storage::{normalise_tag, Storage, StorageError, StoredSummary};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Bytes of text looked at to detect its language
+const LANGUAGE_SAMPLE: usize = 4_096;
+
+/// A tag rule's regular expression for source URLs, compiled as the config is
+/// loaded so an invalid one is reported there
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UrlPattern(Regex);
+
+impl UrlPattern {
+    pub fn is_match(&self, url: &str) -> bool {
+        self.0.is_match(url)
+    }
+}
+
+impl PartialEq for UrlPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl fmt::Display for UrlPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.as_str())
+    }
+}
+
+impl TryFrom<String> for UrlPattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Regex::new(&pattern)
+            .map(Self)
+            .map_err(|e| format!("invalid url pattern in [[tag_rules]]: {}", e))
+    }
+}
+
+impl From<UrlPattern> for String {
+    fn from(pattern: UrlPattern) -> Self {
+        pattern.0.as_str().to_string()
+    }
+}
+
+/// How much a tag is used
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagStats {
+    pub tag: String,
+    /// Number of summaries with the tag
+    pub count: usize,
+    /// When the newest summary with the tag was created
+    pub last_used: DateTime<Utc>,
+}
+
+/// Usage of every tag in the archive, most used first
+pub fn tag_stats(storage: &Storage) -> Result<Vec<TagStats>, StorageError> {
+    let mut by_tag: BTreeMap<String, TagStats> = BTreeMap::new();
+    for stored in storage.iter() {
+        let stored = stored?;
+        for tag in &stored.tags {
+            let stats = by_tag.entry(tag.clone()).or_insert_with(|| TagStats {
+                tag: tag.clone(),
+                count: 0,
+                last_used: stored.created_at,
+            });
+            stats.count += 1;
+            stats.last_used = stats.last_used.max(stored.created_at);
+        }
+    }
+    let mut stats: Vec<TagStats> = by_tag.into_values().collect();
+    // Stable sort, so equally used tags stay alphabetical
+    stats.sort_by_key(|s| std::cmp::Reverse(s.count));
+    Ok(stats)
+}
+
+/// Replace the tags of `stored` with the comma-separated `list`, as typed in the TUI,
+/// returning whether they changed
+pub fn set_tags(stored: &mut StoredSummary, list: &str) -> bool {
+    let before = std::mem::take(&mut stored.tags);
+    for tag in list.split(',').map(normalise_tag).filter(|t| !t.is_empty()) {
+        stored.add_tag(&tag);
+    }
+    stored.tags != before
+}
+
+/// Whether any stored summary has `tag`
+pub fn tag_in_use(storage: &Storage, tag: &str) -> Result<bool, StorageError> {
+    for stored in storage.iter() {
+        if stored?.tags.iter().any(|t| t == tag) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Replace `from` with `into` on every summary tagged `from`, returning the records changed
+pub fn replace_tag(
+    storage: &Storage,
+    from: &str,
+    into: &str,
+) -> Result<Vec<StoredSummary>, StorageError> {
+    let mut changed = Vec::new();
+    for stored in storage.iter() {
+        let mut stored = stored?;
+        if stored.remove_tag(from) {
+            stored.add_tag(into);
+            storage.put(&stored)?;
+            changed.push(stored);
+        }
+    }
+    Ok(changed)
+}
+
+/// Add the tags of every rule that matches `stored`, returning the tags that were new
+pub fn apply_rules(stored: &mut StoredSummary, rules: &[TagRule]) -> Vec<String> {
+    let language = detect_language(stored);
+    let mut added = Vec::new();
+    for rule in rules {
+        if !rule_matches(rule, stored, language) {
+            continue;
+        }
+        for tag in &rule.tags {
+            let tag = normalise_tag(tag);
+            if !tag.is_empty() && stored.add_tag(&tag) {
+                added.push(tag);
+            }
+        }
+    }
+    added
+}
+
+/// Whether every condition of `rule` holds for `stored`
+fn rule_matches(rule: &TagRule, stored: &StoredSummary, language: Option<whatlang::Lang>) -> bool {
+    if rule.domain.is_none()
+        && rule.url.is_none()
+        && rule.language.is_none()
+        && rule.entity.is_none()
+    {
+        return false;
+    }
+    if let Some(ref domain) = rule.domain {
+        let domain = domain.trim_start_matches("www.").to_lowercase();
+        let matches = stored.domain().is_some_and(|host| {
+            let host = host.to_lowercase();
+            host == domain || host.ends_with(&format!(".{}", domain))
+        });
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(ref pattern) = rule.url {
+        if !pattern.is_match(&stored.url) {
+            return false;
+        }
+    }
+    if let Some(ref wanted) = rule.language {
+        let matches = language.is_some_and(|lang| {
+            lang.code().eq_ignore_ascii_case(wanted) || lang.eng_name().eq_ignore_ascii_case(wanted)
+        });
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(ref entity) = rule.entity {
+        let matches = stored
+            .summary
+            .entities
+            .iter()
+            .any(|e| e.name.eq_ignore_ascii_case(entity));
+        if !matches {
+            return false;
+        }
+    }
+    true
+}
+
+/// Language of the source text (or the summary, if the text wasn't kept), when it's clear
+fn detect_language(stored: &StoredSummary) -> Option<whatlang::Lang> {
+    let text = match stored.raw_text {
+        Some(ref raw) => raw.clone(),
+        None => {
+            let summary = &stored.summary;
+            format!("{} {}", summary.conclusion, summary.key_points.join(" "))
+        }
+    };
+    let mut end = text.len().min(LANGUAGE_SAMPLE);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    whatlang::detect(&text[..end])
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Summary;
+
+    #[test]
+    fn merges_tags_and_counts_usage() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::open(dir.path()).unwrap();
+        for (url, tags) in [
+            ("https://a.example", &["rust", "rustlang"][..]),
+            ("https://b.example", &["rustlang"]),
+            ("https://c.example", &["wasm"]),
+        ] {
+            let summary = Summary::new(String::new(), String::new(), vec![], vec![], vec![]);
+            let mut stored = StoredSummary::new(url.to_string(), summary);
+            for tag in tags {
+                stored.add_tag(tag);
+            }
+            storage.put(&stored).unwrap();
+        }
+
+        let changed = replace_tag(&storage, "rustlang", "rust").unwrap();
+        assert_eq!(changed.len(), 2);
+        assert!(!tag_in_use(&storage, "rustlang").unwrap());
+
+        let stats = tag_stats(&storage).unwrap();
+        let counts: Vec<(&str, usize)> = stats.iter().map(|s| (s.tag.as_str(), s.count)).collect();
+        assert_eq!(counts, [("rust", 2), ("wasm", 1)]);
+    }
+
+    #[test]
+    fn sets_tags_from_a_typed_list() {
+        let summary = Summary::new(String::new(), String::new(), vec![], vec![], vec![]);
+        let mut stored = StoredSummary::new("https://a.example".to_string(), summary);
+        stored.add_tag("old");
+        assert!(set_tags(&mut stored, "Rust, machine learning,, rust"));
+        assert_eq!(stored.tags, ["machine-learning", "rust"]);
+        assert!(!set_tags(&mut stored, "rust,machine-learning"));
+    }
+
+    #[test]
+    fn applies_matching_rules() {
+        let summary = Summary::new(
+            String::new(),
+            String::new(),
+            vec![],
+            vec!["Kubernetes".into()],
+            vec![],
+        );
+        let mut stored = StoredSummary::new("https://www.lwn.net/Articles/1".to_string(), summary)
+            .with_raw_text("Der Kernel bekommt neue Treiber, und die Entwickler sind sich einig, dass die Änderungen gut sind.");
+        let rules = [
+            TagRule {
+                tags: vec!["Linux".to_string()],
+                domain: Some("lwn.net".to_string()),
+                ..TagRule::default()
+            },
+            TagRule {
+                tags: vec!["k8s".to_string()],
+                entity: Some("kubernetes".to_string()),
+                url: Some(UrlPattern::try_from(r"/Articles/\d+$".to_string()).unwrap()),
+                ..TagRule::default()
+            },
+            TagRule {
+                tags: vec!["german".to_string()],
+                language: Some("deu".to_string()),
+                ..TagRule::default()
+            },
+            TagRule {
+                tags: vec!["never".to_string()],
+                ..TagRule::default()
+            },
+            TagRule {
+                tags: vec!["no".to_string()],
+                domain: Some("wn.net".to_string()),
+                ..TagRule::default()
+            },
+        ];
+        let added = apply_rules(&mut stored, &rules);
+        assert_eq!(added, ["linux", "k8s", "german"]);
+        assert!(apply_rules(&mut stored, &rules).is_empty());
+    }
+
+    #[test]
+    fn rejects_an_invalid_url_pattern_when_the_rule_is_read() {
+        let rule: TagRule = toml::from_str("tags = [\"rust\"]\nurl = \"^https://\"").unwrap();
+        assert_eq!(rule.url.unwrap().to_string(), "^https://");
+        let invalid = toml::from_str::<TagRule>("tags = [\"rust\"]\nurl = \"(\"").unwrap_err();
+        assert!(invalid.to_string().contains("invalid url pattern"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Pushing action items to external task managers.
+//!
+//! Todoist and GitHub Issues are reached over their REST APIs with tokens from
+//! `TODOIST_API_TOKEN` and `GITHUB_TOKEN`; Taskwarrior is driven through its
+//! `task` command. Every pushed item is recorded on the stored summary, so it
+//! isn't pushed twice by accident.
+
+use crate::config::Config;
+use crate::storage::StoredSummary;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+use std::str::FromStr;
+use thiserror::Error;
+
+const TODOIST_TASKS_URL: &str = "https://api.todoist.com/api/v1/tasks";
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+#[derive(Error, Debug)]
+pub enum TaskError {
+    #[error("request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("{backend} rejected the task ({status}): {body}")]
+    Rejected {
+        backend: TaskBackend,
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    #[error("failed to run taskwarrior: {0}")]
+    CommandError(#[from] std::io::Error),
+    #[error("taskwarrior failed: {0}")]
+    TaskwarriorFailed(String),
+    #[error("{0} is not configured: {1}")]
+    NotConfigured(TaskBackend, &'static str),
+}
+
+/// A task manager that action items can be pushed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskBackend {
+    Todoist,
+    Github,
+    Taskwarrior,
+}
+
+impl fmt::Display for TaskBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Todoist => write!(f, "todoist"),
+            Self::Github => write!(f, "github"),
+            Self::Taskwarrior => write!(f, "taskwarrior"),
+        }
+    }
+}
+
+impl FromStr for TaskBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "todoist" => Ok(Self::Todoist),
+            "github" => Ok(Self::Github),
+            "taskwarrior" | "task" => Ok(Self::Taskwarrior),
+            other => Err(format!(
+                "unknown task backend '{other}' (expected todoist, github or taskwarrior)"
+            )),
+        }
+    }
+}
+
+/// Push one action item from `stored`, returning a reference to the new task
+/// (its URL, or its ID when the backend has no web page for it)
+pub async fn push(
+    backend: TaskBackend,
+    item: &str,
+    stored: &StoredSummary,
+    config: &Config,
+) -> Result<String, TaskError> {
+    let notes = format!("From \"{}\"\n{}", stored.summary.title, stored.url);
+    let tasks = &config.tasks;
+    match backend {
+        TaskBackend::Todoist => {
+            let token = config
+                .api
+                .todoist_token
+                .as_deref()
+                .ok_or(TaskError::NotConfigured(backend, "set TODOIST_API_TOKEN"))?;
+            let mut body = serde_json::json!({
+                "content": item,
+                "description": notes,
+                "labels": tasks.todoist.labels,
+            });
+            if let Some(ref project_id) = tasks.todoist.project_id {
+                body["project_id"] = project_id.clone().into();
+            }
+            let response = post_json(backend, TODOIST_TASKS_URL, token, &body).await?;
+            Ok(response["url"]
+                .as_str()
+                .or(response["id"].as_str())
+                .unwrap_or_default()
+                .to_string())
+        }
+        TaskBackend::Github => {
+            let token = config
+                .api
+                .github_token
+                .as_deref()
+                .ok_or(TaskError::NotConfigured(backend, "set GITHUB_TOKEN"))?;
+            let repo = tasks
+                .github
+                .repo
+                .as_deref()
+                .ok_or(TaskError::NotConfigured(
+                    backend,
+                    "set repo under [tasks.github]",
+                ))?;
+            let url = format!("{}/repos/{}/issues", GITHUB_API_URL, repo);
+            let body = serde_json::json!({
+                "title": item,
+                "body": notes,
+                "labels": tasks.github.labels,
+            });
+            let response = post_json(backend, &url, token, &body).await?;
+            Ok(response["html_url"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string())
+        }
+        TaskBackend::Taskwarrior => {
+            let mut command = Command::new("task");
+            command.arg("rc.confirmation=off").arg("add");
+            if let Some(ref project) = tasks.taskwarrior.project {
+                command.arg(format!("project:{}", project));
+            }
+            for tag in &tasks.taskwarrior.tags {
+                command.arg(format!("+{}", tag));
+            }
+            // Everything after `--` is the description, even if it looks like a modifier
+            command.arg("--").arg(item);
+            let output = command.output()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(TaskError::TaskwarriorFailed(stderr.trim().to_string()));
+            }
+            Ok(taskwarrior_id(&stdout).unwrap_or_else(|| stdout.trim().to_string()))
+        }
+    }
+}
+
+/// POST a JSON body with a bearer token, returning the JSON response
+async fn post_json(
+    backend: TaskBackend,
+    url: &str,
+    token: &str,
+    body: &serde_json::Value,
+) -> Result<serde_json::Value, TaskError> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .bearer_auth(token)
+        // GitHub rejects requests without a user agent
+        .header(reqwest::header::USER_AGENT, "summera")
+        .json(body)
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(TaskError::Rejected {
+            backend,
+            status,
+            body: response.text().await.unwrap_or_default(),
+        });
+    }
+    Ok(response.json().await?)
+}
+
+/// The task ID in Taskwarrior's "Created task 12." message
+fn taskwarrior_id(output: &str) -> Option<String> {
+    let rest = output.split("Created task ").nth(1)?;
+    let id: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    (!id.is_empty()).then(|| format!("task {}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_backends_and_taskwarrior_output() {
+        assert_eq!("GitHub".parse(), Ok(TaskBackend::Github));
+        assert_eq!("task".parse(), Ok(TaskBackend::Taskwarrior));
+        assert!("jira".parse::<TaskBackend>().is_err());
+
+        assert_eq!(
+            taskwarrior_id("Created task 12.\n").as_deref(),
+            Some("task 12")
+        );
+        assert_eq!(taskwarrior_id("Nothing to do."), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! `{{field}}` templates for webhook bodies and `--template` output.
+//!
+//! A template is plain text with `{{field}}` placeholders. In webhook bodies
+//! `{{field}}` is JSON-escaped so it can sit inside a quoted string, and
+//! `{{{field}}}` is inserted as-is. In terminal output both are inserted as-is
+//! and lists are written one item per line. Unknown fields render as nothing.
+//! Nested fields are named with dots, as in `{{custom.difficulty}}`.
+
+use crate::storage::StoredSummary;
+use chrono::Local;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// `{{field}}` or `{{{field}}}`, where the field may be a dotted path
+const PLACEHOLDER: &str = r"\{\{\{\s*(\w+(?:\.\w+)*)\s*\}\}\}|\{\{\s*(\w+(?:\.\w+)*)\s*\}\}";
+
+/// The fields of a stored summary that a `--template` can use
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryFields<'a> {
+    pub url: &'a str,
+    pub title: &'a str,
+    pub conclusion: &'a str,
+    pub key_points: &'a [String],
+    /// Entity names, without their kinds
+    pub entities: Vec<&'a str>,
+    pub action_items: &'a [String],
+    pub topics: &'a [String],
+    pub tags: &'a [String],
+    pub notes: Option<&'a str>,
+    /// The local day the summary was made, e.g. "2026-10-15"
+    pub date: String,
+    pub provider: Option<&'a str>,
+    pub language: Option<&'a str>,
+    pub starred: bool,
+    pub unread: bool,
+    /// Custom summary fields declared under `[agent.fields]`, by name, so they're
+    /// written `{{custom.<name>}}` and can't shadow the fields above
+    pub custom: &'a BTreeMap<String, Value>,
+}
+
+impl<'a> SummaryFields<'a> {
+    pub fn new(stored: &'a StoredSummary) -> Self {
+        let summary = &stored.summary;
+        Self {
+            url: &stored.url,
+            title: &summary.title,
+            conclusion: &summary.conclusion,
+            key_points: &summary.key_points,
+            entities: summary.entities.iter().map(|e| e.name.as_str()).collect(),
+            action_items: &summary.action_items,
+            topics: &summary.topics,
+            tags: &stored.tags,
+            notes: stored.notes.as_deref(),
+            date: stored
+                .created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d")
+                .to_string(),
+            provider: stored.provider.as_deref(),
+            language: stored.language.as_deref(),
+            starred: stored.starred,
+            unread: stored.unread,
+            custom: &summary.custom,
+        }
+    }
+}
+
+/// Render a template for terminal output, with values inserted as-is
+pub fn render(template: &str, payload: &impl Serialize) -> String {
+    substitute(template, payload, |value, _| match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(item) => item.clone(),
+                item => item.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        value => value.to_string(),
+    })
+}
+
+/// Render a template into a JSON body: `{{field}}` JSON-escaped, `{{{field}}}` as-is
+pub fn render_json(template: &str, payload: &impl Serialize) -> String {
+    substitute(template, payload, |value, raw| match value {
+        Value::String(value) if raw => value.clone(),
+        Value::String(value) => {
+            let quoted = serde_json::to_string(value).unwrap_or_default();
+            quoted[1..quoted.len() - 1].to_string()
+        }
+        Value::Null => String::new(),
+        value => value.to_string(),
+    })
+}
+
+/// Render a template for terminal output with the fields of a stored summary
+pub fn render_summary(template: &str, stored: &StoredSummary) -> String {
+    render(template, &SummaryFields::new(stored))
+}
+
+/// The template called `name` under `[templates]` in the config, or else `name`
+/// itself as an inline template, with `\n` and `\t` written out
+pub fn resolve(name: &str, named: &BTreeMap<String, String>) -> String {
+    match named.get(name) {
+        Some(template) => template.clone(),
+        None => name.replace("\\n", "\n").replace("\\t", "\t"),
+    }
+}
+
+/// Replace each placeholder with `format(value, raw)`, where `raw` means `{{{...}}}`
+fn substitute(
+    template: &str,
+    payload: &impl Serialize,
+    format: impl Fn(&Value, bool) -> String,
+) -> String {
+    let fields = serde_json::to_value(payload).unwrap_or_default();
+    let placeholder = Regex::new(PLACEHOLDER).expect("placeholder pattern is valid");
+    placeholder
+        .replace_all(template, |caps: &Captures| {
+            let (name, raw) = match (caps.get(1), caps.get(2)) {
+                (Some(name), _) => (name.as_str(), true),
+                (_, Some(name)) => (name.as_str(), false),
+                _ => return String::new(),
+            };
+            name.split('.')
+                .try_fold(&fields, |value, key| value.get(key))
+                .map(|value| format(value, raw))
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::Summary;
+
+    #[test]
+    fn renders_summaries_with_lists_one_item_per_line() {
+        let mut summary = Summary::new(
+            "Async Rust".into(),
+            "It \"works\".".into(),
+            vec!["Futures are lazy".into(), "Pin matters".into()],
+            vec![],
+            vec![],
+        );
+        summary
+            .custom
+            .insert("difficulty".to_string(), Value::from(3));
+        summary
+            .custom
+            .insert("title".to_string(), Value::from("Shadowed"));
+        let stored = StoredSummary::new("https://a.example".to_string(), summary);
+
+        let named =
+            BTreeMap::from([("brief".to_string(), "{{title}}: {{conclusion}}".to_string())]);
+        let brief = resolve("brief", &named);
+        assert_eq!(render_summary(&brief, &stored), "Async Rust: It \"works\".");
+
+        let inline = resolve(
+            "# {{title}}\\n{{key_points}}\\n{{custom.difficulty}} {{difficulty}} {{missing}}",
+            &named,
+        );
+        assert_eq!(
+            render_summary(&inline, &stored),
+            "# Async Rust\nFutures are lazy\nPin matters\n3  "
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Colour themes for the TUI.
+//!
+//! The default palette is myon/ilseon inspired. `high-contrast` swaps the
+//! green/red accents for the Okabe-Ito blue/orange pair, which stays
+//! distinguishable with the common forms of colour blindness. When colour is
+//! disabled (`NO_COLOR` or `--no-color`) the monochrome theme is used and
+//! emphasis falls back to bold and reversed text.
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+/// Selectable theme presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    /// The original dark palette with green/red accents
+    #[default]
+    Default,
+    /// Colour-blind safe blue/orange accents on black
+    HighContrast,
+    /// Terminal default colours only
+    Mono,
+}
+
+/// Resolved colours used when drawing the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub bg_deep: Color,
+    pub fg_primary: Color,
+    pub fg_muted: Color,
+    pub border_active: Color,
+    pub border_quiet: Color,
+    pub accent_urgent: Color,
+    /// Whether colours are disabled and emphasis relies on modifiers
+    pub monochrome: bool,
+}
+
+impl Theme {
+    /// Build the theme for a preset
+    pub fn from_preset(preset: ThemePreset) -> Self {
+        match preset {
+            ThemePreset::Default => Self {
+                bg_deep: Color::Rgb(54, 52, 58),
+                fg_primary: Color::Rgb(224, 224, 224),
+                fg_muted: Color::Rgb(176, 176, 176),
+                border_active: Color::Rgb(90, 155, 128),
+                border_quiet: Color::Rgb(31, 31, 31),
+                accent_urgent: Color::Rgb(179, 95, 95),
+                monochrome: false,
+            },
+            ThemePreset::HighContrast => Self {
+                bg_deep: Color::Rgb(0, 0, 0),
+                fg_primary: Color::Rgb(255, 255, 255),
+                fg_muted: Color::Rgb(200, 200, 200),
+                border_active: Color::Rgb(86, 180, 233),
+                border_quiet: Color::Rgb(110, 110, 110),
+                accent_urgent: Color::Rgb(230, 159, 0),
+                monochrome: false,
+            },
+            ThemePreset::Mono => Self {
+                bg_deep: Color::Reset,
+                fg_primary: Color::Reset,
+                fg_muted: Color::Reset,
+                border_active: Color::Reset,
+                border_quiet: Color::Reset,
+                accent_urgent: Color::Reset,
+                monochrome: true,
+            },
+        }
+    }
+
+    /// Resolve the configured preset, forcing monochrome when colour is disabled
+    pub fn resolve(preset: ThemePreset) -> Self {
+        if colored::control::SHOULD_COLORIZE.should_colorize() {
+            Self::from_preset(preset)
+        } else {
+            Self::from_preset(ThemePreset::Mono)
+        }
+    }
+
+    /// Style for the selected row of a list
+    pub fn highlight(&self) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        if self.monochrome {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style.fg(self.bg_deep).bg(self.border_active)
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::from_preset(ThemePreset::Default)
+    }
+}
@@ -0,0 +1,163 @@
+//! Grouping of summaries into date sections ("Today", "Yesterday", "Last week", ...).
+//!
+//! Used for the section headers in the TUI list and `summera list`.
+
+use crate::StoredSummary;
+use chrono::{Datelike, Local, NaiveDate};
+use std::ops::Range;
+
+/// A run of consecutive summaries that fall under the same date label
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayGroup {
+    pub label: String,
+    /// Indices into the grouped slice
+    pub range: Range<usize>,
+}
+
+/// Label for the section a day falls under, relative to `today`
+pub fn day_label(day: NaiveDate, today: NaiveDate) -> String {
+    let age = (today - day).num_days();
+    let week = |d: NaiveDate| d.iso_week();
+    let last_week = today - chrono::Duration::weeks(1);
+    if age <= 0 {
+        "Today".to_string()
+    } else if age == 1 {
+        "Yesterday".to_string()
+    } else if week(day) == week(today) {
+        "This week".to_string()
+    } else if week(day) == week(last_week) {
+        "Last week".to_string()
+    } else if day.year() == today.year() {
+        day.format("%B").to_string()
+    } else {
+        day.format("%B %Y").to_string()
+    }
+}
+
+/// Label of the group holding starred summaries above the dates
+pub const STARRED: &str = "Starred";
+
+/// Move starred summaries to the front, keeping the order within each part
+pub fn pin_starred(summaries: &mut [StoredSummary]) {
+    summaries.sort_by_key(|stored| !stored.starred);
+}
+
+/// Group summaries like [`group_by_day`], except that starred ones at the front
+/// (see [`pin_starred`]) get a group of their own
+pub fn group_pinned(summaries: &[StoredSummary], today: NaiveDate) -> Vec<DayGroup> {
+    let starred = summaries.iter().take_while(|s| s.starred).count();
+    let pinned = (starred > 0).then(|| DayGroup {
+        label: STARRED.to_string(),
+        range: 0..starred,
+    });
+    let dated = group_by_day(&summaries[starred..], today)
+        .into_iter()
+        .map(|group| DayGroup {
+            range: group.range.start + starred..group.range.end + starred,
+            ..group
+        });
+    pinned.into_iter().chain(dated).collect()
+}
+
+/// Group summaries, already sorted newest first, by the local day they were created
+pub fn group_by_day(summaries: &[StoredSummary], today: NaiveDate) -> Vec<DayGroup> {
+    let mut groups: Vec<DayGroup> = Vec::new();
+    for (i, stored) in summaries.iter().enumerate() {
+        let day = stored.created_at.with_timezone(&Local).date_naive();
+        let label = day_label(day, today);
+        match groups.last_mut() {
+            Some(group) if group.label == label => group.range.end = i + 1,
+            _ => groups.push(DayGroup {
+                label,
+                range: i..i + 1,
+            }),
+        }
+    }
+    groups
+}
+
+/// Parse an age like "30d", "6w", "3m" or "1y" (a month is 30 days, a year 365)
+pub fn parse_age(age: &str) -> Result<chrono::Duration, String> {
+    let age = age.trim();
+    let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (count, unit) = age.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("invalid age '{}'; expected e.g. 30d, 6w, 3m or 1y", age))?;
+    let too_long = || format!("age '{}' is too long", age);
+    let days = match unit {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        "y" => 365,
+        _ => {
+            return Err(format!(
+                "invalid age '{}'; expected e.g. 30d, 6w, 3m or 1y",
+                age
+            ))
+        }
+    };
+    count
+        .checked_mul(days)
+        .and_then(chrono::Duration::try_days)
+        .ok_or_else(too_long)
+}
+
+/// Today's date in the local timezone
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_days_relative_to_today() {
+        // A Thursday
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let label = |y, m, d| day_label(NaiveDate::from_ymd_opt(y, m, d).unwrap(), today);
+        assert_eq!(label(2026, 10, 15), "Today");
+        assert_eq!(label(2026, 10, 14), "Yesterday");
+        assert_eq!(label(2026, 10, 12), "This week");
+        assert_eq!(label(2026, 10, 11), "Last week");
+        assert_eq!(label(2026, 10, 5), "Last week");
+        assert_eq!(label(2026, 10, 1), "October");
+        assert_eq!(label(2025, 10, 1), "October 2025");
+    }
+
+    #[test]
+    fn parses_ages_in_days_weeks_months_and_years() {
+        assert_eq!(parse_age("30d"), Ok(chrono::Duration::days(30)));
+        assert_eq!(parse_age("2w"), Ok(chrono::Duration::days(14)));
+        assert_eq!(parse_age("3m"), Ok(chrono::Duration::days(90)));
+        assert_eq!(parse_age("1y"), Ok(chrono::Duration::days(365)));
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("5h").is_err());
+        assert!(parse_age("9223372036854775807y").is_err());
+    }
+
+    #[test]
+    fn pins_starred_summaries_above_the_dates() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let mut summaries: Vec<StoredSummary> = [false, true, false]
+            .into_iter()
+            .enumerate()
+            .map(|(i, starred)| {
+                let summary =
+                    crate::Summary::new(i.to_string(), "C".into(), vec![], vec![], vec![]);
+                let mut stored = StoredSummary::new(format!("https://{}.example", i), summary);
+                stored.starred = starred;
+                stored
+            })
+            .collect();
+        pin_starred(&mut summaries);
+        let titles: Vec<&str> = summaries.iter().map(|s| s.summary.title.as_str()).collect();
+        assert_eq!(titles, ["1", "0", "2"]);
+
+        let groups = group_pinned(&summaries, today);
+        assert_eq!(groups[0].label, STARRED);
+        assert_eq!(groups[0].range, 0..1);
+        assert_eq!(groups[1].range, 1..3);
+    }
+}
@@ -0,0 +1,103 @@
+//! Token counting and context window budgeting.
+//!
+//! Counts use OpenAI's `o200k_base` tokenizer. Other providers tokenise
+//! differently, so for Gemini and Ollama models the count is an estimate that is
+//! usually within 10-20%, which is close enough to decide when to chunk.
+
+use tiktoken_rs::o200k_base_singleton;
+
+/// Tokens kept free in the context window for the model's response
+pub const RESPONSE_RESERVE: usize = 8_192;
+
+/// Context window assumed for models that aren't in the table below
+const DEFAULT_CONTEXT_LIMIT: usize = 32_768;
+
+/// Context windows of known models, matched by prefix (longest prefixes first)
+const CONTEXT_LIMITS: &[(&str, usize)] = &[
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini", 1_048_576),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-5", 400_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("llama3", 128_000),
+    ("mistral", 32_768),
+    ("qwen", 32_768),
+];
+
+/// Count the tokens in `text`
+pub fn count_tokens(text: &str) -> usize {
+    o200k_base_singleton().encode_ordinary(text).len()
+}
+
+/// Built-in context window for a model, in tokens
+pub fn default_context_limit(model: &str) -> usize {
+    CONTEXT_LIMITS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(DEFAULT_CONTEXT_LIMIT, |(_, limit)| *limit)
+}
+
+/// How a prompt compares to the model's context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    /// Tokens in the prompt
+    pub tokens: usize,
+    /// The model's context window
+    pub limit: usize,
+}
+
+impl Budget {
+    /// Tokens available for the prompt once the response reserve is set aside
+    pub fn available(&self) -> usize {
+        self.limit.saturating_sub(RESPONSE_RESERVE)
+    }
+
+    /// Whether the prompt fits in one request
+    pub fn fits(&self) -> bool {
+        self.tokens <= self.available()
+    }
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "~{} tokens", self.tokens)?;
+        if !self.fits() {
+            write!(f, ", over the {}-token context window", self.limit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_tokens() {
+        assert_eq!(count_tokens(""), 0);
+        assert!(count_tokens("The quick brown fox jumps over the lazy dog.") < 15);
+    }
+
+    #[test]
+    fn looks_up_limits_by_prefix() {
+        assert_eq!(default_context_limit("gemini-2.0-flash"), 1_048_576);
+        assert_eq!(default_context_limit("gpt-4o-mini"), 128_000);
+        assert_eq!(default_context_limit("gpt-4"), 8_192);
+        assert_eq!(
+            default_context_limit("my-local-model"),
+            DEFAULT_CONTEXT_LIMIT
+        );
+
+        let budget = Budget {
+            tokens: 10_000,
+            limit: 16_385,
+        };
+        assert!(!budget.fits());
+    }
+}
@@ -2,29 +2,57 @@
 //!
 //! Component-based pattern for high responsiveness.
 
-use crate::{agent, reader, scraper, Config, Storage, StoredSummary, Summary};
+use crate::activity::{self, Action};
+use crate::collections::Collection;
+use crate::config::{ConfigError, PersonaPreset, SummaryLength, UiConfig, Workspace};
+use crate::icons::IconStyle;
+use crate::markers::Markers;
+use crate::notify::NotifyEvent;
+use crate::pipeline::CancellationToken;
+use crate::random::{self, RandomFilter};
+use crate::reload::ConfigWatcher;
+use crate::search::{SavedSearch, SearchOptions};
+use crate::storage::QaExchange;
+use crate::theme::Theme;
+use crate::timeline::{self, DayGroup};
+use crate::{
+    extractive, notify, pipeline, queue, reader, search, snooze, tags, Config, EntityKind, Storage,
+    StoredSummary, Summary,
+};
+use chrono::{Local, Utc};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    cursor::Show,
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
+use std::collections::{HashSet, VecDeque};
 use std::io;
-
-// Colour scheme (myon/ilseon inspired)
-const BG_DEEP: Color = Color::Rgb(54, 52, 58);
-const FG_PRIMARY: Color = Color::Rgb(224, 224, 224);
-const FG_MUTED: Color = Color::Rgb(176, 176, 176);
-const BORDER_ACTIVE: Color = Color::Rgb(90, 155, 128);
-const BORDER_QUIET: Color = Color::Rgb(31, 31, 31);
-const ACCENT_URGENT: Color = Color::Rgb(179, 95, 95);
+use std::time::Instant;
+use tokio::sync::mpsc::{self, error::TryRecvError, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// Most summaries the 's' similar filter shows
+const SIMILAR_LIMIT: usize = 10;
+
+/// How long 'z' keeps a pending summary out of review
+const SNOOZE_DAYS: i64 = 1;
+
+/// Key binding hints shown in the status bar
+fn help_status(markers: &Markers) -> String {
+    format!(
+        "'o' open URL/file, 'f' search, 'a' ask, 'd' same domain, 'T' same tag, 'S' similar, 'e' edit, 'n' notes, 't' tags, 's' star, 'u' unread, 'z' snooze, 'D' delete, 'Q' queue, 'R' random, 'r' review, 'l' log, 'w' workspaces, {} navigate, Tab switch panes, 'q' quit",
+        markers.arrows
+    )
+}
 
 /// Application state
 #[derive(Debug, Clone, PartialEq)]
@@ -35,6 +63,20 @@ enum AppState {
     UrlInput,
     /// Search input dialogue
     SearchInput,
+    /// Question about the selected summary
+    AskInput,
+    /// Editing the selected summary's tags
+    TagInput,
+    /// When the selected summary should come back from a snooze
+    SnoozeInput,
+    /// Workspace switcher popup
+    WorkspaceSwitcher,
+    /// Activity log popup
+    Activity,
+    /// The URL entered is already stored, under this URL and title
+    Duplicate { url: String, title: String },
+    /// Asking before deleting the summary with this URL and title
+    ConfirmDelete { url: String, title: String },
     /// Loading content
     Loading,
     /// Error state
@@ -46,6 +88,101 @@ enum AppState {
 enum FocusedPane {
     List,
     Detail,
+    /// Saved searches (smart collections) in the sidebar
+    Saved,
+    /// Collections in the sidebar
+    Collections,
+}
+
+/// A quick filter narrowing the list to summaries like the selected one
+#[derive(Debug, Clone, PartialEq)]
+enum QuickFilter {
+    /// Summaries from the same web domain
+    Domain(String),
+    /// Summaries with this tag
+    Tag(String),
+    /// Summaries most similar to the one with this title
+    Similar { title: String, urls: Vec<String> },
+    /// Pending summaries waiting to be kept, snoozed or discarded
+    Review,
+    /// Summaries in a collection
+    Collection { name: String, urls: Vec<String> },
+    /// Unread summaries, in reading queue order
+    Queue,
+}
+
+impl QuickFilter {
+    fn matches(&self, stored: &StoredSummary) -> bool {
+        match self {
+            QuickFilter::Domain(domain) => stored.domain() == Some(domain.as_str()),
+            QuickFilter::Tag(tag) => stored.tags.contains(tag),
+            QuickFilter::Similar { urls, .. } => urls.contains(&stored.url),
+            QuickFilter::Review => stored.awaits_review(Utc::now()),
+            QuickFilter::Collection { urls, .. } => urls.contains(&stored.url),
+            QuickFilter::Queue => stored.in_queue(Utc::now()),
+        }
+    }
+
+    /// Breadcrumb text for the list title
+    fn label(&self) -> String {
+        match self {
+            QuickFilter::Domain(domain) => format!("domain: {}", domain),
+            QuickFilter::Tag(tag) => format!("tag: #{}", tag),
+            QuickFilter::Similar { title, .. } => format!("similar to: {}", title),
+            QuickFilter::Review => "review".to_string(),
+            QuickFilter::Collection { name, .. } => format!("collection: {}", name),
+            QuickFilter::Queue => "reading queue".to_string(),
+        }
+    }
+}
+
+/// What to do with a pending summary in review
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verdict {
+    /// Move it into the library
+    Keep,
+    /// Ask again in [`SNOOZE_DAYS`]
+    Snooze,
+    /// Delete it
+    Discard,
+}
+
+/// Sent from a running fetch-and-summarise job to the UI
+#[derive(Debug)]
+enum JobEvent {
+    /// A new stage started
+    Stage(String),
+    /// The job finished with a summary ready to save, or an error message
+    Done(Result<Box<StoredSummary>, String>),
+}
+
+/// A fetch-and-summarise job running in a background task
+struct Job {
+    /// Progress and the result, sent by the task
+    events: UnboundedReceiver<JobEvent>,
+    /// Cancelled to stop the task, which then writes nothing
+    cancel: CancellationToken,
+    /// Stages started so far, oldest first
+    stages: Vec<String>,
+    started: Instant,
+    /// Config the job was started with, used to save the result
+    config: Config,
+}
+
+/// A question being answered in a background task
+struct AskJob {
+    question: String,
+    /// The record with the answer added, saved by the task, or an error message
+    handle: JoinHandle<Result<StoredSummary, String>>,
+}
+
+/// A row in the summary list: a date group header or a summary
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListRow {
+    /// Index into `App::groups`
+    Header(usize),
+    /// Index into `App::stored_summaries`
+    Entry(usize),
 }
 
 /// The main TUI application
@@ -54,19 +191,55 @@ pub struct App {
     state: AppState,
     /// URL input buffer
     url_input: String,
+    /// Why the URL input can't be summarised, shown in the dialogue
+    url_error: Option<String>,
+    /// Sources pasted together, waiting to be summarised one after another
+    url_queue: VecDeque<String>,
     /// Search input buffer
     search_input: String,
+    /// Question input buffer
+    ask_input: String,
+    /// Tag list input buffer, comma-separated
+    tag_input: String,
+    /// Snooze end input buffer, e.g. "1w" or "2026-11-01"
+    snooze_input: String,
     /// Current summary being displayed
     summary: Option<Summary>,
     /// Source URL of the current summary
     source_url: Option<String>,
+    /// Whether the current summary was written offline, without an LLM
+    extractive: bool,
+    /// Whether the current summary is a lite capture, with only a title and description
+    lite: bool,
+    /// Source length, reading time and compression of the current summary
+    reading_stats: Option<String>,
+    /// Self-critique score of the current summary, e.g. "Quality 4/5 (...)"
+    critique: Option<String>,
+    /// Tags of the current summary
+    tags: Vec<String>,
+    /// Whether the current summary was corrected by hand
+    edited: bool,
+    /// Set by 'e' so the run loop can hand the terminal to $EDITOR
+    edit_requested: bool,
+    /// Set by 'n' to edit the notes in $EDITOR the same way
+    notes_requested: bool,
+    /// Notes written on the current summary
+    notes: Option<String>,
+    /// Questions asked about the current summary, oldest first
+    qa_history: Vec<QaExchange>,
     /// Whether the app should quit
     should_quit: bool,
     /// Status message
     status: String,
     /// List of stored summaries
     stored_summaries: Vec<StoredSummary>,
-    /// List selection state
+    /// Date groups of `stored_summaries`
+    groups: Vec<DayGroup>,
+    /// Rows shown in the list, skipping the entries of collapsed groups
+    rows: Vec<ListRow>,
+    /// Labels of the collapsed date groups
+    collapsed_groups: HashSet<String>,
+    /// List selection state, indexing `rows`
     list_state: ListState,
     /// Which pane is focused
     focused_pane: FocusedPane,
@@ -76,6 +249,48 @@ pub struct App {
     is_search_results: bool,
     /// Current search query (for display)
     current_search_query: String,
+    /// Saved searches shown as smart collections in the sidebar
+    saved_searches: Vec<SavedSearch>,
+    /// Sidebar selection state
+    saved_state: ListState,
+    /// The smart collection currently applied to the list, if any
+    active_saved: Option<SavedSearch>,
+    /// Collections shown in the sidebar
+    collections: Vec<Collection>,
+    /// Collection sidebar selection state
+    collection_state: ListState,
+    /// URLs in reading queue order, as last saved
+    queue_order: Vec<String>,
+    /// Quick filter applied on top of the list or search results, if any
+    quick_filter: Option<QuickFilter>,
+    /// Whether starred summaries are listed first, from `ui.starred_first`
+    starred_first: bool,
+    /// Colours used for drawing
+    theme: Theme,
+    /// Section markers and glyphs used for drawing
+    markers: Markers,
+    /// Whether `--ascii` was given, which the config can't turn off
+    ascii: bool,
+    /// Source icons shown before list entries
+    icons: IconStyle,
+    /// Workspace switched to in this session, replacing the configured storage
+    workspace: Option<Workspace>,
+    /// Summary length chosen in the URL dialogue, replacing `agent.length`
+    length: Option<SummaryLength>,
+    /// Persona preset chosen in the URL dialogue, replacing the configured persona
+    persona_preset: Option<PersonaPreset>,
+    /// Workspaces listed in the switcher
+    workspaces: Vec<Workspace>,
+    /// Switcher selection state
+    workspace_state: ListState,
+    /// Activity log shown in the popup, newest first
+    activity: Vec<activity::Event>,
+    /// Activity log selection state
+    activity_state: ListState,
+    /// The job behind the Loading view, once started
+    job: Option<Job>,
+    /// The question being answered, if any
+    ask: Option<AskJob>,
 }
 
 impl Default for App {
@@ -83,18 +298,56 @@ impl Default for App {
         Self {
             state: AppState::Main,
             url_input: String::new(),
+            url_error: None,
+            url_queue: VecDeque::new(),
             search_input: String::new(),
+            ask_input: String::new(),
+            tag_input: String::new(),
+            snooze_input: String::new(),
             summary: None,
             source_url: None,
+            extractive: false,
+            lite: false,
+            reading_stats: None,
+            critique: None,
+            tags: Vec::new(),
+            edited: false,
+            edit_requested: false,
+            notes_requested: false,
+            notes: None,
+            qa_history: Vec::new(),
             should_quit: false,
-            status: "'o' open URL/file, 'f' search, ↑↓ navigate, Tab switch panes, 'q' quit"
-                .to_string(),
+            status: help_status(&Markers::default()),
             stored_summaries: Vec::new(),
+            groups: Vec::new(),
+            rows: Vec::new(),
+            collapsed_groups: HashSet::new(),
             list_state: ListState::default(),
             focused_pane: FocusedPane::List,
             detail_scroll: 0,
             is_search_results: false,
             current_search_query: String::new(),
+            saved_searches: Vec::new(),
+            saved_state: ListState::default(),
+            active_saved: None,
+            collections: Vec::new(),
+            collection_state: ListState::default(),
+            queue_order: Vec::new(),
+            quick_filter: None,
+            starred_first: false,
+            theme: Theme::default(),
+            markers: Markers::default(),
+            ascii: false,
+            icons: IconStyle::default(),
+            workspace: None,
+            length: None,
+            persona_preset: None,
+            workspaces: Vec::new(),
+            workspace_state: ListState::default(),
+            activity: Vec::new(),
+            activity_state: ListState::default(),
+            job: None,
+            ask: None,
         }
     }
 }
@@ -105,28 +358,201 @@ impl App {
         Self::default()
     }
 
+    /// Load the config, pointing storage at the workspace switched to, if any
+    fn load_config(&self) -> Result<Config, ConfigError> {
+        let mut config = Config::load()?;
+        if let Some(ref workspace) = self.workspace {
+            config.storage = workspace.storage.clone();
+        }
+        if let Some(length) = self.length {
+            config.agent.length = length;
+        }
+        if self.persona_preset.is_some() {
+            config.agent.persona_preset = self.persona_preset;
+        }
+        Ok(config)
+    }
+
+    /// Take the look and list order from `[ui]`
+    fn apply_ui(&mut self, ui: &UiConfig) {
+        let ascii = self.ascii || ui.ascii;
+        self.theme = Theme::resolve(ui.theme);
+        self.markers = Markers::select(ascii);
+        self.icons = if ascii { IconStyle::None } else { ui.icons };
+        self.starred_first = ui.starred_first;
+    }
+
+    /// Apply the config file after it was edited.
+    ///
+    /// Jobs load the config as they start, so prompts, models and the rest take
+    /// effect with the next one; the look and list order change at once.
+    fn reload_config(&mut self) {
+        let config = match self.load_config() {
+            Ok(config) => config,
+            Err(e) => {
+                self.status = format!("Config not reloaded, keeping the old one: {}", e);
+                return;
+            }
+        };
+        let starred_first = self.starred_first;
+        self.apply_ui(&config.ui);
+        if self.starred_first != starred_first {
+            self.refresh_list();
+        }
+        self.status = "Config reloaded".to_string();
+    }
+
     /// Load stored summaries from storage
     fn load_summaries(&mut self) {
-        if let Ok(config) = Config::load() {
+        if let Ok(config) = self.load_config() {
             if let Ok(storage) = Storage::open(&config.storage.path) {
+                self.load_saved_searches(&storage);
+                self.load_collections(&storage);
+                self.queue_order = storage.queue_order().unwrap_or_default();
                 if let Ok(summaries) = storage.list_all() {
-                    self.stored_summaries = summaries;
-                    // Select first item if available
-                    if !self.stored_summaries.is_empty() {
-                        self.list_state.select(Some(0));
-                        self.update_selected_summary();
-                    }
+                    self.set_summaries(summaries);
                 }
             }
         }
     }
 
+    /// Reload the saved search sidebar, keeping the selection in range
+    fn load_saved_searches(&mut self, storage: &Storage) {
+        self.saved_searches = storage.saved_searches().unwrap_or_default();
+        match self.saved_state.selected() {
+            _ if self.saved_searches.is_empty() => self.saved_state.select(None),
+            Some(i) if i < self.saved_searches.len() => {}
+            _ => self.saved_state.select(Some(0)),
+        }
+    }
+
+    /// Reload the collection sidebar, keeping the selection in range
+    fn load_collections(&mut self, storage: &Storage) {
+        self.collections = storage.collections().unwrap_or_default();
+        match self.collection_state.selected() {
+            _ if self.collections.is_empty() => self.collection_state.select(None),
+            Some(i) if i < self.collections.len() => {}
+            _ => self.collection_state.select(Some(0)),
+        }
+    }
+
+    /// Refresh the list, re-running the active smart collection or search if there is one
+    fn refresh_list(&mut self) {
+        match self.active_saved.clone() {
+            Some(saved) => self.run_saved_search(&saved),
+            None if self.is_search_results => self.perform_search(),
+            None => self.load_summaries(),
+        }
+    }
+
+    /// Replace the listed summaries, selecting the first one
+    fn set_summaries(&mut self, mut summaries: Vec<StoredSummary>) {
+        // Pending summaries only show up in review, keeping the library curated,
+        // and snoozed ones stay out of sight until they're due
+        if self.quick_filter != Some(QuickFilter::Review) {
+            let now = Utc::now();
+            summaries.retain(|stored| !stored.pending && !stored.is_snoozed(now));
+        }
+        if let Some(ref filter) = self.quick_filter {
+            summaries.retain(|stored| filter.matches(stored));
+        }
+        if self.quick_filter == Some(QuickFilter::Queue) {
+            summaries = queue::arrange(summaries, &self.queue_order);
+        } else if self.starred_first {
+            timeline::pin_starred(&mut summaries);
+        }
+        self.stored_summaries = summaries;
+        self.rebuild_rows();
+        let first = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, ListRow::Entry(_)));
+        self.list_state.select(first);
+        if first.is_some() {
+            self.update_selected_summary();
+        } else {
+            self.summary = None;
+            self.source_url = None;
+            self.extractive = false;
+            self.lite = false;
+            self.edited = false;
+            self.reading_stats = None;
+            self.critique = None;
+            self.tags.clear();
+            self.notes = None;
+            self.qa_history.clear();
+        }
+    }
+
+    /// Regroup the summaries by day and rebuild the visible rows
+    fn rebuild_rows(&mut self) {
+        self.groups = if self.quick_filter == Some(QuickFilter::Queue) {
+            // The queue is in reading order, so dates would split it up
+            let len = self.stored_summaries.len();
+            (len > 0)
+                .then(|| DayGroup {
+                    label: "Reading queue".to_string(),
+                    range: 0..len,
+                })
+                .into_iter()
+                .collect()
+        } else if self.starred_first {
+            timeline::group_pinned(&self.stored_summaries, timeline::today())
+        } else {
+            timeline::group_by_day(&self.stored_summaries, timeline::today())
+        };
+        self.rows = self
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(g, group)| {
+                let entries = if self.collapsed_groups.contains(&group.label) {
+                    0..0
+                } else {
+                    group.range.clone()
+                };
+                std::iter::once(ListRow::Header(g)).chain(entries.map(ListRow::Entry))
+            })
+            .collect();
+    }
+
+    /// Index into `stored_summaries` of the selected row, unless it's a header
+    fn selected_summary_index(&self) -> Option<usize> {
+        match self.rows.get(self.list_state.selected()?)? {
+            ListRow::Entry(index) => Some(*index),
+            ListRow::Header(_) => None,
+        }
+    }
+
+    /// Collapse or expand the date group whose header is selected
+    fn toggle_selected_group(&mut self) {
+        let Some(&ListRow::Header(g)) = self.list_state.selected().and_then(|r| self.rows.get(r))
+        else {
+            return;
+        };
+        let label = self.groups[g].label.clone();
+        if !self.collapsed_groups.remove(&label) {
+            self.collapsed_groups.insert(label);
+        }
+        self.rebuild_rows();
+        let header = self.rows.iter().position(|row| *row == ListRow::Header(g));
+        self.list_state.select(header);
+    }
+
     /// Update the displayed summary based on selection
     fn update_selected_summary(&mut self) {
-        if let Some(index) = self.list_state.selected() {
+        if let Some(index) = self.selected_summary_index() {
             if let Some(stored) = self.stored_summaries.get(index) {
                 self.summary = Some(stored.summary.clone());
                 self.source_url = Some(stored.url.clone());
+                self.extractive = stored.provider.as_deref() == Some(extractive::PROVIDER);
+                self.lite = stored.is_lite();
+                self.edited = stored.edited_at.is_some();
+                self.reading_stats = stored.reading_stats();
+                self.critique = stored.critique.as_ref().map(ToString::to_string);
+                self.tags = stored.tags.clone();
+                self.notes = stored.notes.clone();
+                self.qa_history = stored.qa_history.clone();
                 self.detail_scroll = 0; // Reset scroll when selecting new summary
             }
         }
@@ -134,13 +560,13 @@ impl App {
 
     /// Select the previous item in the list
     fn select_previous(&mut self) {
-        if self.stored_summaries.is_empty() {
+        if self.rows.is_empty() {
             return;
         }
         let i = match self.list_state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.stored_summaries.len() - 1
+                    self.rows.len() - 1
                 } else {
                     i - 1
                 }
@@ -149,16 +575,17 @@ impl App {
         };
         self.list_state.select(Some(i));
         self.update_selected_summary();
+        self.mark_selected_read();
     }
 
     /// Select the next item in the list
     fn select_next(&mut self) {
-        if self.stored_summaries.is_empty() {
+        if self.rows.is_empty() {
             return;
         }
         let i = match self.list_state.selected() {
             Some(i) => {
-                if i >= self.stored_summaries.len() - 1 {
+                if i >= self.rows.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -168,12 +595,131 @@ impl App {
         };
         self.list_state.select(Some(i));
         self.update_selected_summary();
+        self.mark_selected_read();
+    }
+
+    /// Mark the summary just opened in the detail view as read
+    fn mark_selected_read(&mut self) {
+        // Moving through the queue to reorder it isn't reading; 'u' marks read there
+        if self.quick_filter == Some(QuickFilter::Queue) {
+            return;
+        }
+        // Moving over summaries already read writes nothing
+        let unread = self
+            .selected_summary_index()
+            .filter(|&index| self.stored_summaries[index].is_unread(Utc::now()));
+        if let Some(index) = unread {
+            // A failed write only leaves it marked unread
+            let _ = self.set_unread(index, false);
+        }
+    }
+
+    /// Mark a listed summary read or unread, saving it if that changes it
+    fn set_unread(&mut self, index: usize, unread: bool) -> Result<(), String> {
+        let Some(mut stored) = self
+            .stored_summaries
+            .get(index)
+            .filter(|stored| stored.is_unread(Utc::now()) != unread)
+            .cloned()
+        else {
+            return Ok(());
+        };
+        stored.set_unread(unread, Utc::now());
+        let config = self.load_config().map_err(|e| e.to_string())?;
+        let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+        storage.put(&stored).map_err(|e| e.to_string())?;
+        self.stored_summaries[index] = stored;
+        Ok(())
+    }
+
+    /// Flip the selected summary between read and unread
+    fn toggle_unread(&mut self) {
+        let Some(index) = self.selected_summary_index() else {
+            return;
+        };
+        let stored = &self.stored_summaries[index];
+        let (unread, title) = (!stored.is_unread(Utc::now()), stored.summary.title.clone());
+        match self.set_unread(index, unread) {
+            Ok(()) if unread => self.status = format!("Marked '{}' unread", title),
+            Ok(()) => self.status = format!("Marked '{}' read", title),
+            Err(e) => self.state = AppState::Error(format!("Failed to save: {}", e)),
+        }
+    }
+
+    /// Select a random older summary from the list, to revisit it
+    fn revisit_random(&mut self) {
+        let filter = RandomFilter::default();
+        let Some(index) = random::pick(&self.stored_summaries, &filter, Utc::now()) else {
+            self.status = format!(
+                "Nothing listed is older than {} days yet",
+                random::DEFAULT_MIN_AGE_DAYS
+            );
+            return;
+        };
+        self.select_entry(index);
+        self.focused_pane = FocusedPane::List;
+        let stored = &self.stored_summaries[index];
+        self.status = format!(
+            "Revisiting '{}', saved {}. 'R' for another",
+            stored.summary.title,
+            stored.created_at.format("%Y-%m-%d")
+        );
+    }
+
+    /// Show the unread summaries in reading queue order
+    fn open_queue(&mut self) {
+        self.is_search_results = false;
+        self.active_saved = None;
+        self.current_search_query.clear();
+        self.quick_filter = Some(QuickFilter::Queue);
+        self.load_summaries();
+        self.focused_pane = FocusedPane::List;
+        self.status = if self.stored_summaries.is_empty() {
+            "The reading queue is empty. Esc to leave.".to_string()
+        } else {
+            format!(
+                "{} in the reading queue: 'K'/'J' move up/down, 'u' marks read, Esc to leave",
+                self.stored_summaries.len()
+            )
+        };
+    }
+
+    /// Move the selected summary up (negative) or down the reading queue, and save the order
+    fn move_in_queue(&mut self, offset: isize) {
+        let Some(index) = self.selected_summary_index() else {
+            return;
+        };
+        let Some(target) = index
+            .checked_add_signed(offset)
+            .filter(|&target| target < self.stored_summaries.len())
+        else {
+            return;
+        };
+        self.stored_summaries.swap(index, target);
+        let order: Vec<String> = self
+            .stored_summaries
+            .iter()
+            .map(|s| s.url.clone())
+            .collect();
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.put_queue_order(&order).map_err(|e| e.to_string())
+            });
+        if let Err(e) = saved {
+            self.stored_summaries.swap(index, target);
+            self.state = AppState::Error(format!("Failed to save the queue: {}", e));
+            return;
+        }
+        self.queue_order = order;
+        self.select_entry(target);
     }
 
     /// Perform a search on stored summaries
     fn perform_search(&mut self) {
-        use crate::SearchIndex;
-
+        self.active_saved = None;
         let query = self.search_input.clone();
         if query.is_empty() {
             // Empty search clears results and shows all
@@ -183,85 +729,689 @@ impl App {
             return;
         }
 
-        if let Ok(config) = Config::load() {
+        let options = SearchOptions {
+            limit: 50,
+            ..SearchOptions::default()
+        };
+        self.run_search(&query, &options, &query);
+    }
+
+    /// Apply the saved search selected in the sidebar
+    fn apply_selected_saved_search(&mut self) {
+        let selected = self
+            .saved_state
+            .selected()
+            .and_then(|i| self.saved_searches.get(i))
+            .cloned();
+        if let Some(saved) = selected {
+            self.active_saved = Some(saved.clone());
+            self.run_saved_search(&saved);
+        }
+    }
+
+    /// Narrow the list to the collection selected in the sidebar
+    fn apply_selected_collection(&mut self) {
+        let Some(collection) = self
+            .collection_state
+            .selected()
+            .and_then(|i| self.collections.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        self.quick_filter = Some(QuickFilter::Collection {
+            name: collection.name.clone(),
+            urls: collection.urls,
+        });
+        self.refresh_list();
+        self.status = format!(
+            "Showing {} summaries in '{}'. Esc to clear filter.",
+            self.stored_summaries.len(),
+            collection.name
+        );
+    }
+
+    /// Run a saved search, replacing the list with its current results
+    fn run_saved_search(&mut self, saved: &SavedSearch) {
+        let label = format!("{} {}", self.markers.star, saved.name);
+        self.run_search(&saved.query, &saved.options(50), &label);
+    }
+
+    /// Run a query and show its results in the list, labelled for display
+    fn run_search(&mut self, query: &str, options: &SearchOptions, label: &str) {
+        if let Ok(config) = self.load_config() {
             if let Ok(storage) = Storage::open(&config.storage.path) {
-                // Try tantivy first
-                let search_path = config.storage.path.join("search_index");
-                let matching_urls: Vec<String> =
-                    if let Ok(search_index) = SearchIndex::open(&search_path) {
-                        search_index.search(&query, 50).unwrap_or_default()
-                    } else {
-                        Vec::new()
-                    };
+                let search_path = config.storage.search_index_path();
+                let outcome = match search::search_summaries(&storage, &search_path, query, options)
+                {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        self.state = AppState::Error(format!("Search failed: {}", e));
+                        return;
+                    }
+                };
 
-                // If tantivy returned results, filter stored summaries by those URLs
-                // Otherwise fall back to simple text search
-                let results: Vec<StoredSummary> = if !matching_urls.is_empty() {
-                    if let Ok(all_summaries) = storage.list_all() {
-                        all_summaries
-                            .into_iter()
-                            .filter(|s| matching_urls.contains(&s.url))
-                            .collect()
-                    } else {
-                        Vec::new()
+                // Keep the stored (newest first) ordering for the matching URLs
+                let results: Vec<StoredSummary> = storage
+                    .list_all()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|s| outcome.urls.contains(&s.url))
+                    .collect();
+
+                self.set_summaries(results);
+                self.is_search_results = true;
+                self.current_search_query = label.to_string();
+
+                // Update status
+                self.status = format!(
+                    "Found {} result(s) for '{}' via {}. Esc to clear search.",
+                    self.stored_summaries.len(),
+                    label,
+                    outcome.backend
+                );
+            }
+        }
+    }
+
+    /// Narrow the list to summaries from the selected summary's domain
+    fn filter_by_selected_domain(&mut self) {
+        let Some(stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+        else {
+            return;
+        };
+        let Some(domain) = stored.domain().map(str::to_string) else {
+            self.status = format!("'{}' isn't a web page", stored.url);
+            return;
+        };
+        self.quick_filter = Some(QuickFilter::Domain(domain.clone()));
+        self.refresh_list();
+        self.status = format!(
+            "Showing {} summaries from {}. Esc to clear filter.",
+            self.stored_summaries.len(),
+            domain
+        );
+    }
+
+    /// Narrow the list to summaries sharing a tag with the selected one: its first
+    /// tag, or the one after the tag already filtered by, so pressing again
+    /// steps through its tags
+    fn filter_by_selected_tag(&mut self) {
+        let Some(stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+        else {
+            return;
+        };
+        let next = match self.quick_filter {
+            Some(QuickFilter::Tag(ref current)) => stored
+                .tags
+                .iter()
+                .position(|tag| tag == current)
+                .map_or(0, |i| (i + 1) % stored.tags.len()),
+            _ => 0,
+        };
+        let Some(tag) = stored.tags.get(next).cloned() else {
+            self.status = format!("'{}' has no tags", stored.summary.title);
+            return;
+        };
+        self.quick_filter = Some(QuickFilter::Tag(tag.clone()));
+        self.refresh_list();
+        self.status = format!(
+            "Showing {} summaries tagged #{}. Esc to clear filter.",
+            self.stored_summaries.len(),
+            tag
+        );
+    }
+
+    /// Narrow the list to the summaries most similar to the selected one
+    fn filter_by_similar(&mut self) {
+        let Some(stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let Ok(config) = self.load_config() else {
+            return;
+        };
+        let similar = Storage::open(&config.storage.path)
+            .map_err(search::SearchError::from)
+            .and_then(|storage| {
+                search::similar_summaries(
+                    &storage,
+                    &config.storage.search_index_path(),
+                    &stored.url,
+                    SIMILAR_LIMIT,
+                )
+            });
+        let urls = match similar {
+            Ok(urls) if urls.is_empty() => {
+                self.status = format!("Nothing similar to '{}' yet", stored.summary.title);
+                return;
+            }
+            Ok(urls) => urls,
+            Err(e) => {
+                self.state = AppState::Error(format!("Finding similar summaries failed: {}", e));
+                return;
+            }
+        };
+        self.quick_filter = Some(QuickFilter::Similar {
+            title: stored.summary.title.clone(),
+            urls,
+        });
+        self.refresh_list();
+        self.status = format!(
+            "Showing {} summaries similar to '{}'. Esc to clear filter.",
+            self.stored_summaries.len(),
+            stored.summary.title
+        );
+    }
+
+    /// Narrow the list to pending summaries so they can be kept or discarded one by one
+    fn start_review(&mut self) {
+        self.is_search_results = false;
+        self.active_saved = None;
+        self.current_search_query.clear();
+        self.quick_filter = Some(QuickFilter::Review);
+        self.load_summaries();
+        self.focused_pane = FocusedPane::List;
+        self.review_status(None);
+    }
+
+    /// Keep, snooze or discard the selected pending summary, then move on to the next
+    fn review_selected(&mut self, verdict: Verdict) {
+        let Some(mut stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                match verdict {
+                    Verdict::Keep => stored.accept(),
+                    Verdict::Snooze => {
+                        stored.snooze(Utc::now() + chrono::Duration::days(SNOOZE_DAYS))
                     }
-                } else {
-                    // Fallback to simple text search
-                    let query_lower = query.to_lowercase();
-                    if let Ok(all_summaries) = storage.list_all() {
-                        all_summaries
-                            .into_iter()
-                            .filter(|stored| {
-                                let summary = &stored.summary;
-                                summary.title.to_lowercase().contains(&query_lower)
-                                    || summary.conclusion.to_lowercase().contains(&query_lower)
-                                    || summary
-                                        .key_points
-                                        .iter()
-                                        .any(|p| p.to_lowercase().contains(&query_lower))
-                                    || summary
-                                        .entities
-                                        .iter()
-                                        .any(|e| e.to_lowercase().contains(&query_lower))
-                                    || stored.url.to_lowercase().contains(&query_lower)
-                            })
-                            .collect()
-                    } else {
-                        Vec::new()
+                    Verdict::Discard => {
+                        return delete_summary(&config, &stored, Some("discarded in review"))
                     }
-                };
+                }
+                storage
+                    .put(&stored)
+                    .map(|_| None)
+                    .map_err(|e| e.to_string())
+            });
+        let warning = match saved {
+            Ok(warning) => warning,
+            Err(e) => {
+                self.state = AppState::Error(format!("Review failed: {}", e));
+                return;
+            }
+        };
+        self.load_summaries();
+        let done = match verdict {
+            Verdict::Keep => "Kept",
+            Verdict::Snooze => "Snoozed",
+            Verdict::Discard => "Discarded",
+        };
+        self.review_status(Some(format!("{} '{}'.", done, stored.summary.title)));
+        if let Some(warning) = warning {
+            self.status = warning;
+        }
+    }
+
+    /// Status line for review mode, leaving it once nothing is left
+    fn review_status(&mut self, done: Option<String>) {
+        let done = done.map(|d| d + " ").unwrap_or_default();
+        if self.stored_summaries.is_empty() {
+            self.clear_quick_filter();
+            self.status = format!("{}Nothing left to review.", done);
+        } else {
+            self.status = format!(
+                "{}{} to review: 'y' keep, 'z' snooze, 'x' discard, Esc to stop",
+                done,
+                self.stored_summaries.len()
+            );
+        }
+    }
+
+    /// Correct the selected summary in $EDITOR, suspending the TUI while it runs
+    fn edit_selected(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let Some(index) = self.selected_summary_index() else {
+            return Ok(());
+        };
+        let Some(mut stored) = self.stored_summaries.get(index).cloned() else {
+            return Ok(());
+        };
+        let original = match stored.summary.to_editable() {
+            Ok(text) => text,
+            Err(e) => {
+                self.state = AppState::Error(e.to_string());
+                return Ok(());
+            }
+        };
+
+        let summary = match edit_suspended(terminal, &original)?
+            .and_then(|text| Summary::from_editable(&text).map_err(|e| e.to_string()))
+        {
+            Ok(summary) => summary,
+            Err(e) => {
+                self.state = AppState::Error(e);
+                return Ok(());
+            }
+        };
+        if summary.to_editable().ok().as_deref() == Some(original.as_str()) {
+            self.status = format!("No changes to '{}'", stored.summary.title);
+            return Ok(());
+        }
+
+        stored.edit(summary);
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.put(&stored).map_err(|e| e.to_string())?;
+                let warning =
+                    log_after_save(&storage, &activity::Event::new(Action::Edited, &stored));
+                if let Ok(index) =
+                    crate::SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+                {
+                    // A stale index entry only affects search until the next reindex
+                    let _ = index.index_stored(&stored);
+                }
+                Ok(warning)
+            });
+        let warning = match saved {
+            Ok(warning) => warning,
+            Err(e) => {
+                self.state = AppState::Error(format!("Failed to save the edit: {}", e));
+                return Ok(());
+            }
+        };
+        self.status =
+            warning.unwrap_or_else(|| format!("Saved your edit of '{}'", stored.summary.title));
+        self.stored_summaries[index] = stored;
+        self.update_selected_summary();
+        Ok(())
+    }
+
+    /// Write notes on the selected summary in $EDITOR, suspending the TUI while it runs
+    fn edit_notes(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let Some(index) = self.selected_summary_index() else {
+            return Ok(());
+        };
+        let mut stored = self.stored_summaries[index].clone();
+        let original = stored.notes.clone().unwrap_or_default();
+        let text = match edit_suspended(terminal, &original)? {
+            Ok(text) => text,
+            Err(e) => {
+                self.state = AppState::Error(e);
+                return Ok(());
+            }
+        };
+        if !stored.set_notes(&text) {
+            self.status = format!("No changes to the notes on '{}'", stored.summary.title);
+            return Ok(());
+        }
+
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.put(&stored).map_err(|e| e.to_string())?;
+                let warning = log_after_save(
+                    &storage,
+                    &activity::Event::new(Action::Edited, &stored).with_detail("notes"),
+                );
+                if let Ok(index) =
+                    crate::SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+                {
+                    // A stale index entry only affects search until the next reindex
+                    let _ = index.index_stored(&stored);
+                }
+                Ok(warning)
+            });
+        let warning = match saved {
+            Ok(warning) => warning,
+            Err(e) => {
+                self.state = AppState::Error(format!("Failed to save the notes: {}", e));
+                return Ok(());
+            }
+        };
+        self.status =
+            warning.unwrap_or_else(|| format!("Saved the notes on '{}'", stored.summary.title));
+        self.stored_summaries[index] = stored;
+        self.update_selected_summary();
+        Ok(())
+    }
+
+    /// Drop the quick filter, showing the full list or search results again
+    fn clear_quick_filter(&mut self) {
+        self.quick_filter = None;
+        self.refresh_list();
+        if !self.is_search_results {
+            self.status = help_status(&self.markers);
+        }
+    }
+
+    /// Open the workspace switcher, or explain why there is nothing to switch to
+    fn open_workspace_switcher(&mut self) {
+        let config = match self.load_config() {
+            Ok(config) => config,
+            Err(e) => {
+                self.state = AppState::Error(format!("Config error: {}", e));
+                return;
+            }
+        };
+        self.workspaces = config.available_workspaces();
+        if self.workspaces.len() < 2 {
+            self.status =
+                "Only one workspace. Run `summera init` in a project or add [workspaces] to the config."
+                    .to_string();
+            return;
+        }
+        let current = self
+            .workspaces
+            .iter()
+            .position(|w| w.storage == config.storage);
+        self.workspace_state.select(current.or(Some(0)));
+        self.state = AppState::WorkspaceSwitcher;
+    }
+
+    /// Start summarising the entered URL, unless the page is already stored
+    fn submit_url(&mut self) {
+        let mut sources = match reader::parse_sources(&self.url_input) {
+            Ok(sources) => sources,
+            Err(e) => {
+                self.url_error = Some(e);
+                return;
+            }
+        };
+        self.url_error = None;
+        if sources.len() > 1 {
+            self.url_queue = sources.into();
+            self.start_next_queued();
+            return;
+        }
+        self.url_input = sources.remove(0);
+        self.state = match self.find_stored(&self.url_input) {
+            Some(stored) => AppState::Duplicate {
+                url: stored.url,
+                title: stored.summary.title,
+            },
+            None => AppState::Loading,
+        };
+    }
+
+    /// The stored summary of the page `source` points to, if it's a URL summarised before
+    fn find_stored(&self, source: &str) -> Option<StoredSummary> {
+        let config = self.load_config().ok().filter(|_| reader::is_url(source))?;
+        let storage = Storage::open(&config.storage.path).ok()?;
+        storage.find_duplicate(source).ok().flatten()
+    }
+
+    /// Start summarising the next queued source, skipping pages already stored
+    fn start_next_queued(&mut self) {
+        let mut skipped = 0;
+        while let Some(source) = self.url_queue.pop_front() {
+            if self.find_stored(&source).is_some() {
+                skipped += 1;
+                continue;
+            }
+            self.url_input = source;
+            self.state = AppState::Loading;
+            return;
+        }
+        self.state = AppState::Main;
+        self.url_input.clear();
+        if skipped > 0 {
+            self.status = format!("Skipped {} already stored page(s)", skipped);
+        }
+    }
+
+    /// Insert pasted text into the open input, joining lines with spaces
+    fn handle_paste(&mut self, text: &str) {
+        let input = match self.state {
+            AppState::UrlInput => {
+                self.url_error = None;
+                &mut self.url_input
+            }
+            AppState::SearchInput => &mut self.search_input,
+            AppState::AskInput => &mut self.ask_input,
+            AppState::TagInput => &mut self.tag_input,
+            AppState::SnoozeInput => &mut self.snooze_input,
+            _ => return,
+        };
+        input.extend(text.lines().flat_map(|line| [line, " "]));
+        input.truncate(input.trim_end().len());
+    }
+
+    /// Select the stored summary for `url` in the full list
+    fn show_stored(&mut self, url: &str) {
+        self.url_input.clear();
+        self.quick_filter = None;
+        self.clear_search();
+        let Some(index) = self.stored_summaries.iter().position(|s| s.url == url) else {
+            self.status = format!("{} is waiting for review; press 'r' to see it", url);
+            return;
+        };
+        self.select_entry(index);
+        self.focused_pane = FocusedPane::List;
+    }
+
+    /// Select a listed summary, expanding its date group if it's collapsed
+    fn select_entry(&mut self, index: usize) {
+        if let Some(group) = self.groups.iter().find(|g| g.range.contains(&index)) {
+            self.collapsed_groups.remove(&group.label);
+        }
+        self.rebuild_rows();
+        let row = self
+            .rows
+            .iter()
+            .position(|row| *row == ListRow::Entry(index));
+        self.list_state.select(row);
+        self.update_selected_summary();
+        self.mark_selected_read();
+    }
+
+    /// Ask before deleting the selected summary
+    fn confirm_delete(&mut self) {
+        let Some(stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+        else {
+            return;
+        };
+        self.state = AppState::ConfirmDelete {
+            url: stored.url.clone(),
+            title: stored.summary.title.clone(),
+        };
+    }
+
+    /// Delete the summary for `url` and drop it from the list, keeping the selection in place
+    fn delete_confirmed(&mut self, url: &str) {
+        self.state = AppState::Main;
+        let Some(index) = self.stored_summaries.iter().position(|s| s.url == url) else {
+            return;
+        };
+        let stored = self.stored_summaries[index].clone();
+        let deleted = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let warning = delete_summary(&config, &stored, None)?;
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                self.load_collections(&storage);
+                Ok(warning)
+            });
+        let warning = match deleted {
+            Ok(warning) => warning,
+            Err(e) => {
+                self.state = AppState::Error(format!("Failed to delete: {}", e));
+                return;
+            }
+        };
+        let mut remaining = std::mem::take(&mut self.stored_summaries);
+        remaining.remove(index);
+        self.set_summaries(remaining);
+        if !self.stored_summaries.is_empty() {
+            self.select_entry(index.min(self.stored_summaries.len() - 1));
+        }
+        self.status = warning.unwrap_or_else(|| format!("Deleted '{}'", stored.summary.title));
+    }
+
+    /// Open the snooze dialogue for the selected summary, suggesting a week
+    fn open_snooze_dialogue(&mut self) {
+        if self.selected_summary_index().is_some() {
+            self.snooze_input = "1w".to_string();
+            self.state = AppState::SnoozeInput;
+        }
+    }
+
+    /// Snooze the selected summary until the entered date, taking it out of the list
+    fn snooze_selected(&mut self) {
+        let Some(index) = self.selected_summary_index() else {
+            return;
+        };
+        let until = match snooze::parse_until(&self.snooze_input, Local::now()) {
+            Ok(until) => until,
+            Err(e) => {
+                self.status = format!("Not snoozed: {}", e);
+                return;
+            }
+        };
+        let mut stored = self.stored_summaries[index].clone();
+        stored.snooze(until);
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.put(&stored).map_err(|e| e.to_string())
+            });
+        if let Err(e) = saved {
+            self.state = AppState::Error(format!("Failed to snooze: {}", e));
+            return;
+        }
+        let mut remaining = std::mem::take(&mut self.stored_summaries);
+        remaining.remove(index);
+        self.set_summaries(remaining);
+        if !self.stored_summaries.is_empty() {
+            self.select_entry(index.min(self.stored_summaries.len() - 1));
+        }
+        self.status = format!(
+            "Snoozed '{}' until {}",
+            stored.summary.title,
+            until.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    /// Star or unstar the selected summary, moving it if starred summaries are listed first
+    fn toggle_star(&mut self) {
+        let Some(index) = self.selected_summary_index() else {
+            return;
+        };
+        let mut stored = self.stored_summaries[index].clone();
+        stored.starred = !stored.starred;
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.put(&stored).map_err(|e| e.to_string())
+            });
+        if let Err(e) = saved {
+            self.state = AppState::Error(format!("Failed to save the star: {}", e));
+            return;
+        }
+        let verb = if stored.starred {
+            "Starred"
+        } else {
+            "Unstarred"
+        };
+        self.status = format!("{} '{}'", verb, stored.summary.title);
+        // Updated where it is, so the selection stays put; with `starred_first` it
+        // moves to the starred group the next time the list loads
+        self.stored_summaries[index] = stored;
+    }
 
-                self.stored_summaries = results;
-                self.is_search_results = true;
-                self.current_search_query = query.clone();
+    /// Open the activity log popup, newest events first
+    fn open_activity(&mut self) {
+        let events = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.events().map_err(|e| e.to_string())
+            });
+        match events {
+            Ok(events) if events.is_empty() => self.status = "No activity yet".to_string(),
+            Ok(mut events) => {
+                events.reverse();
+                self.activity = events;
+                self.activity_state.select(Some(0));
+                self.state = AppState::Activity;
+            }
+            Err(e) => self.state = AppState::Error(format!("Failed to read the log: {}", e)),
+        }
+    }
 
-                // Update status
-                self.status = format!(
-                    "Found {} result(s) for '{}'. Esc to clear search.",
-                    self.stored_summaries.len(),
-                    query
-                );
+    /// Switch to the workspace selected in the switcher, reloading everything from it
+    fn switch_workspace(&mut self) {
+        let selected = self
+            .workspace_state
+            .selected()
+            .and_then(|i| self.workspaces.get(i))
+            .cloned();
+        self.state = AppState::Main;
+        let Some(workspace) = selected else {
+            return;
+        };
 
-                // Select first result if any
-                if !self.stored_summaries.is_empty() {
-                    self.list_state.select(Some(0));
-                    self.update_selected_summary();
-                } else {
-                    self.list_state.select(None);
-                    self.summary = None;
-                    self.source_url = None;
-                }
-            }
+        // Drop everything read from the previous archive
+        self.workspace = Some(workspace.clone());
+        self.set_summaries(Vec::new());
+        self.detail_scroll = 0;
+        self.is_search_results = false;
+        self.active_saved = None;
+        self.quick_filter = None;
+        self.current_search_query.clear();
+        self.search_input.clear();
+        self.saved_state.select(None);
+        self.focused_pane = FocusedPane::List;
+
+        // Pick up config changes made since the TUI started
+        if let Ok(config) = self.load_config() {
+            self.theme = Theme::resolve(config.ui.theme);
         }
+        self.load_summaries();
+        self.status = format!("Switched to workspace '{}'", workspace.name);
     }
 
     /// Clear search and show all summaries
     fn clear_search(&mut self) {
         self.is_search_results = false;
+        self.active_saved = None;
         self.current_search_query.clear();
         self.search_input.clear();
-        self.status =
-            "'o' open URL/file, 'f' search, ↑↓ navigate, Tab switch panes, 'q' quit".to_string();
+        self.status = help_status(&self.markers);
         self.load_summaries();
     }
 
@@ -270,6 +1420,16 @@ impl App {
         match &self.state {
             AppState::Main => match key {
                 KeyCode::Char('q') => self.should_quit = true,
+                KeyCode::Char('r') => self.start_review(),
+                KeyCode::Char('y') if self.quick_filter == Some(QuickFilter::Review) => {
+                    self.review_selected(Verdict::Keep)
+                }
+                KeyCode::Char('z') if self.quick_filter == Some(QuickFilter::Review) => {
+                    self.review_selected(Verdict::Snooze)
+                }
+                KeyCode::Char('x') if self.quick_filter == Some(QuickFilter::Review) => {
+                    self.review_selected(Verdict::Discard)
+                }
                 KeyCode::Char('o') => {
                     self.state = AppState::UrlInput;
                     self.url_input.clear();
@@ -278,48 +1438,106 @@ impl App {
                     self.state = AppState::SearchInput;
                     self.search_input.clear();
                 }
-                KeyCode::Esc => {
+                KeyCode::Char('a') => self.open_ask_dialogue(),
+                KeyCode::Char('w') => self.open_workspace_switcher(),
+                KeyCode::Char('l') => self.open_activity(),
+                KeyCode::Char('d') => self.filter_by_selected_domain(),
+                KeyCode::Char('S') => self.filter_by_similar(),
+                KeyCode::Char('T') => self.filter_by_selected_tag(),
+                KeyCode::Char('t') => self.open_tag_dialogue(),
+                KeyCode::Char('s') | KeyCode::Char('*') => self.toggle_star(),
+                KeyCode::Char('u') => self.toggle_unread(),
+                KeyCode::Char('z') => self.open_snooze_dialogue(),
+                // 'd' is the domain filter, and deleting sits on the shifted key so
+                // it isn't one slip away from a read-only key
+                KeyCode::Char('D') => self.confirm_delete(),
+                KeyCode::Char('Q') => self.open_queue(),
+                KeyCode::Char('R') => self.revisit_random(),
+                KeyCode::Char('K') if self.quick_filter == Some(QuickFilter::Queue) => {
+                    self.move_in_queue(-1)
+                }
+                KeyCode::Char('J') if self.quick_filter == Some(QuickFilter::Queue) => {
+                    self.move_in_queue(1)
+                }
+                KeyCode::Char('e') if self.selected_summary_index().is_some() => {
+                    self.edit_requested = true
+                }
+                KeyCode::Char('n') if self.selected_summary_index().is_some() => {
+                    self.notes_requested = true
+                }
+                KeyCode::Esc if self.quick_filter.is_some() => self.clear_quick_filter(),
+                KeyCode::Esc if self.is_search_results => {
                     // Clear search results and show all
-                    if self.is_search_results {
-                        self.clear_search();
-                    }
+                    self.clear_search();
                 }
                 KeyCode::Tab => {
                     self.focused_pane = match self.focused_pane {
                         FocusedPane::List => FocusedPane::Detail,
-                        FocusedPane::Detail => FocusedPane::List,
+                        FocusedPane::Detail if !self.saved_searches.is_empty() => {
+                            FocusedPane::Saved
+                        }
+                        FocusedPane::Detail | FocusedPane::Saved
+                            if !self.collections.is_empty() =>
+                        {
+                            FocusedPane::Collections
+                        }
+                        FocusedPane::Detail | FocusedPane::Saved | FocusedPane::Collections => {
+                            FocusedPane::List
+                        }
                     };
                 }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    if self.focused_pane == FocusedPane::List {
-                        self.select_previous();
-                    } else {
-                        // Scroll detail view up
+                KeyCode::Enter if self.focused_pane == FocusedPane::Saved => {
+                    self.apply_selected_saved_search();
+                }
+                KeyCode::Enter if self.focused_pane == FocusedPane::Collections => {
+                    self.apply_selected_collection();
+                }
+                KeyCode::Enter | KeyCode::Char(' ') if self.focused_pane == FocusedPane::List => {
+                    self.toggle_selected_group();
+                }
+                KeyCode::Up | KeyCode::Char('k') => match self.focused_pane {
+                    FocusedPane::List => self.select_previous(),
+                    // Scroll detail view up
+                    FocusedPane::Detail => {
                         self.detail_scroll = self.detail_scroll.saturating_sub(1);
                     }
-                }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    if self.focused_pane == FocusedPane::List {
-                        self.select_next();
-                    } else {
-                        // Scroll detail view down
+                    FocusedPane::Saved => {
+                        let len = self.saved_searches.len();
+                        let i = self.saved_state.selected().unwrap_or(0);
+                        self.saved_state.select(Some((i + len - 1) % len.max(1)));
+                    }
+                    FocusedPane::Collections => {
+                        let len = self.collections.len();
+                        let i = self.collection_state.selected().unwrap_or(0);
+                        self.collection_state
+                            .select(Some((i + len - 1) % len.max(1)));
+                    }
+                },
+                KeyCode::Down | KeyCode::Char('j') => match self.focused_pane {
+                    FocusedPane::List => self.select_next(),
+                    // Scroll detail view down
+                    FocusedPane::Detail => {
                         self.detail_scroll = self.detail_scroll.saturating_add(1);
                     }
-                }
-                KeyCode::PageUp => {
-                    if self.focused_pane == FocusedPane::Detail {
-                        self.detail_scroll = self.detail_scroll.saturating_sub(10);
+                    FocusedPane::Saved => {
+                        let len = self.saved_searches.len();
+                        let i = self.saved_state.selected().unwrap_or(0);
+                        self.saved_state.select(Some((i + 1) % len.max(1)));
                     }
-                }
-                KeyCode::PageDown => {
-                    if self.focused_pane == FocusedPane::Detail {
-                        self.detail_scroll = self.detail_scroll.saturating_add(10);
+                    FocusedPane::Collections => {
+                        let len = self.collections.len();
+                        let i = self.collection_state.selected().unwrap_or(0);
+                        self.collection_state.select(Some((i + 1) % len.max(1)));
                     }
+                },
+                KeyCode::PageUp if self.focused_pane == FocusedPane::Detail => {
+                    self.detail_scroll = self.detail_scroll.saturating_sub(10);
                 }
-                KeyCode::Home => {
-                    if self.focused_pane == FocusedPane::Detail {
-                        self.detail_scroll = 0;
-                    }
+                KeyCode::PageDown if self.focused_pane == FocusedPane::Detail => {
+                    self.detail_scroll = self.detail_scroll.saturating_add(10);
+                }
+                KeyCode::Home if self.focused_pane == FocusedPane::Detail => {
+                    self.detail_scroll = 0;
                 }
                 _ => {}
             },
@@ -327,17 +1545,29 @@ impl App {
                 KeyCode::Esc => {
                     self.state = AppState::Main;
                     self.url_input.clear();
+                    self.url_error = None;
                 }
-                KeyCode::Enter => {
-                    if !self.url_input.is_empty() {
-                        self.state = AppState::Loading;
-                    }
+                KeyCode::Enter => self.submit_url(),
+                KeyCode::Tab => {
+                    let length = match self.length {
+                        Some(length) => length,
+                        None => self
+                            .load_config()
+                            .map(|config| config.agent.length)
+                            .unwrap_or_default(),
+                    };
+                    self.length = Some(length.next());
+                }
+                KeyCode::BackTab => {
+                    self.persona_preset = PersonaPreset::next(self.persona_preset);
                 }
                 KeyCode::Backspace => {
                     self.url_input.pop();
+                    self.url_error = None;
                 }
                 KeyCode::Char(c) => {
                     self.url_input.push(c);
+                    self.url_error = None;
                 }
                 _ => {}
             },
@@ -347,8 +1577,8 @@ impl App {
                     self.search_input.clear();
                 }
                 KeyCode::Enter => {
-                    self.perform_search();
                     self.state = AppState::Main;
+                    self.perform_search();
                 }
                 KeyCode::Backspace => {
                     self.search_input.pop();
@@ -358,10 +1588,114 @@ impl App {
                 }
                 _ => {}
             },
+            AppState::AskInput => match key {
+                KeyCode::Esc => {
+                    self.state = AppState::Main;
+                    self.ask_input.clear();
+                }
+                KeyCode::Enter if !self.ask_input.trim().is_empty() => {
+                    self.state = AppState::Main;
+                    self.start_ask();
+                }
+                KeyCode::Backspace => {
+                    self.ask_input.pop();
+                }
+                KeyCode::Char(c) => {
+                    self.ask_input.push(c);
+                }
+                _ => {}
+            },
+            AppState::TagInput => match key {
+                KeyCode::Esc => self.state = AppState::Main,
+                KeyCode::Enter => {
+                    self.state = AppState::Main;
+                    self.save_tags();
+                }
+                KeyCode::Backspace => {
+                    self.tag_input.pop();
+                }
+                KeyCode::Char(c) => self.tag_input.push(c),
+                _ => {}
+            },
+            AppState::SnoozeInput => match key {
+                KeyCode::Esc => self.state = AppState::Main,
+                KeyCode::Enter => {
+                    self.state = AppState::Main;
+                    self.snooze_selected();
+                }
+                KeyCode::Backspace => {
+                    self.snooze_input.pop();
+                }
+                KeyCode::Char(c) => self.snooze_input.push(c),
+                _ => {}
+            },
+            AppState::WorkspaceSwitcher => match key {
+                KeyCode::Esc => self.state = AppState::Main,
+                KeyCode::Enter => self.switch_workspace(),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    let len = self.workspaces.len();
+                    let i = self.workspace_state.selected().unwrap_or(0);
+                    self.workspace_state
+                        .select(Some((i + len - 1) % len.max(1)));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let len = self.workspaces.len();
+                    let i = self.workspace_state.selected().unwrap_or(0);
+                    self.workspace_state.select(Some((i + 1) % len.max(1)));
+                }
+                _ => {}
+            },
+            AppState::Duplicate { url, .. } => match key {
+                KeyCode::Enter | KeyCode::Char('v') => {
+                    let url = url.clone();
+                    self.state = AppState::Main;
+                    self.show_stored(&url);
+                }
+                KeyCode::Char('r') => {
+                    // Summarise the stored URL so its record is revised, not duplicated
+                    self.url_input = url.clone();
+                    self.state = AppState::Loading;
+                }
+                KeyCode::Esc => {
+                    self.state = AppState::Main;
+                    self.url_input.clear();
+                }
+                _ => {}
+            },
+            AppState::ConfirmDelete { url, .. } => match key {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    let url = url.clone();
+                    self.delete_confirmed(&url);
+                }
+                KeyCode::Char('n') | KeyCode::Esc => self.state = AppState::Main,
+                _ => {}
+            },
+            AppState::Activity => match key {
+                KeyCode::Esc | KeyCode::Char('l') => self.state = AppState::Main,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    let i = self.activity_state.selected().unwrap_or(0);
+                    self.activity_state.select(Some(i.saturating_sub(1)));
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let i = self.activity_state.selected().unwrap_or(0);
+                    let last = self.activity.len().saturating_sub(1);
+                    self.activity_state.select(Some((i + 1).min(last)));
+                }
+                _ => {}
+            },
             AppState::Loading => {
-                // Can't cancel loading for now
+                if key == KeyCode::Esc {
+                    self.cancel_job();
+                }
             }
             AppState::Error(_) => match key {
+                // A failed page in a pasted batch doesn't stop the rest unless asked to
+                KeyCode::Enter if !self.url_queue.is_empty() => self.start_next_queued(),
+                KeyCode::Esc if !self.url_queue.is_empty() => {
+                    self.state = AppState::Main;
+                    self.status = format!("Dropped {} queued source(s)", self.url_queue.len());
+                    self.url_queue.clear();
+                }
                 KeyCode::Esc | KeyCode::Enter => {
                     self.state = AppState::Main;
                 }
@@ -371,89 +1705,346 @@ impl App {
         }
     }
 
-    /// Fetch and summarise a URL or local file (PDF/PPTX)
-    async fn fetch_and_summarise(&mut self) {
-        let input = self.url_input.clone();
+    /// Open the question dialogue for the selected summary
+    fn open_ask_dialogue(&mut self) {
+        if self.ask.is_some() {
+            self.status = "Still answering the last question".to_string();
+        } else if self.selected_summary_index().is_some() {
+            self.state = AppState::AskInput;
+            self.ask_input.clear();
+        }
+    }
 
-        // Extract text from URL or local file
-        let (text, source_key) = if reader::is_url(&input) {
-            self.status = format!("Fetching: {}", input);
-            match scraper::fetch_content(&input).await {
-                Ok(content) => (content.text, input.clone()),
-                Err(e) => {
-                    self.state = AppState::Error(format!("Failed to fetch URL: {}", e));
-                    return;
-                }
-            }
-        } else {
-            self.status = format!("Reading: {}", input);
-            match reader::extract_from_file(&input) {
-                Ok(content) => {
-                    let abs_path = std::fs::canonicalize(&input)
-                        .unwrap_or_else(|_| std::path::PathBuf::from(&input));
-                    let key = format!("file://{}", abs_path.display());
-                    (content.text, key)
-                }
-                Err(e) => {
-                    self.state = AppState::Error(format!("Failed to read file: {}", e));
-                    return;
+    /// Open the tag dialogue with the selected summary's tags filled in
+    fn open_tag_dialogue(&mut self) {
+        if let Some(stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+        {
+            self.tag_input = stored.tags.join(", ");
+            self.state = AppState::TagInput;
+        }
+    }
+
+    /// Replace the selected summary's tags with the entered list and re-index it
+    fn save_tags(&mut self) {
+        let Some(index) = self.selected_summary_index() else {
+            return;
+        };
+        let mut stored = self.stored_summaries[index].clone();
+        if !tags::set_tags(&mut stored, &self.tag_input) {
+            return;
+        }
+        let saved = self
+            .load_config()
+            .map_err(|e| e.to_string())
+            .and_then(|config| {
+                let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+                storage.put(&stored).map_err(|e| e.to_string())?;
+                if let Ok(index) =
+                    crate::SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+                {
+                    // A stale index entry only affects tag: searches until the next reindex
+                    let _ = index.index_stored(&stored);
                 }
-            }
+                Ok(())
+            });
+        if let Err(e) = saved {
+            self.state = AppState::Error(format!("Failed to save tags: {}", e));
+            return;
+        }
+        self.status = match stored.tags.len() {
+            0 => format!("Removed the tags from '{}'", stored.summary.title),
+            _ => format!(
+                "Tagged '{}' {}",
+                stored.summary.title,
+                stored.tags.join(", ")
+            ),
         };
+        self.stored_summaries[index] = stored;
+        self.update_selected_summary();
+    }
 
-        self.status = format!("Summarising {} characters...", text.len());
-
-        // Load config and summarise
-        match Config::load() {
-            Ok(config) => match agent::summarize(&text, &config).await {
-                Ok(summary) => {
-                    // Persist the summary
-                    if let Err(e) = self.save_summary(&source_key, &summary, &config) {
-                        eprintln!("Warning: Failed to save summary: {}", e);
-                    }
+    /// Answer the entered question about the selected summary in the background
+    fn start_ask(&mut self) {
+        let Some(stored) = self
+            .selected_summary_index()
+            .and_then(|i| self.stored_summaries.get(i))
+            .cloned()
+        else {
+            return;
+        };
+        let config = match self.load_config() {
+            Ok(config) => config,
+            Err(e) => {
+                self.state = AppState::Error(format!("Config error: {}", e));
+                return;
+            }
+        };
+        let question = self.ask_input.trim().to_string();
+        self.status = format!("Asking: {}", question);
+        self.ask = Some(AskJob {
+            question: question.clone(),
+            handle: tokio::spawn(ask_question(question, stored, config)),
+        });
+    }
 
-                    self.summary = Some(summary);
-                    self.source_url = Some(source_key);
-                    self.state = AppState::Main;
-                    self.status =
-                        "'o' open URL/file, 'f' search, ↑↓ navigate, Tab switch panes, 'q' quit"
-                            .to_string();
+    /// Show the answer once the question has been answered
+    async fn poll_ask(&mut self) {
+        if !self
+            .ask
+            .as_ref()
+            .is_some_and(|ask| ask.handle.is_finished())
+        {
+            return;
+        }
+        let Some(ask) = self.ask.take() else {
+            return;
+        };
+        let stored = match ask.handle.await {
+            Ok(Ok(stored)) => stored,
+            Ok(Err(message)) => {
+                self.state = AppState::Error(message);
+                return;
+            }
+            Err(e) => {
+                self.state = AppState::Error(format!("Answering stopped unexpectedly: {}", e));
+                return;
+            }
+        };
+        self.status = format!("Answered '{}', see Q&A in the detail view", ask.question);
+        if self.source_url.as_deref() == Some(stored.url.as_str()) {
+            self.qa_history = stored.qa_history.clone();
+        }
+        if let Some(entry) = self
+            .stored_summaries
+            .iter_mut()
+            .find(|s| s.url == stored.url)
+        {
+            *entry = stored;
+        }
+    }
 
-                    // Reload summaries list to include the new one
-                    self.load_summaries();
-                }
-                Err(e) => {
-                    self.state = AppState::Error(format!("Summarisation failed: {}", e));
-                }
-            },
+    /// Start fetching and summarising the URL or local file (PDF/PPTX) that was entered
+    fn start_job(&mut self) {
+        let config = match self.load_config() {
+            Ok(config) => config,
             Err(e) => {
                 self.state = AppState::Error(format!("Config error: {}", e));
+                return;
+            }
+        };
+        let (sender, events) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+        tokio::spawn(run_job(
+            self.url_input.clone(),
+            config.clone(),
+            sender,
+            cancel.clone(),
+        ));
+        self.job = Some(Job {
+            events,
+            cancel,
+            stages: Vec::new(),
+            started: Instant::now(),
+            config,
+        });
+    }
+
+    /// Collect progress from the running job, finishing it if it's done
+    async fn poll_job(&mut self) {
+        let Some(job) = self.job.as_mut() else {
+            return;
+        };
+        let result = loop {
+            match job.events.try_recv() {
+                Ok(JobEvent::Stage(stage)) => job.stages.push(stage),
+                Ok(JobEvent::Done(result)) => break result,
+                Err(TryRecvError::Empty) => return,
+                // The task went away without reporting back, e.g. it panicked
+                Err(TryRecvError::Disconnected) => {
+                    break Err("Summarisation stopped unexpectedly".to_string())
+                }
             }
+        };
+        let Some(job) = self.job.take() else {
+            return;
+        };
+        match result {
+            Ok(stored) => self.finish_job(stored, &job.config).await,
+            Err(message) => self.state = AppState::Error(message),
+        }
+        self.notify_job_result();
+        if self.url_queue.is_empty() {
+            return;
+        }
+        match self.state {
+            AppState::Error(ref mut message) => message.push_str(&format!(
+                "\n\n{} more queued: Enter to go on, Esc to drop them",
+                self.url_queue.len()
+            )),
+            _ => self.start_next_queued(),
         }
     }
 
-    /// Save a summary to persistent storage and search index
-    fn save_summary(&self, url: &str, summary: &Summary, config: &Config) -> anyhow::Result<()> {
-        use crate::SearchIndex;
+    /// Cancel the running job and go back to the main view.
+    ///
+    /// Nothing is written until a job finishes, so there is nothing to undo.
+    fn cancel_job(&mut self) {
+        if let Some(job) = self.job.take() {
+            job.cancel.cancel();
+        }
+        self.state = AppState::Main;
+        self.status = match self.url_queue.len() {
+            0 => format!("Cancelled summarising {}", self.url_input),
+            queued => format!(
+                "Cancelled summarising {} and {} queued source(s)",
+                self.url_input, queued
+            ),
+        };
+        self.url_queue.clear();
+    }
 
-        // Store in sled
-        let storage = Storage::open(&config.storage.path)?;
-        storage.store(url, summary)?;
+    /// Save a finished summary, send its alerts and show it
+    async fn finish_job(&mut self, mut stored: Box<StoredSummary>, config: &Config) {
+        // It's shown in the detail view straight away
+        stored.unread = false;
+        let event = activity::Event::new(Action::Summarised, &stored);
+        let report = match Storage::open(&config.storage.path) {
+            Ok(storage) => pipeline::store(&mut stored, event, &storage, config).await,
+            Err(e) => Err(e.into()),
+        };
+        let (alerts, warning) = match report {
+            Ok(report) => (report.alerts, report.warnings.into_iter().next()),
+            Err(e) => (Vec::new(), Some(format!("Failed to save summary: {}", e))),
+        };
 
-        // Index in tantivy for full-text search
-        let search_path = config.storage.path.join("search_index");
-        if let Ok(search_index) = SearchIndex::open(&search_path) {
-            if let Err(e) = search_index.index_summary(url, summary) {
-                eprintln!("Warning: Failed to index summary: {}", e);
-            }
+        self.extractive = stored.provider.as_deref() == Some(extractive::PROVIDER);
+        self.lite = stored.is_lite();
+        self.edited = false;
+        self.reading_stats = stored.reading_stats();
+        self.critique = stored.critique.as_ref().map(ToString::to_string);
+        self.tags = stored.tags.clone();
+        self.notes = stored.notes.clone();
+        self.summary = Some(stored.summary);
+        self.source_url = Some(stored.url);
+        self.state = AppState::Main;
+        self.status = if alerts.is_empty() {
+            help_status(&self.markers)
+        } else {
+            let names: Vec<&str> = alerts.iter().map(|s| s.name.as_str()).collect();
+            format!(
+                "{} Matches saved search: {}",
+                self.markers.alert,
+                names.join(", ")
+            )
+        };
+        if let Some(warning) = warning {
+            self.status = warning;
         }
 
-        Ok(())
+        // Reload the list (or re-run the active smart collection) to include the new one
+        self.refresh_list();
+    }
+
+    /// Raise a desktop notification for the job that just finished, if enabled
+    fn notify_job_result(&self) {
+        let Ok(config) = self.load_config() else {
+            return;
+        };
+        // Desktop notifications are best effort; printing would corrupt the TUI
+        let _ = match &self.state {
+            AppState::Error(msg) => notify::desktop(
+                &config.notifications,
+                NotifyEvent::JobFailed,
+                "Summarisation failed",
+                msg,
+            ),
+            _ => notify::desktop(
+                &config.notifications,
+                NotifyEvent::JobCompleted,
+                "Summary ready",
+                self.summary.as_ref().map_or("", |s| s.title.as_str()),
+            ),
+        };
+    }
+}
+
+/// Delete a summary from storage and the search index, logging it with `detail`.
+///
+/// Returns a warning when the deletion couldn't be logged.
+fn delete_summary(
+    config: &Config,
+    stored: &StoredSummary,
+    detail: Option<&str>,
+) -> Result<Option<String>, String> {
+    let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+    storage.delete(&stored.url).map_err(|e| e.to_string())?;
+    let event = activity::Event::new(Action::Deleted, stored);
+    let event = match detail {
+        Some(detail) => event.with_detail(detail),
+        None => event,
+    };
+    let warning = log_after_save(&storage, &event);
+    if let Ok(index) = crate::SearchIndex::open(config.storage.search_index_path()) {
+        // A stale index entry is skipped when its record can't be loaded
+        let _ = index.remove(&stored.url);
+    }
+    Ok(warning)
+}
+
+/// Log `event`, returning a warning rather than failing, since the change it
+/// records has already been made
+fn log_after_save(storage: &Storage, event: &activity::Event) -> Option<String> {
+    storage
+        .log_event(event)
+        .err()
+        .map(|e| format!("Failed to log the event: {}", e))
+}
+
+/// Answer `question` about `stored`, saving the exchange with the record
+async fn ask_question(
+    question: String,
+    mut stored: StoredSummary,
+    config: Config,
+) -> Result<StoredSummary, String> {
+    let answer = pipeline::answer(&question, &stored, &config)
+        .await
+        .map_err(|e| format!("Answering failed: {}", e))?;
+    stored.record_answer(&question, &answer);
+
+    let storage = Storage::open(&config.storage.path).map_err(|e| e.to_string())?;
+    storage.put(&stored).map_err(|e| e.to_string())?;
+    if let Ok(index) = crate::SearchIndex::open_synced(config.storage.search_index_path(), &storage)
+    {
+        // The answer is searchable once the index catches up; nothing to show for it here
+        let _ = index.index_stored(&stored);
     }
+    Ok(stored)
+}
+
+/// Fetch and summarise `input` in the background, reporting each stage to the UI
+async fn run_job(
+    input: String,
+    config: Config,
+    events: UnboundedSender<JobEvent>,
+    cancel: CancellationToken,
+) {
+    let stage = |stage: pipeline::Stage| {
+        // The UI only stops listening when it quits, and then nobody cares
+        let _ = events.send(JobEvent::Stage(stage.to_string()));
+    };
+    let options = pipeline::Options::default();
+    let result = pipeline::summarise_source(&input, &config, &options, &stage, &cancel)
+        .await
+        .map(Box::new)
+        .map_err(|e| e.to_string());
+    let _ = events.send(JobEvent::Done(result));
 }
 
 /// Draw the UI
 fn draw(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(0), Constraint::Length(1)])
@@ -465,15 +2056,30 @@ fn draw(frame: &mut Frame, app: &mut App) {
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
         .split(chunks[0]);
 
-    // Draw summary list on the left
-    draw_summary_list(frame, app, main_chunks[0]);
+    // Draw saved searches and collections above the summary list on the left
+    let sidebar_height = |len: usize| if len == 0 { 0 } else { (len as u16 + 2).min(8) };
+    let sidebar_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(sidebar_height(app.saved_searches.len())),
+            Constraint::Length(sidebar_height(app.collections.len())),
+            Constraint::Min(0),
+        ])
+        .split(main_chunks[0]);
+    if !app.saved_searches.is_empty() {
+        draw_saved_searches(frame, app, sidebar_chunks[0]);
+    }
+    if !app.collections.is_empty() {
+        draw_collections(frame, app, sidebar_chunks[1]);
+    }
+    draw_summary_list(frame, app, sidebar_chunks[2]);
 
     // Draw detail view on the right
     draw_detail_view(frame, app, main_chunks[1]);
 
     // Status bar
-    let status =
-        Paragraph::new(app.status.clone()).style(Style::default().fg(FG_MUTED).bg(BORDER_QUIET));
+    let status = Paragraph::new(app.status.clone())
+        .style(Style::default().fg(theme.fg_muted).bg(theme.border_quiet));
     frame.render_widget(status, chunks[1]);
 
     // Draw URL input dialogue if active
@@ -486,24 +2092,60 @@ fn draw(frame: &mut Frame, app: &mut App) {
         draw_search_dialogue(frame, app);
     }
 
+    // Draw question dialogue if active
+    if app.state == AppState::AskInput {
+        draw_ask_dialogue(frame, app);
+    }
+
+    // Draw tag dialogue if active
+    if app.state == AppState::TagInput {
+        draw_tag_dialogue(frame, app);
+    }
+
+    // Draw snooze dialogue if active
+    if app.state == AppState::SnoozeInput {
+        draw_snooze_dialogue(frame, app);
+    }
+
+    // Draw workspace switcher if active
+    if app.state == AppState::WorkspaceSwitcher {
+        draw_workspace_switcher(frame, app);
+    }
+
+    // Draw the already-stored prompt if active
+    if let AppState::Duplicate { ref url, ref title } = app.state {
+        draw_duplicate(frame, &theme, url, title);
+    }
+
+    // Draw the delete confirmation if active
+    if let AppState::ConfirmDelete { ref url, ref title } = app.state {
+        draw_confirm_delete(frame, &theme, url, title);
+    }
+
+    // Draw activity log if active
+    if app.state == AppState::Activity {
+        draw_activity(frame, app);
+    }
+
     // Draw loading indicator
     if app.state == AppState::Loading {
-        draw_loading(frame);
+        draw_loading(frame, app);
     }
 
     // Draw error dialogue
     if let AppState::Error(ref msg) = app.state {
-        draw_error(frame, msg);
+        draw_error(frame, &theme, msg);
     }
 }
 
 /// Draw the summary list on the left
 fn draw_summary_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
     let is_focused = app.focused_pane == FocusedPane::List;
     let border_color = if is_focused {
-        BORDER_ACTIVE
+        theme.border_active
     } else {
-        BORDER_QUIET
+        theme.border_quiet
     };
 
     let title = if app.is_search_results {
@@ -515,75 +2157,229 @@ fn draw_summary_list(frame: &mut Frame, app: &mut App, area: Rect) {
     } else {
         format!(" Summaries ({}) ", app.stored_summaries.len())
     };
+    let title = match app.workspace {
+        Some(ref workspace) => format!("{}[{}] ", title, workspace.name),
+        None => title,
+    };
+    let title = match app.quick_filter {
+        Some(ref filter) => format!("{}{} {} ", title, app.markers.breadcrumb, filter.label()),
+        None => title,
+    };
+    // Keep the date group of the top row in view once its header scrolls off
+    let title = match app.rows.get(app.list_state.offset()) {
+        Some(ListRow::Entry(i)) => match app.groups.iter().find(|g| g.range.contains(i)) {
+            Some(group) => format!("{}- {} ", title, group.label),
+            None => title,
+        },
+        _ => title,
+    };
 
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .style(Style::default().fg(border_color).bg(BG_DEEP));
+        .style(Style::default().fg(border_color).bg(theme.bg_deep));
 
     if app.stored_summaries.is_empty() {
         let empty_msg = Paragraph::new("No summaries yet.\nPress 'o' to open a URL or file.")
             .block(block)
-            .style(Style::default().fg(FG_MUTED));
+            .style(Style::default().fg(theme.fg_muted));
         frame.render_widget(empty_msg, area);
         return;
     }
 
+    let now = Utc::now();
     let items: Vec<ListItem> = app
-        .stored_summaries
+        .rows
         .iter()
-        .map(|stored| {
+        .map(|row| {
+            let stored = match *row {
+                ListRow::Entry(i) => &app.stored_summaries[i],
+                ListRow::Header(g) => {
+                    let group = &app.groups[g];
+                    let marker = if app.collapsed_groups.contains(&group.label) {
+                        app.markers.collapsed
+                    } else {
+                        app.markers.expanded
+                    };
+                    return ListItem::new(Line::from(Span::styled(
+                        format!("{} {} ({})", marker, group.label, group.range.len()),
+                        Style::default()
+                            .fg(theme.border_active)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+            };
             let title = &stored.summary.title;
             let date = stored.created_at.format("%m/%d %H:%M").to_string();
-            let content = Line::from(vec![
-                Span::styled(truncate_string(title, 20), Style::default().fg(FG_PRIMARY)),
-                Span::styled(format!(" ({})", date), Style::default().fg(FG_MUTED)),
-            ]);
+            let icon = app.icons.icon(stored).map(|icon| {
+                Span::styled(
+                    format!("{} ", icon),
+                    Style::default().fg(theme.border_active),
+                )
+            });
+            let title_style = if stored.is_unread(now) {
+                Style::default()
+                    .fg(theme.fg_primary)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.fg_primary)
+            };
+            let mut content = Line::from_iter(icon.into_iter().chain([
+                Span::styled(
+                    truncate_string(title, 20, app.markers.ellipsis),
+                    title_style,
+                ),
+                Span::styled(format!(" ({})", date), Style::default().fg(theme.fg_muted)),
+            ]));
+            if let Some(minutes) = stored.reading_minutes() {
+                content.push_span(Span::styled(
+                    format!(" {}m", minutes),
+                    Style::default().fg(theme.fg_muted),
+                ));
+            }
+            if stored.starred {
+                content.push_span(Span::styled(
+                    format!(" {}", app.markers.star),
+                    Style::default().fg(theme.border_active),
+                ));
+            }
             ListItem::new(content)
         })
         .collect();
 
     let list = List::new(items)
         .block(block)
-        .highlight_style(
-            Style::default()
-                .fg(BG_DEEP)
-                .bg(BORDER_ACTIVE)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("▶ ");
+        .highlight_style(theme.highlight())
+        .highlight_symbol(app.markers.selected);
 
     frame.render_stateful_widget(list, area, &mut app.list_state);
 }
 
-/// Truncate a string to a maximum length
-fn truncate_string(s: &str, max_len: usize) -> String {
+/// Draw the saved searches (smart collections) sidebar
+fn draw_saved_searches(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let markers = app.markers;
+    let border_color = if app.focused_pane == FocusedPane::Saved {
+        theme.border_active
+    } else {
+        theme.border_quiet
+    };
+
+    let block = Block::default()
+        .title(" Saved Searches ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(border_color).bg(theme.bg_deep));
+
+    let active_name = app.active_saved.as_ref().map(|s| s.name.as_str());
+    let items: Vec<ListItem> = app
+        .saved_searches
+        .iter()
+        .map(|saved| {
+            let marker = if Some(saved.name.as_str()) == active_name {
+                format!("{} ", markers.star)
+            } else {
+                "  ".to_string()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.border_active)),
+                Span::styled(
+                    truncate_string(&saved.name, 24, markers.ellipsis),
+                    Style::default().fg(theme.fg_primary),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(list, area, &mut app.saved_state);
+}
+
+/// Draw the collections sidebar
+fn draw_collections(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let markers = app.markers;
+    let border_color = if app.focused_pane == FocusedPane::Collections {
+        theme.border_active
+    } else {
+        theme.border_quiet
+    };
+
+    let block = Block::default()
+        .title(" Collections ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(border_color).bg(theme.bg_deep));
+
+    let active_name = match app.quick_filter {
+        Some(QuickFilter::Collection { ref name, .. }) => Some(name.as_str()),
+        _ => None,
+    };
+    let items: Vec<ListItem> = app
+        .collections
+        .iter()
+        .map(|collection| {
+            let marker = if Some(collection.name.as_str()) == active_name {
+                format!("{} ", markers.star)
+            } else {
+                "  ".to_string()
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.border_active)),
+                Span::styled(
+                    truncate_string(&collection.name, 20, markers.ellipsis),
+                    Style::default().fg(theme.fg_primary),
+                ),
+                Span::styled(
+                    format!(" ({})", collection.urls.len()),
+                    Style::default().fg(theme.fg_muted),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(list, area, &mut app.collection_state);
+}
+
+/// Truncate a string to a maximum length, ending with `ellipsis` when shortened
+fn truncate_string(s: &str, max_len: usize, ellipsis: &str) -> String {
     if s.chars().count() <= max_len {
         s.to_string()
     } else {
-        format!("{}…", s.chars().take(max_len - 1).collect::<String>())
+        format!(
+            "{}{}",
+            s.chars().take(max_len - 1).collect::<String>(),
+            ellipsis
+        )
     }
 }
 
 /// Draw the detail view on the right
 fn draw_detail_view(frame: &mut Frame, app: &mut App, area: Rect) {
+    let theme = app.theme;
+    let markers = app.markers;
     let is_focused = app.focused_pane == FocusedPane::Detail;
     let border_color = if is_focused {
-        BORDER_ACTIVE
+        theme.border_active
     } else {
-        BORDER_QUIET
+        theme.border_quiet
     };
 
     let title = if is_focused {
-        " Summary Detail (↑↓ scroll) "
+        format!(" Summary Detail ({} scroll) ", markers.arrows)
     } else {
-        " Summary Detail "
+        " Summary Detail ".to_string()
     };
 
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .style(Style::default().fg(border_color).bg(BG_DEEP));
+        .style(Style::default().fg(border_color).bg(theme.bg_deep));
 
     if let Some(ref summary) = app.summary {
         // Display summary
@@ -592,43 +2388,82 @@ fn draw_detail_view(frame: &mut Frame, app: &mut App, area: Rect) {
         // Title
         lines.push(Line::from(vec![Span::styled(
             &summary.title,
-            Style::default().fg(FG_PRIMARY).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(theme.fg_primary)
+                .add_modifier(Modifier::BOLD),
         )]));
         lines.push(Line::from(""));
 
         // Source URL
         if let Some(ref url) = app.source_url {
             lines.push(Line::from(vec![
-                Span::styled("Source: ", Style::default().fg(FG_MUTED)),
-                Span::styled(url, Style::default().fg(BORDER_ACTIVE)),
+                Span::styled("Source: ", Style::default().fg(theme.fg_muted)),
+                Span::styled(url, Style::default().fg(theme.border_active)),
             ]));
+            for stat in [&app.reading_stats, &app.critique].into_iter().flatten() {
+                lines.push(Line::from(Span::styled(
+                    stat,
+                    Style::default().fg(theme.fg_muted),
+                )));
+            }
+            if !app.tags.is_empty() {
+                let tags: Vec<String> = app.tags.iter().map(|t| format!("#{}", t)).collect();
+                lines.push(Line::from(Span::styled(
+                    tags.join(" "),
+                    Style::default().fg(theme.fg_muted),
+                )));
+            }
+            lines.push(Line::from(""));
+        }
+
+        if app.extractive {
+            lines.push(Line::from(Span::styled(
+                "Extractive summary: quoted offline, without an LLM",
+                Style::default().fg(theme.accent_urgent),
+            )));
+            lines.push(Line::from(""));
+        }
+
+        if app.lite {
+            lines.push(Line::from(Span::styled(
+                "Lite capture: title and description only, without an LLM",
+                Style::default().fg(theme.fg_muted),
+            )));
+            lines.push(Line::from(""));
+        }
+
+        if app.edited {
+            lines.push(Line::from(Span::styled(
+                "Edited by hand",
+                Style::default().fg(theme.fg_muted),
+            )));
             lines.push(Line::from(""));
         }
 
         // Conclusion
         lines.push(Line::from(vec![Span::styled(
-            "💡 Conclusion",
+            markers.conclusion,
             Style::default()
-                .fg(BORDER_ACTIVE)
+                .fg(theme.border_active)
                 .add_modifier(Modifier::BOLD),
         )]));
         lines.push(Line::from(Span::styled(
             &summary.conclusion,
-            Style::default().fg(FG_PRIMARY),
+            Style::default().fg(theme.fg_primary),
         )));
         lines.push(Line::from(""));
 
         // Key Points
         lines.push(Line::from(vec![Span::styled(
-            "📌 Key Points",
+            markers.key_points,
             Style::default()
-                .fg(BORDER_ACTIVE)
+                .fg(theme.border_active)
                 .add_modifier(Modifier::BOLD),
         )]));
         for point in &summary.key_points {
             lines.push(Line::from(Span::styled(
-                format!("• {}", point),
-                Style::default().fg(FG_PRIMARY),
+                format!("{} {}", markers.bullet, point),
+                Style::default().fg(theme.fg_primary),
             )));
         }
         lines.push(Line::from(""));
@@ -636,31 +2471,131 @@ fn draw_detail_view(frame: &mut Frame, app: &mut App, area: Rect) {
         // Entities
         if !summary.entities.is_empty() {
             lines.push(Line::from(vec![Span::styled(
-                "🏷️  Entities",
+                markers.entities,
+                Style::default()
+                    .fg(theme.border_active)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            let groups = summary.entities_by_kind();
+            let labelled = groups.len() > 1 || groups[0].0 != EntityKind::Other;
+            for (kind, names) in groups {
+                let mut line = Line::default();
+                if labelled {
+                    line.push_span(Span::styled(
+                        format!("{}: ", kind.plural()),
+                        Style::default().fg(theme.fg_primary),
+                    ));
+                }
+                line.push_span(Span::styled(
+                    names.join(", "),
+                    Style::default().fg(theme.fg_muted),
+                ));
+                lines.push(line);
+            }
+            lines.push(Line::from(""));
+        }
+
+        // Topics
+        if !summary.topics.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                markers.topics,
+                Style::default()
+                    .fg(theme.border_active)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            lines.push(Line::from(Span::styled(
+                summary.topics.join(", "),
+                Style::default().fg(theme.fg_muted),
+            )));
+            lines.push(Line::from(""));
+        }
+
+        // Stance
+        if let Some(ref sentiment) = summary.sentiment {
+            lines.push(Line::from(vec![Span::styled(
+                markers.sentiment,
                 Style::default()
-                    .fg(BORDER_ACTIVE)
+                    .fg(theme.border_active)
                     .add_modifier(Modifier::BOLD),
             )]));
             lines.push(Line::from(Span::styled(
-                summary.entities.join(", "),
-                Style::default().fg(FG_MUTED),
+                sentiment.to_string(),
+                Style::default().fg(theme.fg_primary),
             )));
             lines.push(Line::from(""));
         }
 
+        // Fields declared under [agent.fields]
+        for (heading, items) in summary.custom_sections() {
+            lines.push(Line::from(vec![Span::styled(
+                heading,
+                Style::default()
+                    .fg(theme.border_active)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            for item in items {
+                lines.push(Line::from(Span::styled(
+                    format!("{} {}", markers.bullet, item),
+                    Style::default().fg(theme.fg_primary),
+                )));
+            }
+            lines.push(Line::from(""));
+        }
+
         // Action Items
         if !summary.action_items.is_empty() {
             lines.push(Line::from(vec![Span::styled(
-                "✅ Action Items",
+                markers.action_items,
                 Style::default()
-                    .fg(BORDER_ACTIVE)
+                    .fg(theme.border_active)
                     .add_modifier(Modifier::BOLD),
             )]));
             for item in &summary.action_items {
                 lines.push(Line::from(Span::styled(
-                    format!("• {}", item),
-                    Style::default().fg(FG_PRIMARY),
+                    format!("{} {}", markers.bullet, item),
+                    Style::default().fg(theme.fg_primary),
+                )));
+            }
+            lines.push(Line::from(""));
+        }
+
+        // The reader's own notes
+        if let Some(ref notes) = app.notes {
+            lines.push(Line::from(vec![Span::styled(
+                "Notes",
+                Style::default()
+                    .fg(theme.border_active)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            for line in notes.lines() {
+                lines.push(Line::from(Span::styled(
+                    line,
+                    Style::default().fg(theme.fg_primary),
+                )));
+            }
+            lines.push(Line::from(""));
+        }
+
+        // Questions and answers
+        if !app.qa_history.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "Q&A",
+                Style::default()
+                    .fg(theme.border_active)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            for qa in &app.qa_history {
+                lines.push(Line::from(Span::styled(
+                    format!("Q: {}", qa.question),
+                    Style::default()
+                        .fg(theme.fg_primary)
+                        .add_modifier(Modifier::BOLD),
+                )));
+                lines.push(Line::from(Span::styled(
+                    format!("A: {}", qa.answer),
+                    Style::default().fg(theme.fg_primary),
                 )));
+                lines.push(Line::from(""));
             }
         }
 
@@ -675,33 +2610,52 @@ fn draw_detail_view(frame: &mut Frame, app: &mut App, area: Rect) {
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Welcome to Summera!",
-                Style::default().fg(FG_PRIMARY).add_modifier(Modifier::BOLD),
+                Style::default()
+                    .fg(theme.fg_primary)
+                    .add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
             Line::from(Span::styled(
                 "Intelligent webpage summarisation powered by LLMs.",
-                Style::default().fg(FG_MUTED),
+                Style::default().fg(theme.fg_muted),
             )),
             Line::from(""),
             Line::from(vec![
-                Span::styled("  o    ", Style::default().fg(BORDER_ACTIVE)),
-                Span::styled("Open a URL to summarise", Style::default().fg(FG_PRIMARY)),
+                Span::styled("  o    ", Style::default().fg(theme.border_active)),
+                Span::styled(
+                    "Open a URL to summarise",
+                    Style::default().fg(theme.fg_primary),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  f    ", Style::default().fg(theme.border_active)),
+                Span::styled("Search summaries", Style::default().fg(theme.fg_primary)),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    format!("  {:<5}", markers.arrows),
+                    Style::default().fg(theme.border_active),
+                ),
+                Span::styled("Navigate summaries", Style::default().fg(theme.fg_primary)),
             ]),
             Line::from(vec![
-                Span::styled("  f    ", Style::default().fg(BORDER_ACTIVE)),
-                Span::styled("Search summaries", Style::default().fg(FG_PRIMARY)),
+                Span::styled("  a    ", Style::default().fg(theme.border_active)),
+                Span::styled(
+                    "Ask about the selected summary",
+                    Style::default().fg(theme.fg_primary),
+                ),
             ]),
             Line::from(vec![
-                Span::styled("  ↑↓   ", Style::default().fg(BORDER_ACTIVE)),
-                Span::styled("Navigate summaries", Style::default().fg(FG_PRIMARY)),
+                Span::styled("  w    ", Style::default().fg(theme.border_active)),
+                Span::styled("Switch workspace", Style::default().fg(theme.fg_primary)),
             ]),
             Line::from(vec![
-                Span::styled("  Tab  ", Style::default().fg(BORDER_ACTIVE)),
-                Span::styled("Switch panes", Style::default().fg(FG_PRIMARY)),
+                Span::styled("  Tab  ", Style::default().fg(theme.border_active)),
+                Span::styled("Switch panes", Style::default().fg(theme.fg_primary)),
             ]),
             Line::from(vec![
-                Span::styled("  q    ", Style::default().fg(BORDER_ACTIVE)),
-                Span::styled("Quit", Style::default().fg(FG_PRIMARY)),
+                Span::styled("  q    ", Style::default().fg(theme.border_active)),
+                Span::styled("Quit", Style::default().fg(theme.fg_primary)),
             ]),
         ];
         let paragraph = Paragraph::new(welcome).block(block);
@@ -711,6 +2665,7 @@ fn draw_detail_view(frame: &mut Frame, app: &mut App, area: Rect) {
 
 /// Draw the URL input dialogue
 fn draw_url_dialogue(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
     let area = centered_rect(70, 30, frame.area());
 
     // Clear the area behind the dialogue
@@ -719,7 +2674,7 @@ fn draw_url_dialogue(frame: &mut Frame, app: &App) {
     let block = Block::default()
         .title(" Enter URL or File Path ")
         .borders(Borders::ALL)
-        .style(Style::default().fg(BORDER_ACTIVE).bg(BG_DEEP));
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -735,25 +2690,45 @@ fn draw_url_dialogue(frame: &mut Frame, app: &App) {
         ])
         .split(inner);
 
-    let label = Paragraph::new("URL or path (PDF, PPTX):").style(Style::default().fg(FG_MUTED));
+    let label = Paragraph::new("URLs (paste several at once) or a path (PDF, PPTX):")
+        .style(Style::default().fg(theme.fg_muted));
     frame.render_widget(label, chunks[0]);
 
     let input = Paragraph::new(format!(" {}", app.url_input))
-        .style(Style::default().fg(FG_PRIMARY))
+        .style(Style::default().fg(theme.fg_primary))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(BORDER_ACTIVE)),
+                .border_style(Style::default().fg(theme.border_active)),
         );
     frame.render_widget(input, chunks[2]);
 
-    let help =
-        Paragraph::new("Press Enter to submit, Esc to cancel").style(Style::default().fg(FG_MUTED));
+    let length = match app.length {
+        Some(length) => length.to_string(),
+        None => "as configured".to_string(),
+    };
+    let persona = match app.persona_preset {
+        Some(preset) => preset.to_string(),
+        None => "as configured".to_string(),
+    };
+    let help = match app.url_error {
+        Some(ref error) => Paragraph::new(error.as_str()).style(
+            Style::default()
+                .fg(theme.accent_urgent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        None => Paragraph::new(format!(
+            "Enter to submit, Tab length ({}), Shift+Tab persona ({}), Esc to cancel",
+            length, persona
+        ))
+        .style(Style::default().fg(theme.fg_muted)),
+    };
     frame.render_widget(help, chunks[4]);
 }
 
 /// Draw the search input dialogue
 fn draw_search_dialogue(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
     let area = centered_rect(70, 30, frame.area());
 
     // Clear the area behind the dialogue
@@ -762,7 +2737,7 @@ fn draw_search_dialogue(frame: &mut Frame, app: &App) {
     let block = Block::default()
         .title(" Search Summaries ")
         .borders(Borders::ALL)
-        .style(Style::default().fg(BORDER_ACTIVE).bg(BG_DEEP));
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -778,54 +2753,381 @@ fn draw_search_dialogue(frame: &mut Frame, app: &App) {
         ])
         .split(inner);
 
-    let label = Paragraph::new("Search:").style(Style::default().fg(FG_MUTED));
+    let label = Paragraph::new("Search:").style(Style::default().fg(theme.fg_muted));
     frame.render_widget(label, chunks[0]);
 
     let input = Paragraph::new(format!(" {}", app.search_input))
-        .style(Style::default().fg(FG_PRIMARY))
+        .style(Style::default().fg(theme.fg_primary))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(BORDER_ACTIVE)),
+                .border_style(Style::default().fg(theme.border_active)),
         );
     frame.render_widget(input, chunks[2]);
 
     let help =
         Paragraph::new("Enter to search, Esc to cancel. Searches titles, content & entities.")
-            .style(Style::default().fg(FG_MUTED));
+            .style(Style::default().fg(theme.fg_muted));
     frame.render_widget(help, chunks[4]);
 }
 
-/// Draw loading indicator
-fn draw_loading(frame: &mut Frame) {
-    let area = centered_rect(40, 10, frame.area());
+/// Draw the dialogue for a question about the selected summary
+fn draw_ask_dialogue(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let area = centered_rect(70, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Ask About This Summary ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Label
+            Constraint::Length(1), // Spacing
+            Constraint::Length(3), // Input field
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help text
+        ])
+        .split(inner);
+
+    let title = app.summary.as_ref().map_or("", |s| s.title.as_str());
+    let label = Paragraph::new(format!(
+        "Question about '{}':",
+        truncate_string(title, 40, app.markers.ellipsis)
+    ))
+    .style(Style::default().fg(theme.fg_muted));
+    frame.render_widget(label, chunks[0]);
+
+    let input = Paragraph::new(format!(" {}", app.ask_input))
+        .style(Style::default().fg(theme.fg_primary))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_active)),
+        );
+    frame.render_widget(input, chunks[2]);
+
+    let help = Paragraph::new("Enter to ask, Esc to cancel. Answers use the page's stored text.")
+        .style(Style::default().fg(theme.fg_muted));
+    frame.render_widget(help, chunks[4]);
+}
+
+/// Draw the tag dialogue
+fn draw_tag_dialogue(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let area = centered_rect(70, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Edit Tags ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Label
+            Constraint::Length(1), // Spacing
+            Constraint::Length(3), // Input field
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help text
+        ])
+        .split(inner);
+
+    let title = app.summary.as_ref().map_or("", |s| s.title.as_str());
+    let label = Paragraph::new(format!(
+        "Tags for '{}':",
+        truncate_string(title, 40, app.markers.ellipsis)
+    ))
+    .style(Style::default().fg(theme.fg_muted));
+    frame.render_widget(label, chunks[0]);
+
+    let input = Paragraph::new(format!(" {}", app.tag_input))
+        .style(Style::default().fg(theme.fg_primary))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_active)),
+        );
+    frame.render_widget(input, chunks[2]);
+
+    let help = Paragraph::new("Separate tags with commas. Enter to save, Esc to cancel.")
+        .style(Style::default().fg(theme.fg_muted));
+    frame.render_widget(help, chunks[4]);
+}
+
+/// Draw the snooze dialogue
+fn draw_snooze_dialogue(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let area = centered_rect(70, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Snooze ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Label
+            Constraint::Length(1), // Spacing
+            Constraint::Length(3), // Input field
+            Constraint::Length(1), // Spacing
+            Constraint::Length(1), // Help text
+        ])
+        .split(inner);
+
+    let title = app.summary.as_ref().map_or("", |s| s.title.as_str());
+    let label = Paragraph::new(format!(
+        "Bring back '{}' on:",
+        truncate_string(title, 40, app.markers.ellipsis)
+    ))
+    .style(Style::default().fg(theme.fg_muted));
+    frame.render_widget(label, chunks[0]);
+
+    let input = Paragraph::new(format!(" {}", app.snooze_input))
+        .style(Style::default().fg(theme.fg_primary))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_active)),
+        );
+    frame.render_widget(input, chunks[2]);
+
+    let help =
+        Paragraph::new("tomorrow, 2026-11-01 or e.g. 3d, 2w, 1m. Enter to snooze, Esc to cancel.")
+            .style(Style::default().fg(theme.fg_muted));
+    frame.render_widget(help, chunks[4]);
+}
+
+/// Draw the workspace switcher popup
+fn draw_workspace_switcher(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Switch Workspace (Enter to switch, Esc to cancel) ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    let items: Vec<ListItem> = app
+        .workspaces
+        .iter()
+        .map(|workspace| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<12}", workspace.name),
+                    Style::default().fg(theme.fg_primary),
+                ),
+                Span::styled(
+                    workspace.storage.path.display().to_string(),
+                    Style::default().fg(theme.fg_muted),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme.highlight())
+        .highlight_symbol(app.markers.selected);
+
+    frame.render_stateful_widget(list, area, &mut app.workspace_state);
+}
+
+/// Draw the prompt shown when the URL entered is already stored
+fn draw_duplicate(frame: &mut Frame, theme: &Theme, url: &str, title: &str) {
+    let area = centered_rect(60, 25, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Already stored ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    let text = vec![
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(theme.fg_primary)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(url, Style::default().fg(theme.fg_muted))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Enter to show it, 'r' to summarise it again, Esc to cancel",
+            Style::default().fg(theme.fg_muted),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// Draw the prompt asking before a summary is deleted
+fn draw_confirm_delete(frame: &mut Frame, theme: &Theme, url: &str, title: &str) {
+    let area = centered_rect(60, 25, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Delete summary? ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.accent_urgent).bg(theme.bg_deep));
+
+    let text = vec![
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(theme.fg_primary)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(url, Style::default().fg(theme.fg_muted))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "'y' to delete it from the archive and search index, Esc to cancel",
+            Style::default().fg(theme.fg_muted),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// Draw the activity log popup
+fn draw_activity(frame: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    let area = centered_rect(80, 70, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
-        .title(" Loading ")
+        .title(format!(
+            " Activity ({}) - Esc to close ",
+            app.activity.len()
+        ))
         .borders(Borders::ALL)
-        .style(Style::default().fg(BORDER_ACTIVE).bg(BG_DEEP));
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    let items: Vec<ListItem> = app
+        .activity
+        .iter()
+        .map(|event| {
+            let mut line = Line::from(vec![
+                Span::styled(
+                    format!("{} ", event.at.format("%Y-%m-%d %H:%M")),
+                    Style::default().fg(theme.fg_muted),
+                ),
+                Span::styled(
+                    format!("{:<10} ", event.action.to_string()),
+                    Style::default().fg(theme.border_active),
+                ),
+                Span::styled(event.title.clone(), Style::default().fg(theme.fg_primary)),
+            ]);
+            if let Some(ref detail) = event.detail {
+                line.push_span(Span::styled(
+                    format!(" ({})", detail),
+                    Style::default().fg(theme.fg_muted),
+                ));
+            }
+            ListItem::new(vec![
+                line,
+                Line::from(Span::styled(
+                    format!("  {}", event.url),
+                    Style::default().fg(theme.fg_muted),
+                )),
+            ])
+        })
+        .collect();
 
-    let text = Paragraph::new("Please wait...")
+    let list = List::new(items)
         .block(block)
-        .style(Style::default().fg(FG_MUTED));
+        .highlight_style(theme.highlight())
+        .highlight_symbol(app.markers.selected);
+
+    frame.render_stateful_widget(list, area, &mut app.activity_state);
+}
+
+/// Draw loading indicator
+fn draw_loading(frame: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let markers = app.markers;
+    let area = centered_rect(60, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let (stages, elapsed) = match app.job {
+        Some(ref job) => (job.stages.as_slice(), job.started.elapsed()),
+        None => (&[][..], std::time::Duration::ZERO),
+    };
+    let block = Block::default()
+        .title(match app.url_queue.len() {
+            0 => format!(" Summarising ({}s) - Esc to cancel ", elapsed.as_secs()),
+            queued => format!(
+                " Summarising ({}s, {} queued) - Esc to cancel ",
+                elapsed.as_secs(),
+                queued
+            ),
+        })
+        .borders(Borders::ALL)
+        .style(Style::default().fg(theme.border_active).bg(theme.bg_deep));
+
+    // Earlier stages are done; the last one is still running
+    let frame_index = (elapsed.as_millis() / 100) as usize % markers.spinner.len();
+    let mut lines: Vec<Line> = stages
+        .iter()
+        .enumerate()
+        .map(|(i, stage)| {
+            if i + 1 == stages.len() {
+                Line::from(Span::styled(
+                    format!("{} {}", markers.spinner[frame_index], stage),
+                    Style::default().fg(theme.fg_primary),
+                ))
+            } else {
+                Line::from(Span::styled(
+                    format!("{} {}", markers.done, stage),
+                    Style::default().fg(theme.fg_muted),
+                ))
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("{} Starting", markers.spinner[frame_index]),
+            Style::default().fg(theme.fg_muted),
+        )));
+    }
+    // Keep the latest stages in view
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = lines.len().saturating_sub(visible);
+
+    let text = Paragraph::new(lines.split_off(skip)).block(block);
     frame.render_widget(text, area);
 }
 
 /// Draw error dialogue
-fn draw_error(frame: &mut Frame, message: &str) {
+fn draw_error(frame: &mut Frame, theme: &Theme, message: &str) {
     let area = centered_rect(60, 20, frame.area());
     frame.render_widget(Clear, area);
 
     let block = Block::default()
         .title(" Error ")
         .borders(Borders::ALL)
-        .style(Style::default().fg(ACCENT_URGENT).bg(BG_DEEP));
+        .style(Style::default().fg(theme.accent_urgent).bg(theme.bg_deep));
 
     let text = Paragraph::new(message)
         .block(block)
         .wrap(Wrap { trim: false })
-        .style(Style::default().fg(FG_PRIMARY));
+        .style(Style::default().fg(theme.fg_primary));
     frame.render_widget(text, area);
 }
 
@@ -850,17 +3152,66 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-/// Run the TUI application
-pub async fn run() -> anyhow::Result<()> {
-    // Setup terminal
+/// Open `text` in $EDITOR with the TUI suspended, returning the edited text or
+/// why the editor couldn't run
+fn edit_suspended(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    text: &str,
+) -> io::Result<Result<String, String>> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, Show)?;
+    let edited = edit::edit(text);
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(edited.map_err(|e| format!("Couldn't run the editor: {}", e)))
+}
+
+/// Puts the terminal into raw mode on the alternate screen and restores it on drop.
+///
+/// Restoring in `Drop` means the user's shell is left usable even when the TUI
+/// bails out with an error or panics. Mouse capture is deliberately not enabled:
+/// nothing uses mouse events, and capturing them breaks native text selection
+/// and right-click paste in the Windows console.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        enable_raw_mode()?;
+        if let Err(e) = execute!(io::stdout(), EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(e);
+        }
+        // Without it a pasted newline is an Enter; the legacy Windows console can't do it
+        let _ = execute!(io::stdout(), EnableBracketedPaste);
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), DisableBracketedPaste);
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+    }
+}
+
+/// Run the TUI application, using ASCII markers if `ascii` is set or configured
+pub async fn run(ascii: bool) -> anyhow::Result<()> {
+    // Setup terminal
+    let _guard = TerminalGuard::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
     let mut app = App::new();
+    app.ascii = ascii;
+    app.apply_ui(&Config::load().map(|c| c.ui).unwrap_or_default());
+    app.status = help_status(&app.markers);
+    // Without a watcher, edits to the config apply from the next start
+    let mut watcher = Config::find_config_file()
+        .ok()
+        .and_then(|path| ConfigWatcher::new(&path).ok());
 
     // Load saved summaries
     app.load_summaries();
@@ -870,18 +3221,32 @@ pub async fn run() -> anyhow::Result<()> {
         // Draw UI
         terminal.draw(|f| draw(f, &mut app))?;
 
-        // Handle loading state - need to process async
-        if app.state == AppState::Loading {
-            app.fetch_and_summarise().await;
-            continue;
+        // Start the job behind the Loading view, and pick up its progress
+        if app.state == AppState::Loading && app.job.is_none() {
+            app.start_job();
+        }
+        app.poll_job().await;
+        app.poll_ask().await;
+        if watcher.as_mut().is_some_and(ConfigWatcher::poll) {
+            app.reload_config();
         }
 
         // Poll for events with a timeout
         if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+            if let Event::Paste(ref text) = event {
+                app.handle_paste(text);
+            }
+            if let Event::Key(key) = event {
                 if key.kind == KeyEventKind::Press {
                     app.handle_key(key.code);
                 }
+                if std::mem::take(&mut app.edit_requested) {
+                    app.edit_selected(&mut terminal)?;
+                }
+                if std::mem::take(&mut app.notes_requested) {
+                    app.edit_notes(&mut terminal)?;
+                }
             }
         }
 
@@ -890,14 +3255,6 @@ pub async fn run() -> anyhow::Result<()> {
         }
     }
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-
+    // The terminal is restored when `_guard` is dropped
     Ok(())
 }
@@ -0,0 +1,247 @@
+//! Wikipedia articles via the MediaWiki API.
+//!
+//! Scraping an article picks up infoboxes, navboxes and footnote markers, so
+//! the article is read as plain text from the `extracts` API instead, with
+//! the reference and link sections at the end dropped. The external links the
+//! article cites can be kept with the summary (`[wikipedia] references`).
+
+use crate::scraper::{self, WebContent};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Trailing sections that list sources rather than say anything
+const SKIPPED_SECTIONS: &[&str] = &[
+    "See also",
+    "Notes",
+    "References",
+    "Citations",
+    "Sources",
+    "Bibliography",
+    "Further reading",
+    "External links",
+];
+
+/// Namespaces whose pages aren't articles
+const NAMESPACES: &[&str] = &[
+    "Special:",
+    "File:",
+    "Category:",
+    "Template:",
+    "Help:",
+    "Portal:",
+    "Talk:",
+    "User:",
+    "Wikipedia:",
+];
+
+#[derive(Error, Debug)]
+pub enum WikipediaError {
+    #[error("failed to fetch from Wikipedia: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("no Wikipedia article titled '{0}'")]
+    NotFound(String),
+}
+
+/// An article, as named by its URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Article {
+    /// The wiki's host, e.g. "en.wikipedia.org"
+    pub host: String,
+    /// Title with spaces, e.g. "Rust (programming language)"
+    pub title: String,
+}
+
+impl Article {
+    /// Recognise `https://<lang>.wikipedia.org/wiki/<Title>` links, mobile ones included
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("https://")?;
+        let (host, path) = rest.split_once('/')?;
+        let lang = host.strip_suffix(".wikipedia.org")?;
+        let lang = lang.strip_suffix(".m").unwrap_or(lang);
+        let title = path.strip_prefix("wiki/")?.split(['?', '#']).next()?;
+        let title = percent_decode(title).replace('_', " ");
+        if title.is_empty() || NAMESPACES.iter().any(|ns| title.starts_with(ns)) {
+            return None;
+        }
+        Some(Self {
+            host: format!("{}.wikipedia.org", lang),
+            title,
+        })
+    }
+
+    /// Canonical link to the article
+    pub fn url(&self) -> String {
+        format!(
+            "https://{}/wiki/{}",
+            self.host,
+            self.title.replace(' ', "_")
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    query: Query,
+}
+
+#[derive(Debug, Deserialize)]
+struct Query {
+    pages: Vec<Page>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    title: String,
+    #[serde(default)]
+    missing: bool,
+    extract: Option<String>,
+    #[serde(default)]
+    extlinks: Vec<Link>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Link {
+    url: String,
+}
+
+/// Fetch an article as text, and the external links it cites if `references` is set
+pub async fn fetch_article(
+    article: &Article,
+    references: bool,
+) -> Result<(WebContent, Vec<String>), WikipediaError> {
+    let props = if references {
+        "extracts|extlinks"
+    } else {
+        "extracts"
+    };
+    let response: Response = scraper::create_client()?
+        .get(format!("https://{}/w/api.php", article.host))
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("redirects", "1"),
+            ("prop", props),
+            ("explaintext", "1"),
+            ("exsectionformat", "wiki"),
+            ("ellimit", "max"),
+            ("titles", &article.title),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let page = response
+        .query
+        .pages
+        .into_iter()
+        .next()
+        .filter(|page| !page.missing)
+        .ok_or_else(|| WikipediaError::NotFound(article.title.clone()))?;
+    let text = strip_trailing_sections(page.extract.as_deref().unwrap_or_default());
+    if text.trim().is_empty() {
+        return Err(WikipediaError::NotFound(article.title.clone()));
+    }
+    // Redirects resolve to the article's real title
+    let resolved = Article {
+        host: article.host.clone(),
+        title: page.title.clone(),
+    };
+    let links = page
+        .extlinks
+        .into_iter()
+        .map(|link| match link.url.strip_prefix("//") {
+            Some(rest) => format!("https://{}", rest),
+            None => link.url,
+        })
+        .collect();
+    Ok((
+        WebContent {
+            url: resolved.url(),
+            title: Some(page.title),
+            text,
+            description: None,
+        },
+        links,
+    ))
+}
+
+/// Cut the article at the first of the reference and link sections
+fn strip_trailing_sections(extract: &str) -> String {
+    let mut kept = Vec::new();
+    for line in extract.lines() {
+        let heading = line.trim().trim_matches('=').trim();
+        if line.trim_start().starts_with("==") && SKIPPED_SECTIONS.contains(&heading) {
+            break;
+        }
+        kept.push(line);
+    }
+    kept.join("\n").trim().to_string()
+}
+
+/// Decode `%XX` escapes, e.g. in a title from a URL
+fn percent_decode(text: &str) -> String {
+    let raw = text.as_bytes();
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let escaped = match raw.get(i + 1..i + 3) {
+            Some(hex) if raw[i] == b'%' => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            None => {
+                bytes.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_article_urls() {
+        let article =
+            Article::parse("https://en.m.wikipedia.org/wiki/Rust_(programming_language)#History")
+                .unwrap();
+        assert_eq!(article.host, "en.wikipedia.org");
+        assert_eq!(article.title, "Rust (programming language)");
+        assert_eq!(
+            article.url(),
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        );
+        assert_eq!(
+            Article::parse("https://sv.wikipedia.org/wiki/G%C3%B6teborg")
+                .unwrap()
+                .title,
+            "Göteborg"
+        );
+        assert_eq!(
+            Article::parse("https://en.wikipedia.org/wiki/Special:Random"),
+            None
+        );
+        assert_eq!(Article::parse("https://example.org/wiki/Rust"), None);
+    }
+
+    #[test]
+    fn drops_reference_sections() {
+        let extract = "Rust is a language.\n\n== History ==\nIt began in 2006.\n\n\
+            == See also ==\nC++\n\n== References ==\n1. A book";
+        assert_eq!(
+            strip_trailing_sections(extract),
+            "Rust is a language.\n\n== History ==\nIt began in 2006."
+        );
+    }
+}
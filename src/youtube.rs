@@ -0,0 +1,341 @@
+//! YouTube videos, summarised from their transcripts.
+//!
+//! The watch page is mostly script, so scraping it gives little more than the
+//! title. Instead the player data embedded in the page is read for the title,
+//! channel and length, along with the caption tracks the video has. Captions
+//! written by the uploader are preferred over YouTube's automatic ones, and
+//! English over other languages, and the chosen track is fetched and joined into
+//! one paragraph per minute of video.
+
+use crate::scraper::{self, WebContent};
+use crate::storage::VideoDetails;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Prompt used instead of `agent.prompt` for video transcripts
+pub const TRANSCRIPT_PROMPT: &str = "Summarise this video from its transcript. In the \
+conclusion, state what the video shows or argues. List the main points, in the order they \
+come up, as key points, and anything the viewer is asked or shown how to do as action items. \
+The transcript may be automatically generated: correct obviously misheard names and terms, \
+and ignore filler words, sponsor segments and requests to like or subscribe.";
+
+/// Hosts that serve YouTube videos
+const HOSTS: &[&str] = &["youtube.com", "m.youtube.com", "music.youtube.com"];
+
+/// Where the player data starts in a watch page
+const PLAYER_RESPONSE: &str = "ytInitialPlayerResponse = ";
+
+/// Length of the transcript paragraphs, in milliseconds of video
+const PARAGRAPH_MS: u64 = 60_000;
+
+#[derive(Error, Debug)]
+pub enum YoutubeError {
+    #[error("failed to fetch video: {0}")]
+    FetchError(#[from] reqwest::Error),
+    #[error("{url} returned {status}")]
+    Rejected {
+        status: reqwest::StatusCode,
+        url: String,
+    },
+    #[error("no player data on the watch page of {0}")]
+    NoPlayer(String),
+    #[error("video {id} is unavailable: {reason}")]
+    Unavailable { id: String, reason: String },
+    #[error("video {0} has no captions to summarise")]
+    NoCaptions(String),
+}
+
+/// A video, as named by any of the URLs YouTube uses for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Video {
+    pub id: String,
+}
+
+impl Video {
+    /// Recognise `watch?v=`, `youtu.be/`, `/shorts/`, `/live/` and `/embed/` links
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/')?;
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        let path = path.split('#').next()?;
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let id = if host == "youtu.be" {
+            match segments[..] {
+                [id] => id,
+                _ => return None,
+            }
+        } else if HOSTS.contains(&host) {
+            match segments[..] {
+                ["watch"] => query
+                    .split('&')
+                    .find_map(|param| param.strip_prefix("v="))?,
+                ["shorts" | "live" | "embed", id] => id,
+                _ => return None,
+            }
+        } else {
+            return None;
+        };
+        let valid = id.len() == 11
+            && id
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        valid.then(|| Self { id: id.to_string() })
+    }
+
+    /// Canonical watch page URL, the key the summary is stored under
+    pub fn url(&self) -> String {
+        format!("https://www.youtube.com/watch?v={}", self.id)
+    }
+}
+
+/// A video's transcript, and who published it
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    /// The transcript as text, with the video's description as the page's own
+    pub content: WebContent,
+    pub details: VideoDetails,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerResponse {
+    #[serde(default)]
+    playability_status: Option<Playability>,
+    video_details: Option<Details>,
+    captions: Option<Captions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Playability {
+    status: String,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Details {
+    title: String,
+    author: String,
+    length_seconds: String,
+    #[serde(default)]
+    short_description: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Captions {
+    player_captions_tracklist_renderer: Tracklist,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tracklist {
+    #[serde(default)]
+    caption_tracks: Vec<CaptionTrack>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaptionTrack {
+    base_url: String,
+    language_code: String,
+    /// "asr" for automatic captions
+    kind: Option<String>,
+}
+
+impl CaptionTrack {
+    fn is_automatic(&self) -> bool {
+        self.kind.as_deref() == Some("asr")
+    }
+}
+
+/// Captions in YouTube's `json3` format
+#[derive(Debug, Deserialize)]
+struct Timedtext {
+    #[serde(default)]
+    events: Vec<CaptionEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaptionEvent {
+    #[serde(default)]
+    t_start_ms: u64,
+    #[serde(default)]
+    segs: Vec<Segment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Segment {
+    #[serde(default)]
+    utf8: String,
+}
+
+/// Fetch the video's details and transcript
+pub async fn fetch_transcript(video: &Video) -> Result<Transcript, YoutubeError> {
+    let client = scraper::create_client()?;
+    // The consent cookie skips the cookie banner served in place of the page in the EU
+    let page = get(client
+        .get(video.url())
+        .header(reqwest::header::ACCEPT_LANGUAGE, "en")
+        .header(reqwest::header::COOKIE, "CONSENT=YES+1"))
+    .await?;
+    let player = parse_player(&page).ok_or_else(|| YoutubeError::NoPlayer(video.url()))?;
+    if let Some(playability) = player
+        .playability_status
+        .filter(|p| p.status != "OK" && player.video_details.is_none())
+    {
+        return Err(YoutubeError::Unavailable {
+            id: video.id.clone(),
+            reason: playability.reason.unwrap_or(playability.status),
+        });
+    }
+    let details = player
+        .video_details
+        .ok_or_else(|| YoutubeError::NoPlayer(video.url()))?;
+    let tracks = player
+        .captions
+        .map(|c| c.player_captions_tracklist_renderer.caption_tracks)
+        .unwrap_or_default();
+    let track = choose_track(&tracks).ok_or_else(|| YoutubeError::NoCaptions(video.id.clone()))?;
+
+    let captions = get(client.get(format!("{}&fmt=json3", track.base_url))).await?;
+    let timedtext: Timedtext =
+        serde_json::from_str(&captions).map_err(|_| YoutubeError::NoCaptions(video.id.clone()))?;
+    let text = transcript_text(&timedtext);
+    if text.is_empty() {
+        return Err(YoutubeError::NoCaptions(video.id.clone()));
+    }
+    let description = details.short_description.trim();
+    Ok(Transcript {
+        content: WebContent {
+            url: video.url(),
+            title: Some(details.title),
+            text,
+            description: (!description.is_empty()).then(|| description.to_string()),
+        },
+        details: VideoDetails {
+            channel: details.author,
+            duration_secs: details.length_seconds.parse().unwrap_or(0),
+        },
+    })
+}
+
+/// GET a page as text, turning error statuses into [`YoutubeError::Rejected`]
+async fn get(request: reqwest::RequestBuilder) -> Result<String, YoutubeError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(YoutubeError::Rejected {
+            status,
+            url: response.url().to_string(),
+        });
+    }
+    Ok(response.text().await?)
+}
+
+/// The player data assigned to `ytInitialPlayerResponse` in a watch page
+fn parse_player(page: &str) -> Option<PlayerResponse> {
+    let start = page.find(PLAYER_RESPONSE)? + PLAYER_RESPONSE.len();
+    // The object is followed by more script, so read just the first JSON value
+    serde_json::Deserializer::from_str(&page[start..])
+        .into_iter::<PlayerResponse>()
+        .next()?
+        .ok()
+}
+
+/// The uploader's captions before automatic ones, and English before other languages
+fn choose_track(tracks: &[CaptionTrack]) -> Option<&CaptionTrack> {
+    tracks
+        .iter()
+        .min_by_key(|track| (track.is_automatic(), !track.language_code.starts_with("en")))
+}
+
+/// The captions as text, one paragraph per minute of video
+fn transcript_text(timedtext: &Timedtext) -> String {
+    let mut paragraphs: Vec<(u64, String)> = Vec::new();
+    for event in &timedtext.events {
+        let line: String = event.segs.iter().map(|seg| seg.utf8.as_str()).collect();
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            continue;
+        }
+        let minute = event.t_start_ms / PARAGRAPH_MS;
+        match paragraphs.last_mut() {
+            Some((last, paragraph)) if *last == minute => {
+                paragraph.push(' ');
+                paragraph.push_str(&line);
+            }
+            _ => paragraphs.push((minute, line)),
+        }
+    }
+    paragraphs
+        .into_iter()
+        .map(|(_, paragraph)| paragraph)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_video_urls() {
+        let video = Video {
+            id: "dQw4w9WgXcQ".to_string(),
+        };
+        for url in [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42s",
+            "https://m.youtube.com/watch?feature=share&v=dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ?si=abc",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+        ] {
+            assert_eq!(Video::parse(url).as_ref(), Some(&video), "{}", url);
+        }
+        assert_eq!(video.url(), "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(Video::parse("https://www.youtube.com/@rustlang"), None);
+        assert_eq!(Video::parse("https://www.youtube.com/watch?v=short"), None);
+        assert_eq!(Video::parse("https://vimeo.com/76979871"), None);
+    }
+
+    #[test]
+    fn reads_player_data_and_joins_captions_by_minute() {
+        let page = r#"<script>var ytInitialPlayerResponse = {"playabilityStatus":{"status":"OK"},
+            "videoDetails":{"title":"Rust in 100 Seconds","author":"Fireship",
+            "lengthSeconds":"150","shortDescription":"Rust, explained."},
+            "captions":{"playerCaptionsTracklistRenderer":{"captionTracks":[
+              {"baseUrl":"https://www.youtube.com/api/timedtext?v=1&lang=en&kind=asr","languageCode":"en","kind":"asr"},
+              {"baseUrl":"https://www.youtube.com/api/timedtext?v=1&lang=de","languageCode":"de"},
+              {"baseUrl":"https://www.youtube.com/api/timedtext?v=1&lang=en-GB","languageCode":"en-GB"}
+            ]}}};var meta = {};</script>"#;
+        let player = parse_player(page).unwrap();
+        let details = player.video_details.unwrap();
+        assert_eq!(details.author, "Fireship");
+        assert_eq!(details.length_seconds, "150");
+        let tracks = player
+            .captions
+            .unwrap()
+            .player_captions_tracklist_renderer
+            .caption_tracks;
+        assert_eq!(choose_track(&tracks).unwrap().language_code, "en-GB");
+        assert!(parse_player("<html>no player</html>").is_none());
+
+        let timedtext: Timedtext = serde_json::from_str(
+            r#"{"events":[{"tStartMs":0,"dDurationMs":1},
+              {"tStartMs":1200,"segs":[{"utf8":"Rust is"},{"utf8":" a language"}]},
+              {"tStartMs":4000,"segs":[{"utf8":"\n"}]},
+              {"tStartMs":5000,"segs":[{"utf8":"built for speed."}]},
+              {"tStartMs":61000,"segs":[{"utf8":"Ownership  comes next."}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            transcript_text(&timedtext),
+            "Rust is a language built for speed.\n\nOwnership comes next."
+        );
+    }
+}